@@ -0,0 +1,93 @@
+//! 错误类型定义
+//!
+//! 存储层统一返回 `AnyboxError`，便于调用方按错误类型区分处理
+
+use std::fmt;
+
+/// Anybox 存储错误类型
+#[derive(Debug)]
+pub enum AnyboxError {
+    /// TextBox 不存在
+    NotFound(String),
+    /// Redis 连接或命令执行失败
+    Connection(redis::RedisError),
+    /// TextBox 序列化或反序列化失败
+    Serialization(serde_json::Error),
+    /// 超出存储限制（如 Redis 内存达到 maxmemory 上限）
+    LimitExceeded(String),
+    /// TextBox 字段不合法（如作者或内容为空）
+    Validation(String),
+}
+
+impl fmt::Display for AnyboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnyboxError::NotFound(id) => write!(f, "TextBox 不存在: id={id}"),
+            AnyboxError::Connection(err) => write!(f, "Redis 访问失败: {err}"),
+            AnyboxError::Serialization(err) => write!(f, "TextBox 序列化失败: {err}"),
+            AnyboxError::LimitExceeded(msg) => write!(f, "超出存储限制: {msg}"),
+            AnyboxError::Validation(msg) => write!(f, "TextBox 参数无效: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for AnyboxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AnyboxError::Connection(err) => Some(err),
+            AnyboxError::Serialization(err) => Some(err),
+            AnyboxError::NotFound(_)
+            | AnyboxError::LimitExceeded(_)
+            | AnyboxError::Validation(_) => None,
+        }
+    }
+}
+
+impl From<redis::RedisError> for AnyboxError {
+    fn from(err: redis::RedisError) -> Self {
+        // OOM 表示 Redis 已达到 maxmemory 上限，重试连接无济于事
+        if err.code() == Some("OOM") {
+            AnyboxError::LimitExceeded(err.to_string())
+        } else {
+            AnyboxError::Connection(err)
+        }
+    }
+}
+
+impl From<serde_json::Error> for AnyboxError {
+    fn from(err: serde_json::Error) -> Self {
+        AnyboxError::Serialization(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, AnyboxError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_redis_error_maps_oom_to_limit_exceeded() {
+        let oom = redis::parse_redis_value(
+            b"-OOM command not allowed when used memory > 'maxmemory'\r\n",
+        )
+        .and_then(|value| value.extract_error())
+        .unwrap_err();
+        assert!(matches!(
+            AnyboxError::from(oom),
+            AnyboxError::LimitExceeded(_)
+        ));
+
+        let io =
+            redis::RedisError::from(std::io::Error::from(std::io::ErrorKind::ConnectionRefused));
+        assert!(matches!(AnyboxError::from(io), AnyboxError::Connection(_)));
+    }
+
+    #[test]
+    fn test_from_serde_error() {
+        let err = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let err = AnyboxError::from(err);
+        assert!(matches!(err, AnyboxError::Serialization(_)));
+        assert!(err.to_string().starts_with("TextBox 序列化失败"));
+    }
+}
@@ -7,6 +7,8 @@
 //! - 分页列表
 //! - Redis 存储
 //! - 过期自动清理
+//! - 统一的错误类型（`AnyboxError`）
+//! - 按格式渲染为 HTML（Markdown、代码高亮）
 //!
 //! ## 使用示例
 //!
@@ -14,7 +16,7 @@
 //! use anybox::{RedisConfig, TextBoxManager, TextBox};
 //!
 //! #[tokio::main]
-//! async fn main() -> anyhow::Result<()> {
+//! async fn main() -> anybox::Result<()> {
 //!     // 创建管理器
 //!     let config = RedisConfig::new("redis://127.0.0.1:6379".to_string());
 //!     let mut manager = TextBoxManager::new(config).await?;
@@ -35,9 +37,18 @@
 //! }
 //! ```
 
+pub mod error;
 pub mod models;
+pub mod render;
+pub mod retry;
 pub mod storage;
 
 // 重新导出常用类型
-pub use models::{PaginatedResult, PaginationParams, TextBox, TextBoxMetadata, TextFormat};
+pub use error::{AnyboxError, Result};
+pub use models::{
+    MAX_EXPIRE_HOURS, PaginatedResult, PaginationParams, SortBy, TextBox, TextBoxMetadata,
+    TextFormat, expires_after_hours,
+};
+pub use render::render_html;
+pub use retry::RetryPolicy;
 pub use storage::{RedisConfig, TextBoxManager, TextBoxStats};
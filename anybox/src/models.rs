@@ -2,13 +2,17 @@ use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
 use uuid::Uuid;
 
+use crate::error::AnyboxError;
+
 /// 文本格式
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum TextFormat {
     /// 纯文本
+    #[default]
     Plain,
     /// Markdown
     Markdown,
@@ -22,12 +26,10 @@ pub enum TextFormat {
     Html,
     /// YAML
     Yaml,
-}
-
-impl Default for TextFormat {
-    fn default() -> Self {
-        Self::Plain
-    }
+    /// CSV
+    Csv,
+    /// TOML
+    Toml,
 }
 
 impl TextFormat {
@@ -40,7 +42,76 @@ impl TextFormat {
             Self::Xml => "xml",
             Self::Html => "html",
             Self::Yaml => "yaml",
+            Self::Csv => "csv",
+            Self::Toml => "toml",
+        }
+    }
+
+    /// 原始内容对应的 Content-Type
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            // HTML 按纯文本返回，避免用户内容在本站域名下被浏览器渲染执行脚本
+            Self::Plain | Self::Code | Self::Html => "text/plain; charset=utf-8",
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Json => "application/json",
+            Self::Xml => "application/xml",
+            Self::Yaml => "application/yaml",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Toml => "application/toml",
+        }
+    }
+
+    /// 根据内容猜测格式，无法判断时返回 `Plain`
+    ///
+    /// 只做轻量的启发式判断：JSON 需能完整解析，CSV 要求至少两行且每行逗号数相同，
+    /// TOML 要求每个有效行都是 `[表头]` 或 `键 = 值`。
+    pub fn detect(content: &str) -> Self {
+        let trimmed = content.trim();
+        if trimmed.is_empty() {
+            return Self::Plain;
         }
+
+        if (trimmed.starts_with('{') || trimmed.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+        {
+            return Self::Json;
+        }
+
+        let lowercase_head = trimmed.chars().take(16).collect::<String>().to_lowercase();
+        if lowercase_head.starts_with("<!doctype html") || lowercase_head.starts_with("<html") {
+            return Self::Html;
+        }
+        if trimmed.starts_with("<?xml") {
+            return Self::Xml;
+        }
+
+        let lines: Vec<&str> = trimmed
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if looks_like_toml(&lines) {
+            return Self::Toml;
+        }
+        if looks_like_csv(&lines) {
+            return Self::Csv;
+        }
+        // 以 `#` 开头时更可能是 Markdown 标题，不按 YAML 注释处理
+        let yaml_mapping = !lines[0].starts_with('#')
+            && lines.iter().any(|line| line.contains(": "))
+            && lines
+                .iter()
+                .all(|line| line.starts_with('#') || line.starts_with("- ") || line.contains(": "));
+        if trimmed.starts_with("---") || yaml_mapping {
+            return Self::Yaml;
+        }
+        if lines.iter().any(|line| {
+            line.starts_with("# ") || line.starts_with("## ") || line.starts_with("```")
+        }) {
+            return Self::Markdown;
+        }
+
+        Self::Plain
     }
 
     pub fn parse(s: &str) -> Option<Self> {
@@ -52,11 +123,53 @@ impl TextFormat {
             "xml" => Some(Self::Xml),
             "html" => Some(Self::Html),
             "yaml" | "yml" => Some(Self::Yaml),
+            "csv" => Some(Self::Csv),
+            "toml" => Some(Self::Toml),
             _ => None,
         }
     }
 }
 
+/// 每个有效行都是 `[表头]` 或 `键 = 值`（`#` 开头为注释），且至少有一个键值对
+fn looks_like_toml(lines: &[&str]) -> bool {
+    let is_key = |key: &str| {
+        let key = key.trim();
+        !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '"'))
+    };
+    let mut has_pair = false;
+    for line in lines {
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            if !is_key(line.trim_matches(|c| c == '[' || c == ']')) {
+                return false;
+            }
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) if is_key(key) && !value.trim().is_empty() => has_pair = true,
+            _ => return false,
+        }
+    }
+    has_pair
+}
+
+/// 至少两行，且每行都含逗号、逗号数相同
+fn looks_like_csv(lines: &[&str]) -> bool {
+    if lines.len() < 2 {
+        return false;
+    }
+    let columns = lines[0].matches(',').count();
+    columns > 0
+        && lines
+            .iter()
+            .all(|line| line.matches(',').count() == columns)
+}
+
 impl FromStr for TextFormat {
     type Err = String;
 
@@ -75,6 +188,9 @@ pub struct TextBoxMetadata {
     /// 过期时间（可选）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<DateTime<Utc>>,
+    /// 创建时明确指定永不过期，批量补充默认过期时间时跳过
+    #[serde(default)]
+    pub never_expires: bool,
     /// 浏览次数
     #[serde(default)]
     pub view_count: u64,
@@ -100,6 +216,7 @@ impl Default for TextBoxMetadata {
             created_at: now,
             updated_at: now,
             expires_at: None,
+            never_expires: false,
             view_count: 0,
             is_public: true,
             language: None,
@@ -165,6 +282,13 @@ impl TextBox {
         self
     }
 
+    /// 标记为明确永不过期（同时清除过期时间）
+    pub fn with_never_expires(mut self) -> Self {
+        self.metadata.expires_at = None;
+        self.metadata.never_expires = true;
+        self
+    }
+
     pub fn with_public(mut self, is_public: bool) -> Self {
         self.metadata.is_public = is_public;
         self
@@ -176,6 +300,27 @@ impl TextBox {
         self.metadata.updated_at = Utc::now();
     }
 
+    /// 添加标签，已存在时不做修改；返回是否发生了变化
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        if self.metadata.tags.iter().any(|t| t == tag) {
+            return false;
+        }
+        self.metadata.tags.push(tag.to_string());
+        self.metadata.updated_at = Utc::now();
+        true
+    }
+
+    /// 移除标签，不存在时不做修改；返回是否发生了变化
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let before = self.metadata.tags.len();
+        self.metadata.tags.retain(|t| t != tag);
+        if self.metadata.tags.len() == before {
+            return false;
+        }
+        self.metadata.updated_at = Utc::now();
+        true
+    }
+
     /// 检查是否过期
     pub fn is_expired(&self) -> bool {
         if let Some(expires_at) = self.metadata.expires_at {
@@ -190,6 +335,68 @@ impl TextBox {
         self.content = content;
         self.metadata.updated_at = Utc::now();
     }
+
+    /// 基于内容计算的 ETag（带引号）
+    ///
+    /// 浏览次数会在每次读取时刷新 `updated_at`，因此这里只对内容相关字段取哈希，
+    /// 保证内容不变时 ETag 稳定。
+    pub fn etag(&self) -> String {
+        let fingerprint = serde_json::json!([
+            self.id,
+            self.author,
+            self.title,
+            self.format,
+            self.content,
+            self.metadata.expires_at,
+            self.metadata.is_public,
+            self.metadata.language,
+            self.metadata.tags,
+        ]);
+        let digest = Sha1::digest(fingerprint.to_string().as_bytes());
+        format!("\"{digest:x}\"")
+    }
+
+    /// 校验必填字段：作者不能为空白，内容不能为空
+    pub fn validate(&self) -> crate::error::Result<()> {
+        if self.author.trim().is_empty() {
+            return Err(AnyboxError::Validation("author 不能为空".to_string()));
+        }
+        if self.content.is_empty() {
+            return Err(AnyboxError::Validation("content 不能为空".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 内容去重用的哈希，只取作者与正文
+    pub fn content_hash(&self) -> String {
+        let fingerprint = serde_json::json!([self.author, self.content]);
+        let digest = Sha1::digest(fingerprint.to_string().as_bytes());
+        format!("{digest:x}")
+    }
+}
+
+/// 过期时间最多设置为多少小时之后（10 年）
+pub const MAX_EXPIRE_HOURS: u64 = 24 * 365 * 10;
+
+/// 计算 `hours` 小时之后的过期时间，`hours` 需在 1 到 [`MAX_EXPIRE_HOURS`] 之间
+pub fn expires_after_hours(hours: u64, now: DateTime<Utc>) -> crate::error::Result<DateTime<Utc>> {
+    if !(1..=MAX_EXPIRE_HOURS).contains(&hours) {
+        return Err(AnyboxError::Validation(format!(
+            "hours 需在 1-{MAX_EXPIRE_HOURS} 之间"
+        )));
+    }
+    Ok(now + chrono::Duration::hours(hours as i64))
+}
+
+/// 列表排序字段，均按时间倒序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortBy {
+    /// 按创建时间
+    #[default]
+    Created,
+    /// 按最近修改时间（浏览不算修改）
+    Updated,
 }
 
 /// 分页参数
@@ -201,6 +408,9 @@ pub struct PaginationParams {
     /// 每页数量
     #[serde(default = "default_page_size")]
     pub page_size: u32,
+    /// 排序字段，默认按创建时间
+    #[serde(default)]
+    pub sort: SortBy,
 }
 
 fn default_page() -> u32 {
@@ -216,6 +426,7 @@ impl Default for PaginationParams {
         Self {
             page: 1,
             page_size: 20,
+            sort: SortBy::default(),
         }
     }
 }
@@ -225,9 +436,15 @@ impl PaginationParams {
         Self {
             page: page.max(1),
             page_size: page_size.clamp(1, 100),
+            sort: SortBy::default(),
         }
     }
 
+    pub fn with_sort(mut self, sort: SortBy) -> Self {
+        self.sort = sort;
+        self
+    }
+
     /// 计算偏移量
     pub fn offset(&self) -> usize {
         ((self.page - 1) * self.page_size) as usize
@@ -279,6 +496,59 @@ mod tests {
         assert!("unknown".parse::<TextFormat>().is_err());
     }
 
+    #[test]
+    fn test_text_format_csv_and_toml_round_trip() {
+        for (format, name) in [(TextFormat::Csv, "csv"), (TextFormat::Toml, "toml")] {
+            assert_eq!(format.as_str(), name);
+            assert_eq!(name.to_uppercase().parse::<TextFormat>().unwrap(), format);
+            let json = serde_json::to_string(&format).unwrap();
+            assert_eq!(json, format!("\"{name}\""));
+            assert_eq!(serde_json::from_str::<TextFormat>(&json).unwrap(), format);
+        }
+    }
+
+    #[test]
+    fn test_text_format_content_type() {
+        assert_eq!(TextFormat::Csv.content_type(), "text/csv; charset=utf-8");
+        assert_eq!(TextFormat::Toml.content_type(), "application/toml");
+        assert_eq!(TextFormat::Json.content_type(), "application/json");
+        assert_eq!(
+            TextFormat::Plain.content_type(),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(TextFormat::Html.content_type(), "text/plain; charset=utf-8");
+    }
+
+    #[test]
+    fn test_text_format_detect() {
+        assert_eq!(
+            TextFormat::detect("name,age\nAlice,30\nBob,25\n"),
+            TextFormat::Csv
+        );
+        assert_eq!(
+            TextFormat::detect("# 配置\ntitle = \"demo\"\n\n[server]\nport = 8080\n"),
+            TextFormat::Toml
+        );
+        assert_eq!(TextFormat::detect("{\"a\": [1, 2]}"), TextFormat::Json);
+        assert_eq!(TextFormat::detect("[1, 2, 3]"), TextFormat::Json);
+        assert_eq!(
+            TextFormat::detect("name: demo\nport: 8080"),
+            TextFormat::Yaml
+        );
+        assert_eq!(
+            TextFormat::detect("# Title\n\nsome text"),
+            TextFormat::Markdown
+        );
+        assert_eq!(
+            TextFormat::detect("<?xml version=\"1.0\"?><a/>"),
+            TextFormat::Xml
+        );
+        // 逗号数不一致、单行都不视为 CSV
+        assert_eq!(TextFormat::detect("hello, world\nbye"), TextFormat::Plain);
+        assert_eq!(TextFormat::detect("a,b,c"), TextFormat::Plain);
+        assert_eq!(TextFormat::detect("just some text"), TextFormat::Plain);
+    }
+
     #[test]
     fn test_text_box_creation() {
         let text_box = TextBox::new("Alice".to_string(), "Hello, world!".to_string());
@@ -302,6 +572,60 @@ mod tests {
         assert_eq!(text_box.metadata.tags.len(), 2);
     }
 
+    #[test]
+    fn test_text_box_validate() {
+        assert!(
+            TextBox::new("Alice".to_string(), "Hello".to_string())
+                .validate()
+                .is_ok()
+        );
+
+        let blank_author = TextBox::new("  ".to_string(), "Hello".to_string());
+        let err = blank_author.validate().unwrap_err();
+        assert!(matches!(err, AnyboxError::Validation(_)));
+        assert!(err.to_string().contains("author"));
+
+        let empty_content = TextBox::new("Alice".to_string(), String::new());
+        let err = empty_content.validate().unwrap_err();
+        assert!(err.to_string().contains("content"));
+    }
+
+    #[test]
+    fn test_add_and_remove_tag() {
+        let mut text_box =
+            TextBox::new("Alice".to_string(), "Hello".to_string()).with_tags(vec!["a".to_string()]);
+
+        assert!(text_box.add_tag("b"));
+        assert!(!text_box.add_tag("a"));
+        assert!(!text_box.remove_tag("missing"));
+        assert!(text_box.remove_tag("a"));
+        assert_eq!(text_box.metadata.tags, ["b"]);
+    }
+
+    #[test]
+    fn test_etag_ignores_views_but_tracks_content() {
+        let mut text_box = TextBox::new("Alice".to_string(), "Hello".to_string());
+        let etag = text_box.etag();
+
+        text_box.increment_view();
+        assert_eq!(text_box.etag(), etag);
+
+        text_box.update_content("Hello, world".to_string());
+        assert_ne!(text_box.etag(), etag);
+    }
+
+    #[test]
+    fn test_content_hash_depends_on_author_and_content_only() {
+        let first = TextBox::new("Alice".to_string(), "Hello".to_string());
+        let second =
+            TextBox::new("Alice".to_string(), "Hello".to_string()).with_title("Other".to_string());
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.content_hash(), second.content_hash());
+
+        let other_author = TextBox::new("Bob".to_string(), "Hello".to_string());
+        assert_ne!(first.content_hash(), other_author.content_hash());
+    }
+
     #[test]
     fn test_pagination_params() {
         let params = PaginationParams::new(2, 50);
@@ -314,5 +638,34 @@ mod tests {
         let params = PaginationParams::new(0, 200);
         assert_eq!(params.page, 1); // Min page
         assert_eq!(params.page_size, 100); // Max page_size
+        assert_eq!(params.sort, SortBy::Created);
+    }
+
+    #[test]
+    fn test_pagination_params_sort() {
+        let params: PaginationParams =
+            serde_json::from_value(serde_json::json!({ "sort": "updated" })).unwrap();
+        assert_eq!(params.sort, SortBy::Updated);
+        assert_eq!(params.page, 1);
+
+        let unknown =
+            serde_json::from_value::<PaginationParams>(serde_json::json!({ "sort": "views" }));
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn test_expires_after_hours_is_bounded() {
+        let now = Utc::now();
+        assert_eq!(
+            expires_after_hours(2, now).unwrap(),
+            now + chrono::Duration::hours(2)
+        );
+        assert!(expires_after_hours(MAX_EXPIRE_HOURS, now).is_ok());
+        for hours in [0, MAX_EXPIRE_HOURS + 1, u64::MAX] {
+            assert!(matches!(
+                expires_after_hours(hours, now),
+                Err(AnyboxError::Validation(_))
+            ));
+        }
     }
 }
@@ -0,0 +1,177 @@
+//! TextBox 内容渲染
+//!
+//! 按文本格式把帖子内容渲染为可直接嵌入页面的 HTML 片段：
+//! - Markdown 渲染后经过 HTML 清洗
+//! - 代码及 JSON/XML/YAML/CSV/TOML 按语言高亮（输出 CSS class，样式由前端决定）
+//! - HTML 默认清洗，只有显式允许时才原样输出
+//! - 纯文本转义后放入 `<pre>`
+
+use std::sync::LazyLock;
+
+use pulldown_cmark::{Options, Parser};
+use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use crate::models::{TextBox, TextFormat};
+
+/// 高亮输出的 CSS class 前缀，如 `hl-keyword`
+const HIGHLIGHT_CLASS_PREFIX: &str = "hl-";
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+/// 将 TextBox 内容渲染为 HTML 片段
+///
+/// `allow_raw_html` 为 true 时 `Html` 格式的内容原样输出，否则一律清洗。
+pub fn render_html(text_box: &TextBox, allow_raw_html: bool) -> String {
+    let body = match text_box.format {
+        TextFormat::Markdown => render_markdown(&text_box.content),
+        TextFormat::Html if allow_raw_html => text_box.content.clone(),
+        TextFormat::Html => ammonia::clean(&text_box.content),
+        TextFormat::Code => highlight(&text_box.content, text_box.metadata.language.as_deref()),
+        TextFormat::Json
+        | TextFormat::Xml
+        | TextFormat::Yaml
+        | TextFormat::Csv
+        | TextFormat::Toml => highlight(&text_box.content, Some(text_box.format.as_str())),
+        TextFormat::Plain => format!("<pre>{}</pre>", escape_html(&text_box.content)),
+    };
+
+    format!(
+        "<article class=\"anybox anybox-{}\">{body}</article>",
+        text_box.format.as_str()
+    )
+}
+
+/// Markdown 转 HTML 并清洗，内嵌的原始 HTML 同样会被清洗
+fn render_markdown(content: &str) -> String {
+    let parser = Parser::new_ext(
+        content,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS,
+    );
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+    ammonia::clean(&html)
+}
+
+/// 代码高亮，找不到对应语法时按纯文本转义输出
+fn highlight(content: &str, language: Option<&str>) -> String {
+    let Some(syntax) = language.and_then(find_syntax) else {
+        return format!(
+            "<pre class=\"code\"><code>{}</code></pre>",
+            escape_html(content)
+        );
+    };
+
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(
+        syntax,
+        &SYNTAX_SET,
+        ClassStyle::SpacedPrefixed {
+            prefix: HIGHLIGHT_CLASS_PREFIX,
+        },
+    );
+    for line in LinesWithEndings::from(content) {
+        if generator
+            .parse_html_for_line_which_includes_newline(line)
+            .is_err()
+        {
+            return format!(
+                "<pre class=\"code\"><code>{}</code></pre>",
+                escape_html(content)
+            );
+        }
+    }
+
+    format!(
+        "<pre class=\"code\"><code class=\"language-{}\">{}</code></pre>",
+        escape_html(&syntax.name.to_lowercase()),
+        generator.finalize()
+    )
+}
+
+fn find_syntax(language: &str) -> Option<&'static SyntaxReference> {
+    SYNTAX_SET
+        .find_syntax_by_token(language)
+        .or_else(|| SYNTAX_SET.find_syntax_by_name(language))
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_box(format: TextFormat, content: &str) -> TextBox {
+        TextBox::new("alice".to_string(), content.to_string()).with_format(format)
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let html = render_html(
+            &text_box(TextFormat::Markdown, "# Title\n\n**bold** text"),
+            false,
+        );
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<strong>bold</strong>"));
+    }
+
+    #[test]
+    fn test_markdown_strips_script() {
+        let html = render_html(
+            &text_box(
+                TextFormat::Markdown,
+                "hello <script>alert(1)</script>\n\n<img src=x onerror=alert(1)>",
+            ),
+            false,
+        );
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("onerror"));
+    }
+
+    #[test]
+    fn test_html_sanitized_unless_allowed() {
+        let content = "<p>hi</p><script>alert(1)</script>";
+        let sanitized = render_html(&text_box(TextFormat::Html, content), false);
+        assert!(sanitized.contains("<p>hi</p>"));
+        assert!(!sanitized.contains("<script"));
+
+        let raw = render_html(&text_box(TextFormat::Html, content), true);
+        assert!(raw.contains("<script>"));
+    }
+
+    #[test]
+    fn test_plain_is_escaped() {
+        let html = render_html(&text_box(TextFormat::Plain, "<b>&</b>"), false);
+        assert!(html.contains("<pre>&lt;b&gt;&amp;&lt;/b&gt;</pre>"));
+    }
+
+    #[test]
+    fn test_code_is_highlighted() {
+        let text_box =
+            text_box(TextFormat::Code, "fn main() {}\n").with_language("rust".to_string());
+        let html = render_html(&text_box, false);
+        assert!(html.contains("language-rust"));
+        assert!(html.contains("<span class=\"hl-"));
+    }
+
+    #[test]
+    fn test_unknown_language_falls_back_to_escaped_text() {
+        let text_box =
+            text_box(TextFormat::Code, "<x>").with_language("no-such-language".to_string());
+        let html = render_html(&text_box, false);
+        assert!(html.contains("<code>&lt;x&gt;</code>"));
+    }
+}
@@ -0,0 +1,167 @@
+//! Redis 命令重试
+//!
+//! 只对连接级错误（断连、拒绝连接、超时）重试，序列化或命令本身的错误直接返回。
+
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::error::Result;
+
+/// 重试策略
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 首次失败后的最大重试次数，0 表示不重试
+    pub max_retries: u32,
+    /// 首次重试前的等待时间，之后每次翻倍
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            backoff,
+        }
+    }
+
+    /// 不重试
+    pub fn none() -> Self {
+        Self::new(0, Duration::ZERO)
+    }
+
+    /// 第 `attempt` 次重试（从 0 开始）前的等待时间
+    fn delay(&self, attempt: u32) -> Duration {
+        self.backoff.saturating_mul(1 << attempt.min(16))
+    }
+}
+
+/// 是否为连接级错误，这类错误重试可能成功
+pub fn is_connection_error(err: &redis::RedisError) -> bool {
+    err.is_io_error()
+        || err.is_connection_refusal()
+        || err.is_connection_dropped()
+        || err.is_timeout()
+}
+
+/// 执行操作，连接级错误按策略重试，重试耗尽后返回最后一次的错误
+pub async fn retry<T, F, Fut>(policy: &RetryPolicy, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = redis::RedisResult<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < policy.max_retries && is_connection_error(&err) => {
+                let delay = policy.delay(attempt);
+                attempt += 1;
+                warn!(
+                    "Redis 连接错误，{}ms 后第 {} 次重试: {}",
+                    delay.as_millis(),
+                    attempt,
+                    err
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AnyboxError;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// 内存版键值存储，前 `failures` 次调用返回给定错误
+    struct FlakyStore {
+        entries: Mutex<HashMap<String, String>>,
+        failures: Mutex<u32>,
+        calls: Mutex<u32>,
+        error: fn() -> redis::RedisError,
+    }
+
+    impl FlakyStore {
+        fn new(failures: u32, error: fn() -> redis::RedisError) -> Self {
+            Self {
+                entries: Mutex::new(HashMap::new()),
+                failures: Mutex::new(failures),
+                calls: Mutex::new(0),
+                error,
+            }
+        }
+
+        async fn set(&self, key: &str, value: &str) -> redis::RedisResult<()> {
+            *self.calls.lock().unwrap() += 1;
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err((self.error)());
+            }
+            self.entries
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+
+        fn calls(&self) -> u32 {
+            *self.calls.lock().unwrap()
+        }
+    }
+
+    fn connection_dropped() -> redis::RedisError {
+        std::io::Error::from(std::io::ErrorKind::ConnectionReset).into()
+    }
+
+    fn type_error() -> redis::RedisError {
+        (redis::ErrorKind::TypeError, "unexpected type").into()
+    }
+
+    fn policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy::new(max_retries, Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_after_connection_errors() {
+        let store = FlakyStore::new(2, connection_dropped);
+        retry(&policy(2), || store.set("k", "v")).await.unwrap();
+        assert_eq!(store.calls(), 3);
+        assert_eq!(store.entries.lock().unwrap()["k"], "v");
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausted_returns_connection_error() {
+        let store = FlakyStore::new(5, connection_dropped);
+        let err = retry(&policy(2), || store.set("k", "v")).await.unwrap_err();
+        assert!(matches!(err, AnyboxError::Connection(_)));
+        assert_eq!(store.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_skips_non_connection_errors() {
+        let store = FlakyStore::new(1, type_error);
+        assert!(retry(&policy(3), || store.set("k", "v")).await.is_err());
+        assert_eq!(store.calls(), 1);
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(100));
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+    }
+}
@@ -1,9 +1,112 @@
-use anyhow::{Context, Result};
-use redis::{AsyncCommands, aio::ConnectionManager};
+use redis::{
+    AsyncCommands, ExistenceCheck, RedisResult, Script, SetExpiry, SetOptions,
+    aio::ConnectionManager,
+};
 use std::fmt::Debug;
+use std::future::Future;
+use std::sync::LazyLock;
 use tracing::{debug, info};
 
-use crate::models::{PaginatedResult, PaginationParams, TextBox};
+use crate::error::{AnyboxError, Result};
+use crate::models::{PaginatedResult, PaginationParams, SortBy, TextBox, expires_after_hours};
+use crate::retry::{RetryPolicy, retry};
+
+/// 按内容哈希去重创建：哈希索引指向仍存在的其它 TextBox 时返回其数据，
+/// 否则在同一个脚本内写入 TextBox、两个索引与哈希索引（与 TextBox 同时过期）
+///
+/// KEYS: 哈希索引、TextBox、创建时间索引、修改时间索引
+/// ARGV: id、TextBox 键前缀、数据、过期时间戳（0 表示不过期）、创建时间分数、修改时间分数
+static CREATE_BY_HASH_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+local existing = redis.call('GET', KEYS[1])
+if existing and existing ~= ARGV[1] then
+    local data = redis.call('GET', ARGV[2] .. existing)
+    if data then
+        return data
+    end
+end
+if ARGV[4] == '0' then
+    redis.call('SET', KEYS[2], ARGV[3])
+    redis.call('SET', KEYS[1], ARGV[1])
+else
+    redis.call('SET', KEYS[2], ARGV[3], 'EXAT', ARGV[4])
+    redis.call('SET', KEYS[1], ARGV[1], 'EXAT', ARGV[4])
+end
+redis.call('ZADD', KEYS[3], ARGV[5], ARGV[1])
+redis.call('ZADD', KEYS[4], ARGV[6], ARGV[1])
+return false
+",
+    )
+});
+
+/// 更新 TextBox，哈希索引跟随内容变化：旧哈希索引指向该 TextBox 时释放它，
+/// 新哈希索引空闲或指向已不存在的 TextBox 时改为指向该 TextBox（与 TextBox 同时过期）
+///
+/// KEYS: TextBox、旧哈希索引、新哈希索引
+/// ARGV: id、TextBox 键前缀、新数据、过期时间戳（0 表示不过期）、
+///       是否校验读取时的内容（"1"/"0"）、读取时的作者、读取时的正文
+/// 返回 0 表示 TextBox 不存在，-1 表示读取后作者或正文已被修改，1 表示更新成功
+static UPDATE_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+local current = redis.call('GET', KEYS[1])
+if not current then
+    return 0
+end
+if ARGV[5] == '1' then
+    local ok, stored = pcall(cjson.decode, current)
+    if ok and (stored.author ~= ARGV[6] or stored.content ~= ARGV[7]) then
+        return -1
+    end
+end
+local function set(key, value)
+    if ARGV[4] == '0' then
+        redis.call('SET', key, value)
+    else
+        redis.call('SET', key, value, 'EXAT', ARGV[4])
+    end
+end
+set(KEYS[1], ARGV[3])
+if redis.call('GET', KEYS[2]) ~= ARGV[1] then
+    return 1
+end
+if KEYS[2] ~= KEYS[3] then
+    redis.call('DEL', KEYS[2])
+    local owner = redis.call('GET', KEYS[3])
+    if owner and owner ~= ARGV[1] and redis.call('EXISTS', ARGV[2] .. owner) == 1 then
+        return 1
+    end
+end
+set(KEYS[3], ARGV[1])
+return 1
+",
+    )
+});
+
+/// 哈希索引仍指向 ARGV[1] 时删除它，避免误删已指向其它 TextBox 的索引
+static RELEASE_HASH_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+",
+    )
+});
+
+/// 哈希索引仍指向 ARGV[1] 时把它的过期时间设为 ARGV[2]
+static EXPIRE_HASH_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('EXPIREAT', KEYS[1], ARGV[2])
+end
+return 0
+",
+    )
+});
 
 /// Redis 存储配置
 #[derive(Debug, Clone)]
@@ -12,6 +115,8 @@ pub struct RedisConfig {
     pub url: String,
     /// 键前缀
     pub key_prefix: String,
+    /// 连接级错误的重试策略
+    pub retry: RetryPolicy,
 }
 
 impl Default for RedisConfig {
@@ -19,6 +124,7 @@ impl Default for RedisConfig {
         Self {
             url: "redis://127.0.0.1:6379".to_string(),
             key_prefix: "anybox".to_string(),
+            retry: RetryPolicy::default(),
         }
     }
 }
@@ -27,7 +133,7 @@ impl RedisConfig {
     pub fn new(url: String) -> Self {
         Self {
             url,
-            key_prefix: "anybox".to_string(),
+            ..Default::default()
         }
     }
 
@@ -35,6 +141,11 @@ impl RedisConfig {
         self.key_prefix = prefix;
         self
     }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
 }
 
 /// TextBox 管理器
@@ -44,26 +155,36 @@ pub struct TextBoxManager {
     conn: ConnectionManager,
     /// 键前缀
     key_prefix: String,
+    /// 连接级错误的重试策略
+    retry: RetryPolicy,
 }
 
 impl TextBoxManager {
     /// 创建新的管理器
     pub async fn new(config: RedisConfig) -> Result<Self> {
         info!("连接 Redis: {}", config.url);
-        let client = redis::Client::open(config.url.as_str()).context("无法创建 Redis 客户端")?;
+        let client = redis::Client::open(config.url.as_str())?;
 
-        let conn = ConnectionManager::new(client)
-            .await
-            .context("无法连接到 Redis")?;
+        let conn = ConnectionManager::new(client).await?;
 
         info!("✅ Redis 连接成功");
 
         Ok(Self {
             conn,
             key_prefix: config.key_prefix,
+            retry: config.retry,
         })
     }
 
+    /// 执行 Redis 命令，连接级错误按重试策略重试
+    async fn run<T, F, Fut>(&self, command: F) -> Result<T>
+    where
+        F: Fn(ConnectionManager) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        retry(&self.retry, || command(self.conn.clone())).await
+    }
+
     /// 生成 TextBox 的键
     fn text_box_key(&self, id: &str) -> String {
         format!("{}:textbox:{}", self.key_prefix, id)
@@ -74,55 +195,184 @@ impl TextBoxManager {
         format!("{}:index", self.key_prefix)
     }
 
+    /// 生成按修改时间排序的索引键
+    fn updated_index_key(&self) -> String {
+        format!("{}:index:updated", self.key_prefix)
+    }
+
+    /// 列表排序字段对应的索引键
+    fn sorted_index_key(&self, sort: SortBy) -> String {
+        match sort {
+            SortBy::Created => self.index_key(),
+            SortBy::Updated => self.updated_index_key(),
+        }
+    }
+
+    /// 写入修改时间索引（毫秒精度，同一秒内的多次修改也能区分先后）
+    async fn touch_updated_index(&self, text_box: &TextBox) -> Result<()> {
+        let updated_index_key = &self.updated_index_key();
+        let id = &text_box.id;
+        let score = text_box.metadata.updated_at.timestamp_millis() as f64;
+        self.run(
+            |mut conn| async move { conn.zadd::<_, _, _, ()>(updated_index_key, id, score).await },
+        )
+        .await
+    }
+
+    /// 生成内容哈希索引键（用于去重）
+    fn hash_key(&self, hash: &str) -> String {
+        format!("{}:hash:{}", self.key_prefix, hash)
+    }
+
     /// 创建 TextBox
     pub async fn create(&mut self, text_box: TextBox) -> Result<TextBox> {
-        let id = text_box.id.clone();
-        let key = self.text_box_key(&id);
+        text_box.validate()?;
+        let id = &text_box.id;
+        let key = &self.text_box_key(id);
 
         // 序列化
-        let data = serde_json::to_string(&text_box).context("序列化 TextBox 失败")?;
+        let data = &serde_json::to_string(&text_box)?;
 
-        // 存储到 Redis
-        self.conn
-            .set::<_, _, ()>(&key, data)
-            .await
-            .context("保存 TextBox 到 Redis 失败")?;
+        // 存储到 Redis，设置了过期时间时由 Redis 到期自动删除
+        let options = set_options(&text_box);
+        self.run(|mut conn| async move { conn.set_options::<_, _, ()>(key, data, options).await })
+            .await?;
 
-        // 添加到索引（使用 sorted set，按创建时间排序）
+        // 添加到索引（使用 sorted set，分别按创建时间和修改时间排序）
+        let index_key = &self.index_key();
         let score = text_box.metadata.created_at.timestamp() as f64;
-        self.conn
-            .zadd::<_, _, _, ()>(&self.index_key(), &id, score)
-            .await
-            .context("添加到索引失败")?;
+        self.run(|mut conn| async move { conn.zadd::<_, _, _, ()>(index_key, id, score).await })
+            .await?;
+        self.touch_updated_index(&text_box).await?;
 
         info!("✅ 创建 TextBox: id={}, author={}", id, text_box.author);
         Ok(text_box)
     }
 
+    /// 按原样写入 TextBox，保留 id 与创建时间（用于导入）
+    ///
+    /// id 已存在且内容不同时不覆盖，返回 `Ok(false)`
+    pub async fn create_preserving_id(&mut self, text_box: &TextBox) -> Result<bool> {
+        text_box.validate()?;
+        let id = &text_box.id;
+        let key = &self.text_box_key(id);
+        let data = &serde_json::to_string(text_box)?;
+
+        // SET NX 保证已存在的 TextBox 不会被覆盖
+        let options = set_options(text_box).conditional_set(ExistenceCheck::NX);
+        let created: bool = self
+            .run(|mut conn| async move { conn.set_options(key, data, options).await })
+            .await?;
+        if !created {
+            // 连接错误重试时首次 SET NX 可能已经成功，已存的值与本次写入相同说明是自己写入的
+            let stored: Option<String> = self
+                .run(|mut conn| async move { conn.get(key).await })
+                .await?;
+            if stored.as_deref() != Some(data.as_str()) {
+                debug!("TextBox 已存在，跳过导入: id={}", text_box.id);
+                return Ok(false);
+            }
+        }
+
+        let index_key = &self.index_key();
+        let score = text_box.metadata.created_at.timestamp() as f64;
+        self.run(|mut conn| async move { conn.zadd::<_, _, _, ()>(index_key, id, score).await })
+            .await?;
+        self.touch_updated_index(text_box).await?;
+
+        info!("📥 导入 TextBox: id={}", text_box.id);
+        Ok(true)
+    }
+
+    /// 按内容去重创建：作者与正文相同的 TextBox 已存在时直接返回它
+    ///
+    /// 返回值第二项表示是否为新创建。查找哈希索引与写入 TextBox 在同一个 Lua 脚本中完成，
+    /// 并发提交相同内容时只有一个请求会真正创建；哈希索引与 TextBox 同时过期，删除时一并移除。
+    pub async fn create_or_get_by_content_hash(
+        &mut self,
+        text_box: TextBox,
+    ) -> Result<(TextBox, bool)> {
+        text_box.validate()?;
+        let hash_key = &self.hash_key(&text_box.content_hash());
+        let key = &self.text_box_key(&text_box.id);
+        let index_key = &self.index_key();
+        let updated_index_key = &self.updated_index_key();
+        let key_prefix = &self.text_box_key("");
+        let id = &text_box.id;
+        let data = &serde_json::to_string(&text_box)?;
+        let expire_at = text_box
+            .metadata
+            .expires_at
+            .map_or(0, |expires_at| expires_at.timestamp().max(1));
+        let created_score = text_box.metadata.created_at.timestamp();
+        let updated_score = text_box.metadata.updated_at.timestamp_millis();
+
+        // 重试时脚本可能已经执行成功，此时索引指向自己，会按新建重新写入
+        let existing: Option<String> = self
+            .run(|mut conn| async move {
+                CREATE_BY_HASH_SCRIPT
+                    .key(hash_key)
+                    .key(key)
+                    .key(index_key)
+                    .key(updated_index_key)
+                    .arg(id)
+                    .arg(key_prefix)
+                    .arg(data)
+                    .arg(expire_at)
+                    .arg(created_score)
+                    .arg(updated_score)
+                    .invoke_async(&mut conn)
+                    .await
+            })
+            .await?;
+
+        if let Some(json) = existing {
+            let existing: TextBox = serde_json::from_str(&json)?;
+            debug!("内容重复，返回已有 TextBox: id={}", existing.id);
+            return Ok((existing, false));
+        }
+
+        info!("✅ 创建 TextBox: id={}, author={}", id, text_box.author);
+        Ok((text_box, true))
+    }
+
+    /// 哈希索引仍指向该 TextBox 时删除它
+    async fn release_hash_key(&self, text_box: &TextBox) -> Result<()> {
+        let hash_key = &self.hash_key(&text_box.content_hash());
+        let id = &text_box.id;
+        self.run(|mut conn| async move {
+            RELEASE_HASH_SCRIPT
+                .key(hash_key)
+                .arg(id)
+                .invoke_async::<()>(&mut conn)
+                .await
+        })
+        .await
+    }
+
     /// 获取 TextBox
     pub async fn get(&mut self, id: &str) -> Result<Option<TextBox>> {
-        let key = self.text_box_key(id);
+        let key = &self.text_box_key(id);
 
         let data: Option<String> = self
-            .conn
-            .get(&key)
-            .await
-            .context("从 Redis 获取 TextBox 失败")?;
+            .run(|mut conn| async move { conn.get(key).await })
+            .await?;
 
         match data {
             Some(json) => {
-                let mut text_box: TextBox =
-                    serde_json::from_str(&json).context("反序列化 TextBox 失败")?;
+                let mut text_box: TextBox = serde_json::from_str(&json)?;
 
                 // 增加浏览次数
                 text_box.increment_view();
 
                 // 更新到 Redis
-                let updated_data = serde_json::to_string(&text_box)?;
-                self.conn
-                    .set::<_, _, ()>(&key, updated_data)
-                    .await
-                    .context("更新浏览次数失败")?;
+                let updated_data = &serde_json::to_string(&text_box)?;
+                let options = set_options(&text_box);
+                self.run(|mut conn| async move {
+                    conn.set_options::<_, _, ()>(key, updated_data, options)
+                        .await
+                })
+                .await?;
 
                 debug!(
                     "获取 TextBox: id={}, views={}",
@@ -137,29 +387,30 @@ impl TextBoxManager {
         }
     }
 
-    /// 列出 TextBox（分页）
+    /// 列出 TextBox（分页），按 `params.sort` 对应的时间倒序
     pub async fn list(&mut self, params: PaginationParams) -> Result<PaginatedResult<TextBox>> {
-        let index_key = self.index_key();
+        if params.sort == SortBy::Updated {
+            self.backfill_updated_index().await?;
+        }
+        let index_key = &self.sorted_index_key(params.sort);
 
         // 获取总数
-        let total: u64 = self.conn.zcard(&index_key).await.context("获取总数失败")?;
+        let total: u64 = self
+            .run(|mut conn| async move { conn.zcard(index_key).await })
+            .await?;
 
         if total == 0 {
             return Ok(PaginatedResult::new(vec![], 0, &params));
         }
 
         // 计算范围（倒序，最新的在前）
-        let offset = params.offset() as isize;
-        let limit = params.limit() as isize;
-        let end = -(offset + 1);
-        let start = end - limit + 1;
+        let start = params.offset() as isize;
+        let end = start + params.limit() as isize - 1;
 
         // 从 sorted set 获取 ID 列表（倒序）
         let ids: Vec<String> = self
-            .conn
-            .zrevrange(&index_key, start, end)
-            .await
-            .context("获取 ID 列表失败")?;
+            .run(|mut conn| async move { conn.zrevrange(index_key, start, end).await })
+            .await?;
 
         // 批量获取 TextBox
         let mut items = Vec::new();
@@ -173,9 +424,10 @@ impl TextBoxManager {
         }
 
         debug!(
-            "列出 TextBox: page={}, page_size={}, total={}, items={}",
+            "列出 TextBox: page={}, page_size={}, sort={:?}, total={}, items={}",
             params.page,
             params.page_size,
+            params.sort,
             total,
             items.len()
         );
@@ -183,20 +435,66 @@ impl TextBoxManager {
         Ok(PaginatedResult::new(items, total, &params))
     }
 
+    /// 补全修改时间索引
+    ///
+    /// 修改时间索引在引入排序选项后才开始维护，两个索引数量不一致时，
+    /// 把只在创建时间索引中的 TextBox 按修改时间补进来；内容已过期删除的按创建时间补，
+    /// 之后由清理或删除一并移除。
+    async fn backfill_updated_index(&mut self) -> Result<()> {
+        let index_key = &self.index_key();
+        let updated_index_key = &self.updated_index_key();
+        let (created, updated): (u64, u64) = self
+            .run(|mut conn| async move {
+                redis::pipe()
+                    .zcard(index_key)
+                    .zcard(updated_index_key)
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        if created == updated {
+            return Ok(());
+        }
+
+        let entries: Vec<(String, f64)> = self
+            .run(|mut conn| async move { conn.zrange_withscores(index_key, 0, -1).await })
+            .await?;
+        let mut backfilled = 0;
+        for (id, created_score) in entries {
+            let id = &id;
+            let indexed: Option<f64> = self
+                .run(|mut conn| async move { conn.zscore(updated_index_key, id).await })
+                .await?;
+            if indexed.is_some() {
+                continue;
+            }
+            let score = match self.get_without_increment(id).await? {
+                Some(text_box) => text_box.metadata.updated_at.timestamp_millis() as f64,
+                None => created_score * 1000.0,
+            };
+            self.run(|mut conn| async move {
+                conn.zadd::<_, _, _, ()>(updated_index_key, id, score).await
+            })
+            .await?;
+            backfilled += 1;
+        }
+        if backfilled > 0 {
+            info!("补全修改时间索引: {} 个", backfilled);
+        }
+        Ok(())
+    }
+
     /// 获取 TextBox（不增加浏览次数）
     async fn get_without_increment(&mut self, id: &str) -> Result<Option<TextBox>> {
-        let key = self.text_box_key(id);
+        let key = &self.text_box_key(id);
 
         let data: Option<String> = self
-            .conn
-            .get(&key)
-            .await
-            .context("从 Redis 获取 TextBox 失败")?;
+            .run(|mut conn| async move { conn.get(key).await })
+            .await?;
 
         match data {
             Some(json) => {
-                let text_box: TextBox =
-                    serde_json::from_str(&json).context("反序列化 TextBox 失败")?;
+                let text_box: TextBox = serde_json::from_str(&json)?;
                 Ok(Some(text_box))
             }
             None => Ok(None),
@@ -205,16 +503,25 @@ impl TextBoxManager {
 
     /// 删除 TextBox
     pub async fn delete(&mut self, id: &str) -> Result<bool> {
-        let key = self.text_box_key(id);
+        // 内容无法解析时无从得知哈希，只删除 TextBox 本身
+        if let Ok(Some(text_box)) = self.get_without_increment(id).await {
+            self.release_hash_key(&text_box).await?;
+        }
+
+        let key = &self.text_box_key(id);
 
         // 从存储中删除
-        let deleted: u32 = self.conn.del(&key).await.context("删除 TextBox 失败")?;
+        let deleted: u32 = self
+            .run(|mut conn| async move { conn.del(key).await })
+            .await?;
 
         // 从索引中删除
-        self.conn
-            .zrem::<_, _, ()>(&self.index_key(), id)
-            .await
-            .context("从索引删除失败")?;
+        let index_key = &self.index_key();
+        self.run(|mut conn| async move { conn.zrem::<_, _, ()>(index_key, id).await })
+            .await?;
+        let updated_index_key = &self.updated_index_key();
+        self.run(|mut conn| async move { conn.zrem::<_, _, ()>(updated_index_key, id).await })
+            .await?;
 
         let success = deleted > 0;
         if success {
@@ -225,52 +532,131 @@ impl TextBoxManager {
     }
 
     /// 更新 TextBox
+    ///
+    /// 内容变化时按去重创建建立的哈希索引一并迁移：旧内容的索引被释放，新内容的索引指向该 TextBox。
     pub async fn update(&mut self, text_box: TextBox) -> Result<TextBox> {
-        let id = text_box.id.clone();
-        let key = self.text_box_key(&id);
+        let key = &self.text_box_key(&text_box.id);
+        let new_hash_key = &self.hash_key(&text_box.content_hash());
+        let key_prefix = &self.text_box_key("");
+        let id = &text_box.id;
+        let data = &serde_json::to_string(&text_box)?;
+        let expire_at = text_box
+            .metadata
+            .expires_at
+            .map_or(0, |expires_at| expires_at.timestamp().max(1));
+
+        // 读取旧内容后 TextBox 又被修改时重新读取，保证释放的是当前内容的哈希索引
+        loop {
+            let (old_hash_key, check, author, content) = match self.get_without_increment(id).await
+            {
+                Ok(Some(previous)) => (
+                    self.hash_key(&previous.content_hash()),
+                    "1",
+                    previous.author,
+                    previous.content,
+                ),
+                Ok(None) => return Err(AnyboxError::NotFound(id.clone())),
+                // 旧内容无法解析时无从得知哈希，直接覆盖
+                Err(AnyboxError::Serialization(_)) => {
+                    (new_hash_key.clone(), "0", String::new(), String::new())
+                }
+                Err(e) => return Err(e),
+            };
+            let (old_hash_key, author, content) = (&old_hash_key, &author, &content);
+
+            let status: i64 = self
+                .run(|mut conn| async move {
+                    UPDATE_SCRIPT
+                        .key(key)
+                        .key(old_hash_key)
+                        .key(new_hash_key)
+                        .arg(id)
+                        .arg(key_prefix)
+                        .arg(data)
+                        .arg(expire_at)
+                        .arg(check)
+                        .arg(author)
+                        .arg(content)
+                        .invoke_async(&mut conn)
+                        .await
+                })
+                .await?;
+            match status {
+                0 => return Err(AnyboxError::NotFound(id.clone())),
+                -1 => debug!("TextBox 在更新期间被修改，重新读取: id={}", id),
+                _ => break,
+            }
+        }
+        self.touch_updated_index(&text_box).await?;
 
-        // 检查是否存在
-        let exists: bool = self
-            .conn
-            .exists(&key)
-            .await
-            .context("检查 TextBox 是否存在失败")?;
+        info!("✏️  更新 TextBox: id={}", text_box.id);
+        Ok(text_box)
+    }
 
-        if !exists {
-            anyhow::bail!("TextBox 不存在: id={id}");
+    /// 添加标签，只修改标签列表与更新时间，不增加浏览次数；标签已存在时原样返回
+    pub async fn add_tag(&mut self, id: &str, tag: &str) -> Result<TextBox> {
+        if tag.trim().is_empty() {
+            return Err(AnyboxError::Validation("tag 不能为空".to_string()));
         }
+        self.modify_tags(id, |text_box| text_box.add_tag(tag)).await
+    }
 
-        // 序列化并保存
-        let data = serde_json::to_string(&text_box).context("序列化 TextBox 失败")?;
-
-        self.conn
-            .set::<_, _, ()>(&key, data)
+    /// 移除标签，只修改标签列表与更新时间；标签不存在时原样返回
+    pub async fn remove_tag(&mut self, id: &str, tag: &str) -> Result<TextBox> {
+        self.modify_tags(id, |text_box| text_box.remove_tag(tag))
             .await
-            .context("更新 TextBox 到 Redis 失败")?;
+    }
+
+    /// 读取-修改-写回标签，有变化时同步刷新修改时间索引
+    async fn modify_tags(
+        &mut self,
+        id: &str,
+        modify: impl FnOnce(&mut TextBox) -> bool,
+    ) -> Result<TextBox> {
+        let mut text_box = self
+            .get_without_increment(id)
+            .await?
+            .ok_or_else(|| AnyboxError::NotFound(id.to_string()))?;
+        if !modify(&mut text_box) {
+            return Ok(text_box);
+        }
 
-        info!("✏️  更新 TextBox: id={}", id);
+        let key = &self.text_box_key(id);
+        let data = &serde_json::to_string(&text_box)?;
+        let options = set_options(&text_box);
+        self.run(|mut conn| async move { conn.set_options::<_, _, ()>(key, data, options).await })
+            .await?;
+        self.touch_updated_index(&text_box).await?;
+
+        info!(
+            "🏷️  更新 TextBox 标签: id={}, tags={:?}",
+            id, text_box.metadata.tags
+        );
         Ok(text_box)
     }
 
     /// 清理过期的 TextBox
     pub async fn cleanup_expired(&mut self) -> Result<u32> {
-        let index_key = self.index_key();
+        let index_key = &self.index_key();
 
         // 获取所有 ID
         let ids: Vec<String> = self
-            .conn
-            .zrange(&index_key, 0, -1)
-            .await
-            .context("获取所有 ID 失败")?;
+            .run(|mut conn| async move { conn.zrange(index_key, 0, -1).await })
+            .await?;
 
         let mut deleted_count = 0;
 
         for id in ids {
-            if let Ok(Some(text_box)) = self.get_without_increment(&id).await
-                && text_box.is_expired()
-                && self.delete(&id).await?
-            {
-                deleted_count += 1;
+            match self.get_without_increment(&id).await {
+                Ok(Some(text_box)) if text_box.is_expired() => {
+                    deleted_count += u32::from(self.delete(&id).await?);
+                }
+                Ok(None) => {
+                    // 内容已由 Redis 按过期时间删除，只需移除索引
+                    self.delete(&id).await?;
+                    deleted_count += 1;
+                }
+                _ => {}
             }
         }
 
@@ -281,18 +667,95 @@ impl TextBoxManager {
         Ok(deleted_count)
     }
 
+    /// 为没有过期时间的 TextBox 补充 `hours` 小时后的过期时间并设置 Redis TTL，
+    /// 明确指定永不过期的会被跳过；返回更新的数量
+    pub async fn apply_default_expiry(&mut self, hours: u64) -> Result<u32> {
+        let expires_at = expires_after_hours(hours, chrono::Utc::now())?;
+        let index_key = &self.index_key();
+        let ids: Vec<String> = self
+            .run(|mut conn| async move { conn.zrange(index_key, 0, -1).await })
+            .await?;
+
+        let mut updated = 0;
+        for id in ids {
+            let Some(mut text_box) = self.get_without_increment(&id).await? else {
+                continue;
+            };
+            if text_box.metadata.expires_at.is_some() || text_box.metadata.never_expires {
+                continue;
+            }
+
+            // 只补充过期时间，不视为内容修改，updated_at 与修改时间索引保持不变
+            text_box.metadata.expires_at = Some(expires_at);
+            let key = &self.text_box_key(&id);
+            let data = &serde_json::to_string(&text_box)?;
+            let options = set_options(&text_box);
+            self.run(
+                |mut conn| async move { conn.set_options::<_, _, ()>(key, data, options).await },
+            )
+            .await?;
+
+            // 去重创建的哈希索引与 TextBox 同时过期
+            let hash_key = &self.hash_key(&text_box.content_hash());
+            let expire_at = expires_at.timestamp().max(1);
+            let id = &id;
+            self.run(|mut conn| async move {
+                EXPIRE_HASH_SCRIPT
+                    .key(hash_key)
+                    .arg(id)
+                    .arg(expire_at)
+                    .invoke_async::<()>(&mut conn)
+                    .await
+            })
+            .await?;
+            updated += 1;
+        }
+
+        info!(
+            "⏰ 补充默认过期时间: 更新 {} 个, {} 小时后过期",
+            updated, hours
+        );
+        Ok(updated)
+    }
+
+    /// 发送 PING 检查 Redis 是否可用，不做重试，用于健康检查
+    pub async fn ping(&self) -> Result<()> {
+        let mut conn = self.conn.clone();
+        redis::cmd("PING").query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+
+    /// 检查 TextBox 是否存在，不读取内容也不增加浏览次数
+    pub async fn exists(&mut self, id: &str) -> Result<bool> {
+        let key = &self.text_box_key(id);
+        self.run(|mut conn| async move { conn.exists(key).await })
+            .await
+    }
+
+    /// 索引中的 TextBox 数量（可能包含尚未清理的过期项）
+    pub async fn count(&mut self) -> Result<u64> {
+        let index_key = &self.index_key();
+        self.run(|mut conn| async move { conn.zcard(index_key).await })
+            .await
+    }
+
     /// 获取统计信息
     pub async fn stats(&mut self) -> Result<TextBoxStats> {
-        let total: u64 = self
-            .conn
-            .zcard(self.index_key())
-            .await
-            .context("获取总数失败")?;
+        let total = self.count().await?;
 
         Ok(TextBoxStats { total })
     }
 }
 
+/// 写入 TextBox 的 SET 选项：设置了过期时间时附带 EXAT，交由 Redis 到期删除
+fn set_options(text_box: &TextBox) -> SetOptions {
+    match text_box.metadata.expires_at {
+        Some(expires_at) => SetOptions::default()
+            .with_expiration(SetExpiry::EXAT(expires_at.timestamp().max(1) as u64)),
+        None => SetOptions::default(),
+    }
+}
+
 impl Debug for TextBoxManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TextBoxManager")
@@ -336,6 +799,175 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[ignore] // 需要 Redis 运行
+    async fn test_create_preserving_id_skips_existing() -> Result<()> {
+        let mut manager = create_test_manager().await?;
+
+        let existing = TextBox::new("Alice".to_string(), "existing".to_string());
+        manager.create(existing.clone()).await?;
+
+        let mut imported = TextBox::new("Bob".to_string(), "imported".to_string());
+        imported.metadata.created_at = chrono::Utc::now() - chrono::Duration::days(3);
+        let mut conflicting = existing.clone();
+        conflicting.content = "overwritten".to_string();
+
+        assert!(manager.create_preserving_id(&imported).await?);
+        // 模拟首次写入成功后的重试：已存的值与本次相同，仍视为创建
+        assert!(manager.create_preserving_id(&imported).await?);
+        assert!(!manager.create_preserving_id(&conflicting).await?);
+
+        let fetched = manager.get(&imported.id).await?.expect("imported box");
+        assert_eq!(fetched.metadata.created_at, imported.metadata.created_at);
+        let kept = manager.get(&existing.id).await?.expect("existing box");
+        assert_eq!(kept.content, "existing");
+
+        manager.delete(&existing.id).await?;
+        manager.delete(&imported.id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要 Redis 运行
+    async fn test_create_or_get_by_content_hash_returns_first_id() -> Result<()> {
+        let mut manager = create_test_manager().await?;
+        let content = format!("dedup {}", uuid::Uuid::new_v4());
+
+        let first = TextBox::new("Alice".to_string(), content.clone());
+        let (created, is_new) = manager.create_or_get_by_content_hash(first).await?;
+        assert!(is_new);
+
+        let second = TextBox::new("Alice".to_string(), content.clone());
+        let (existing, is_new) = manager.create_or_get_by_content_hash(second).await?;
+        assert!(!is_new);
+        assert_eq!(existing.id, created.id);
+
+        // 作者不同视为不同内容
+        let other = TextBox::new("Bob".to_string(), content);
+        let (other, is_new) = manager.create_or_get_by_content_hash(other).await?;
+        assert!(is_new);
+        assert_ne!(other.id, created.id);
+
+        manager.delete(&created.id).await?;
+        manager.delete(&other.id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要 Redis 运行
+    async fn test_concurrent_content_hash_creates_once() -> Result<()> {
+        let manager = create_test_manager().await?;
+        let content = format!("dedup race {}", uuid::Uuid::new_v4());
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let mut manager = manager.clone();
+                let text_box = TextBox::new("Alice".to_string(), content.clone());
+                tokio::spawn(async move { manager.create_or_get_by_content_hash(text_box).await })
+            })
+            .collect();
+
+        let mut ids = std::collections::HashSet::new();
+        let mut created = 0;
+        for task in tasks {
+            let (text_box, is_new) = task.await.expect("task panicked")?;
+            created += usize::from(is_new);
+            ids.insert(text_box.id);
+        }
+        assert_eq!(created, 1);
+        assert_eq!(ids.len(), 1);
+
+        let mut manager = manager;
+        for id in ids {
+            manager.delete(&id).await?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要 Redis 运行
+    async fn test_content_hash_key_follows_text_box() -> Result<()> {
+        let mut manager = create_test_manager().await?;
+        let content = format!("dedup ttl {}", uuid::Uuid::new_v4());
+
+        let text_box = TextBox::new("Alice".to_string(), content.clone())
+            .with_expires_at(chrono::Utc::now() + chrono::Duration::hours(1));
+        let hash_key = manager.hash_key(&text_box.content_hash());
+        let (created, _) = manager.create_or_get_by_content_hash(text_box).await?;
+
+        let mut conn = manager.conn.clone();
+        let ttl: i64 = conn.ttl(&hash_key).await?;
+        assert!(ttl > 0 && ttl <= 3600, "ttl={ttl}");
+
+        manager.delete(&created.id).await?;
+        let exists: bool = conn.exists(&hash_key).await?;
+        assert!(!exists);
+
+        // 删除后相同内容可以重新创建
+        let again = TextBox::new("Alice".to_string(), content);
+        let (recreated, is_new) = manager.create_or_get_by_content_hash(again).await?;
+        assert!(is_new);
+        assert_ne!(recreated.id, created.id);
+        manager.delete(&recreated.id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要 Redis 运行
+    async fn test_update_moves_content_hash_key() -> Result<()> {
+        let mut manager = create_test_manager().await?;
+        let original = format!("dedup edit {}", uuid::Uuid::new_v4());
+
+        let text_box = TextBox::new("Alice".to_string(), original.clone());
+        let old_hash_key = manager.hash_key(&text_box.content_hash());
+        let (mut created, _) = manager.create_or_get_by_content_hash(text_box).await?;
+
+        created.update_content(format!("{original} edited"));
+        let new_hash_key = manager.hash_key(&created.content_hash());
+        manager.update(created.clone()).await?;
+
+        let mut conn = manager.conn.clone();
+        let exists: bool = conn.exists(&old_hash_key).await?;
+        assert!(!exists);
+        let owner: Option<String> = conn.get(&new_hash_key).await?;
+        assert_eq!(owner.as_deref(), Some(created.id.as_str()));
+
+        // 原内容不再命中编辑过的 TextBox
+        let again = TextBox::new("Alice".to_string(), original);
+        let (recreated, is_new) = manager.create_or_get_by_content_hash(again).await?;
+        assert!(is_new);
+        assert_ne!(recreated.id, created.id);
+
+        manager.delete(&created.id).await?;
+        let exists: bool = conn.exists(&new_hash_key).await?;
+        assert!(!exists);
+        manager.delete(&recreated.id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要 Redis 运行
+    async fn test_exists_and_count() -> Result<()> {
+        let mut manager = create_test_manager().await?;
+
+        let text_box = TextBox::new("Alice".to_string(), "exists".to_string());
+        let id = text_box.id.clone();
+        assert!(!manager.exists(&id).await?);
+
+        let before = manager.count().await?;
+        manager.create(text_box).await?;
+        assert!(manager.exists(&id).await?);
+        assert_eq!(manager.count().await?, before + 1);
+
+        // exists 不计入浏览次数
+        let fetched = manager.get(&id).await?.expect("created box");
+        assert_eq!(fetched.metadata.view_count, 1);
+
+        manager.delete(&id).await?;
+        assert!(!manager.exists(&id).await?);
+        Ok(())
+    }
+
     #[tokio::test]
     #[ignore] // 需要 Redis 运行
     async fn test_list_pagination() -> Result<()> {
@@ -356,4 +988,137 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    #[ignore] // 需要 Redis 运行
+    async fn test_apply_default_expiry_skips_explicit_never_expire() -> Result<()> {
+        let config = RedisConfig::default()
+            .with_prefix(format!("anybox_test_expiry_{}", uuid::Uuid::new_v4()));
+        let mut manager = TextBoxManager::new(config).await?;
+
+        let unset = TextBox::new("Alice".to_string(), "no expiry".to_string());
+        let never = TextBox::new("Bob".to_string(), "keep".to_string()).with_never_expires();
+        let explicit_at = chrono::Utc::now() + chrono::Duration::hours(2);
+        let explicit =
+            TextBox::new("Carol".to_string(), "explicit".to_string()).with_expires_at(explicit_at);
+        for text_box in [&unset, &never, &explicit] {
+            manager.create(text_box.clone()).await?;
+        }
+
+        assert_eq!(manager.apply_default_expiry(24).await?, 1);
+
+        let refreshed = manager.get_without_increment(&unset.id).await?.unwrap();
+        let expires_at = refreshed.metadata.expires_at.expect("expiry applied");
+        assert!(expires_at > chrono::Utc::now() + chrono::Duration::hours(23));
+        let key = manager.text_box_key(&unset.id);
+        let ttl: i64 = manager.conn.clone().ttl(&key).await?;
+        assert!(ttl > 0, "TTL should be installed, got {ttl}");
+
+        let skipped = manager.get_without_increment(&never.id).await?.unwrap();
+        assert!(skipped.metadata.expires_at.is_none());
+        let kept = manager.get_without_increment(&explicit.id).await?.unwrap();
+        assert_eq!(
+            kept.metadata.expires_at.map(|t| t.timestamp()),
+            Some(explicit_at.timestamp())
+        );
+
+        // 再次执行时没有需要补充的
+        assert_eq!(manager.apply_default_expiry(24).await?, 0);
+        assert!(manager.apply_default_expiry(0).await.is_err());
+
+        for text_box in [&unset, &never, &explicit] {
+            manager.delete(&text_box.id).await?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要 Redis 运行
+    async fn test_add_and_remove_tag() -> Result<()> {
+        let mut manager = create_test_manager().await?;
+
+        let text_box =
+            TextBox::new("Alice".to_string(), "tags".to_string()).with_tags(vec!["a".to_string()]);
+        let id = text_box.id.clone();
+        manager.create(text_box).await?;
+
+        let added = manager.add_tag(&id, "b").await?;
+        assert_eq!(added.metadata.tags, ["a", "b"]);
+
+        // 重复添加与移除不存在的标签都是空操作
+        let duplicate = manager.add_tag(&id, "a").await?;
+        assert_eq!(duplicate.metadata.tags, ["a", "b"]);
+        assert_eq!(duplicate.metadata.updated_at, added.metadata.updated_at);
+        let unchanged = manager.remove_tag(&id, "missing").await?;
+        assert_eq!(unchanged.metadata.updated_at, added.metadata.updated_at);
+
+        let removed = manager.remove_tag(&id, "a").await?;
+        assert_eq!(removed.metadata.tags, ["b"]);
+        assert_eq!(removed.metadata.view_count, 0);
+        assert!(matches!(
+            manager.add_tag("missing", "a").await,
+            Err(AnyboxError::NotFound(_))
+        ));
+
+        manager.delete(&id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要 Redis 运行
+    async fn test_list_sorted_by_updated() -> Result<()> {
+        let config = RedisConfig::default()
+            .with_prefix(format!("anybox_test_sort_{}", uuid::Uuid::new_v4()));
+        let mut manager = TextBoxManager::new(config).await?;
+
+        let mut older = TextBox::new("Alice".to_string(), "older".to_string());
+        older.metadata.created_at = chrono::Utc::now() - chrono::Duration::minutes(5);
+        older.metadata.updated_at = older.metadata.created_at;
+        let newer = TextBox::new("Bob".to_string(), "newer".to_string());
+        manager.create(older.clone()).await?;
+        manager.create(newer.clone()).await?;
+
+        older.update_content("edited".to_string());
+        manager.update(older.clone()).await?;
+
+        let ids = |result: PaginatedResult<TextBox>| -> Vec<String> {
+            result.items.into_iter().map(|t| t.id).collect()
+        };
+        let by_created = manager.list(PaginationParams::new(1, 10)).await?;
+        assert_eq!(ids(by_created), vec![newer.id.clone(), older.id.clone()]);
+        let by_updated = manager
+            .list(PaginationParams::new(1, 10).with_sort(SortBy::Updated))
+            .await?;
+        assert_eq!(ids(by_updated), vec![older.id.clone(), newer.id.clone()]);
+
+        manager.delete(&older.id).await?;
+        manager.delete(&newer.id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要 Redis 运行
+    async fn test_list_by_updated_backfills_legacy_entries() -> Result<()> {
+        let config = RedisConfig::default()
+            .with_prefix(format!("anybox_test_backfill_{}", uuid::Uuid::new_v4()));
+        let mut manager = TextBoxManager::new(config).await?;
+
+        let legacy = manager
+            .create(TextBox::new("Alice".to_string(), "legacy".to_string()))
+            .await?;
+        // 模拟引入修改时间索引之前创建的 TextBox
+        let updated_index_key = manager.updated_index_key();
+        let mut conn = manager.conn.clone();
+        conn.zrem::<_, _, ()>(&updated_index_key, &legacy.id)
+            .await?;
+
+        let by_updated = manager
+            .list(PaginationParams::new(1, 10).with_sort(SortBy::Updated))
+            .await?;
+        assert_eq!(by_updated.total, 1);
+        assert_eq!(by_updated.items[0].id, legacy.id);
+
+        manager.delete(&legacy.id).await?;
+        Ok(())
+    }
 }
@@ -1,36 +1,54 @@
-use anybox::{PaginationParams, RedisConfig, TextBox, TextBoxManager};
+use crate::pagination::{PageInfo, pagination_headers};
+use anybox::{AnyboxError, PaginationParams, RedisConfig, RetryPolicy, TextBox, TextBoxManager};
 use axum::{
     Router,
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    extract::{OriginalUri, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tracing::{error, info};
+use util::task::{PeriodicHandle, PeriodicTask};
+use utoipa::{IntoParams, ToSchema};
 
 /// Anybox 服务状态
 #[derive(Clone, Debug)]
 pub struct AnyboxState {
     manager: Arc<Mutex<TextBoxManager>>,
+    /// 渲染时是否允许原样输出 HTML 格式内容
+    allow_raw_html: bool,
+    /// 请求未指定过期时间时的默认过期小时数
+    default_expire_hours: Option<u64>,
+    /// 管理接口令牌，未配置时不开放管理接口
+    admin_token: Option<String>,
 }
 
 impl AnyboxState {
     pub async fn new(config: config::anybox::AnyboxConfig) -> anyhow::Result<Self> {
-        let redis_config = RedisConfig::new(config.redis_url).with_prefix(config.key_prefix);
+        let redis_config = RedisConfig::new(config.redis_url.clone())
+            .with_prefix(config.key_prefix.clone())
+            .with_retry(RetryPolicy::new(
+                config.retry_max,
+                Duration::from_millis(config.retry_backoff_ms),
+            ));
 
         let manager = TextBoxManager::new(redis_config).await?;
 
         Ok(Self {
             manager: Arc::new(Mutex::new(manager)),
+            allow_raw_html: config.render_allow_raw_html,
+            default_expire_hours: config.default_expire_hours,
+            admin_token: config.admin_token,
         })
     }
 }
 
 /// 创建 TextBox 请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateTextBoxRequest {
     pub author: String,
     pub content: String,
@@ -44,36 +62,110 @@ pub struct CreateTextBoxRequest {
     pub tags: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_public: Option<bool>,
+    /// 过期小时数，未指定时使用配置的默认值，0 表示永不过期
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expire_hours: Option<u64>,
 }
 
+/// 添加标签请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddTextBoxTagRequest {
+    pub tag: String,
+}
+
 /// TextBox 响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TextBoxResponse {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
     pub data: Option<TextBox>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
 /// 列表响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ListResponse {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
     pub data: Option<anybox::PaginatedResult<TextBox>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
+/// 补充默认过期时间的查询参数
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ApplyDefaultExpiryQuery {
+    /// 过期小时数，不指定时使用配置的 default_expire_hours
+    pub hours: Option<u64>,
+}
+
+/// 补充默认过期时间的结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApplyDefaultExpiryResponse {
+    pub success: bool,
+    /// 补充了过期时间的 TextBox 数量
+    pub updated: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ApplyDefaultExpiryResponse {
+    fn failed(status: StatusCode, error: impl Into<String>) -> (StatusCode, Json<Self>) {
+        (
+            status,
+            Json(Self {
+                success: false,
+                updated: 0,
+                error: Some(error.into()),
+            }),
+        )
+    }
+}
+
+/// 导入结果汇总
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct ImportSummary {
+    /// 成功导入的数量
+    pub imported: usize,
+    /// 已存在而被跳过的 id
+    pub skipped: Vec<String>,
+    /// 解析或写入失败的记录
+    pub errors: Vec<String>,
+}
+
+/// 创建 TextBox 查询参数
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CreateTextBoxQuery {
+    /// 作者与内容相同的 TextBox 已存在时返回已有的，而不是新建
+    #[serde(default)]
+    pub dedup: bool,
+}
+
 /// 创建 TextBox
+///
+/// POST /textbox?dedup=true 时按作者与内容去重
+#[utoipa::path(
+    post,
+    path = "/api/anybox/textbox",
+    tag = "anybox",
+    params(CreateTextBoxQuery),
+    request_body = CreateTextBoxRequest,
+    responses(
+        (status = 200, description = "创建成功", body = TextBoxResponse),
+        (status = 400, description = "参数错误", body = TextBoxResponse),
+    )
+)]
 async fn create_textbox(
     State(state): State<AnyboxState>,
+    Query(query): Query<CreateTextBoxQuery>,
     Json(req): Json<CreateTextBoxRequest>,
 ) -> Result<Json<TextBoxResponse>, (StatusCode, Json<TextBoxResponse>)> {
-    info!("创建 TextBox: author={}", req.author);
+    info!("创建 TextBox: author={}, dedup={}", req.author, query.dedup);
 
     let mut text_box = TextBox::new(req.author, req.content);
 
@@ -81,11 +173,8 @@ async fn create_textbox(
         text_box = text_box.with_title(title);
     }
 
-    if let Some(format_str) = req.format
-        && let Some(format) = anybox::TextFormat::parse(&format_str)
-    {
-        text_box = text_box.with_format(format);
-    }
+    let format = resolve_format(req.format.as_deref(), &text_box.content);
+    text_box = text_box.with_format(format);
 
     if let Some(language) = req.language {
         text_box = text_box.with_language(language);
@@ -99,13 +188,46 @@ async fn create_textbox(
         text_box = text_box.with_public(is_public);
     }
 
-    if let Some(expire_hours) = req.expire_hours {
-        let expires_at = chrono::Utc::now() + chrono::Duration::hours(expire_hours as i64);
-        text_box = text_box.with_expires_at(expires_at);
+    let expires_at = resolve_expires_at(
+        req.expire_hours,
+        state.default_expire_hours,
+        chrono::Utc::now(),
+    );
+    match expires_at {
+        Ok(Some(expires_at)) => text_box = text_box.with_expires_at(expires_at),
+        // 明确要求永不过期，之后批量补充默认过期时间时保留
+        Ok(None) if req.expire_hours == Some(0) => text_box = text_box.with_never_expires(),
+        _ => {}
+    }
+
+    // 过期时间与必填字段在访问存储前校验
+    if let Err(e) = expires_at.and_then(|_| text_box.validate()) {
+        info!("拒绝创建 TextBox: {}", e);
+        return Err((
+            error_status(&e),
+            Json(TextBoxResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+            }),
+        ));
     }
 
     let mut manager = state.manager.lock().await;
-    match manager.create(text_box).await {
+    let result = if query.dedup {
+        manager
+            .create_or_get_by_content_hash(text_box)
+            .await
+            .map(|(text_box, created)| {
+                if !created {
+                    info!("内容重复，返回已有 TextBox: id={}", text_box.id);
+                }
+                text_box
+            })
+    } else {
+        manager.create(text_box).await
+    };
+    match result {
         Ok(created) => Ok(Json(TextBoxResponse {
             success: true,
             data: Some(created),
@@ -114,7 +236,7 @@ async fn create_textbox(
         Err(e) => {
             error!("创建 TextBox 失败: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
                 Json(TextBoxResponse {
                     success: false,
                     data: None,
@@ -125,20 +247,154 @@ async fn create_textbox(
     }
 }
 
+/// 计算过期时间：请求指定的小时数优先于默认值，0 表示永不过期，
+/// 超过 [`anybox::MAX_EXPIRE_HOURS`] 时返回校验错误
+fn resolve_expires_at(
+    requested_hours: Option<u64>,
+    default_hours: Option<u64>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> anybox::Result<Option<chrono::DateTime<chrono::Utc>>> {
+    match requested_hours.or(default_hours) {
+        None | Some(0) => Ok(None),
+        Some(hours) => anybox::expires_after_hours(hours, now).map(Some),
+    }
+}
+
 /// 获取 TextBox
+///
+/// 响应携带基于内容的 `ETag`；请求的 `If-None-Match` 匹配时返回 304。
+/// 304 同样计入浏览次数。
+#[utoipa::path(
+    get,
+    path = "/api/anybox/textbox/{id}",
+    tag = "anybox",
+    params(("id" = String, Path, description = "TextBox ID")),
+    responses(
+        (status = 200, description = "TextBox 内容", body = TextBoxResponse),
+        (status = 304, description = "内容未变化"),
+        (status = 404, description = "不存在或已过期", body = TextBoxResponse),
+    )
+)]
 async fn get_textbox(
     State(state): State<AnyboxState>,
     Path(id): Path<String>,
-) -> Result<Json<TextBoxResponse>, (StatusCode, Json<TextBoxResponse>)> {
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<TextBoxResponse>)> {
     info!("获取 TextBox: id={}", id);
 
     let mut manager = state.manager.lock().await;
     match manager.get(&id).await {
-        Ok(Some(text_box)) => Ok(Json(TextBoxResponse {
-            success: true,
-            data: Some(text_box),
-            error: None,
-        })),
+        Ok(Some(text_box)) => Ok(conditional_response(&headers, text_box)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(TextBoxResponse {
+                success: false,
+                data: None,
+                error: Some("TextBox 不存在".to_string()),
+            }),
+        )),
+        Err(e) => {
+            error!("获取 TextBox 失败: {}", e);
+            Err((
+                error_status(&e),
+                Json(TextBoxResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                }),
+            ))
+        }
+    }
+}
+
+/// 检查 TextBox 是否存在
+///
+/// HEAD /textbox/:id 返回 200 或 404，无响应体，不计入浏览次数
+async fn head_textbox(State(state): State<AnyboxState>, Path(id): Path<String>) -> StatusCode {
+    let mut manager = state.manager.lock().await;
+    match manager.exists(&id).await {
+        Ok(true) => StatusCode::OK,
+        Ok(false) => StatusCode::NOT_FOUND,
+        Err(e) => {
+            error!("检查 TextBox 是否存在失败: {}", e);
+            error_status(&e)
+        }
+    }
+}
+
+/// 渲染 TextBox 为 HTML
+///
+/// GET /textbox/:id/render
+/// 按帖子格式渲染：Markdown 转 HTML 并清洗，代码按 language 高亮，纯文本转义
+async fn render_textbox(
+    State(state): State<AnyboxState>,
+    Path(id): Path<String>,
+) -> Result<Response, (StatusCode, Json<TextBoxResponse>)> {
+    info!("渲染 TextBox: id={}", id);
+
+    let mut manager = state.manager.lock().await;
+    match manager.get(&id).await {
+        Ok(Some(text_box)) => Ok((
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            anybox::render_html(&text_box, state.allow_raw_html),
+        )
+            .into_response()),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(TextBoxResponse {
+                success: false,
+                data: None,
+                error: Some("TextBox 不存在".to_string()),
+            }),
+        )),
+        Err(e) => {
+            error!("获取 TextBox 失败: {}", e);
+            Err((
+                error_status(&e),
+                Json(TextBoxResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                }),
+            ))
+        }
+    }
+}
+
+/// 存储错误对应的 HTTP 状态码
+fn error_status(err: &AnyboxError) -> StatusCode {
+    match err {
+        AnyboxError::NotFound(_) => StatusCode::NOT_FOUND,
+        AnyboxError::Validation(_) => StatusCode::BAD_REQUEST,
+        AnyboxError::Connection(_) => StatusCode::SERVICE_UNAVAILABLE,
+        AnyboxError::LimitExceeded(_) => StatusCode::INSUFFICIENT_STORAGE,
+        AnyboxError::Serialization(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// 获取 TextBox 原始内容
+///
+/// GET /textbox/:id/raw
+/// 按帖子格式设置 Content-Type（如 CSV 为 `text/csv`，TOML 为 `application/toml`），HTML 按纯文本返回
+#[utoipa::path(
+    get,
+    path = "/api/anybox/textbox/{id}/raw",
+    tag = "anybox",
+    params(("id" = String, Path, description = "TextBox ID")),
+    responses(
+        (status = 200, description = "原始内容", content_type = "text/plain", body = String),
+        (status = 404, description = "不存在或已过期", body = TextBoxResponse),
+    )
+)]
+async fn raw_textbox(
+    State(state): State<AnyboxState>,
+    Path(id): Path<String>,
+) -> Result<Response, (StatusCode, Json<TextBoxResponse>)> {
+    info!("获取 TextBox 原始内容: id={}", id);
+
+    let mut manager = state.manager.lock().await;
+    match manager.get(&id).await {
+        Ok(Some(text_box)) => Ok(raw_response(text_box)),
         Ok(None) => Err((
             StatusCode::NOT_FOUND,
             Json(TextBoxResponse {
@@ -150,7 +406,7 @@ async fn get_textbox(
         Err(e) => {
             error!("获取 TextBox 失败: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
                 Json(TextBoxResponse {
                     success: false,
                     data: None,
@@ -161,27 +417,104 @@ async fn get_textbox(
     }
 }
 
+/// 未指定格式或格式无法识别时按内容猜测
+fn resolve_format(requested: Option<&str>, content: &str) -> anybox::TextFormat {
+    requested
+        .and_then(anybox::TextFormat::parse)
+        .unwrap_or_else(|| anybox::TextFormat::detect(content))
+}
+
+/// 原始内容来自用户，禁止浏览器嗅探类型并以沙箱方式处理，防止被当作页面执行
+fn raw_response(text_box: TextBox) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, text_box.format.content_type()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+            (header::CONTENT_SECURITY_POLICY, "sandbox"),
+        ],
+        text_box.content,
+    )
+        .into_response()
+}
+
+/// 根据 `If-None-Match` 返回 304 或带 `ETag` 的完整响应
+fn conditional_response(headers: &HeaderMap, text_box: TextBox) -> Response {
+    let etag = text_box.etag();
+
+    let matched = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+        });
+
+    if matched {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    (
+        [(header::ETAG, etag)],
+        Json(TextBoxResponse {
+            success: true,
+            data: Some(text_box),
+            error: None,
+        }),
+    )
+        .into_response()
+}
+
 /// 列出 TextBox
+#[utoipa::path(
+    get,
+    path = "/api/anybox/textbox",
+    tag = "anybox",
+    params(
+        ("page" = Option<u32>, Query, description = "页码，从 1 开始"),
+        ("page_size" = Option<u32>, Query, description = "每页数量"),
+        ("sort" = Option<String>, Query, description = "排序字段：created（默认）或 updated，均为倒序"),
+    ),
+    responses(
+        (status = 200, description = "分页列表，分页信息同时写入 X-Total-Count/Link 头", body = ListResponse),
+        (status = 400, description = "排序字段不支持"),
+    )
+)]
 async fn list_textboxes(
     State(state): State<AnyboxState>,
+    OriginalUri(uri): OriginalUri,
     Query(params): Query<PaginationParams>,
-) -> Result<Json<ListResponse>, (StatusCode, Json<ListResponse>)> {
+) -> Result<(HeaderMap, Json<ListResponse>), (StatusCode, Json<ListResponse>)> {
     info!(
-        "列出 TextBox: page={}, page_size={}",
-        params.page, params.page_size
+        "列出 TextBox: page={}, page_size={}, sort={:?}",
+        params.page, params.page_size, params.sort
     );
 
     let mut manager = state.manager.lock().await;
     match manager.list(params).await {
-        Ok(result) => Ok(Json(ListResponse {
-            success: true,
-            data: Some(result),
-            error: None,
-        })),
+        Ok(result) => {
+            let headers = pagination_headers(
+                &uri,
+                PageInfo {
+                    total: result.total,
+                    page: result.page,
+                    total_pages: result.total_pages,
+                },
+            );
+            Ok((
+                headers,
+                Json(ListResponse {
+                    success: true,
+                    data: Some(result),
+                    error: None,
+                }),
+            ))
+        }
         Err(e) => {
             error!("列出 TextBox 失败: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
                 Json(ListResponse {
                     success: false,
                     data: None,
@@ -193,6 +526,16 @@ async fn list_textboxes(
 }
 
 /// 删除 TextBox
+#[utoipa::path(
+    delete,
+    path = "/api/anybox/textbox/{id}",
+    tag = "anybox",
+    params(("id" = String, Path, description = "TextBox ID")),
+    responses(
+        (status = 200, description = "删除成功", body = TextBoxResponse),
+        (status = 404, description = "不存在", body = TextBoxResponse),
+    )
+)]
 async fn delete_textbox(
     State(state): State<AnyboxState>,
     Path(id): Path<String>,
@@ -217,7 +560,7 @@ async fn delete_textbox(
         Err(e) => {
             error!("删除 TextBox 失败: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
                 Json(TextBoxResponse {
                     success: false,
                     data: None,
@@ -228,53 +571,420 @@ async fn delete_textbox(
     }
 }
 
-/// 健康检查
-async fn health_check() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "status": "ok",
-        "service": "anybox-api",
-        "version": env!("CARGO_PKG_VERSION")
-    }))
+/// 添加标签
+///
+/// POST /textbox/:id/tags，标签已存在时原样返回
+#[utoipa::path(
+    post,
+    path = "/api/anybox/textbox/{id}/tags",
+    tag = "anybox",
+    params(("id" = String, Path, description = "TextBox ID")),
+    request_body = AddTextBoxTagRequest,
+    responses(
+        (status = 200, description = "添加成功", body = TextBoxResponse),
+        (status = 400, description = "标签为空", body = TextBoxResponse),
+        (status = 404, description = "不存在", body = TextBoxResponse),
+    )
+)]
+async fn add_textbox_tag(
+    State(state): State<AnyboxState>,
+    Path(id): Path<String>,
+    Json(req): Json<AddTextBoxTagRequest>,
+) -> Result<Json<TextBoxResponse>, (StatusCode, Json<TextBoxResponse>)> {
+    info!("添加 TextBox 标签: id={}, tag={}", id, req.tag);
+
+    let mut manager = state.manager.lock().await;
+    match manager.add_tag(&id, &req.tag).await {
+        Ok(text_box) => Ok(Json(TextBoxResponse {
+            success: true,
+            data: Some(text_box),
+            error: None,
+        })),
+        Err(e) => {
+            error!("添加 TextBox 标签失败: {}", e);
+            Err((
+                error_status(&e),
+                Json(TextBoxResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                }),
+            ))
+        }
+    }
 }
 
-/// 启动定时清理任务
-pub fn start_cleanup_task(state: AnyboxState, interval_secs: u64) {
-    info!("启动 Anybox 清理任务: 间隔={}秒", interval_secs);
+/// 移除标签
+///
+/// DELETE /textbox/:id/tags/:tag，标签不存在时原样返回
+#[utoipa::path(
+    delete,
+    path = "/api/anybox/textbox/{id}/tags/{tag}",
+    tag = "anybox",
+    params(
+        ("id" = String, Path, description = "TextBox ID"),
+        ("tag" = String, Path, description = "要移除的标签"),
+    ),
+    responses(
+        (status = 200, description = "移除成功", body = TextBoxResponse),
+        (status = 404, description = "不存在", body = TextBoxResponse),
+    )
+)]
+async fn remove_textbox_tag(
+    State(state): State<AnyboxState>,
+    Path((id, tag)): Path<(String, String)>,
+) -> Result<Json<TextBoxResponse>, (StatusCode, Json<TextBoxResponse>)> {
+    info!("移除 TextBox 标签: id={}, tag={}", id, tag);
+
+    let mut manager = state.manager.lock().await;
+    match manager.remove_tag(&id, &tag).await {
+        Ok(text_box) => Ok(Json(TextBoxResponse {
+            success: true,
+            data: Some(text_box),
+            error: None,
+        })),
+        Err(e) => {
+            error!("移除 TextBox 标签失败: {}", e);
+            Err((
+                error_status(&e),
+                Json(TextBoxResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                }),
+            ))
+        }
+    }
+}
+
+/// 校验管理令牌：未配置令牌时管理接口视为不存在，令牌不匹配时返回 401
+fn check_admin(admin_token: Option<&str>, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let Some(expected) = admin_token else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    if util::server::bearer_token_matches(headers, expected) {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// 为没有过期时间的 TextBox 补充默认过期时间
+///
+/// POST /textbox/admin/apply-default-expiry，需携带 `Authorization: Bearer <admin_token>`；
+/// 创建时明确指定永不过期的 TextBox 会被跳过
+#[utoipa::path(
+    post,
+    path = "/api/anybox/textbox/admin/apply-default-expiry",
+    tag = "anybox",
+    params(ApplyDefaultExpiryQuery),
+    responses(
+        (status = 200, description = "更新数量", body = ApplyDefaultExpiryResponse),
+        (status = 400, description = "未指定且未配置默认过期时间，或小时数超出范围", body = ApplyDefaultExpiryResponse),
+        (status = 401, description = "管理令牌无效", body = ApplyDefaultExpiryResponse),
+        (status = 404, description = "未配置管理令牌", body = ApplyDefaultExpiryResponse),
+    )
+)]
+async fn apply_default_expiry(
+    State(state): State<AnyboxState>,
+    headers: HeaderMap,
+    Query(query): Query<ApplyDefaultExpiryQuery>,
+) -> Result<Json<ApplyDefaultExpiryResponse>, (StatusCode, Json<ApplyDefaultExpiryResponse>)> {
+    check_admin(state.admin_token.as_deref(), &headers).map_err(|status| {
+        ApplyDefaultExpiryResponse::failed(status, "管理令牌无效或管理接口未启用")
+    })?;
+
+    let Some(hours) = query.hours.or(state.default_expire_hours) else {
+        return Err(ApplyDefaultExpiryResponse::failed(
+            StatusCode::BAD_REQUEST,
+            "未指定 hours，且未配置 default_expire_hours",
+        ));
+    };
+    // 超出范围的小时数会让过期时间溢出，访问存储前拒绝
+    if let Err(e) = anybox::expires_after_hours(hours, chrono::Utc::now()) {
+        return Err(ApplyDefaultExpiryResponse::failed(
+            StatusCode::BAD_REQUEST,
+            e.to_string(),
+        ));
+    }
+    info!("补充默认过期时间: hours={}", hours);
+
+    let mut manager = state.manager.lock().await;
+    match manager.apply_default_expiry(hours).await {
+        Ok(updated) => Ok(Json(ApplyDefaultExpiryResponse {
+            success: true,
+            updated,
+            error: None,
+        })),
+        Err(e) => {
+            error!("补充默认过期时间失败: {}", e);
+            Err(ApplyDefaultExpiryResponse::failed(
+                error_status(&e),
+                e.to_string(),
+            ))
+        }
+    }
+}
+
+/// 从 NDJSON 导入 TextBox，保留 id 与时间戳，已存在的 id 跳过
+#[utoipa::path(
+    post,
+    path = "/api/anybox/textbox/import",
+    tag = "anybox",
+    request_body(content = String, content_type = "application/x-ndjson", description = "每行一个 TextBox JSON"),
+    responses((status = 200, description = "导入结果汇总", body = ImportSummary))
+)]
+async fn import_textboxes(State(state): State<AnyboxState>, body: String) -> Json<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let mut manager = state.manager.lock().await;
+
+    for (index, line) in body.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let text_box: TextBox = match serde_json::from_str(line) {
+            Ok(text_box) => text_box,
+            Err(e) => {
+                summary
+                    .errors
+                    .push(format!("第 {} 行解析失败: {e}", index + 1));
+                continue;
+            }
+        };
+
+        match manager.create_preserving_id(&text_box).await {
+            Ok(true) => summary.imported += 1,
+            Ok(false) => summary.skipped.push(text_box.id),
+            Err(e) => {
+                error!("导入 TextBox 失败: id={}, {}", text_box.id, e);
+                summary.errors.push(format!("{}: {e}", text_box.id));
+            }
+        }
+    }
 
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
+    info!(
+        "导入 TextBox 完成: imported={}, skipped={}, errors={}",
+        summary.imported,
+        summary.skipped.len(),
+        summary.errors.len()
+    );
+    Json(summary)
+}
 
-        loop {
-            interval.tick().await;
+/// 健康检查，会向 Redis 发送 PING
+async fn health_check(State(state): State<AnyboxState>) -> Response {
+    crate::health::backend_health("anybox-api", async {
+        state.manager.lock().await.ping().await
+    })
+    .await
+}
+
+/// 启动定时清理任务，返回的句柄用于在退出时停止任务，停止时会再清理一次
+pub fn start_cleanup_task(state: AnyboxState, interval_secs: u64) -> PeriodicHandle {
+    info!("启动 Anybox 清理任务: 间隔={}秒", interval_secs);
 
-            let mut manager = state.manager.lock().await;
-            match manager.cleanup_expired().await {
-                Ok(count) => {
-                    if count > 0 {
-                        info!("清理过期 TextBox: 删除 {} 个", count);
+    let interval = Duration::from_secs(interval_secs);
+    PeriodicTask::new(interval)
+        .with_jitter(interval / 10)
+        .with_final_run(true)
+        .spawn(move || {
+            let state = state.clone();
+            async move {
+                let mut manager = state.manager.lock().await;
+                match manager.cleanup_expired().await {
+                    Ok(count) => {
+                        if count > 0 {
+                            info!("清理过期 TextBox: 删除 {} 个", count);
+                        }
+                    }
+                    Err(e) => {
+                        error!("清理过期 TextBox 失败: {}", e);
                     }
-                }
-                Err(e) => {
-                    error!("清理过期 TextBox 失败: {}", e);
                 }
             }
-        }
-    });
+        })
 }
 
 /// 创建 Anybox 路由
 pub async fn create_routes(config: config::anybox::AnyboxConfig) -> anyhow::Result<Router> {
+    let (routes, _cleanup) = create_routes_with_cleanup(config).await?;
+    Ok(routes)
+}
+
+/// 创建 Anybox 路由，同时返回清理任务句柄
+pub async fn create_routes_with_cleanup(
+    config: config::anybox::AnyboxConfig,
+) -> anyhow::Result<(Router, PeriodicHandle)> {
     // 异步初始化 state
     let state = AnyboxState::new(config.clone()).await?;
 
     // 启动清理任务
-    start_cleanup_task(state.clone(), config.cleanup_interval_secs);
+    let cleanup = start_cleanup_task(state.clone(), config.cleanup_interval_secs);
 
-    Ok(Router::new()
+    let routes = Router::new()
         .route("/health", get(health_check))
         .route("/textbox", post(create_textbox))
         .route("/textbox", get(list_textboxes))
-        .route("/textbox/:id", get(get_textbox))
+        .route("/textbox/import", post(import_textboxes))
+        .route(
+            "/textbox/admin/apply-default-expiry",
+            post(apply_default_expiry),
+        )
+        .route("/textbox/:id", get(get_textbox).head(head_textbox))
+        .route("/textbox/:id/raw", get(raw_textbox))
+        .route("/textbox/:id/render", get(render_textbox))
         .route("/textbox/:id", axum::routing::delete(delete_textbox))
-        .with_state(state))
+        .route("/textbox/:id/tags", post(add_textbox_tag))
+        .route(
+            "/textbox/:id/tags/:tag",
+            axum::routing::delete(remove_textbox_tag),
+        )
+        .with_state(state);
+    Ok((routes, cleanup))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_conditional_get_returns_304_for_matching_etag() {
+        let text_box = TextBox::new("Alice".to_string(), "Hello".to_string());
+
+        let first = conditional_response(&HeaderMap::new(), text_box.clone());
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers()[header::ETAG].clone();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag.clone());
+        let second = conditional_response(&headers, text_box.clone());
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(second.headers()[header::ETAG], etag);
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"stale\""));
+        let third = conditional_response(&headers, text_box);
+        assert_eq!(third.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_raw_response_uses_format_content_type() {
+        for (format, content_type) in [
+            (anybox::TextFormat::Csv, "text/csv; charset=utf-8"),
+            (anybox::TextFormat::Toml, "application/toml"),
+        ] {
+            let text_box =
+                TextBox::new("Alice".to_string(), "a,b\n1,2".to_string()).with_format(format);
+            let response = raw_response(text_box);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], content_type);
+        }
+    }
+
+    #[test]
+    fn test_resolve_format_detects_when_missing_or_unknown() {
+        assert_eq!(
+            resolve_format(None, r#"{"a": 1}"#),
+            anybox::TextFormat::Json
+        );
+        assert_eq!(
+            resolve_format(Some("bogus"), "a,b\n1,2\n"),
+            anybox::TextFormat::Csv
+        );
+        assert_eq!(
+            resolve_format(Some("markdown"), r#"{"a": 1}"#),
+            anybox::TextFormat::Markdown
+        );
+    }
+
+    #[test]
+    fn test_raw_response_serves_html_as_inert_text() {
+        let text_box = TextBox::new("Alice".to_string(), "<script>alert(1)</script>".to_string())
+            .with_format(anybox::TextFormat::Html);
+        let response = raw_response(text_box);
+        let headers = response.headers();
+        assert_eq!(headers[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::CONTENT_SECURITY_POLICY], "sandbox");
+    }
+
+    #[test]
+    fn test_default_expiry_applied_when_request_omits_it() {
+        let now = chrono::Utc::now();
+        assert_eq!(
+            resolve_expires_at(None, Some(24), now).unwrap(),
+            Some(now + chrono::Duration::hours(24))
+        );
+        assert_eq!(resolve_expires_at(None, None, now).unwrap(), None);
+    }
+
+    #[test]
+    fn test_explicit_expiry_overrides_default() {
+        let now = chrono::Utc::now();
+        assert_eq!(
+            resolve_expires_at(Some(2), Some(24), now).unwrap(),
+            Some(now + chrono::Duration::hours(2))
+        );
+    }
+
+    #[test]
+    fn test_zero_expiry_never_expires() {
+        let now = chrono::Utc::now();
+        assert_eq!(resolve_expires_at(Some(0), Some(24), now).unwrap(), None);
+        assert_eq!(resolve_expires_at(Some(0), None, now).unwrap(), None);
+    }
+
+    #[test]
+    fn test_out_of_range_expiry_is_rejected() {
+        let now = chrono::Utc::now();
+        for hours in [anybox::MAX_EXPIRE_HOURS + 1, u64::MAX] {
+            let err = resolve_expires_at(Some(hours), None, now).unwrap_err();
+            assert!(matches!(err, AnyboxError::Validation(_)), "{hours}");
+            assert_eq!(error_status(&err), StatusCode::BAD_REQUEST);
+        }
+        assert!(resolve_expires_at(Some(anybox::MAX_EXPIRE_HOURS), None, now).is_ok());
+    }
+
+    #[test]
+    fn test_check_admin_requires_configured_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(check_admin(None, &headers), Err(StatusCode::NOT_FOUND));
+        assert_eq!(
+            check_admin(Some("secret"), &headers),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer wrong"),
+        );
+        assert_eq!(
+            check_admin(Some("secret"), &headers),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        assert_eq!(check_admin(Some("secret"), &headers), Ok(()));
+        assert_eq!(check_admin(None, &headers), Err(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_error_status_by_variant() {
+        assert_eq!(
+            error_status(&AnyboxError::NotFound("abc".to_string())),
+            StatusCode::NOT_FOUND
+        );
+        let invalid = serde_json::from_str::<TextBox>("{").unwrap_err();
+        assert_eq!(
+            error_status(&AnyboxError::from(invalid)),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            error_status(&AnyboxError::LimitExceeded("maxmemory".to_string())),
+            StatusCode::INSUFFICIENT_STORAGE
+        );
+    }
 }
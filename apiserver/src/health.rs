@@ -0,0 +1,92 @@
+//! 依赖后端存储的健康检查
+//!
+//! anybox（Redis）与 prompt（MySQL）的 `/health` 通过 [`backend_health`] 实际探测后端，
+//! 后端不可用时返回 503，避免负载均衡继续把流量转给无法工作的实例。
+
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+/// 后端探测超时时间，超时视为不可用
+const BACKEND_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 执行后端探测并生成健康检查响应
+///
+/// 可用时返回 200 与 `"backend": "up"`；不可用时返回 503、`"backend": "down"` 及错误信息。
+pub async fn backend_health<E: Display>(
+    service: &str,
+    check: impl Future<Output = Result<(), E>>,
+) -> Response {
+    backend_health_with_timeout(service, check, BACKEND_CHECK_TIMEOUT).await
+}
+
+async fn backend_health_with_timeout<E: Display>(
+    service: &str,
+    check: impl Future<Output = Result<(), E>>,
+    timeout: Duration,
+) -> Response {
+    let error = match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!("backend check timed out after {timeout:?}")),
+    };
+
+    let mut body = serde_json::json!({
+        "status": if error.is_none() { "ok" } else { "unavailable" },
+        "service": service,
+        "version": env!("CARGO_PKG_VERSION"),
+        "backend": if error.is_none() { "up" } else { "down" },
+    });
+    match error {
+        None => (StatusCode::OK, Json(body)).into_response(),
+        Some(error) => {
+            warn!("{service} backend check failed: {error}");
+            body["error"] = serde_json::Value::String(error);
+            (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+
+    async fn read(response: Response) -> (StatusCode, serde_json::Value) {
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_backend_up_and_down() {
+        let (status, body) =
+            read(backend_health("svc", async { Ok::<(), String>(()) }).await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["backend"], "up");
+        assert!(body.get("error").is_none());
+
+        let (status, body) =
+            read(backend_health("svc", async { Err("connection refused") }).await).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["service"], "svc");
+        assert_eq!(body["backend"], "down");
+        assert_eq!(body["error"], "connection refused");
+    }
+
+    #[tokio::test]
+    async fn test_backend_check_times_out() {
+        let check = std::future::pending::<Result<(), String>>();
+        let response = backend_health_with_timeout("svc", check, Duration::from_millis(10)).await;
+        let (status, body) = read(response).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body["error"].as_str().unwrap().contains("timed out"));
+    }
+}
@@ -1,34 +1,214 @@
+mod metadata;
+
 use axum::{
     Router,
-    extract::{Multipart, State},
-    http::StatusCode,
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State, multipart::Field},
+    http::{HeaderMap, StatusCode},
     response::Json,
-    routing::post,
+    routing::{get, post},
 };
+use chrono::{DateTime, Utc};
 use config::image_host::ImageHostingConfig;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageEncoder, ImageFormat, ImageReader};
+use pic_recog::utils::probe_file_format;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::io::AsyncWriteExt;
 use tracing::{error, info, warn};
 
 use util::metrics::ImageMetrics;
+use util::task::{PeriodicHandle, PeriodicTask};
+use utoipa::{IntoParams, ToSchema};
 
 #[derive(Clone)]
 pub struct ImageState {
     config: Arc<ImageHostingConfig>,
+    /// 存储目录占用的字节数，上传时累加，淘汰时按目录扫描结果校正
+    usage: Arc<AtomicU64>,
+    /// 幂等键 -> (记录时间, 上传状态)，上传失败时记录被移除
+    idempotency: Arc<Mutex<HashMap<String, (Instant, IdempotentUpload)>>>,
+}
+
+/// 幂等键对应的上传状态
+enum IdempotentUpload {
+    /// 首次上传仍在进行
+    Pending,
+    /// 上传成功，记录存储文件名
+    Done(String),
+}
+
+/// 占用幂等键的结果
+enum IdempotencyClaim {
+    /// 本次请求占用了该键，需继续上传
+    Claimed(IdempotencyGuard),
+    /// 窗口内已上传成功，返回存储文件名
+    Uploaded(String),
+    /// 相同键的上传正在进行
+    InProgress,
+}
+
+/// 本次请求占用的幂等键；未记录结果就被丢弃（上传失败或请求被取消）时释放该键
+struct IdempotencyGuard {
+    state: ImageState,
+    key: Option<String>,
+}
+
+impl IdempotencyGuard {
+    /// 记录上传成功的文件名，窗口内的重试直接返回它
+    fn finish(mut self, path: String) {
+        if let Some(key) = self.key.take() {
+            let mut uploads = self.state.lock_idempotency();
+            uploads.insert(key, (Instant::now(), IdempotentUpload::Done(path)));
+        }
+    }
+}
+
+impl Drop for IdempotencyGuard {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.state.lock_idempotency().remove(&key);
+        }
+    }
 }
 
 impl ImageState {
     pub fn new(config: ImageHostingConfig) -> Self {
+        let usage = scan_storage_usage(&config.storage_dir);
         Self {
             config: Arc::new(config),
+            usage: Arc::new(AtomicU64::new(usage)),
+            idempotency: Arc::default(),
+        }
+    }
+
+    fn idempotency_window(&self) -> Option<Duration> {
+        match self.config.idempotency_window_secs {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    fn lock_idempotency(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<String, (Instant, IdempotentUpload)>> {
+        self.idempotency.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 检查并占用幂等键，检查与占用在同一次加锁内完成；未开启幂等窗口时返回 `None`
+    ///
+    /// 顺带清除已过期的成功记录，进行中的记录由 [`IdempotencyGuard`] 负责移除。
+    fn claim_upload(&self, key: &str) -> Option<IdempotencyClaim> {
+        let window = self.idempotency_window()?;
+        let mut uploads = self.lock_idempotency();
+        uploads.retain(|_, (recorded_at, upload)| {
+            matches!(upload, IdempotentUpload::Pending) || recorded_at.elapsed() < window
+        });
+        let claim = match uploads.get(key) {
+            Some((_, IdempotentUpload::Pending)) => IdempotencyClaim::InProgress,
+            Some((_, IdempotentUpload::Done(path))) => IdempotencyClaim::Uploaded(path.clone()),
+            None => {
+                uploads.insert(key.to_string(), (Instant::now(), IdempotentUpload::Pending));
+                IdempotencyClaim::Claimed(IdempotencyGuard {
+                    state: self.clone(),
+                    key: Some(key.to_string()),
+                })
+            }
+        };
+        Some(claim)
+    }
+
+    /// 成功记录对应的文件已不存在时移除该记录
+    fn forget_upload(&self, key: &str, path: &str) {
+        let mut uploads = self.lock_idempotency();
+        if matches!(uploads.get(key), Some((_, IdempotentUpload::Done(done))) if done == path) {
+            uploads.remove(key);
+        }
+    }
+
+    /// 更新占用统计，返回更新后的值
+    fn adjust_usage(&self, added: u64, removed: u64) -> u64 {
+        let apply = |usage: u64| usage.saturating_add(added).saturating_sub(removed);
+        let previous = self
+            .usage
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |usage| {
+                Some(apply(usage))
+            })
+            .unwrap_or_default();
+        apply(previous)
+    }
+}
+
+/// 统计存储目录下文件的总大小，仅在启动时调用一次
+fn scan_storage_usage(storage_dir: &str) -> u64 {
+    walk_stored_files(FsPath::new(storage_dir))
+        .iter()
+        .map(|(_, metadata)| metadata.len())
+        .sum()
+}
+
+/// 递归列出存储目录下的文件，跳过以 `.` 开头的目录（上传临时目录）
+///
+/// 按日期分目录存储时文件位于 `YYYY/MM/DD/` 下，平铺存储时只有一层
+fn walk_stored_files(storage_path: &FsPath) -> Vec<(PathBuf, std::fs::Metadata)> {
+    let mut files = Vec::new();
+    let mut pending = vec![storage_path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                if !entry.file_name().to_string_lossy().starts_with('.') {
+                    pending.push(entry.path());
+                }
+            } else if metadata.is_file() {
+                files.push((entry.path(), metadata));
+            }
         }
     }
+    files
+}
+
+async fn list_stored_files(storage_path: &FsPath) -> Vec<(PathBuf, std::fs::Metadata)> {
+    let storage_path = storage_path.to_path_buf();
+    tokio::task::spawn_blocking(move || walk_stored_files(&storage_path))
+        .await
+        .unwrap_or_default()
+}
+
+/// 删除文件后逐级移除变空的日期目录，不会删除存储目录本身
+async fn remove_empty_parents(storage_path: &FsPath, file: &FsPath) {
+    let mut dir = file.parent();
+    while let Some(current) = dir
+        && current != storage_path
+        && current.starts_with(storage_path)
+    {
+        // 目录非空时删除失败，说明上层目录也不会变空
+        if tokio::fs::remove_dir(current).await.is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+/// 按日期分目录时的子目录，如 `2024/05/01`
+fn date_shard(now: DateTime<Utc>) -> String {
+    now.format("%Y/%m/%d").to_string()
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct UploadResponse {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -37,6 +217,99 @@ pub struct UploadResponse {
     pub error: Option<String>,
 }
 
+/// 上传查询参数
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UploadQuery {
+    /// 客户端指定的文件名已存在时是否覆盖
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// 客户端指定文件名的最大长度
+const MAX_KEY_LEN: usize = 128;
+
+/// 上传临时目录（存储目录下的子目录），文件写完后再移动到最终文件名，
+/// 清理与淘汰只扫描存储目录下的文件，不会处理写入中的临时文件
+const PARTIAL_DIR: &str = ".partial";
+
+/// 临时文件超过该时长未写入即视为上传中断（如进程退出）留下的残留
+const PARTIAL_MAX_AGE: Duration = Duration::from_secs(3600);
+
+/// multipart 中除文件外的其它字段与分隔符预留的请求体大小
+const MULTIPART_OVERHEAD_BYTES: usize = 1024 * 1024;
+
+/// 上传幂等键请求头，客户端重试时携带相同的值可避免重复存储
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// 图片元信息
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ImageMeta {
+    pub name: String,
+    pub size_bytes: u64,
+    /// 无法识别为图片时为空
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub format: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 校验文件名，只允许字母、数字、`.`、`-`、`_`，
+/// 不允许路径分隔符与以 `.` 开头（防止目录穿越和隐藏文件）
+fn validate_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        return Err(format!("文件名长度需在 1-{MAX_KEY_LEN} 之间"));
+    }
+    if key.starts_with('.') || key.contains("..") {
+        return Err(format!("非法文件名: {key}"));
+    }
+    if !key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    {
+        return Err(format!(
+            "非法文件名: {key}（仅允许字母、数字、'.'、'-'、'_'）"
+        ));
+    }
+    Ok(())
+}
+
+/// 校验客户端指定的文件名，扩展名统一替换为按文件内容识别出的扩展名
+///
+/// 避免把图片以 `.html`、`.svg` 等扩展名存储后被当作其他类型的文件访问
+fn sanitize_key(key: &str, extension: &str) -> Result<String, String> {
+    let key = key.trim();
+    validate_key(key)?;
+
+    let stem = key.rsplit_once('.').map_or(key, |(stem, _)| stem);
+    Ok(format!("{stem}.{extension}"))
+}
+
+/// 按文件头识别图片格式，返回该格式的规范扩展名，无法识别时返回 None
+fn sniff_extension(path: &FsPath) -> Option<&'static str> {
+    let format = ImageReader::open(path)
+        .ok()?
+        .with_guessed_format()
+        .ok()?
+        .format()?;
+    reencode_format(format)
+        .map(|(_, extension)| extension)
+        .or_else(|| format.extensions_str().first().copied())
+}
+
+/// 读取文件开头的若干字节，用于识别格式
+fn read_file_head(path: &FsPath) -> Option<Vec<u8>> {
+    use std::io::Read;
+
+    let mut head = Vec::with_capacity(16);
+    std::fs::File::open(path)
+        .ok()?
+        .take(16)
+        .read_to_end(&mut head)
+        .ok()?;
+    Some(head)
+}
+
 /// 生成唯一文件名：时间戳 + 随机数
 fn generate_filename(extension: &str) -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -71,174 +344,772 @@ fn get_extension(filename: Option<&str>, content_type: Option<&str>) -> String {
 }
 
 /// 处理图片上传
+///
+/// 可选的 `key` 字段指定存储文件名（需开启 `allow_client_key`），
+/// 文件名已存在时返回 409，`?overwrite=true` 时覆盖。
+/// 携带 `Idempotency-Key` 头重试时，窗口内（`idempotency_window_secs`）直接返回首次上传的结果。
+/// 配置了 `convert_to` 时先转换成目标格式再存储，原格式相同则不转换；
+/// 开启 `strip_metadata`（默认）时去除 EXIF 等元数据，像素数据不变，
+/// 无法去除元数据的 TIFF、HEIC、AVIF 返回 415。
+#[utoipa::path(
+    post,
+    path = "/api/image/upload",
+    tag = "image",
+    params(
+        UploadQuery,
+        ("Idempotency-Key" = Option<String>, Header, description = "幂等键，重试时携带相同的值避免重复存储"),
+    ),
+    request_body(
+        content_type = "multipart/form-data",
+        description = "`file` 图片文件，可选 `key` 指定存储文件名"
+    ),
+    responses(
+        (status = 200, description = "上传成功，返回存储路径", body = UploadResponse),
+        (status = 400, description = "缺少文件、文件名不合法或图片无法解码"),
+        (status = 409, description = "文件名已存在，或相同幂等键的上传正在进行"),
+        (status = 413, description = "文件超出大小限制"),
+        (status = 415, description = "开启 strip_metadata 时无法去除该格式的元数据"),
+    )
+)]
 pub async fn handle_upload(
     State(state): State<ImageState>,
+    Query(query): Query<UploadQuery>,
+    headers: HeaderMap,
     mut multipart: Multipart,
 ) -> Result<Json<UploadResponse>, (StatusCode, String)> {
     info!("收到图片上传请求");
 
-    // 确保存储目录存在
     let storage_path = PathBuf::from(&state.config.storage_dir);
-    if !storage_path.exists() {
-        tokio::fs::create_dir_all(&storage_path)
-            .await
-            .map_err(|e| {
-                error!("创建存储目录失败: {e}");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("创建存储目录失败: {e}"),
-                )
-            })?;
-    }
 
-    // 处理上传的文件
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
-        error!("读取上传字段失败: {e}");
-        (StatusCode::BAD_REQUEST, format!("读取上传字段失败: {e}"))
-    })? {
-        let name = field.name().unwrap_or("").to_string();
-        if name != "file" && name != "files" {
-            continue;
+    // 同一幂等键此前已上传成功且文件仍在时，直接返回上次的结果；
+    // 相同键的上传仍在进行时返回 409，由客户端稍后重试
+    let idempotency_key = headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let mut idempotency_guard = None;
+    if let Some(key) = idempotency_key {
+        loop {
+            match state.claim_upload(key) {
+                None => break,
+                Some(IdempotencyClaim::Claimed(guard)) => {
+                    idempotency_guard = Some(guard);
+                    break;
+                }
+                Some(IdempotencyClaim::Uploaded(prior)) => {
+                    if tokio::fs::try_exists(storage_path.join(&prior))
+                        .await
+                        .unwrap_or(false)
+                    {
+                        info!("幂等键命中，返回已上传的文件: {prior}");
+                        return Ok(Json(UploadResponse {
+                            success: true,
+                            path: Some(prior),
+                            error: None,
+                        }));
+                    }
+                    // 文件已被删除或淘汰，按新的上传处理
+                    state.forget_upload(key, &prior);
+                }
+                Some(IdempotencyClaim::InProgress) => {
+                    return Err((StatusCode::CONFLICT, "相同幂等键的上传正在进行".to_string()));
+                }
+            }
         }
+    }
+
+    // 确保存储目录与临时目录存在
+    let partial_dir = storage_path.join(PARTIAL_DIR);
+    tokio::fs::create_dir_all(&partial_dir).await.map_err(|e| {
+        error!("创建存储目录失败: {e}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("创建存储目录失败: {e}"),
+        )
+    })?;
+
+    let (key, upload) =
+        receive_upload(&mut multipart, &partial_dir, state.config.max_file_bytes).await?;
+    let Some(upload) = upload else {
+        // 没有找到文件字段
+        error!("未找到上传文件");
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "未找到上传文件（需要 'file' 或 'files' 字段）".to_string(),
+        ));
+    };
+
+    let upload = if state.config.convert_to.is_some() || state.config.strip_metadata {
+        reencode_upload(upload, state.config.clone()).await?
+    } else {
+        upload
+    };
 
-        let filename = field.file_name().map(|s| s.to_string());
-        let content_type = field.content_type().map(|s| s.to_string());
+    let committed = commit_upload(&state, &storage_path, &upload, key, query.overwrite).await;
+    // 移动成功后临时文件已不存在，失败时在这里清理
+    let _ = tokio::fs::remove_file(&upload.path).await;
+    let (new_filename, replaced) = committed?;
+
+    let file_size = upload.size;
+    info!("文件上传成功: {new_filename}, size: {file_size} bytes");
+
+    // 记录 metrics 指标
+    ImageMetrics::record_upload_success(file_size);
+
+    let usage = state.adjust_usage(file_size, replaced);
+    let max_total_bytes = state.config.max_total_bytes;
+    if max_total_bytes > 0 && usage > max_total_bytes {
+        evict_oldest_files(&state, &new_filename).await;
+    }
+
+    if let Some(guard) = idempotency_guard {
+        guard.finish(new_filename.clone());
+    }
+
+    // 返回相对路径
+    Ok(Json(UploadResponse {
+        success: true,
+        path: Some(new_filename),
+        error: None,
+    }))
+}
+
+/// 已写入临时目录的上传文件
+struct PartialUpload {
+    extension: String,
+    path: PathBuf,
+    size: u64,
+    /// 文件头能识别为图片格式，此时 `extension` 为识别出的扩展名
+    is_image: bool,
+}
+
+/// 按配置转换格式或去除元数据，返回指向新临时文件的上传信息
+///
+/// 无需重新编码时原样返回（只规范扩展名）；失败时删除临时文件
+async fn reencode_upload(
+    upload: PartialUpload,
+    config: Arc<ImageHostingConfig>,
+) -> Result<PartialUpload, (StatusCode, String)> {
+    let source = upload.path.clone();
+    let reencoded = tokio::task::spawn_blocking(move || reencode_image_file(upload, &config))
+        .await
+        .unwrap_or_else(|e| {
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("图片转换任务失败: {e}"),
+            ))
+        });
+    if reencoded.is_err() {
+        let _ = tokio::fs::remove_file(&source).await;
+    }
+    reencoded
+}
+
+/// 支持重新编码的格式及其扩展名，这几种格式也是可能携带 EXIF 的格式
+fn reencode_format(format: ImageFormat) -> Option<(ImageFormat, &'static str)> {
+    match format {
+        ImageFormat::Jpeg => Some((ImageFormat::Jpeg, "jpg")),
+        ImageFormat::Png => Some((ImageFormat::Png, "png")),
+        ImageFormat::WebP => Some((ImageFormat::WebP, "webp")),
+        _ => None,
+    }
+}
+
+fn reencode_image_file(
+    upload: PartialUpload,
+    config: &ImageHostingConfig,
+) -> Result<PartialUpload, (StatusCode, String)> {
+    let target = match config.convert_to.as_deref() {
+        Some(target) => Some(
+            ImageFormat::from_extension(target)
+                .and_then(reencode_format)
+                .ok_or_else(|| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("不支持的转换格式: {target}"),
+                    )
+                })?,
+        ),
+        None => None,
+    };
+    let decode_error = |e: &dyn std::fmt::Display| {
+        warn!("图片解码失败: {e}");
+        (StatusCode::BAD_REQUEST, format!("图片解码失败: {e}"))
+    };
+
+    if config.strip_metadata
+        && let Some(format) =
+            read_file_head(&upload.path).and_then(|head| metadata::unstrippable_format(&head))
+    {
+        warn!("无法去除 {format} 图片的元数据，拒绝上传");
+        return Err((
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!("无法去除 {format} 图片的元数据，请转换为 JPEG、PNG 或 WebP 后上传"),
+        ));
+    }
+
+    let reader = ImageReader::open(&upload.path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| decode_error(&e))?;
+    let source = reader.format();
+    // 只有可能携带 EXIF 的格式需要去除元数据，其他格式原样保存
+    let strip = config.strip_metadata && source.and_then(reencode_format).is_some();
+    let Some((target_format, extension)) =
+        target.or_else(|| source.filter(|_| strip).and_then(reencode_format))
+    else {
+        return Ok(upload);
+    };
+    let converting = source != Some(target_format);
+    if !converting && !strip {
+        return Ok(PartialUpload {
+            extension: extension.to_string(),
+            ..upload
+        });
+    }
+
+    let mut decoder = reader.into_decoder().map_err(|e| decode_error(&e))?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let path = upload.path.with_file_name(generate_filename("part"));
+    let encode_error = |e: &dyn std::fmt::Display| {
+        error!("图片编码失败: {e}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("图片编码失败: {e}"),
+        )
+    };
 
+    if !converting {
+        // 只去除元数据时改写容器结构，像素数据原样保留
+        drop(decoder);
+        let data = std::fs::read(&upload.path).map_err(|e| decode_error(&e))?;
+        let stripped = metadata::strip(target_format, &data, orientation).map_err(|e| {
+            warn!("去除图片元数据失败: {e}");
+            (StatusCode::BAD_REQUEST, format!("图片解码失败: {e}"))
+        })?;
+        if let Err(e) = std::fs::write(&path, &stripped) {
+            let _ = std::fs::remove_file(&path);
+            return Err(encode_error(&e));
+        }
+        let _ = std::fs::remove_file(&upload.path);
         info!(
-            "处理上传文件: filename={:?}, content_type={:?}",
-            filename, content_type
+            "已去除图片元数据: {} -> {} bytes",
+            upload.size,
+            stripped.len()
         );
+        ImageMetrics::record_metadata_stripped();
+        return Ok(PartialUpload {
+            extension: extension.to_string(),
+            path,
+            size: stripped.len() as u64,
+            is_image: true,
+        });
+    }
 
-        // 获取扩展名
-        let extension = get_extension(filename.as_deref(), content_type.as_deref());
+    // 转换格式时先按 EXIF 方向摆正，新文件不带 EXIF，像素方向保持不变
+    let icc_profile = decoder.icc_profile().ok().flatten();
+    let mut img = DynamicImage::from_decoder(decoder).map_err(|e| decode_error(&e))?;
+    img.apply_orientation(orientation);
+    // 灰度图的 ICC 配置不适用于转换后的 RGB 图像
+    let icc_profile = icc_profile.filter(|_| img.color().has_color());
 
-        // 生成唯一文件名
-        let new_filename = generate_filename(&extension);
-        let file_path = storage_path.join(&new_filename);
+    // 编码器只写入 ICC 色彩配置，不写入其他元数据
+    let encoded = std::fs::File::create(&path)
+        .map_err(image::ImageError::IoError)
+        .and_then(|file| {
+            let mut writer = std::io::BufWriter::new(file);
+            match target_format {
+                // JPEG 不支持透明通道，WebP 编码器只支持无损模式
+                ImageFormat::Jpeg => {
+                    let mut encoder =
+                        JpegEncoder::new_with_quality(&mut writer, config.convert_quality);
+                    if let Some(icc) = icc_profile {
+                        let _ = encoder.set_icc_profile(icc);
+                    }
+                    DynamicImage::from(img.to_rgb8()).write_with_encoder(encoder)
+                }
+                ImageFormat::WebP => {
+                    let mut encoder = WebPEncoder::new_lossless(&mut writer);
+                    if let Some(icc) = icc_profile {
+                        let _ = encoder.set_icc_profile(icc);
+                    }
+                    DynamicImage::from(img.to_rgba8()).write_with_encoder(encoder)
+                }
+                _ => {
+                    let mut encoder = PngEncoder::new(&mut writer);
+                    if let Some(icc) = icc_profile {
+                        let _ = encoder.set_icc_profile(icc);
+                    }
+                    img.write_with_encoder(encoder)
+                }
+            }?;
+            writer.flush().map_err(image::ImageError::IoError)
+        });
+    if let Err(e) = encoded {
+        let _ = std::fs::remove_file(&path);
+        return Err(encode_error(&e));
+    }
+
+    let size = std::fs::metadata(&path)
+        .map_err(|e| encode_error(&e))?
+        .len();
+    let _ = std::fs::remove_file(&upload.path);
+    info!(
+        "图片已重新编码为 {extension}: {} -> {size} bytes",
+        upload.size
+    );
+    ImageMetrics::record_conversion(extension);
+    if strip {
+        ImageMetrics::record_metadata_stripped();
+    }
+    Ok(PartialUpload {
+        extension: extension.to_string(),
+        path,
+        size,
+        is_image: true,
+    })
+}
+
+/// 读取上传字段：`key` 可以出现在文件之前或之后，只保存第一个文件
+///
+/// 文件逐块写入临时目录，不在内存中缓存整个文件；出错时删除已写入的部分
+async fn receive_upload(
+    multipart: &mut Multipart,
+    partial_dir: &FsPath,
+    max_file_bytes: u64,
+) -> Result<(Option<String>, Option<PartialUpload>), (StatusCode, String)> {
+    let mut key: Option<String> = None;
+    let mut upload: Option<PartialUpload> = None;
+    let result = async {
+        while let Some(field) = multipart.next_field().await.map_err(|e| {
+            error!("读取上传字段失败: {e}");
+            // 请求体超过限制时为 413，其余格式错误为 400
+            (e.status(), format!("读取上传字段失败: {e}"))
+        })? {
+            let name = field.name().unwrap_or("").to_string();
+            if name == "key" {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|e| (StatusCode::BAD_REQUEST, format!("读取 key 字段失败: {e}")))?;
+                key = Some(value);
+                continue;
+            }
+            if (name != "file" && name != "files") || upload.is_some() {
+                continue;
+            }
+
+            let filename = field.file_name().map(|s| s.to_string());
+            let content_type = field.content_type().map(|s| s.to_string());
+
+            info!(
+                "处理上传文件: filename={:?}, content_type={:?}",
+                filename, content_type
+            );
+
+            let path = partial_dir.join(generate_filename("part"));
+            let size = stream_field_to_file(field, &path, max_file_bytes).await?;
+            // 扩展名以文件内容为准，非图片时才使用客户端给出的文件名或 content-type
+            let sniff_path = path.clone();
+            let sniffed = tokio::task::spawn_blocking(move || sniff_extension(&sniff_path))
+                .await
+                .ok()
+                .flatten();
+            let extension = sniffed
+                .map(str::to_string)
+                .unwrap_or_else(|| get_extension(filename.as_deref(), content_type.as_deref()));
+            upload = Some(PartialUpload {
+                extension,
+                path,
+                size,
+                is_image: sniffed.is_some(),
+            });
+        }
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => Ok((key, upload)),
+        Err(e) => {
+            if let Some(upload) = upload {
+                let _ = tokio::fs::remove_file(&upload.path).await;
+            }
+            Err(e)
+        }
+    }
+}
 
-        // 读取文件数据
-        let data = field.bytes().await.map_err(|e| {
+/// 将上传字段逐块写入文件，超过 `max_bytes`（0 表示不限制）时中止并删除已写入的部分
+async fn stream_field_to_file(
+    mut field: Field<'_>,
+    path: &FsPath,
+    max_bytes: u64,
+) -> Result<u64, (StatusCode, String)> {
+    let mut file = tokio::fs::File::create(path).await.map_err(|e| {
+        error!("创建文件失败: {e}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("创建文件失败: {e}"),
+        )
+    })?;
+
+    let mut written: u64 = 0;
+    let result = async {
+        while let Some(chunk) = field.chunk().await.map_err(|e| {
             error!("读取文件数据失败: {e}");
-            (StatusCode::BAD_REQUEST, format!("读取文件数据失败: {e}"))
-        })?;
+            (e.status(), format!("读取文件数据失败: {e}"))
+        })? {
+            written += chunk.len() as u64;
+            if max_bytes > 0 && written > max_bytes {
+                warn!("上传文件超出大小限制: 已读取 {written} 字节, 上限 {max_bytes} 字节");
+                return Err((
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    format!("文件超出大小限制 (最多 {max_bytes} 字节)"),
+                ));
+            }
+            file.write_all(&chunk).await.map_err(|e| {
+                error!("写入文件失败: {e}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("写入文件失败: {e}"),
+                )
+            })?;
+        }
+        file.flush().await.map_err(|e| {
+            error!("写入文件失败: {e}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("写入文件失败: {e}"),
+            )
+        })
+    }
+    .await;
 
-        // 保存文件
-        let mut file = tokio::fs::File::create(&file_path).await.map_err(|e| {
-            error!("创建文件失败: {e}");
+    match result {
+        Ok(()) => Ok(written),
+        Err(e) => {
+            drop(file);
+            let _ = tokio::fs::remove_file(path).await;
+            Err(e)
+        }
+    }
+}
+
+/// 确定存储路径并把临时文件移动过去，返回相对存储目录的路径与被覆盖文件的大小
+async fn commit_upload(
+    state: &ImageState,
+    storage_path: &FsPath,
+    upload: &PartialUpload,
+    key: Option<String>,
+    overwrite: bool,
+) -> Result<(String, u64), (StatusCode, String)> {
+    let client_key = key.is_some();
+    let new_filename = match key {
+        Some(_) if !state.config.allow_client_key => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "未开启客户端指定文件名（allow_client_key）".to_string(),
+            ));
+        }
+        Some(_) if !upload.is_image => {
+            warn!("拒绝上传文件名: 文件内容不是可识别的图片");
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "无法识别图片格式，指定文件名时只接受图片".to_string(),
+            ));
+        }
+        Some(key) => sanitize_key(&key, &upload.extension).map_err(|e| {
+            warn!("拒绝上传文件名: {e}");
+            (StatusCode::BAD_REQUEST, e)
+        })?,
+        // 生成唯一文件名
+        None => generate_filename(&upload.extension),
+    };
+    // 返回给客户端的路径相对于存储目录，按日期分目录时带上日期前缀
+    let relative_path = if state.config.shard_by_date {
+        format!("{}/{new_filename}", date_shard(Utc::now()))
+    } else {
+        new_filename.clone()
+    };
+    let file_path = storage_path.join(&relative_path);
+    if let Some(parent) = file_path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+            error!("创建存储目录失败: {e}");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                format!("创建文件失败: {e}"),
+                format!("创建存储目录失败: {e}"),
             )
         })?;
+    }
 
-        file.write_all(&data).await.map_err(|e| {
-            error!("写入文件失败: {e}");
+    // 覆盖已有文件时需要从占用统计中扣除旧文件
+    let replaced = if client_key && overwrite {
+        tokio::fs::metadata(&file_path)
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    // 客户端指定的文件名不覆盖已有文件，除非显式要求：硬链接在目标已存在时失败
+    let moved = if client_key && !overwrite {
+        tokio::fs::hard_link(&upload.path, &file_path).await
+    } else {
+        tokio::fs::rename(&upload.path, &file_path).await
+    };
+    moved.map_err(|e| {
+        if e.kind() == std::io::ErrorKind::AlreadyExists {
+            warn!("文件已存在: {new_filename}");
+            return (
+                StatusCode::CONFLICT,
+                format!("文件已存在: {new_filename}（可使用 overwrite=true 覆盖）"),
+            );
+        }
+        error!("保存文件失败: {e}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("保存文件失败: {e}"),
+        )
+    })?;
+
+    Ok((relative_path, replaced))
+}
+
+/// 获取图片元信息，只读取文件头探测格式与尺寸
+#[utoipa::path(
+    get,
+    path = "/api/image/{name}/meta",
+    tag = "image",
+    params(("name" = String, Path, description = "上传时返回的文件名")),
+    responses(
+        (status = 200, description = "图片元信息", body = ImageMeta),
+        (status = 404, description = "文件不存在"),
+    )
+)]
+pub async fn handle_meta(
+    State(state): State<ImageState>,
+    Path(name): Path<String>,
+) -> Result<Json<ImageMeta>, (StatusCode, String)> {
+    validate_key(&name).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    image_meta(&state, name).await
+}
+
+/// 获取按日期分目录存储的图片元信息，路径即上传时返回的 `YYYY/MM/DD/文件名`
+#[utoipa::path(
+    get,
+    path = "/api/image/{year}/{month}/{day}/{name}/meta",
+    tag = "image",
+    params(
+        ("year" = String, Path, description = "上传日期的年份，4 位数字"),
+        ("month" = String, Path, description = "月份，2 位数字"),
+        ("day" = String, Path, description = "日，2 位数字"),
+        ("name" = String, Path, description = "文件名"),
+    ),
+    responses(
+        (status = 200, description = "图片元信息", body = ImageMeta),
+        (status = 404, description = "文件不存在"),
+    )
+)]
+pub async fn handle_sharded_meta(
+    State(state): State<ImageState>,
+    Path((year, month, day, name)): Path<(String, String, String, String)>,
+) -> Result<Json<ImageMeta>, (StatusCode, String)> {
+    let is_digits =
+        |part: &str, len: usize| part.len() == len && part.chars().all(|c| c.is_ascii_digit());
+    if !(is_digits(&year, 4) && is_digits(&month, 2) && is_digits(&day, 2)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("非法日期目录: {year}/{month}/{day}"),
+        ));
+    }
+    validate_key(&name).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    image_meta(&state, format!("{year}/{month}/{day}/{name}")).await
+}
+
+/// 读取已校验的相对路径对应文件的元信息
+async fn image_meta(
+    state: &ImageState,
+    name: String,
+) -> Result<Json<ImageMeta>, (StatusCode, String)> {
+    let file_path = PathBuf::from(&state.config.storage_dir).join(&name);
+    let metadata = match tokio::fs::metadata(&file_path).await {
+        Ok(metadata) if metadata.is_file() => metadata,
+        Ok(_) => return Err((StatusCode::NOT_FOUND, format!("文件不存在: {name}"))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err((StatusCode::NOT_FOUND, format!("文件不存在: {name}")));
+        }
+        Err(e) => {
+            error!("读取文件信息失败: {e}");
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("读取文件信息失败: {e}"),
+            ));
+        }
+    };
+
+    // 部分文件系统不支持创建时间，退回修改时间
+    let created_at = metadata
+        .created()
+        .or_else(|_| metadata.modified())
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(|_| Utc::now());
+
+    let probe_path = file_path.clone();
+    let probed = tokio::task::spawn_blocking(move || probe_file_format(&probe_path))
+        .await
+        .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                format!("写入文件失败: {e}"),
+                format!("探测图片格式失败: {e}"),
             )
         })?;
 
-        let file_size = data.len() as u64;
-        info!("文件上传成功: {new_filename}, size: {file_size} bytes");
+    Ok(Json(ImageMeta {
+        name,
+        size_bytes: metadata.len(),
+        width: probed.map(|(_, width, _)| width),
+        height: probed.map(|(_, _, height)| height),
+        format: probed.map(|(format, _, _)| format.to_string()),
+        created_at,
+    }))
+}
+
+/// 按修改时间从旧到新删除文件，直到占用不超过上限，`keep` 为刚上传的文件不参与淘汰
+async fn evict_oldest_files(state: &ImageState, keep: &str) {
+    let max_total_bytes = state.config.max_total_bytes;
+    let storage_path = PathBuf::from(&state.config.storage_dir);
+    let keep = storage_path.join(keep);
 
-        // 记录 metrics 指标
-        ImageMetrics::record_upload_success(file_size);
+    let mut files: Vec<_> = list_stored_files(&storage_path)
+        .await
+        .into_iter()
+        .map(|(path, metadata)| {
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, path, metadata.len())
+        })
+        .collect();
+    files.sort();
 
-        // 返回相对路径
-        return Ok(Json(UploadResponse {
-            success: true,
-            path: Some(new_filename),
-            error: None,
-        }));
+    // 以扫描结果为准，顺便校正过期清理等造成的统计偏差
+    let mut total: u64 = files.iter().map(|(_, _, len)| len).sum();
+    let mut evicted = 0;
+    for (_, path, len) in files {
+        if total <= max_total_bytes {
+            break;
+        }
+        if path == keep {
+            continue;
+        }
+        match tokio::fs::remove_file(&path).await {
+            Ok(_) => {
+                total = total.saturating_sub(len);
+                evicted += 1;
+                info!("超出容量上限，淘汰文件: {path:?}, size: {len} bytes");
+                remove_empty_parents(&storage_path, &path).await;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                total = total.saturating_sub(len);
+            }
+            Err(e) => error!("淘汰文件失败 {path:?}: {e}"),
+        }
     }
 
-    // 没有找到文件字段
-    error!("未找到上传文件");
-    Err((
-        StatusCode::BAD_REQUEST,
-        "未找到上传文件（需要 'file' 或 'files' 字段）".to_string(),
-    ))
+    state.usage.store(total, Ordering::SeqCst);
+    ImageMetrics::record_eviction(evicted);
+    ImageMetrics::record_storage_usage(total);
 }
 
-/// 清理过期文件
+/// 删除临时目录中长时间未写入的残留文件，返回删除的数量
+async fn sweep_partial_files(storage_path: &FsPath, max_age: Duration) -> usize {
+    let Ok(mut entries) = tokio::fs::read_dir(storage_path.join(PARTIAL_DIR)).await else {
+        return 0;
+    };
+    let now = SystemTime::now();
+    let mut removed = 0;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        let stale = metadata.is_file()
+            && metadata
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age > max_age);
+        if !stale {
+            continue;
+        }
+        match tokio::fs::remove_file(entry.path()).await {
+            Ok(_) => removed += 1,
+            Err(e) => error!("删除残留临时文件失败 {:?}: {e}", entry.path()),
+        }
+    }
+    if removed > 0 {
+        info!("删除 {removed} 个上传中断留下的临时文件");
+    }
+    removed
+}
+
+/// 清理过期文件，同时删除上传中断留下的临时文件
 async fn cleanup_expired_files(storage_dir: &str, expire_secs: u64) {
     let storage_path = PathBuf::from(storage_dir);
 
     if !storage_path.exists() {
         return;
     }
+    sweep_partial_files(&storage_path, PARTIAL_MAX_AGE).await;
 
     info!(
         "开始清理过期文件: storage_dir={}, expire_secs={}",
         storage_dir, expire_secs
     );
 
-    let mut total_files = 0;
     let mut deleted_files = 0;
     let mut deleted_size: u64 = 0;
 
     let now = SystemTime::now();
     let expire_duration = Duration::from_secs(expire_secs);
 
-    match tokio::fs::read_dir(&storage_path).await {
-        Ok(mut entries) => {
-            while let Ok(Some(entry)) = entries.next_entry().await {
-                total_files += 1;
-                let path = entry.path();
-
-                // 只处理文件，跳过目录
-                if !path.is_file() {
-                    continue;
+    let files = list_stored_files(&storage_path).await;
+    let total_files = files.len();
+    for (path, metadata) in files {
+        // 检查创建时间
+        if let Ok(created) = metadata.created()
+            && let Ok(age) = now.duration_since(created)
+            && age > expire_duration
+        {
+            // 文件过期，删除
+            let file_size = metadata.len();
+            match tokio::fs::remove_file(&path).await {
+                Ok(_) => {
+                    deleted_files += 1;
+                    deleted_size += file_size;
+                    info!("删除过期文件: {:?}, 年龄: {:?}", path, age);
+                    remove_empty_parents(&storage_path, &path).await;
                 }
-
-                // 获取文件元数据
-                match tokio::fs::metadata(&path).await {
-                    Ok(metadata) => {
-                        // 检查创建时间
-                        if let Ok(created) = metadata.created()
-                            && let Ok(age) = now.duration_since(created)
-                            && age > expire_duration
-                        {
-                            // 文件过期，删除
-                            let file_size = metadata.len();
-                            match tokio::fs::remove_file(&path).await {
-                                Ok(_) => {
-                                    deleted_files += 1;
-                                    deleted_size += file_size;
-                                    info!("删除过期文件: {:?}, 年龄: {:?}", path.file_name(), age);
-                                }
-                                Err(e) => {
-                                    error!("删除文件失败 {:?}: {e}", path);
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        warn!("获取文件元数据失败 {:?}: {e}", path);
-                    }
+                Err(e) => {
+                    error!("删除文件失败 {:?}: {e}", path);
                 }
             }
+        }
+    }
 
-            if deleted_files > 0 {
-                info!(
-                    "清理完成: 扫描 {total_files} 个文件, 删除 {deleted_files} 个过期文件, 释放 {deleted_size} 字节"
-                );
+    if deleted_files > 0 {
+        info!(
+            "清理完成: 扫描 {total_files} 个文件, 删除 {deleted_files} 个过期文件, 释放 {deleted_size} 字节"
+        );
 
-                // 记录清理指标
-                ImageMetrics::record_cleanup(deleted_files, deleted_size);
-            } else {
-                info!("清理完成: 扫描 {total_files} 个文件, 无过期文件");
-            }
-        }
-        Err(e) => {
-            error!("读取存储目录失败: {e}");
-        }
+        // 记录清理指标
+        ImageMetrics::record_cleanup(deleted_files, deleted_size);
+    } else {
+        info!("清理完成: 扫描 {total_files} 个文件, 无过期文件");
     }
 }
 
-/// 启动定时清理任务
-pub fn start_cleanup_task(config: ImageHostingConfig) {
+/// 启动定时清理任务，启动时立即执行一次，返回的句柄用于在退出时停止任务，停止时会再清理一次
+pub fn start_cleanup_task(config: ImageHostingConfig) -> PeriodicHandle {
     let storage_dir = config.storage_dir.clone();
     let cleanup_interval = if config.cleanup_interval_secs == 0 {
         3600 // 默认 1 小时
@@ -253,21 +1124,32 @@ pub fn start_cleanup_task(config: ImageHostingConfig) {
 
     info!("启动文件清理任务: 间隔={cleanup_interval}秒, 过期时间={file_expire}秒");
 
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_secs(cleanup_interval));
-
-        loop {
-            interval.tick().await;
-            cleanup_expired_files(&storage_dir, file_expire).await;
-        }
-    });
+    let interval = Duration::from_secs(cleanup_interval);
+    PeriodicTask::new(interval)
+        .with_jitter(interval / 10)
+        .with_final_run(true)
+        .spawn(move || {
+            let storage_dir = storage_dir.clone();
+            async move { cleanup_expired_files(&storage_dir, file_expire).await }
+        })
 }
 
 /// 创建图片路由
 pub fn create_routes(config: ImageHostingConfig) -> Router {
+    // 默认的 2MB 请求体限制会在流式写入前截断大文件，按单文件上限放宽
+    let body_limit = match config.max_file_bytes {
+        0 => DefaultBodyLimit::disable(),
+        max => DefaultBodyLimit::max(
+            usize::try_from(max)
+                .unwrap_or(usize::MAX)
+                .saturating_add(MULTIPART_OVERHEAD_BYTES),
+        ),
+    };
     let state = ImageState::new(config);
 
     Router::new()
-        .route("/upload", post(handle_upload))
+        .route("/upload", post(handle_upload).layer(body_limit))
+        .route("/:name/meta", get(handle_meta))
+        .route("/:year/:month/:day/:name/meta", get(handle_sharded_meta))
         .with_state(state)
 }
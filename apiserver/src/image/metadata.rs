@@ -0,0 +1,352 @@
+//! 无损去除图片元数据
+//!
+//! 只改写容器结构，删除 EXIF、XMP、文本注释等元数据块，压缩后的像素数据原样拷贝；
+//! ICC 色彩配置保留，EXIF 方向不是默认值时只写回方向这一项。
+
+use image::ImageFormat;
+use image::metadata::Orientation;
+
+/// 无法去除元数据的格式（不支持解析其容器），开启 `strip_metadata` 时拒绝上传
+///
+/// 返回格式名称，其他格式返回 None
+pub(super) fn unstrippable_format(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"II*\0") || head.starts_with(b"MM\0*") {
+        return Some("tiff");
+    }
+    // ISO BMFF：第 4 字节起为 ftyp，随后是主品牌
+    if head.get(4..8) != Some(b"ftyp") {
+        return None;
+    }
+    match head.get(8..12)? {
+        b"avif" | b"avis" => Some("avif"),
+        b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"hevx" | b"hevm" | b"hevs" => {
+            Some("heic")
+        }
+        b"mif1" | b"msf1" => Some("heif"),
+        _ => None,
+    }
+}
+
+/// 去除 `data` 中的元数据，`orientation` 为原图的 EXIF 方向
+///
+/// 仅支持 JPEG、PNG、WebP，其他格式返回错误
+pub(super) fn strip(
+    format: ImageFormat,
+    data: &[u8],
+    orientation: Orientation,
+) -> Result<Vec<u8>, &'static str> {
+    let exif = (orientation != Orientation::NoTransforms).then(|| orientation_exif(orientation));
+    match format {
+        ImageFormat::Jpeg => strip_jpeg(data, exif.as_deref()),
+        ImageFormat::Png => strip_png(data, exif.as_deref()),
+        ImageFormat::WebP => strip_webp(data, exif.as_deref()),
+        _ => Err("不支持的图片格式"),
+    }
+}
+
+/// 只包含方向一项的 EXIF（TIFF 结构，大端）
+fn orientation_exif(orientation: Orientation) -> Vec<u8> {
+    let mut exif = b"MM\0\x2a\0\0\0\x08".to_vec();
+    exif.extend_from_slice(&[0, 1]);
+    exif.extend_from_slice(&[0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, orientation.to_exif(), 0, 0]);
+    exif.extend_from_slice(&[0, 0, 0, 0]);
+    exif
+}
+
+/// 保留的 JPEG APPn 段：JFIF（APP0）、ICC 配置（APP2）、Adobe 色彩变换（APP14）
+fn keep_jpeg_segment(marker: u8, payload: &[u8]) -> bool {
+    match marker {
+        0xE0 => true,
+        0xE2 => payload.starts_with(b"ICC_PROFILE\0"),
+        0xEE => payload.starts_with(b"Adobe"),
+        // 其他 APPn（EXIF、XMP、IPTC 等）与注释
+        0xE1..=0xEF | 0xFE => false,
+        _ => true,
+    }
+}
+
+fn strip_jpeg(data: &[u8], exif: Option<&[u8]>) -> Result<Vec<u8>, &'static str> {
+    const INVALID: &str = "JPEG 结构无效";
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return Err(INVALID);
+    }
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..2]);
+    let mut exif = exif.map(|exif| {
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&(exif.len() as u16 + 8).to_be_bytes());
+        segment.extend_from_slice(b"Exif\0\0");
+        segment.extend_from_slice(exif);
+        segment
+    });
+
+    let mut pos = 2;
+    loop {
+        if data.get(pos) != Some(&0xFF) {
+            return Err(INVALID);
+        }
+        // 标记前可以有任意个填充的 0xFF
+        while data.get(pos + 1) == Some(&0xFF) {
+            pos += 1;
+        }
+        let marker = *data.get(pos + 1).ok_or(INVALID)?;
+        // JFIF 要求 APP0 紧跟 SOI，方向 EXIF 写在它之后
+        if marker != 0xE0
+            && let Some(segment) = exif.take()
+        {
+            out.extend_from_slice(&segment);
+        }
+        match marker {
+            // 扫描数据开始，之后的内容原样拷贝
+            0xDA => {
+                out.extend_from_slice(&data[pos..]);
+                return Ok(out);
+            }
+            // 没有长度字段的独立标记
+            0x01 | 0xD0..=0xD7 => {
+                out.extend_from_slice(&data[pos..pos + 2]);
+                pos += 2;
+            }
+            0xD9 => return Err(INVALID),
+            _ => {
+                let length = data.get(pos + 2..pos + 4).ok_or(INVALID)?;
+                let length = u16::from_be_bytes([length[0], length[1]]) as usize;
+                let end = pos + 2 + length;
+                if length < 2 || end > data.len() {
+                    return Err(INVALID);
+                }
+                if keep_jpeg_segment(marker, &data[pos + 4..end]) {
+                    out.extend_from_slice(&data[pos..end]);
+                }
+                pos = end;
+            }
+        }
+    }
+}
+
+/// PNG 中的元数据块：EXIF、文本、修改时间
+const PNG_METADATA_CHUNKS: &[&[u8; 4]] = &[b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+fn strip_png(data: &[u8], mut exif: Option<&[u8]>) -> Result<Vec<u8>, &'static str> {
+    const INVALID: &str = "PNG 结构无效";
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if !data.starts_with(SIGNATURE) {
+        return Err(INVALID);
+    }
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(SIGNATURE);
+
+    let mut pos = SIGNATURE.len();
+    while pos < data.len() {
+        let header = data.get(pos..pos + 8).ok_or(INVALID)?;
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = &header[4..8];
+        let end = pos
+            .checked_add(12)
+            .and_then(|end| end.checked_add(length))
+            .filter(|end| *end <= data.len())
+            .ok_or(INVALID)?;
+        // eXIf 必须位于 IDAT 之前
+        if kind == b"IDAT"
+            && let Some(exif) = exif.take()
+        {
+            out.extend_from_slice(&png_chunk(b"eXIf", exif));
+        }
+        if !PNG_METADATA_CHUNKS.iter().any(|chunk| kind == *chunk) {
+            out.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+    }
+    Ok(out)
+}
+
+fn png_chunk(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(payload.len() + 12);
+    chunk.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    chunk.extend_from_slice(kind);
+    chunk.extend_from_slice(payload);
+    let crc = crc32(&chunk[4..]);
+    chunk.extend_from_slice(&crc.to_be_bytes());
+    chunk
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// VP8X 标志位中的 EXIF 与 XMP
+const WEBP_EXIF_FLAG: u8 = 0x08;
+const WEBP_XMP_FLAG: u8 = 0x04;
+
+fn strip_webp(data: &[u8], exif: Option<&[u8]>) -> Result<Vec<u8>, &'static str> {
+    const INVALID: &str = "WebP 结构无效";
+    if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+        return Err(INVALID);
+    }
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..12]);
+    let mut extended = None;
+
+    let mut pos = 12;
+    while pos < data.len() {
+        let header = data.get(pos..pos + 8).ok_or(INVALID)?;
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let kind = &header[..4];
+        // 块大小为奇数时补一个字节
+        let end = (pos + 8)
+            .checked_add(size + (size & 1))
+            .map(|end| end.min(data.len()))
+            .filter(|end| *end >= pos + 8 + size)
+            .ok_or(INVALID)?;
+        match kind {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" => {
+                if size < 1 {
+                    return Err(INVALID);
+                }
+                extended = Some(out.len() + 8);
+                out.extend_from_slice(&data[pos..end]);
+            }
+            _ => out.extend_from_slice(&data[pos..end]),
+        }
+        pos = end;
+    }
+
+    // 只有扩展格式（VP8X）能携带 EXIF，方向 EXIF 追加在末尾
+    if let Some(flags) = extended {
+        out[flags] &= !(WEBP_EXIF_FLAG | WEBP_XMP_FLAG);
+        if let Some(exif) = exif {
+            out[flags] |= WEBP_EXIF_FLAG;
+            out.extend_from_slice(b"EXIF");
+            out.extend_from_slice(&(exif.len() as u32).to_le_bytes());
+            out.extend_from_slice(exif);
+            if exif.len() % 2 == 1 {
+                out.push(0);
+            }
+        }
+    }
+    let riff_size = u32::try_from(out.len() - 8).map_err(|_| INVALID)?;
+    out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::ImageDecoder;
+
+    fn png_with_metadata() -> Vec<u8> {
+        let mut png = Vec::new();
+        image::RgbImage::from_pixel(3, 2, image::Rgb([10, 20, 30]))
+            .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        // 在 IHDR 之后插入 iCCP 与 tEXt
+        let mut chunks = png_chunk(b"iCCP", b"icc\0\0x\x9c\x03\0\0\0\0\x01");
+        chunks.extend(png_chunk(b"tEXt", b"GPS\0somewhere"));
+        png.splice(33..33, chunks);
+        png
+    }
+
+    fn chunk_kinds(png: &[u8]) -> Vec<[u8; 4]> {
+        let mut kinds = Vec::new();
+        let mut pos = 8;
+        while pos < png.len() {
+            let length = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            kinds.push(png[pos + 4..pos + 8].try_into().unwrap());
+            pos += length + 12;
+        }
+        kinds
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+
+    #[test]
+    fn test_unstrippable_format() {
+        assert_eq!(unstrippable_format(b"MM\0*\0\0\0\x08"), Some("tiff"));
+        assert_eq!(unstrippable_format(b"\0\0\0\x1cftypavif"), Some("avif"));
+        assert_eq!(unstrippable_format(b"\0\0\0\x18ftypheic"), Some("heic"));
+        assert_eq!(unstrippable_format(b"\0\0\0\x18ftypmp42"), None);
+        assert_eq!(unstrippable_format(b"\xFF\xD8\xFF\xE0"), None);
+    }
+
+    #[test]
+    fn test_strip_png_keeps_icc_and_pixels() {
+        let png = png_with_metadata();
+        let stripped = strip(ImageFormat::Png, &png, Orientation::NoTransforms).unwrap();
+        assert_eq!(
+            chunk_kinds(&stripped),
+            [*b"IHDR", *b"iCCP", *b"IDAT", *b"IEND"]
+        );
+        let original = image::load_from_memory(&png).unwrap();
+        let decoded = image::load_from_memory(&stripped).unwrap();
+        assert_eq!(original.as_bytes(), decoded.as_bytes());
+    }
+
+    #[test]
+    fn test_strip_png_keeps_orientation() {
+        let png = png_with_metadata();
+        let stripped = strip(ImageFormat::Png, &png, Orientation::Rotate180).unwrap();
+        assert_eq!(
+            chunk_kinds(&stripped),
+            [*b"IHDR", *b"iCCP", *b"eXIf", *b"IDAT", *b"IEND"]
+        );
+        let mut decoder =
+            image::codecs::png::PngDecoder::new(std::io::Cursor::new(&stripped)).unwrap();
+        assert_eq!(decoder.orientation().unwrap(), Orientation::Rotate180);
+    }
+
+    #[test]
+    fn test_strip_webp_drops_exif_and_xmp() {
+        let mut webp = b"RIFF\0\0\0\0WEBP".to_vec();
+        // VP8X：带 ICC、EXIF、XMP 标志
+        webp.extend_from_slice(b"VP8X\x0a\0\0\0");
+        webp.extend_from_slice(&[0x2C, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        webp.extend_from_slice(b"ICCP\x03\0\0\0icc\0");
+        webp.extend_from_slice(b"VP8L\x02\0\0\0ab");
+        webp.extend_from_slice(b"EXIF\x03\0\0\0gps\0");
+        webp.extend_from_slice(b"XMP \x02\0\0\0xm");
+        let size = (webp.len() - 8) as u32;
+        webp[4..8].copy_from_slice(&size.to_le_bytes());
+
+        let stripped = strip(ImageFormat::WebP, &webp, Orientation::NoTransforms).unwrap();
+        let mut expected = b"RIFF\0\0\0\0WEBP".to_vec();
+        expected.extend_from_slice(b"VP8X\x0a\0\0\0");
+        expected.extend_from_slice(&[0x20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(b"ICCP\x03\0\0\0icc\0");
+        expected.extend_from_slice(b"VP8L\x02\0\0\0ab");
+        let size = (expected.len() - 8) as u32;
+        expected[4..8].copy_from_slice(&size.to_le_bytes());
+        assert_eq!(stripped, expected);
+
+        let oriented = strip(ImageFormat::WebP, &webp, Orientation::Rotate90).unwrap();
+        assert_eq!(oriented[20] & WEBP_EXIF_FLAG, WEBP_EXIF_FLAG);
+        assert!(oriented.ends_with(&orientation_exif(Orientation::Rotate90)));
+    }
+
+    #[test]
+    fn test_strip_rejects_truncated_jpeg() {
+        assert!(
+            strip(
+                ImageFormat::Jpeg,
+                b"\xFF\xD8\xFF\xE1\x00\x10Exif",
+                Orientation::NoTransforms
+            )
+            .is_err()
+        );
+        assert!(strip(ImageFormat::Jpeg, b"not a jpeg", Orientation::NoTransforms).is_err());
+    }
+}
@@ -1,43 +1,96 @@
 pub mod anybox;
 pub mod datalink_engine;
+pub mod health;
 pub mod image;
 pub mod job_manage;
 pub mod nodemanage;
 pub mod object_storage;
 pub mod ocr;
+pub mod openapi;
+pub mod pagination;
 pub mod prompt;
+pub mod rate_limit;
 
 use axum::Router;
 use config::{
     GlobalConfig,
     datalink_engine::{DataLinkEngineBackend, DataLinkEngineConfig},
 };
+use std::sync::Arc;
+use tower_http::compression::CompressionLayer;
+use tower_http::decompression::RequestDecompressionLayer;
 use tower_http::services::{ServeDir, ServeFile};
+use util::server::HttpMiddlewareConfig;
+use util::task::PeriodicHandle;
 
 pub fn build_datalink_v1_router(config: DataLinkEngineConfig) -> anyhow::Result<Router> {
     let routes = datalink_engine::create_routes(config)?;
     Ok(Router::new().nest("/api/datalink/v1", routes))
 }
 
+/// 按 [apiserver] 配置构建通用中间件配置
+pub fn middleware_config(config: &config::apiserver::ApiServerConfig) -> HttpMiddlewareConfig {
+    HttpMiddlewareConfig::default()
+        .with_cors_enabled(config.cors_enabled)
+        .with_allowed_origins(config.cors_allowed_origins.clone())
+        .with_allowed_methods(config.cors_allowed_methods.clone())
+        .with_allowed_headers(config.cors_allowed_headers.clone())
+}
+
+/// 为路由加上响应压缩与请求体解压
+///
+/// 按客户端 `Accept-Encoding` 选择 gzip/deflate/br 压缩响应，并解压带
+/// `Content-Encoding` 的请求体。应在合并 `/metrics` 与前端静态文件之后调用，使其一并生效。
+pub fn with_compression(app: Router) -> Router {
+    app.layer(CompressionLayer::new())
+        .layer(RequestDecompressionLayer::new())
+}
+
 pub async fn build_api_app(global_config: GlobalConfig) -> anyhow::Result<Router> {
+    let (app, _tasks) = build_api_app_with_tasks(global_config).await?;
+    Ok(app)
+}
+
+/// 构建 API 路由，同时返回各模块的后台任务句柄，供优雅退出时停止
+pub async fn build_api_app_with_tasks(
+    global_config: GlobalConfig,
+) -> anyhow::Result<(Router, Vec<PeriodicHandle>)> {
     let remote_ocr_config = global_config
         .remote_ocr
         .ok_or_else(|| anyhow::anyhow!("配置文件中缺少 [remote_ocr] 部分"))?;
     let image_hosting_config = global_config
         .image_hosting
         .ok_or_else(|| anyhow::anyhow!("配置文件中缺少 [image_hosting] 部分"))?;
+    let apiserver_config = global_config.apiserver.clone().unwrap_or_default();
+    let limiter = rate_limit::RateLimiter::new(apiserver_config.requests_per_minute);
+    let ocr_cache = build_ocr_cache(&remote_ocr_config, global_config.redis.as_ref()).await?;
     let anybox_config = global_config.anybox;
     let prompt_config = global_config.prompt;
     let object_storage_config = global_config.object_storage;
     let datalink_engine_config = global_config.datalink_engine;
     let nodemanage_config = global_config.nodemanage;
+    let mut tasks = Vec::new();
 
     let mut app = Router::new()
         .nest(
             "/api/ocr",
-            ocr::create_routes(remote_ocr_config, image_hosting_config.storage_dir.clone()),
+            ocr::create_routes_with_cache(
+                remote_ocr_config,
+                image_hosting_config.storage_dir.clone(),
+                ocr_cache,
+            )
+            .route_layer(axum::middleware::from_fn_with_state(
+                limiter.clone(),
+                rate_limit::rate_limit,
+            )),
         )
-        .nest("/api/image", image::create_routes(image_hosting_config))
+        .nest(
+            "/api/image",
+            image::create_routes(image_hosting_config).route_layer(
+                axum::middleware::from_fn_with_state(limiter, rate_limit::rate_limit),
+            ),
+        )
+        .merge(openapi::create_routes())
         .nest("/api/rc", rc::create_routes())
         .nest(
             "/api/job-manage/v1",
@@ -45,7 +98,8 @@ pub async fn build_api_app(global_config: GlobalConfig) -> anyhow::Result<Router
         );
 
     if let Some(anybox_cfg) = anybox_config {
-        let anybox_routes = anybox::create_routes(anybox_cfg).await?;
+        let (anybox_routes, cleanup) = anybox::create_routes_with_cleanup(anybox_cfg).await?;
+        tasks.push(cleanup);
         app = app.nest("/api/anybox", anybox_routes);
     }
 
@@ -88,7 +142,23 @@ pub async fn build_api_app(global_config: GlobalConfig) -> anyhow::Result<Router
         (None, None) => {}
     }
 
-    Ok(app)
+    Ok((app, tasks))
+}
+
+/// 按配置创建 OCR 识别结果缓存，仅在启用且配置了 [redis] 时生效
+async fn build_ocr_cache(
+    remote_ocr_config: &config::ocr::RemoteOcrConfig,
+    redis_config: Option<&config::redis::RedisConfig>,
+) -> anyhow::Result<Option<Arc<dyn ocr::OcrCache>>> {
+    if !remote_ocr_config.cache_enabled {
+        return Ok(None);
+    }
+    let Some(redis_config) = redis_config else {
+        tracing::warn!("已启用 OCR 缓存但缺少 [redis] 配置，缓存不生效");
+        return Ok(None);
+    };
+    let cache = ocr::RedisOcrCache::connect(redis_config).await?;
+    Ok(Some(Arc::new(cache)))
 }
 
 pub async fn build_app_for_test(global_config: GlobalConfig) -> anyhow::Result<Router> {
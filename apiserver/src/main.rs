@@ -1,4 +1,6 @@
-use apiserver::{build_api_app, build_frontend_router, image};
+use apiserver::{
+    build_api_app_with_tasks, build_frontend_router, image, middleware_config, with_compression,
+};
 
 use axum::Router;
 use config::{ConfigLoader, GlobalConfig};
@@ -7,17 +9,13 @@ use std::{
     panic,
     path::Path,
 };
-use tower::ServiceBuilder;
-use tower_http::{
-    cors::{Any, CorsLayer},
-    trace::TraceLayer,
-};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use axum::http::header;
 use axum::response::{IntoResponse, Response};
 use util::metrics::{increment_counter, init_metrics, track_http_metrics};
+use util::server::default_layers;
 
 async fn metrics_handler() -> Response {
     // 执行维护操作以确保指标被正确收集
@@ -62,21 +60,17 @@ async fn main() -> anyhow::Result<()> {
     info!("配置加载成功");
 
     // 启动图片清理任务
-    image::start_cleanup_task(image_hosting_config.clone());
-
-    // 配置 CORS
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    let image_cleanup = image::start_cleanup_task(image_hosting_config.clone());
 
     // 前端静态文件目录
     let frontend_dir = "webserver/frontend/dist";
     let has_frontend = Path::new(&frontend_dir).exists();
 
+    let (api_app, mut background_tasks) = build_api_app_with_tasks(global_config).await?;
+    background_tasks.push(image_cleanup);
     let mut app = Router::new()
         .route("/metrics", axum::routing::get(metrics_handler))
-        .merge(build_api_app(global_config).await?);
+        .merge(api_app);
 
     if !has_frontend {
         error!("前端文件未找到: {frontend_dir}");
@@ -87,12 +81,13 @@ async fn main() -> anyhow::Result<()> {
         info!("前端服务已启用");
     }
 
-    app = app.layer(
-        ServiceBuilder::new()
-            .layer(TraceLayer::new_for_http())
-            .layer(axum::middleware::from_fn(track_http_metrics))
-            .layer(cors),
-    );
+    let middleware = middleware_config(&apiserver_config);
+    if middleware.cors_enabled && apiserver_config.cors_allowed_origins.is_empty() {
+        warn!("未配置 cors_allowed_origins，CORS 允许任意来源，生产环境请配置来源白名单");
+    }
+    app = with_compression(app)
+        .layer(axum::middleware::from_fn(track_http_metrics))
+        .layer(default_layers(&middleware));
 
     // 监听地址
     let listen_address = apiserver_config.listen_address;
@@ -111,6 +106,48 @@ async fn main() -> anyhow::Result<()> {
     );
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // 限流中间件按客户端 IP 计数，需要连接信息
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
+
+    info!("停止后台任务，执行最后一次清理...");
+    for task in background_tasks {
+        task.stop().await;
+    }
     Ok(())
 }
+
+/// 等待 Ctrl+C 或 SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("监听 Ctrl+C 失败: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                error!("监听 SIGTERM 失败: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    info!("收到退出信号，开始优雅退出");
+}
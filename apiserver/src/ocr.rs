@@ -2,17 +2,30 @@
 //!
 //! 提供 Remote OCR 图片识别的 HTTP API
 
+use async_trait::async_trait;
 use axum::{
     Router,
-    extract::State,
+    extract::{DefaultBodyLimit, Multipart, Query, State},
     http::StatusCode,
-    response::{IntoResponse, Json},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
 };
 use config::ocr::RemoteOcrConfig;
+use config::redis::RedisConfig;
+use pic_recog::{BatchImage, Engine, EngineInfo, ImageRecognitionError};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+use util::client::redis::{RedisClient, RedisClientConfig};
+use utoipa::{IntoParams, ToSchema};
+
+/// 缓存命中标识响应头
+const CACHE_HEADER: &str = "x-cache";
+/// 识别结果缓存键前缀
+const CACHE_KEY_PREFIX: &str = "ocr:cache";
 
 /// OCR 服务状态
 #[derive(Clone)]
@@ -21,10 +34,224 @@ pub struct OcrState {
     pub remote_config: Arc<RemoteOcrConfig>,
     /// 图片存储目录（用于解析相对路径）
     pub storage_dir: Arc<String>,
+    /// 识别结果缓存（未启用时为 None）
+    pub cache: Option<Arc<dyn OcrCache>>,
+}
+
+/// OCR 识别结果缓存
+#[async_trait]
+pub trait OcrCache: Send + Sync {
+    /// 读取缓存，未命中或读取失败时返回 None
+    async fn get(&self, key: &str) -> Option<String>;
+    /// 写入缓存，失败时仅记录日志
+    async fn set(&self, key: &str, value: &str, ttl: Duration);
+}
+
+/// 基于 Redis 的识别结果缓存
+pub struct RedisOcrCache {
+    client: Mutex<RedisClient>,
+}
+
+impl RedisOcrCache {
+    /// 根据全局 Redis 配置建立连接
+    pub async fn connect(config: &RedisConfig) -> anyhow::Result<Self> {
+        let mut client_config = RedisClientConfig::new(config.address.clone());
+        if let Some(password) = &config.password {
+            client_config = client_config.with_password(password.clone());
+        }
+        let client = RedisClient::new(&client_config)
+            .await
+            .map_err(|e| anyhow::anyhow!("连接 OCR 缓存 Redis 失败: {e}"))?;
+        Ok(Self {
+            client: Mutex::new(client),
+        })
+    }
+}
+
+#[async_trait]
+impl OcrCache for RedisOcrCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        match self.client.lock().await.get(key).await {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("读取 OCR 缓存失败: {e}");
+                None
+            }
+        }
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) {
+        if let Err(e) = self
+            .client
+            .lock()
+            .await
+            .set_ex(key, value, ttl.as_secs().max(1))
+            .await
+        {
+            warn!("写入 OCR 缓存失败: {e}");
+        }
+    }
+}
+
+/// OCR 接口错误
+///
+/// 统一返回 `{ "success": false, "error": { "code": "...", "message": "..." } }`，
+/// 客户端可以根据 `code` 区分图片问题与上游服务问题。
+#[derive(Debug)]
+pub enum ApiError {
+    /// 请求参数错误
+    BadRequest(String),
+    /// 图片格式、尺寸或体积不符合要求
+    InvalidImage(String),
+    /// 图片文件不存在
+    ImageNotFound(String),
+    /// 上传或下载的数据超出限制
+    PayloadTooLarge(String),
+    /// 远程 OCR 服务超时
+    UpstreamTimeout(String),
+    /// 远程 OCR 服务鉴权失败
+    Auth(String),
+    /// 远程 OCR 服务或图片源返回错误
+    Upstream(String),
+    /// 识别被取消（客户端已断开）
+    Cancelled,
+    /// 服务内部错误
+    Internal(String),
+}
+
+impl ApiError {
+    /// HTTP 状态码
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) | ApiError::InvalidImage(_) => StatusCode::BAD_REQUEST,
+            ApiError::ImageNotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::UpstreamTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::Auth(_) | ApiError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            // 499: 客户端在响应前关闭了连接（nginx 约定）
+            ApiError::Cancelled => {
+                StatusCode::from_u16(499).unwrap_or(StatusCode::SERVICE_UNAVAILABLE)
+            }
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// 稳定的错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "BAD_REQUEST",
+            ApiError::InvalidImage(_) => "INVALID_IMAGE",
+            ApiError::ImageNotFound(_) => "IMAGE_NOT_FOUND",
+            ApiError::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
+            ApiError::UpstreamTimeout(_) => "UPSTREAM_TIMEOUT",
+            ApiError::Auth(_) => "AUTH",
+            ApiError::Upstream(_) => "UPSTREAM_ERROR",
+            ApiError::Cancelled => "CANCELLED",
+            ApiError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::BadRequest(msg)
+            | ApiError::InvalidImage(msg)
+            | ApiError::ImageNotFound(msg)
+            | ApiError::PayloadTooLarge(msg)
+            | ApiError::UpstreamTimeout(msg)
+            | ApiError::Auth(msg)
+            | ApiError::Upstream(msg)
+            | ApiError::Internal(msg) => msg.clone(),
+            ApiError::Cancelled => "识别已取消".to_string(),
+        }
+    }
+}
+
+impl From<ImageRecognitionError> for ApiError {
+    fn from(err: ImageRecognitionError) -> Self {
+        let message = err.to_string();
+        match err {
+            ImageRecognitionError::UnsupportedFormat(_)
+            | ImageRecognitionError::ValidationError(_) => ApiError::InvalidImage(message),
+            ImageRecognitionError::FileNotFound(_) => ApiError::ImageNotFound(message),
+            ImageRecognitionError::Timeout(_) => ApiError::UpstreamTimeout(message),
+            ImageRecognitionError::AuthError(_) => ApiError::Auth(message),
+            ImageRecognitionError::EngineError(_)
+            | ImageRecognitionError::Network(_)
+            | ImageRecognitionError::Parse(_) => ApiError::Upstream(message),
+            ImageRecognitionError::Cancelled => ApiError::Cancelled,
+            ImageRecognitionError::TesseractError(_)
+            | ImageRecognitionError::IoError(_)
+            | ImageRecognitionError::ConfigError(_) => ApiError::Internal(message),
+        }
+    }
+}
+
+/// 阻塞线程池中的识别任务 panic 或被取消
+impl From<tokio::task::JoinError> for ApiError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        error!("OCR 任务执行失败: {}", err);
+        ApiError::Internal(format!("OCR 任务执行失败: {err}"))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "success": false,
+            "error": {
+                "code": self.code(),
+                "message": self.message(),
+            }
+        });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+/// 请求被丢弃（如客户端断开连接）时通知阻塞中的识别任务停止轮询
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl CancelOnDrop {
+    fn new() -> Self {
+        Self(Arc::new(AtomicBool::new(false)))
+    }
+
+    fn token(&self) -> Arc<AtomicBool> {
+        self.0.clone()
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 缓存键：图片 SHA-1 + 识别配置指纹 + 结果类型（是否包含坐标）
+fn cache_key(bytes: &[u8], config: &RemoteOcrConfig, include_position: bool) -> String {
+    let kind = if include_position { "position" } else { "text" };
+    format!(
+        "{CACHE_KEY_PREFIX}:{}:{}:{kind}",
+        pic_recog::utils::sha1_hex(bytes),
+        config_fingerprint(config)
+    )
+}
+
+/// 影响识别结果的配置项摘要，配置变更后旧缓存不再命中
+fn config_fingerprint(config: &RemoteOcrConfig) -> String {
+    let settings = [
+        config.start_url.as_str(),
+        config.mode.as_str(),
+        config.language.as_deref().unwrap_or(""),
+        if config.auto_orient { "orient" } else { "" },
+    ]
+    .join("\n");
+    let mut digest = pic_recog::utils::sha1_hex(settings.as_bytes());
+    digest.truncate(12);
+    digest
 }
 
 /// OCR 单张图片请求体
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SinglePicRequest {
     /// 图片路径或 base64 编码
     pub image_path: String,
@@ -33,8 +260,43 @@ pub struct SinglePicRequest {
     pub include_position: bool,
 }
 
+/// 按 URL 识别请求体
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FromUrlRequest {
+    /// 图片地址（仅支持 http/https）
+    pub url: String,
+    /// 是否包含坐标信息（可选，默认 false）
+    #[serde(default)]
+    pub include_position: bool,
+}
+
+/// 批量 OCR 查询参数
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BatchQuery {
+    /// 是否包含坐标信息（可选，默认 false）
+    #[serde(default)]
+    pub include_position: bool,
+}
+
+/// 批量 OCR 中单个文件的识别结果
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchOcrItem {
+    /// 上传时的文件名
+    pub filename: String,
+    /// 识别的文本内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// 错误码，与单张识别接口的 `error.code` 一致
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// 错误信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// OCR 响应体
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct OcrResponse {
     /// 是否成功
     pub success: bool,
@@ -80,15 +342,39 @@ async fn health_check() -> impl IntoResponse {
     }))
 }
 
+/// 已配置的识别引擎及其能力
+///
+/// GET /ocr/engines
+#[utoipa::path(
+    get,
+    path = "/api/ocr/engines",
+    tag = "ocr",
+    responses((status = 200, description = "引擎名称与支持的能力", body = Vec<serde_json::Value>))
+)]
+async fn list_engines(State(state): State<OcrState>) -> Json<Vec<EngineInfo>> {
+    let remote = Engine::Remote(Box::new(state.remote_config.as_ref().clone()));
+    Json(vec![remote.capabilities()])
+}
+
 /// 单张图片 OCR 识别 - 使用 remote OCR
 ///
 /// POST /ocr/single_pic
 /// Content-Type: application/json
 /// Body: { "image_path": "/path/to/image.png", "include_position": true }
+#[utoipa::path(
+    post,
+    path = "/api/ocr/single_pic",
+    tag = "ocr",
+    request_body = SinglePicRequest,
+    responses(
+        (status = 200, description = "识别成功", body = OcrResponse),
+        (status = 400, description = "参数错误"),
+    )
+)]
 async fn single_pic_remote(
     State(state): State<OcrState>,
     Json(payload): Json<SinglePicRequest>,
-) -> Result<Json<OcrResponse>, (StatusCode, Json<OcrResponse>)> {
+) -> Result<Response, ApiError> {
     info!(
         "收到 OCR 请求: image_path={}, include_position={}",
         payload.image_path, payload.include_position
@@ -106,53 +392,673 @@ async fn single_pic_remote(
 
     info!("解析后的图片路径: {}", image_path);
 
+    // 只读取一次文件，校验后的数据同时用于计算缓存键与识别
+    let file_name = std::path::Path::new(&image_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("image")
+        .to_string();
+    let image = tokio::task::spawn_blocking(move || {
+        pic_recog::utils::load_and_validate_remote_image(&image_path)
+    })
+    .await??;
+
+    let cache_key = state
+        .cache
+        .as_ref()
+        .map(|_| cache_key(&image.bytes, &state.remote_config, payload.include_position));
+    if let Some(cached) = lookup_cache(&state, cache_key.as_deref()).await {
+        return Ok(cached_response(cached, Some(payload.image_path)));
+    }
+
     let remote_config = state.remote_config.clone();
     let include_position = payload.include_position;
+    let cancel_guard = CancelOnDrop::new();
+    let cancel = cancel_guard.token();
 
     // 在阻塞线程池中调用 remote OCR（因为它使用 blocking HTTP client）
-    let result = tokio::task::spawn_blocking(move || {
-        if include_position {
-            pic_recog::recognize_image_by_remote_with_position(&image_path, &remote_config)
-        } else {
-            pic_recog::recognize_image_by_remote(&image_path, &remote_config)
+    let text = tokio::task::spawn_blocking(move || {
+        pic_recog::engines::remote::recognize_payload_cancellable(
+            &image,
+            &file_name,
+            &remote_config,
+            include_position,
+            &cancel,
+        )
+    })
+    .await?
+    .inspect_err(|e| error!("OCR 识别失败: {}", e))?;
+
+    info!("OCR 识别成功: {} 字符", text.len());
+    Ok(fresh_response(&state, cache_key.as_deref(), text, Some(payload.image_path)).await)
+}
+
+async fn lookup_cache(state: &OcrState, key: Option<&str>) -> Option<String> {
+    let (Some(cache), Some(key)) = (&state.cache, key) else {
+        return None;
+    };
+    let cached = cache.get(key).await;
+    if cached.is_some() {
+        info!("OCR 缓存命中: {key}");
+    }
+    cached
+}
+
+fn cached_response(text: String, image_path: Option<String>) -> Response {
+    (
+        [(CACHE_HEADER, "HIT")],
+        Json(OcrResponse::success(text, image_path)),
+    )
+        .into_response()
+}
+
+/// 返回新识别的结果，启用缓存时顺便写入
+async fn fresh_response(
+    state: &OcrState,
+    key: Option<&str>,
+    text: String,
+    image_path: Option<String>,
+) -> Response {
+    let (Some(cache), Some(key)) = (&state.cache, key) else {
+        return Json(OcrResponse::success(text, image_path)).into_response();
+    };
+    let ttl = Duration::from_secs(state.remote_config.cache_ttl_secs);
+    cache.set(key, &text, ttl).await;
+    (
+        [(CACHE_HEADER, "MISS")],
+        Json(OcrResponse::success(text, image_path)),
+    )
+        .into_response()
+}
+
+/// 批量图片 OCR 识别 - 使用 remote OCR
+///
+/// POST /ocr/batch?include_position=false
+/// Content-Type: multipart/form-data
+/// Body: 多个 `files` 字段
+#[utoipa::path(
+    post,
+    path = "/api/ocr/batch",
+    tag = "ocr",
+    params(BatchQuery),
+    request_body(content_type = "multipart/form-data", description = "多个 `files` 文件字段"),
+    responses(
+        (status = 200, description = "每个文件的识别结果", body = Vec<BatchOcrItem>),
+        (status = 413, description = "文件数量或总大小超出限制"),
+    )
+)]
+async fn batch_remote(
+    State(state): State<OcrState>,
+    Query(query): Query<BatchQuery>,
+    mut multipart: Multipart,
+) -> Result<Json<Vec<BatchOcrItem>>, ApiError> {
+    let max_files = state.remote_config.batch_max_files;
+    let max_total_bytes = state.remote_config.batch_max_total_bytes;
+
+    let mut images = Vec::new();
+    let mut total_bytes: u64 = 0;
+
+    while let Some(mut field) = multipart.next_field().await.map_err(|e| {
+        error!("读取上传字段失败: {e}");
+        bad_request(format!("读取上传字段失败: {e}"))
+    })? {
+        if field.name() != Some("files") {
+            continue;
         }
+
+        if images.len() >= max_files {
+            return Err(ApiError::PayloadTooLarge(format!(
+                "文件数量超出限制 (最多 {max_files} 个)"
+            )));
+        }
+
+        let file_name = field.file_name().unwrap_or("image").to_string();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(|e| {
+            error!("读取文件数据失败: {e}");
+            bad_request(format!("读取文件数据失败: {e}"))
+        })? {
+            total_bytes += chunk.len() as u64;
+            if total_bytes > max_total_bytes {
+                return Err(ApiError::PayloadTooLarge(format!(
+                    "文件总大小超出限制 (最大 {max_total_bytes} 字节)"
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        images.push(BatchImage { file_name, bytes });
+    }
+
+    if images.is_empty() {
+        return Err(bad_request(
+            "未找到上传文件（需要 'files' 字段）".to_string(),
+        ));
+    }
+
+    info!(
+        "收到批量 OCR 请求: files={}, total_bytes={}, include_position={}",
+        images.len(),
+        total_bytes,
+        query.include_position
+    );
+
+    let filenames: Vec<String> = images.iter().map(|img| img.file_name.clone()).collect();
+    let remote_config = state.remote_config.clone();
+    let concurrency = remote_config.batch_concurrency;
+    let include_position = query.include_position;
+
+    let results = tokio::task::spawn_blocking(move || {
+        pic_recog::recognize_batch_remote(images, &remote_config, include_position, concurrency)
+    })
+    .await?;
+
+    let items = filenames
+        .into_iter()
+        .zip(results)
+        .map(|(filename, result)| match result {
+            Ok(text) => BatchOcrItem {
+                filename,
+                text: Some(text),
+                code: None,
+                error: None,
+            },
+            Err(e) => {
+                error!("批量 OCR 识别失败: {filename}: {e}");
+                let err = ApiError::from(e);
+                BatchOcrItem {
+                    filename,
+                    text: None,
+                    code: Some(err.code().to_string()),
+                    error: Some(err.message()),
+                }
+            }
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
+/// 按 URL 下载图片并 OCR 识别 - 使用 remote OCR
+///
+/// POST /ocr/from_url
+/// Content-Type: application/json
+/// Body: { "url": "https://example.com/image.png", "include_position": false }
+#[utoipa::path(
+    post,
+    path = "/api/ocr/from_url",
+    tag = "ocr",
+    request_body = FromUrlRequest,
+    responses(
+        (status = 200, description = "识别成功", body = OcrResponse),
+        (status = 400, description = "地址无效或下载失败"),
+    )
+)]
+async fn from_url_remote(
+    State(state): State<OcrState>,
+    Json(payload): Json<FromUrlRequest>,
+) -> Result<Response, ApiError> {
+    info!(
+        "收到 URL OCR 请求: url={}, include_position={}",
+        payload.url, payload.include_position
+    );
+
+    let url = reqwest::Url::parse(&payload.url)
+        .map_err(|e| bad_request(format!("无效的图片地址: {e}")))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(bad_request(format!(
+            "不支持的协议: {}（仅支持 http/https）",
+            url.scheme()
+        )));
+    }
+
+    let (bytes, file_name) = download_image(url, &state.remote_config).await?;
+    let cache_key = state
+        .cache
+        .as_ref()
+        .map(|_| cache_key(&bytes, &state.remote_config, payload.include_position));
+    if let Some(cached) = lookup_cache(&state, cache_key.as_deref()).await {
+        return Ok(cached_response(cached, Some(payload.url)));
+    }
+
+    let (image, file_name) = tokio::task::spawn_blocking(move || {
+        pic_recog::utils::load_and_validate_remote_image_bytes(bytes, &file_name)
+            .map(|image| (image, file_name))
     })
-    .await
-    .map_err(|e| {
-        error!("OCR 任务执行失败: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(OcrResponse::error(format!("OCR 任务执行失败: {e}"))),
+    .await??;
+
+    let remote_config = state.remote_config.clone();
+    let include_position = payload.include_position;
+    let cancel_guard = CancelOnDrop::new();
+    let cancel = cancel_guard.token();
+    let text = tokio::task::spawn_blocking(move || {
+        pic_recog::engines::remote::recognize_payload_cancellable(
+            &image,
+            &file_name,
+            &remote_config,
+            include_position,
+            &cancel,
         )
+    })
+    .await?
+    .inspect_err(|e| error!("OCR 识别失败: {}", e))?;
+
+    info!("OCR 识别成功: {} 字符", text.len());
+    Ok(fresh_response(&state, cache_key.as_deref(), text, Some(payload.url)).await)
+}
+
+/// 下载图片，限制超时与体积，返回图片数据和推断出的文件名
+async fn download_image(
+    url: reqwest::Url,
+    config: &RemoteOcrConfig,
+) -> Result<(Vec<u8>, String), ApiError> {
+    let max_bytes = config.download_max_bytes;
+    let too_large =
+        || ApiError::PayloadTooLarge(format!("图片体积超出限制 (最大 {max_bytes} 字节)"));
+    let bad_gateway = |message: String| {
+        error!("{message}");
+        ApiError::Upstream(message)
+    };
+
+    // 解析一次地址并固定下来，避免校验后 DNS 被改指向内网；不跟随重定向，防止跳转到内网地址
+    let mut builder = reqwest::Client::builder()
+        .timeout(config.request_timeout())
+        .redirect(reqwest::redirect::Policy::none());
+    let addr = resolve_download_host(&url, config.download_allow_private_hosts).await?;
+    if let Some(host) = url.host_str()
+        && host_ip(host).is_none()
+    {
+        builder = builder.resolve(host, addr);
+    }
+    let client = builder
+        .build()
+        .map_err(|e| bad_gateway(format!("构建 HTTP 客户端失败: {e}")))?;
+    let mut response = client.get(url.clone()).send().await.map_err(|e| {
+        if e.is_timeout() {
+            ApiError::UpstreamTimeout(format!("下载图片超时: {e}"))
+        } else {
+            bad_gateway(format!("下载图片失败: {e}"))
+        }
     })?;
 
-    match result {
-        Ok(text) => {
-            info!("OCR 识别成功: {} 字符", text.len());
-            Ok(Json(OcrResponse::success(
-                text,
-                Some(payload.image_path.clone()),
-            )))
-        }
-        Err(e) => {
-            error!("OCR 识别失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(OcrResponse::error(format!("OCR 识别失败: {e}"))),
-            ))
+    if !response.status().is_success() {
+        return Err(bad_gateway(format!(
+            "下载图片失败，状态码 {}",
+            response.status()
+        )));
+    }
+    if response.content_length().is_some_and(|len| len > max_bytes) {
+        return Err(too_large());
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| bad_gateway(format!("读取图片数据失败: {e}")))?
+    {
+        if (bytes.len() + chunk.len()) as u64 > max_bytes {
+            return Err(too_large());
         }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok((bytes, url_file_name(&url, content_type.as_deref())))
+}
+
+/// 解析下载地址的主机，未开启 `download_allow_private_hosts` 时拒绝非公网地址
+///
+/// 域名解析出的任一地址不是公网地址都会拒绝，返回第一个地址用于实际连接
+async fn resolve_download_host(
+    url: &reqwest::Url,
+    allow_private: bool,
+) -> Result<std::net::SocketAddr, ApiError> {
+    let port = url.port_or_known_default().unwrap_or(80);
+    let host = url
+        .host_str()
+        .ok_or_else(|| bad_request("图片地址缺少主机名".to_string()))?;
+    let addrs: Vec<std::net::SocketAddr> = match host_ip(host) {
+        Some(ip) => vec![(ip, port).into()],
+        None => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| bad_request(format!("无法解析图片地址 {host}: {e}")))?
+            .collect(),
+    };
+    if !allow_private && let Some(addr) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        warn!("拒绝下载非公网地址的图片: {url} -> {}", addr.ip());
+        return Err(bad_request(format!("不允许访问非公网地址: {}", addr.ip())));
     }
+    addrs
+        .into_iter()
+        .next()
+        .ok_or_else(|| bad_request(format!("无法解析图片地址: {url}")))
+}
+
+/// URL 主机部分为 IP 字面量时返回该地址（IPv6 带方括号）
+fn host_ip(host: &str) -> Option<std::net::IpAddr> {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// 是否为公网地址：排除回环、内网、链路本地（含云厂商元数据地址）、CGNAT、组播等
+fn is_public_ip(ip: std::net::IpAddr) -> bool {
+    match ip {
+        std::net::IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                // 100.64.0.0/10 运营商级 NAT
+                || (a == 100 && (64..128).contains(&b))
+                // 198.18.0.0/15 基准测试网段
+                || (a == 198 && (b == 18 || b == 19))
+                || a >= 240)
+        }
+        std::net::IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ip(mapped.into());
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // fc00::/7 唯一本地地址
+                || (first & 0xfe00) == 0xfc00
+                // fe80::/10 链路本地地址
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// 从 URL 路径推断文件名，路径中没有扩展名时按 content-type 补全
+fn url_file_name(url: &reqwest::Url, content_type: Option<&str>) -> String {
+    let last_segment = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
+        .unwrap_or("image");
+
+    if last_segment.contains('.') {
+        return last_segment.to_string();
+    }
+
+    let extension = match content_type.map(|ct| ct.split(';').next().unwrap_or("").trim()) {
+        Some("image/png") => "png",
+        Some("image/jpeg") | Some("image/jpg") => "jpg",
+        Some("image/gif") => "gif",
+        Some("image/webp") => "webp",
+        Some("image/heic") => "heic",
+        Some("image/heif") => "heif",
+        Some("image/bmp") => "bmp",
+        Some("image/tiff") => "tiff",
+        Some("application/pdf") => "pdf",
+        _ => "png",
+    };
+    format!("{last_segment}.{extension}")
+}
+
+fn bad_request(message: String) -> ApiError {
+    ApiError::BadRequest(message)
 }
 
 /// 创建 OCR 路由
 pub fn create_routes(remote_config: RemoteOcrConfig, storage_dir: String) -> Router {
+    create_routes_with_cache(remote_config, storage_dir, None)
+}
+
+/// 创建 OCR 路由，并使用给定的识别结果缓存
+pub fn create_routes_with_cache(
+    remote_config: RemoteOcrConfig,
+    storage_dir: String,
+    cache: Option<Arc<dyn OcrCache>>,
+) -> Router {
+    // multipart 编码有额外开销，在总字节限制基础上预留 1MB
+    let batch_body_limit = remote_config.batch_max_total_bytes as usize + 1024 * 1024;
     let state = OcrState {
         remote_config: Arc::new(remote_config),
         storage_dir: Arc::new(storage_dir),
+        cache,
     };
 
     Router::new()
         .route("/health", get(health_check))
+        .route("/engines", get(list_engines))
         .route("/single_pic", post(single_pic_remote))
+        .route("/from_url", post(from_url_remote))
+        .route(
+            "/batch",
+            post(batch_remote).layer(DefaultBodyLimit::max(batch_body_limit)),
+        )
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+
+    #[test]
+    fn test_api_error_maps_recognition_errors() {
+        let cases = [
+            (
+                ImageRecognitionError::ValidationError("too small".into()),
+                StatusCode::BAD_REQUEST,
+                "INVALID_IMAGE",
+            ),
+            (
+                ImageRecognitionError::UnsupportedFormat("txt".into()),
+                StatusCode::BAD_REQUEST,
+                "INVALID_IMAGE",
+            ),
+            (
+                ImageRecognitionError::FileNotFound("a.png".into()),
+                StatusCode::NOT_FOUND,
+                "IMAGE_NOT_FOUND",
+            ),
+            (
+                ImageRecognitionError::Timeout("poll".into()),
+                StatusCode::GATEWAY_TIMEOUT,
+                "UPSTREAM_TIMEOUT",
+            ),
+            (
+                ImageRecognitionError::AuthError("token".into()),
+                StatusCode::BAD_GATEWAY,
+                "AUTH",
+            ),
+            (
+                ImageRecognitionError::EngineError("boom".into()),
+                StatusCode::BAD_GATEWAY,
+                "UPSTREAM_ERROR",
+            ),
+            (
+                ImageRecognitionError::Cancelled,
+                StatusCode::from_u16(499).unwrap(),
+                "CANCELLED",
+            ),
+            (
+                ImageRecognitionError::ConfigError("missing".into()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL",
+            ),
+        ];
+
+        for (err, status, code) in cases {
+            let api_error = ApiError::from(err);
+            assert_eq!(api_error.status(), status);
+            assert_eq!(api_error.code(), code);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_error_response_shape() {
+        let response =
+            ApiError::from(ImageRecognitionError::Timeout("poll".into())).into_response();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "UPSTREAM_TIMEOUT");
+        assert!(body["error"]["message"].as_str().unwrap().contains("poll"));
+    }
+
+    #[tokio::test]
+    async fn test_recognition_errors_propagate_through_handler() {
+        use tower::ServiceExt;
+
+        fn recognition_error(kind: &str) -> ImageRecognitionError {
+            match kind {
+                "validation" => ImageRecognitionError::ValidationError("too small".into()),
+                "format" => ImageRecognitionError::UnsupportedFormat("txt".into()),
+                "not_found" => ImageRecognitionError::FileNotFound("a.png".into()),
+                "timeout" => ImageRecognitionError::Timeout("poll".into()),
+                "auth" => ImageRecognitionError::AuthError("token".into()),
+                "engine" => ImageRecognitionError::EngineError("boom".into()),
+                "network" => ImageRecognitionError::Network("refused".into()),
+                "parse" => ImageRecognitionError::Parse("eof".into()),
+                "cancelled" => ImageRecognitionError::Cancelled,
+                "tesseract" => ImageRecognitionError::TesseractError("init".into()),
+                "io" => ImageRecognitionError::IoError(std::io::Error::other("disk")),
+                _ => ImageRecognitionError::ConfigError("missing".into()),
+            }
+        }
+
+        // 与真实处理函数一样直接对识别结果使用 `?`
+        async fn handler(
+            axum::extract::Path(kind): axum::extract::Path<String>,
+        ) -> Result<String, ApiError> {
+            let result: Result<String, ImageRecognitionError> = Err(recognition_error(&kind));
+            Ok(result?)
+        }
+
+        let app = Router::new().route("/:kind", get(handler));
+        let cases = [
+            ("validation", 400, "INVALID_IMAGE"),
+            ("format", 400, "INVALID_IMAGE"),
+            ("not_found", 404, "IMAGE_NOT_FOUND"),
+            ("timeout", 504, "UPSTREAM_TIMEOUT"),
+            ("auth", 502, "AUTH"),
+            ("engine", 502, "UPSTREAM_ERROR"),
+            ("network", 502, "UPSTREAM_ERROR"),
+            ("parse", 502, "UPSTREAM_ERROR"),
+            ("cancelled", 499, "CANCELLED"),
+            ("tesseract", 500, "INTERNAL"),
+            ("io", 500, "INTERNAL"),
+            ("config", 500, "INTERNAL"),
+        ];
+
+        for (kind, status, code) in cases {
+            let response = app
+                .clone()
+                .oneshot(
+                    axum::http::Request::builder()
+                        .uri(format!("/{kind}"))
+                        .body(axum::body::Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status().as_u16(), status, "{kind}");
+
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            assert_eq!(body["success"], false);
+            assert_eq!(body["error"]["code"], code, "{kind}");
+        }
+    }
+
+    #[test]
+    fn test_cache_key_tracks_result_settings() {
+        let config = RemoteOcrConfig::for_testing("http://127.0.0.1:1");
+        let key = cache_key(b"image", &config, false);
+        assert!(key.starts_with(&format!(
+            "{CACHE_KEY_PREFIX}:{}:",
+            pic_recog::utils::sha1_hex(b"image")
+        )));
+        assert!(key.ends_with(":text"));
+        assert_eq!(key, cache_key(b"image", &config.clone(), false));
+        assert_ne!(key, cache_key(b"image", &config, true));
+
+        let mut language = config.clone();
+        language.language = Some("eng".to_string());
+        let mut oriented = config.clone();
+        oriented.auto_orient = true;
+        let mut other_service = config.clone();
+        other_service.start_url = "http://127.0.0.1:2/start".to_string();
+        for changed in [language, oriented, other_service] {
+            assert_ne!(key, cache_key(b"image", &changed, false));
+        }
+
+        // 与结果无关的配置不影响缓存键
+        let mut tuned = config.clone();
+        tuned.timeout_secs = 60;
+        tuned.batch_concurrency = 8;
+        assert_eq!(key, cache_key(b"image", &tuned, false));
+    }
+
+    #[test]
+    fn test_cancel_on_drop_sets_flag() {
+        let guard = CancelOnDrop::new();
+        let token = guard.token();
+        assert!(!token.load(Ordering::Relaxed));
+        drop(guard);
+        assert!(token.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in ["8.8.8.8", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "224.0.0.1",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_host_ip() {
+        assert_eq!(host_ip("[::1]"), Some("::1".parse().unwrap()));
+        assert_eq!(host_ip("10.0.0.1"), Some("10.0.0.1".parse().unwrap()));
+        assert_eq!(host_ip("example.com"), None);
+    }
+
+    #[test]
+    fn test_url_file_name() {
+        let url = reqwest::Url::parse("https://example.com/shots/a.png?x=1").unwrap();
+        assert_eq!(url_file_name(&url, None), "a.png");
+
+        let url = reqwest::Url::parse("https://example.com/render/123").unwrap();
+        assert_eq!(url_file_name(&url, Some("image/jpeg")), "123.jpg");
+
+        let url = reqwest::Url::parse("https://example.com/").unwrap();
+        assert_eq!(url_file_name(&url, Some("image/webp")), "image.webp");
+    }
+}
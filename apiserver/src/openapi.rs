@@ -0,0 +1,61 @@
+//! OpenAPI 文档
+//!
+//! 汇总各模块处理函数上的 `#[utoipa::path]` 注解，通过 `/api/openapi.json` 提供规范，
+//! 启用 `docs` feature 时同时在 `/api/docs` 提供 Swagger UI。
+
+use crate::{anybox, image, ocr, prompt};
+use axum::{Json, Router, routing::get};
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "rsde apiserver"),
+    paths(
+        ocr::list_engines,
+        ocr::single_pic_remote,
+        ocr::batch_remote,
+        ocr::from_url_remote,
+        image::handle_upload,
+        image::handle_meta,
+        image::handle_sharded_meta,
+        anybox::create_textbox,
+        anybox::list_textboxes,
+        anybox::get_textbox,
+        anybox::raw_textbox,
+        anybox::delete_textbox,
+        anybox::import_textboxes,
+        anybox::apply_default_expiry,
+        anybox::add_textbox_tag,
+        anybox::remove_textbox_tag,
+        prompt::create_prompt,
+        prompt::list_prompts,
+        prompt::get_prompt,
+        prompt::update_prompt,
+        prompt::delete_prompt,
+        prompt::restore_prompt,
+        prompt::add_prompt_tag,
+        prompt::remove_prompt_tag,
+        prompt::export_prompts,
+        prompt::import_prompts,
+    ),
+    tags(
+        (name = "ocr", description = "远程 OCR 识别"),
+        (name = "image", description = "图床"),
+        (name = "anybox", description = "文本分享"),
+        (name = "prompt", description = "Prompt 模板管理"),
+    )
+)]
+pub struct ApiDoc;
+
+/// 创建文档路由
+pub fn create_routes() -> Router {
+    let router = Router::new().route(
+        "/api/openapi.json",
+        get(|| async { Json(ApiDoc::openapi()) }),
+    );
+
+    #[cfg(feature = "docs")]
+    let router = router.merge(utoipa_swagger_ui::SwaggerUi::new("/api/docs"));
+
+    router
+}
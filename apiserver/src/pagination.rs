@@ -0,0 +1,115 @@
+//! 列表接口的分页响应头
+//!
+//! 根据分页结果生成 `X-Total-Count`、`X-Page` 与 RFC 5988 `Link` 头，
+//! 方便通用客户端在不解析响应体的情况下翻页
+
+use axum::http::{HeaderMap, HeaderValue, Uri};
+
+/// 分页信息（anybox 与 prompt 的 `PaginatedResult` 字段一致）
+#[derive(Debug, Clone, Copy)]
+pub struct PageInfo {
+    pub total: u64,
+    pub page: u32,
+    pub total_pages: u32,
+}
+
+/// 生成分页响应头，`uri` 应为原始请求 URI（含挂载前缀）
+pub fn pagination_headers(uri: &Uri, info: PageInfo) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-total-count", HeaderValue::from(info.total));
+    headers.insert("x-page", HeaderValue::from(info.page));
+
+    // 空结果的 total_pages 为 0，页码从 1 开始，最后一页至少是第 1 页
+    let last_page = info.total_pages.max(1);
+    let mut links = Vec::new();
+    if info.page < info.total_pages {
+        links.push(link(uri, info.page + 1, "next"));
+    }
+    if info.page > 1 {
+        links.push(link(uri, (info.page - 1).min(last_page), "prev"));
+    }
+    links.push(link(uri, last_page, "last"));
+
+    if let Ok(value) = HeaderValue::from_str(&links.join(", ")) {
+        headers.insert("link", value);
+    }
+    headers
+}
+
+/// 替换查询参数中的 page，其余参数原样保留
+fn link(uri: &Uri, page: u32, rel: &str) -> String {
+    let mut params: Vec<&str> = uri
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| !pair.is_empty() && pair.split('=').next() != Some("page"))
+        .collect();
+    let page_param = format!("page={page}");
+    params.insert(0, &page_param);
+    format!("<{}?{}>; rel=\"{rel}\"", uri.path(), params.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_header_on_middle_page() {
+        let uri: Uri = "/api/prompt/template?page=2&page_size=10&name=foo"
+            .parse()
+            .unwrap();
+        let headers = pagination_headers(
+            &uri,
+            PageInfo {
+                total: 45,
+                page: 2,
+                total_pages: 5,
+            },
+        );
+
+        assert_eq!(headers["x-total-count"], "45");
+        assert_eq!(headers["x-page"], "2");
+        let link = headers["link"].to_str().unwrap();
+        assert!(link.contains("</api/prompt/template?page=3&page_size=10&name=foo>; rel=\"next\""));
+        assert!(link.contains("</api/prompt/template?page=1&page_size=10&name=foo>; rel=\"prev\""));
+        assert!(link.contains("</api/prompt/template?page=5&page_size=10&name=foo>; rel=\"last\""));
+    }
+
+    #[test]
+    fn test_link_header_on_last_page_has_no_next() {
+        let uri: Uri = "/api/anybox/textbox".parse().unwrap();
+        let headers = pagination_headers(
+            &uri,
+            PageInfo {
+                total: 3,
+                page: 1,
+                total_pages: 1,
+            },
+        );
+
+        let link = headers["link"].to_str().unwrap();
+        assert!(!link.contains("rel=\"next\""));
+        assert!(!link.contains("rel=\"prev\""));
+        assert_eq!(link, "</api/anybox/textbox?page=1>; rel=\"last\"");
+    }
+
+    #[test]
+    fn test_link_header_on_empty_result_points_to_first_page() {
+        let uri: Uri = "/api/anybox/textbox?page=3".parse().unwrap();
+        let headers = pagination_headers(
+            &uri,
+            PageInfo {
+                total: 0,
+                page: 3,
+                total_pages: 0,
+            },
+        );
+
+        let link = headers["link"].to_str().unwrap();
+        assert!(!link.contains("page=0"));
+        assert_eq!(
+            link,
+            "</api/anybox/textbox?page=1>; rel=\"prev\", </api/anybox/textbox?page=1>; rel=\"last\""
+        );
+    }
+}
@@ -1,33 +1,40 @@
+use crate::pagination::{PageInfo, pagination_headers};
 use axum::{
     Router,
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    body::Body,
+    extract::{OriginalUri, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
 };
+use futures::StreamExt;
 use prompt::{
-    PaginatedResult, PaginationParams, PromptCategory, PromptTemplate, PromptTemplateManager,
+    PaginatedResult, PaginationParams, PromptCategory, PromptError, PromptStore, PromptTemplate,
+    PromptTemplateManager, SortBy,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::sync::Mutex;
 use tracing::{error, info};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct PromptState {
-    manager: Arc<Mutex<PromptTemplateManager>>,
+    store: Arc<dyn PromptStore>,
 }
 
 impl PromptState {
     pub async fn new(config: config::prompt::PromptConfig) -> anyhow::Result<Self> {
         let manager = PromptTemplateManager::new(config).await?;
-        Ok(Self {
-            manager: Arc::new(Mutex::new(manager)),
-        })
+        Ok(Self::with_store(Arc::new(manager)))
+    }
+
+    /// 使用指定的存储实现，测试中可传入 `InMemoryPromptStore`
+    pub fn with_store(store: Arc<dyn PromptStore>) -> Self {
+        Self { store }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreatePromptRequest {
     pub name: String,
     pub content: String,
@@ -43,7 +50,7 @@ pub struct CreatePromptRequest {
     pub created_by: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdatePromptRequest {
     pub name: String,
     pub content: String,
@@ -59,31 +66,61 @@ pub struct UpdatePromptRequest {
     pub is_active: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddPromptTagRequest {
+    pub tag: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PromptResponse {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
     pub data: Option<PromptTemplate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ListPromptResponse {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
     pub data: Option<PaginatedResult<PromptTemplate>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportPromptResponse {
+    pub success: bool,
+    pub imported: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ImportPromptResponse {
+    fn failed(error: String) -> Self {
+        Self {
+            success: false,
+            imported: 0,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SearchParams {
     #[serde(default = "default_page")]
     pub page: u32,
     #[serde(default = "default_page_size")]
     pub page_size: u32,
     pub name: Option<String>,
+    /// 排序字段：created（默认）或 updated，均为倒序
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub sort: SortBy,
 }
 
 fn default_page() -> u32 {
@@ -94,6 +131,26 @@ fn default_page_size() -> u32 {
     20
 }
 
+/// 存储错误对应的 HTTP 状态码
+fn error_status(err: &PromptError) -> StatusCode {
+    match err {
+        PromptError::NotFound(_) => StatusCode::NOT_FOUND,
+        PromptError::Conflict(_) => StatusCode::CONFLICT,
+        PromptError::Validation(_) => StatusCode::BAD_REQUEST,
+        PromptError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/prompt/template",
+    tag = "prompt",
+    request_body = CreatePromptRequest,
+    responses(
+        (status = 200, description = "创建成功", body = PromptResponse),
+        (status = 409, description = "名称冲突", body = PromptResponse),
+    )
+)]
 async fn create_prompt(
     State(state): State<PromptState>,
     Json(req): Json<CreatePromptRequest>,
@@ -122,8 +179,21 @@ async fn create_prompt(
         template = template.with_created_by(created_by);
     }
 
-    let manager = state.manager.lock().await;
-    match manager.create(template).await {
+    // 必填字段在访问存储前校验
+    if let Err(e) = template.validate() {
+        info!("Rejected PromptTemplate: {}", e);
+        return Err((
+            error_status(&e),
+            Json(PromptResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+            }),
+        ));
+    }
+
+    let store = &state.store;
+    match store.create(template).await {
         Ok(created) => Ok(Json(PromptResponse {
             success: true,
             data: Some(created),
@@ -132,7 +202,7 @@ async fn create_prompt(
         Err(e) => {
             error!("Failed to create PromptTemplate: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
                 Json(PromptResponse {
                     success: false,
                     data: None,
@@ -143,14 +213,24 @@ async fn create_prompt(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/prompt/template/{id}",
+    tag = "prompt",
+    params(("id" = String, Path, description = "模板 ID")),
+    responses(
+        (status = 200, description = "模板内容", body = PromptResponse),
+        (status = 404, description = "模板不存在", body = PromptResponse),
+    )
+)]
 async fn get_prompt(
     State(state): State<PromptState>,
     Path(id): Path<String>,
 ) -> Result<Json<PromptResponse>, (StatusCode, Json<PromptResponse>)> {
     info!("Getting PromptTemplate: id={}", id);
 
-    let manager = state.manager.lock().await;
-    match manager.get(&id).await {
+    let store = &state.store;
+    match store.get(&id).await {
         Ok(Some(template)) => Ok(Json(PromptResponse {
             success: true,
             data: Some(template),
@@ -167,7 +247,7 @@ async fn get_prompt(
         Err(e) => {
             error!("Failed to get PromptTemplate: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
                 Json(PromptResponse {
                     success: false,
                     data: None,
@@ -178,34 +258,58 @@ async fn get_prompt(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/prompt/template",
+    tag = "prompt",
+    params(SearchParams),
+    responses(
+        (status = 200, description = "分页列表，分页信息同时写入 X-Total-Count/Link 头", body = ListPromptResponse),
+        (status = 400, description = "排序字段不支持"),
+    )
+)]
 async fn list_prompts(
     State(state): State<PromptState>,
+    OriginalUri(uri): OriginalUri,
     Query(params): Query<SearchParams>,
-) -> Result<Json<ListPromptResponse>, (StatusCode, Json<ListPromptResponse>)> {
+) -> Result<(HeaderMap, Json<ListPromptResponse>), (StatusCode, Json<ListPromptResponse>)> {
     info!(
-        "Listing PromptTemplates: page={}, page_size={}, name={:?}",
-        params.page, params.page_size, params.name
+        "Listing PromptTemplates: page={}, page_size={}, name={:?}, sort={:?}",
+        params.page, params.page_size, params.name, params.sort
     );
 
-    let manager = state.manager.lock().await;
-    let pagination = PaginationParams::new(params.page, params.page_size);
+    let store = &state.store;
+    let pagination = PaginationParams::new(params.page, params.page_size).with_sort(params.sort);
 
     let result = if let Some(name) = params.name {
-        manager.search_by_name(&name, pagination).await
+        store.search_by_name(&name, pagination).await
     } else {
-        manager.list(pagination).await
+        store.list(pagination).await
     };
 
     match result {
-        Ok(result) => Ok(Json(ListPromptResponse {
-            success: true,
-            data: Some(result),
-            error: None,
-        })),
+        Ok(result) => {
+            let headers = pagination_headers(
+                &uri,
+                PageInfo {
+                    total: result.total,
+                    page: result.page,
+                    total_pages: result.total_pages,
+                },
+            );
+            Ok((
+                headers,
+                Json(ListPromptResponse {
+                    success: true,
+                    data: Some(result),
+                    error: None,
+                }),
+            ))
+        }
         Err(e) => {
             error!("Failed to list PromptTemplates: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
                 Json(ListPromptResponse {
                     success: false,
                     data: None,
@@ -216,6 +320,17 @@ async fn list_prompts(
     }
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/prompt/template/{id}",
+    tag = "prompt",
+    params(("id" = String, Path, description = "模板 ID")),
+    request_body = UpdatePromptRequest,
+    responses(
+        (status = 200, description = "更新成功", body = PromptResponse),
+        (status = 404, description = "模板不存在", body = PromptResponse),
+    )
+)]
 async fn update_prompt(
     State(state): State<PromptState>,
     Path(id): Path<String>,
@@ -223,9 +338,9 @@ async fn update_prompt(
 ) -> Result<Json<PromptResponse>, (StatusCode, Json<PromptResponse>)> {
     info!("Updating PromptTemplate: id={}", id);
 
-    let manager = state.manager.lock().await;
+    let store = &state.store;
 
-    let existing = match manager.get(&id).await {
+    let existing = match store.get(&id).await {
         Ok(Some(t)) => t,
         Ok(None) => {
             return Err((
@@ -239,7 +354,7 @@ async fn update_prompt(
         }
         Err(e) => {
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
                 Json(PromptResponse {
                     success: false,
                     data: None,
@@ -267,9 +382,10 @@ async fn update_prompt(
         created_at: existing.created_at,
         updated_at: chrono::Utc::now(),
         created_by: existing.created_by,
+        deleted_at: existing.deleted_at,
     };
 
-    match manager.update(updated).await {
+    match store.update(updated).await {
         Ok(result) => Ok(Json(PromptResponse {
             success: true,
             data: Some(result),
@@ -278,7 +394,7 @@ async fn update_prompt(
         Err(e) => {
             error!("Failed to update PromptTemplate: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
                 Json(PromptResponse {
                     success: false,
                     data: None,
@@ -289,14 +405,33 @@ async fn update_prompt(
     }
 }
 
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteParams {
+    /// 软删除（可恢复），默认永久删除
+    #[serde(default)]
+    pub soft: bool,
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/prompt/template/{id}",
+    tag = "prompt",
+    params(("id" = String, Path, description = "模板 ID"), DeleteParams),
+    responses(
+        (status = 200, description = "删除成功", body = PromptResponse),
+        (status = 404, description = "模板不存在", body = PromptResponse),
+    )
+)]
 async fn delete_prompt(
     State(state): State<PromptState>,
     Path(id): Path<String>,
+    Query(params): Query<DeleteParams>,
 ) -> Result<Json<PromptResponse>, (StatusCode, Json<PromptResponse>)> {
-    info!("Deleting PromptTemplate: id={}", id);
+    info!("Deleting PromptTemplate: id={}, soft={}", id, params.soft);
 
-    let manager = state.manager.lock().await;
-    match manager.delete(&id).await {
+    let store = &state.store;
+    match store.delete(&id, params.soft).await {
         Ok(true) => Ok(Json(PromptResponse {
             success: true,
             data: None,
@@ -313,7 +448,183 @@ async fn delete_prompt(
         Err(e) => {
             error!("Failed to delete PromptTemplate: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
+                Json(PromptResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                }),
+            ))
+        }
+    }
+}
+
+/// 以 NDJSON 流式导出全部模板（包括已停用的）
+#[utoipa::path(
+    get,
+    path = "/api/prompt/template/export",
+    tag = "prompt",
+    responses((status = 200, description = "每行一个模板 JSON", content_type = "application/x-ndjson", body = String))
+)]
+async fn export_prompts(State(state): State<PromptState>) -> Response {
+    info!("Exporting PromptTemplates");
+
+    let stream = state.store.export_stream();
+    let body = Body::from_stream(stream.map(|template| {
+        template.and_then(|t| {
+            let mut line = serde_json::to_string(&t)?;
+            line.push('\n');
+            Ok(line)
+        })
+    }));
+
+    ([(header::CONTENT_TYPE, "application/x-ndjson")], body).into_response()
+}
+
+/// 导入 NDJSON 格式的模板，整体在一个事务中完成
+#[utoipa::path(
+    post,
+    path = "/api/prompt/template/import",
+    tag = "prompt",
+    request_body(content = String, content_type = "application/x-ndjson", description = "每行一个模板 JSON"),
+    responses(
+        (status = 200, description = "导入成功", body = ImportPromptResponse),
+        (status = 400, description = "格式错误", body = ImportPromptResponse),
+    )
+)]
+async fn import_prompts(
+    State(state): State<PromptState>,
+    body: String,
+) -> Result<Json<ImportPromptResponse>, (StatusCode, Json<ImportPromptResponse>)> {
+    let mut templates = Vec::new();
+    for (index, line) in body.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let template: PromptTemplate = serde_json::from_str(line).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ImportPromptResponse::failed(format!(
+                    "Invalid template on line {}: {e}",
+                    index + 1
+                ))),
+            )
+        })?;
+        templates.push(template);
+    }
+
+    info!("Importing {} PromptTemplates", templates.len());
+
+    let store = &state.store;
+    match store.import(templates).await {
+        Ok(imported) => Ok(Json(ImportPromptResponse {
+            success: true,
+            imported,
+            error: None,
+        })),
+        Err(e) => {
+            error!("Failed to import PromptTemplates: {}", e);
+            Err((
+                error_status(&e),
+                Json(ImportPromptResponse::failed(e.to_string())),
+            ))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/prompt/template/{id}/restore",
+    tag = "prompt",
+    params(("id" = String, Path, description = "模板 ID")),
+    responses(
+        (status = 200, description = "恢复成功", body = PromptResponse),
+        (status = 404, description = "模板不存在", body = PromptResponse),
+    )
+)]
+async fn restore_prompt(
+    State(state): State<PromptState>,
+    Path(id): Path<String>,
+) -> Result<Json<PromptResponse>, (StatusCode, Json<PromptResponse>)> {
+    info!("Restoring PromptTemplate: id={}", id);
+
+    let store = &state.store;
+    let restored = match store.restore(&id).await {
+        Ok(restored) => restored,
+        Err(e) => {
+            error!("Failed to restore PromptTemplate: {}", e);
+            return Err((
+                error_status(&e),
+                Json(PromptResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                }),
+            ));
+        }
+    };
+
+    if !restored {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(PromptResponse {
+                success: false,
+                data: None,
+                error: Some("Deleted PromptTemplate not found".to_string()),
+            }),
+        ));
+    }
+
+    match store.get(&id).await {
+        Ok(template) => Ok(Json(PromptResponse {
+            success: true,
+            data: template,
+            error: None,
+        })),
+        Err(e) => {
+            error!("Failed to get PromptTemplate: {}", e);
+            Err((
+                error_status(&e),
+                Json(PromptResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                }),
+            ))
+        }
+    }
+}
+
+/// 添加标签，只修改标签列表；标签已存在时原样返回
+#[utoipa::path(
+    post,
+    path = "/api/prompt/template/{id}/tags",
+    tag = "prompt",
+    params(("id" = String, Path, description = "模板 ID")),
+    request_body = AddPromptTagRequest,
+    responses(
+        (status = 200, description = "添加成功", body = PromptResponse),
+        (status = 400, description = "标签为空", body = PromptResponse),
+        (status = 404, description = "模板不存在", body = PromptResponse),
+    )
+)]
+async fn add_prompt_tag(
+    State(state): State<PromptState>,
+    Path(id): Path<String>,
+    Json(req): Json<AddPromptTagRequest>,
+) -> Result<Json<PromptResponse>, (StatusCode, Json<PromptResponse>)> {
+    info!("Adding PromptTemplate tag: id={}, tag={}", id, req.tag);
+
+    match state.store.add_tag(&id, &req.tag).await {
+        Ok(template) => Ok(Json(PromptResponse {
+            success: true,
+            data: Some(template),
+            error: None,
+        })),
+        Err(e) => {
+            error!("Failed to add PromptTemplate tag: {}", e);
+            Err((
+                error_status(&e),
                 Json(PromptResponse {
                     success: false,
                     data: None,
@@ -324,23 +635,72 @@ async fn delete_prompt(
     }
 }
 
-async fn health_check() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "status": "ok",
-        "service": "prompt-api",
-        "version": env!("CARGO_PKG_VERSION")
-    }))
+/// 移除标签，只修改标签列表；标签不存在时原样返回
+#[utoipa::path(
+    delete,
+    path = "/api/prompt/template/{id}/tags/{tag}",
+    tag = "prompt",
+    params(
+        ("id" = String, Path, description = "模板 ID"),
+        ("tag" = String, Path, description = "要移除的标签"),
+    ),
+    responses(
+        (status = 200, description = "移除成功", body = PromptResponse),
+        (status = 404, description = "模板不存在", body = PromptResponse),
+    )
+)]
+async fn remove_prompt_tag(
+    State(state): State<PromptState>,
+    Path((id, tag)): Path<(String, String)>,
+) -> Result<Json<PromptResponse>, (StatusCode, Json<PromptResponse>)> {
+    info!("Removing PromptTemplate tag: id={}, tag={}", id, tag);
+
+    match state.store.remove_tag(&id, &tag).await {
+        Ok(template) => Ok(Json(PromptResponse {
+            success: true,
+            data: Some(template),
+            error: None,
+        })),
+        Err(e) => {
+            error!("Failed to remove PromptTemplate tag: {}", e);
+            Err((
+                error_status(&e),
+                Json(PromptResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                }),
+            ))
+        }
+    }
+}
+
+/// 健康检查，会 ping MySQL
+async fn health_check(State(state): State<PromptState>) -> Response {
+    crate::health::backend_health("prompt-api", state.store.ping()).await
 }
 
 pub async fn create_routes(config: config::prompt::PromptConfig) -> anyhow::Result<Router> {
     let state = PromptState::new(config).await?;
+    Ok(create_routes_with_state(state))
+}
 
-    Ok(Router::new()
+/// 使用已构建的状态创建路由，便于注入其他存储实现
+pub fn create_routes_with_state(state: PromptState) -> Router {
+    Router::new()
         .route("/health", get(health_check))
         .route("/template", post(create_prompt))
         .route("/template", get(list_prompts))
+        .route("/template/export", get(export_prompts))
+        .route("/template/import", post(import_prompts))
         .route("/template/:id", get(get_prompt))
         .route("/template/:id", axum::routing::put(update_prompt))
         .route("/template/:id", axum::routing::delete(delete_prompt))
-        .with_state(state))
+        .route("/template/:id/restore", post(restore_prompt))
+        .route("/template/:id/tags", post(add_prompt_tag))
+        .route(
+            "/template/:id/tags/:tag",
+            axum::routing::delete(remove_prompt_tag),
+        )
+        .with_state(state)
 }
@@ -0,0 +1,173 @@
+//! 请求限流中间件
+//!
+//! 按客户端 IP 的令牌桶限流，桶保存在内存中，超限时返回 429 与 `Retry-After`。
+//! 只限制会产生写入或调用远程服务的请求，GET/HEAD/OPTIONS 直接放行。
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// 清理空闲桶的间隔
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 单个客户端的令牌桶
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// 按 IP 的令牌桶限流器
+#[derive(Clone)]
+pub struct RateLimiter {
+    /// 桶容量，即每分钟允许的请求数
+    capacity: f64,
+    /// 每秒补充的令牌数
+    refill_per_sec: f64,
+    buckets: Arc<DashMap<String, Bucket>>,
+    /// 上次清理空闲桶的时间
+    last_sweep: Arc<Mutex<Instant>>,
+}
+
+impl RateLimiter {
+    /// 创建限流器，`requests_per_minute` 为 0 时不限流
+    pub fn new(requests_per_minute: u32) -> Self {
+        Self {
+            capacity: requests_per_minute as f64,
+            refill_per_sec: requests_per_minute as f64 / 60.0,
+            buckets: Arc::new(DashMap::new()),
+            last_sweep: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// 是否启用限流
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0.0
+    }
+
+    /// 尝试消耗一个令牌，超限时返回需要等待的秒数
+    pub fn check(&self, key: &str) -> Result<(), u64> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        self.sweep_idle(now);
+        let mut bucket = self.buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            last_refill: now,
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = (1.0 - bucket.tokens) / self.refill_per_sec;
+            Err(wait.ceil().max(1.0) as u64)
+        }
+    }
+
+    /// 当前保存的桶数量
+    pub fn tracked_clients(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// 每隔 `SWEEP_INTERVAL` 移除已经补满的桶，补满的桶与新建的桶等价，移除不影响限流结果
+    fn sweep_idle(&self, now: Instant) {
+        let Ok(mut last_sweep) = self.last_sweep.try_lock() else {
+            return;
+        };
+        if now.duration_since(*last_sweep) < SWEEP_INTERVAL {
+            return;
+        }
+        *last_sweep = now;
+
+        let full_after = Duration::from_secs_f64(self.capacity / self.refill_per_sec);
+        self.buckets
+            .retain(|_, bucket| now.duration_since(bucket.last_refill) < full_after);
+    }
+}
+
+/// 限流中间件，配合 `axum::middleware::from_fn_with_state` 使用
+pub async fn rate_limit(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        return next.run(request).await;
+    }
+
+    // 没有对端地址时无法区分客户端，放行而不是让所有请求共用一个桶
+    let Some(ConnectInfo(addr)) = request.extensions().get::<ConnectInfo<SocketAddr>>() else {
+        debug!("缺少客户端地址，跳过限流: path={}", request.uri().path());
+        return next.run(request).await;
+    };
+    let client = addr.ip().to_string();
+
+    match limiter.check(&client) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            warn!("请求被限流: client={client}, path={}", request.uri().path());
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                "请求过于频繁，请稍后再试",
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_exhausts_after_capacity() {
+        let limiter = RateLimiter::new(2);
+        assert!(limiter.check("1.1.1.1").is_ok());
+        assert!(limiter.check("1.1.1.1").is_ok());
+        assert_eq!(limiter.check("1.1.1.1"), Err(30));
+        // 其他 IP 不受影响
+        assert!(limiter.check("2.2.2.2").is_ok());
+    }
+
+    #[test]
+    fn test_sweep_removes_refilled_buckets() {
+        let limiter = RateLimiter::new(60);
+        assert!(limiter.check("1.1.1.1").is_ok());
+        assert!(limiter.check("2.2.2.2").is_ok());
+        assert_eq!(limiter.tracked_clients(), 2);
+
+        // 1.1.1.1 已空闲超过补满所需时间，2.2.2.2 刚刚访问过
+        let now = Instant::now() + SWEEP_INTERVAL;
+        limiter.buckets.get_mut("2.2.2.2").unwrap().last_refill = now;
+        limiter.sweep_idle(now);
+
+        assert_eq!(limiter.tracked_clients(), 1);
+        assert!(limiter.buckets.contains_key("2.2.2.2"));
+    }
+
+    #[test]
+    fn test_zero_limit_disables() {
+        let limiter = RateLimiter::new(0);
+        for _ in 0..100 {
+            assert!(limiter.check("1.1.1.1").is_ok());
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use axum::{
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use config::anybox::AnyboxConfig;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+fn request(method: &str, uri: &str, body: Body) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)
+        .expect("request")
+}
+
+#[tokio::test]
+#[ignore] // 需要 Redis 运行
+async fn head_reports_existence_without_counting_views() {
+    let app = apiserver::anybox::create_routes(AnyboxConfig {
+        key_prefix: "anybox_head_test".to_string(),
+        ..Default::default()
+    })
+    .await
+    .expect("anybox routes");
+
+    let created = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/textbox",
+            Body::from(json!({ "author": "Alice", "content": "head" }).to_string()),
+        ))
+        .await
+        .expect("create response");
+    let bytes = to_bytes(created.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    let id = body["data"]["id"].as_str().expect("id").to_string();
+
+    let head = app
+        .clone()
+        .oneshot(request("HEAD", &format!("/textbox/{id}"), Body::empty()))
+        .await
+        .expect("head response");
+    assert_eq!(head.status(), StatusCode::OK);
+    let bytes = to_bytes(head.into_body(), usize::MAX).await.unwrap();
+    assert!(bytes.is_empty());
+
+    let missing = app
+        .clone()
+        .oneshot(request("HEAD", "/textbox/missing", Body::empty()))
+        .await
+        .expect("head response");
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+    let fetched = app
+        .clone()
+        .oneshot(request("GET", &format!("/textbox/{id}"), Body::empty()))
+        .await
+        .expect("get response");
+    let bytes = to_bytes(fetched.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["data"]["metadata"]["view_count"], 1);
+
+    app.oneshot(request("DELETE", &format!("/textbox/{id}"), Body::empty()))
+        .await
+        .expect("delete response");
+}
+
+#[tokio::test]
+#[ignore] // 需要 Redis 运行
+async fn list_rejects_unknown_sort() {
+    let app = apiserver::anybox::create_routes(AnyboxConfig {
+        key_prefix: "anybox_sort_test".to_string(),
+        ..Default::default()
+    })
+    .await
+    .expect("anybox routes");
+
+    for (uri, expected) in [
+        ("/textbox?sort=updated", StatusCode::OK),
+        ("/textbox?sort=views", StatusCode::BAD_REQUEST),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(request("GET", uri, Body::empty()))
+            .await
+            .expect("list response");
+        assert_eq!(response.status(), expected, "{uri}");
+    }
+}
+
+#[tokio::test]
+#[ignore] // 需要 Redis 运行
+async fn create_rejects_empty_required_fields() {
+    let app = apiserver::anybox::create_routes(AnyboxConfig {
+        key_prefix: "anybox_validate_test".to_string(),
+        ..Default::default()
+    })
+    .await
+    .expect("anybox routes");
+
+    for (payload, field) in [
+        (json!({ "author": " ", "content": "hello" }), "author"),
+        (json!({ "author": "Alice", "content": "" }), "content"),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(request("POST", "/textbox", Body::from(payload.to_string())))
+            .await
+            .expect("create response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert!(body["error"].as_str().expect("error").contains(field));
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/textbox",
+            Body::from(json!({ "author": "Alice", "content": "hello" }).to_string()),
+        ))
+        .await
+        .expect("create response");
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    let id = body["data"]["id"].as_str().expect("id");
+    app.oneshot(request("DELETE", &format!("/textbox/{id}"), Body::empty()))
+        .await
+        .expect("delete response");
+}
+
+#[tokio::test]
+#[ignore] // 需要 Redis 运行
+async fn add_and_remove_tags() {
+    let app = apiserver::anybox::create_routes(AnyboxConfig {
+        key_prefix: "anybox_tag_test".to_string(),
+        ..Default::default()
+    })
+    .await
+    .expect("anybox routes");
+
+    let send = |method: &'static str, uri: String, body: Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(request(method, &uri, Body::from(body.to_string())))
+                .await
+                .expect("response");
+            let status = response.status();
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<Value>(&bytes).unwrap())
+        }
+    };
+
+    let (_, body) = send(
+        "POST",
+        "/textbox".to_string(),
+        json!({ "author": "Alice", "content": "tags", "tags": ["a"] }),
+    )
+    .await;
+    let id = body["data"]["id"].as_str().expect("id").to_string();
+
+    let (status, body) = send("POST", format!("/textbox/{id}/tags"), json!({ "tag": "b" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["metadata"]["tags"], json!(["a", "b"]));
+
+    // 重复添加与移除不存在的标签都是空操作
+    let (status, body) = send("POST", format!("/textbox/{id}/tags"), json!({ "tag": "b" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["metadata"]["tags"], json!(["a", "b"]));
+    let (status, body) = send("DELETE", format!("/textbox/{id}/tags/missing"), json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["metadata"]["tags"], json!(["a", "b"]));
+
+    let (status, body) = send("DELETE", format!("/textbox/{id}/tags/a"), json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["metadata"]["tags"], json!(["b"]));
+
+    let (status, _) = send("POST", format!("/textbox/{id}/tags"), json!({ "tag": " " })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        "POST",
+        "/textbox/missing/tags".to_string(),
+        json!({ "tag": "a" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    send("DELETE", format!("/textbox/{id}"), json!({})).await;
+}
+
+#[tokio::test]
+#[ignore] // 需要 Redis 运行
+async fn apply_default_expiry_requires_admin_token() {
+    let app = apiserver::anybox::create_routes(AnyboxConfig {
+        key_prefix: format!("anybox_expiry_test_{}", uuid::Uuid::new_v4()),
+        admin_token: Some("secret".to_string()),
+        ..Default::default()
+    })
+    .await
+    .expect("anybox routes");
+
+    let create = |payload: Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(request("POST", "/textbox", Body::from(payload.to_string())))
+                .await
+                .expect("create response");
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: Value = serde_json::from_slice(&bytes).unwrap();
+            body["data"]["id"].as_str().expect("id").to_string()
+        }
+    };
+    let unset = create(json!({ "author": "Alice", "content": "unset" })).await;
+    let never = create(json!({ "author": "Bob", "content": "never", "expire_hours": 0 })).await;
+
+    let apply = |token: Option<&'static str>| {
+        let app = app.clone();
+        async move {
+            let mut builder = Request::builder()
+                .method("POST")
+                .uri("/textbox/admin/apply-default-expiry?hours=24");
+            if let Some(token) = token {
+                builder = builder.header("authorization", format!("Bearer {token}"));
+            }
+            let response = app
+                .oneshot(builder.body(Body::empty()).unwrap())
+                .await
+                .expect("apply response");
+            let status = response.status();
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<Value>(&bytes).unwrap())
+        }
+    };
+    assert_eq!(apply(None).await.0, StatusCode::UNAUTHORIZED);
+    assert_eq!(apply(Some("wrong")).await.0, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = apply(Some("secret")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["updated"], 1);
+
+    let fetch = |id: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(request("GET", &format!("/textbox/{id}"), Body::empty()))
+                .await
+                .expect("get response");
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<Value>(&bytes).unwrap()
+        }
+    };
+    assert!(fetch(unset).await["data"]["metadata"]["expires_at"].is_string());
+    assert!(fetch(never).await["data"]["metadata"]["expires_at"].is_null());
+}
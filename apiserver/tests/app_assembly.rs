@@ -1,6 +1,7 @@
 use axum::{
     Router,
     body::{Body, to_bytes},
+    extract::ConnectInfo,
     http::{Method, Request, StatusCode},
 };
 use config::{
@@ -12,6 +13,7 @@ use config::{
     ocr::RemoteOcrConfig,
 };
 use serde_json::{Value, json};
+use std::net::SocketAddr;
 use tower::ServiceExt;
 
 fn build_config() -> GlobalConfig {
@@ -31,6 +33,19 @@ fn build_config() -> GlobalConfig {
             poll_max_attempts: 20,
             poll_initial_delay_ms: 0,
             accept_invalid_certs: false,
+            batch_max_files: 10,
+            batch_max_total_bytes: 20 * 1024 * 1024,
+            batch_concurrency: 4,
+            requests_per_second: 0.0,
+            download_max_bytes: 10 * 1024 * 1024,
+            download_allow_private_hosts: false,
+            cache_enabled: false,
+            cache_ttl_secs: 3600,
+            language: None,
+            reject_heic: false,
+            auto_orient: false,
+            extra_headers: Default::default(),
+            user_agent: None,
         }),
         image_hosting: Some(ImageHostingConfig {
             storage_dir: "/tmp/rsde-test-images".to_string(),
@@ -123,3 +138,166 @@ fn build_datalink_router_keeps_existing_test_surface() {
 
     let _ = router;
 }
+
+#[tokio::test]
+async fn ocr_routes_return_429_past_rate_limit() {
+    let mut config = build_config();
+    config.apiserver = Some(ApiServerConfig {
+        requests_per_minute: 2,
+        ..ApiServerConfig::default()
+    });
+    let app = apiserver::build_app_for_test(config)
+        .await
+        .expect("build app");
+
+    // 非 http 地址在下载前就会被拒绝，不依赖外部服务
+    let from_url_request = |ip: Option<[u8; 4]>| {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/api/ocr/from_url")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"url":"file:///etc/passwd"}"#))
+            .unwrap();
+        if let Some(ip) = ip {
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from((ip, 40000))));
+        }
+        request
+    };
+
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(from_url_request(Some([10, 0, 0, 1])))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    let limited = app
+        .clone()
+        .oneshot(from_url_request(Some([10, 0, 0, 1])))
+        .await
+        .unwrap();
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(limited.headers().contains_key("retry-after"));
+
+    // 其他客户端不受影响
+    let other = app
+        .clone()
+        .oneshot(from_url_request(Some([10, 0, 0, 2])))
+        .await
+        .unwrap();
+    assert_eq!(other.status(), StatusCode::BAD_REQUEST);
+
+    // GET 请求不计入限流
+    let mut health = Request::builder()
+        .uri("/api/ocr/health")
+        .body(Body::empty())
+        .unwrap();
+    health
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 40000))));
+    let health = app.clone().oneshot(health).await.unwrap();
+    assert_eq!(health.status(), StatusCode::OK);
+
+    // 没有对端地址的请求不会共用同一个桶
+    for _ in 0..3 {
+        let response = app.clone().oneshot(from_url_request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn openapi_spec_lists_ocr_and_upload_paths() {
+    let app = apiserver::build_app_for_test(build_config())
+        .await
+        .expect("build app");
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/openapi.json")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let spec = read_json(response).await;
+    let paths = spec["paths"].as_object().expect("paths");
+    for path in [
+        "/api/ocr/single_pic",
+        "/api/ocr/batch",
+        "/api/ocr/from_url",
+        "/api/image/upload",
+    ] {
+        assert!(paths.contains_key(path), "missing {path}");
+    }
+    assert!(
+        paths["/api/ocr/batch"]["post"]["requestBody"]["content"]
+            .get("multipart/form-data")
+            .is_some()
+    );
+}
+
+#[tokio::test]
+async fn cors_allow_list_from_apiserver_config() {
+    let config = ApiServerConfig {
+        cors_allowed_origins: vec!["https://app.example.com".to_string()],
+        ..ApiServerConfig::default()
+    };
+    let app = apiserver::build_app_for_test(build_config())
+        .await
+        .expect("build app")
+        .layer(util::server::default_layers(&apiserver::middleware_config(
+            &config,
+        )));
+    let request = |origin: &str| {
+        Request::builder()
+            .uri("/api/openapi.json")
+            .header("origin", origin)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let allowed = app
+        .clone()
+        .oneshot(request("https://app.example.com"))
+        .await
+        .unwrap();
+    assert_eq!(
+        allowed.headers()["access-control-allow-origin"],
+        "https://app.example.com"
+    );
+
+    let denied = app
+        .oneshot(request("https://evil.example.com"))
+        .await
+        .unwrap();
+    assert!(!denied.headers().contains_key("access-control-allow-origin"));
+}
+
+#[tokio::test]
+async fn large_responses_are_gzip_compressed() {
+    let app = apiserver::with_compression(
+        apiserver::build_app_for_test(build_config())
+            .await
+            .expect("build app"),
+    );
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/openapi.json")
+                .header("accept-encoding", "gzip")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+}
@@ -0,0 +1,724 @@
+use axum::{
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use config::image_host::ImageHostingConfig;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tower::ServiceExt;
+
+const IMAGE: &[u8] = include_bytes!("../../manifest/dev/tm_1.png");
+const BOUNDARY: &str = "rsde-image-test-boundary";
+
+/// 每个测试使用独立的存储目录
+fn storage_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rsde-image-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// 不重新编码，存储的文件与上传的字节一致
+fn routes(dir: &Path, allow_client_key: bool) -> axum::Router {
+    apiserver::image::create_routes(ImageHostingConfig {
+        storage_dir: dir.to_string_lossy().into_owned(),
+        allow_client_key,
+        strip_metadata: false,
+        ..Default::default()
+    })
+}
+
+fn upload_request(key: Option<&str>, query: &str) -> Request<Body> {
+    upload_payload_request(key, IMAGE, query)
+}
+
+fn upload_payload_request(key: Option<&str>, payload: &[u8], query: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/upload{query}"))
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(upload_body(key, payload)))
+        .expect("request")
+}
+
+/// 上传请求的 multipart 请求体
+fn upload_body(key: Option<&str>, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    if let Some(key) = key {
+        body.extend_from_slice(
+            format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"key\"\r\n\r\n{key}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"shot.png\"\r\nContent-Type: image/png\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(payload);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+/// 按块发送的上传请求，模拟网络上分多次到达的大文件
+fn streamed_upload_request(payload: &[u8], chunk_size: usize) -> Request<Body> {
+    let mut body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"big.bin\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(payload);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+    let chunks: Vec<Result<Vec<u8>, std::io::Error>> = body
+        .chunks(chunk_size)
+        .map(|chunk| Ok(chunk.to_vec()))
+        .collect();
+
+    Request::builder()
+        .method("POST")
+        .uri("/upload")
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from_stream(futures::stream::iter(chunks)))
+        .expect("request")
+}
+
+/// 存储目录下的文件（不含上传临时目录）
+fn stored_files(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+async fn read_json(response: axum::response::Response) -> Value {
+    let bytes = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+#[tokio::test]
+async fn upload_without_key_generates_name() {
+    let dir = storage_dir("generated");
+    let response = routes(&dir, false)
+        .oneshot(upload_request(None, ""))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let path = read_json(response).await["path"]
+        .as_str()
+        .expect("path")
+        .to_string();
+    assert!(path.ends_with(".png"));
+    assert!(dir.join(path).exists());
+}
+
+#[tokio::test]
+async fn upload_with_key_uses_provided_name() {
+    let dir = storage_dir("provided");
+    let response = routes(&dir, true)
+        .oneshot(upload_request(Some("avatar-42"), ""))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(read_json(response).await["path"], "avatar-42.png");
+    assert_eq!(std::fs::read(dir.join("avatar-42.png")).unwrap(), IMAGE);
+}
+
+#[tokio::test]
+async fn upload_with_key_requires_config() {
+    let dir = storage_dir("disabled");
+    let response = routes(&dir, false)
+        .oneshot(upload_request(Some("avatar.png"), ""))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn upload_rejects_path_traversal_key() {
+    let dir = storage_dir("traversal");
+    for key in ["../evil.png", "nested/evil.png", ".hidden", "..\\evil.png"] {
+        let response = routes(&dir, true)
+            .oneshot(upload_request(Some(key), ""))
+            .await
+            .expect("upload response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "key={key}");
+    }
+    assert!(!dir.parent().unwrap().join("evil.png").exists());
+}
+
+#[tokio::test]
+async fn upload_with_key_uses_sniffed_extension() {
+    let dir = storage_dir("sniffed-key");
+    let app = routes(&dir, true);
+    for (key, expected) in [
+        ("page.html", "page.png"),
+        ("avatar.JPG", "avatar.png"),
+        ("v1.2.svg", "v1.2.png"),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(upload_request(Some(key), ""))
+            .await
+            .expect("upload response");
+        assert_eq!(response.status(), StatusCode::OK, "key={key}");
+        assert_eq!(read_json(response).await["path"], expected, "key={key}");
+    }
+    assert!(!dir.join("page.html").exists());
+}
+
+#[tokio::test]
+async fn upload_with_key_rejects_non_image() {
+    let dir = storage_dir("non-image-key");
+    let response = routes(&dir, true)
+        .oneshot(upload_payload_request(
+            Some("page.html"),
+            b"<script>alert(1)</script>",
+            "",
+        ))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(stored_files(&dir).is_empty());
+}
+
+#[tokio::test]
+async fn upload_key_collision_conflicts_unless_overwrite() {
+    let dir = storage_dir("collision");
+    let app = routes(&dir, true);
+
+    let first = app
+        .clone()
+        .oneshot(upload_request(Some("same.png"), ""))
+        .await
+        .expect("first response");
+    assert_eq!(first.status(), StatusCode::OK);
+
+    let second = app
+        .clone()
+        .oneshot(upload_request(Some("same.png"), ""))
+        .await
+        .expect("second response");
+    assert_eq!(second.status(), StatusCode::CONFLICT);
+
+    let overwrite = app
+        .oneshot(upload_request(Some("same.png"), "?overwrite=true"))
+        .await
+        .expect("overwrite response");
+    assert_eq!(overwrite.status(), StatusCode::OK);
+    assert_eq!(read_json(overwrite).await["path"], "same.png");
+}
+
+fn meta_request(name: &str) -> Request<Body> {
+    Request::builder()
+        .uri(format!("/{name}/meta"))
+        .body(Body::empty())
+        .expect("request")
+}
+
+#[tokio::test]
+async fn meta_reports_image_dimensions() {
+    let dir = storage_dir("meta-image");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("shot.png"), IMAGE).unwrap();
+
+    let response = routes(&dir, false)
+        .oneshot(meta_request("shot.png"))
+        .await
+        .expect("meta response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = read_json(response).await;
+    let (width, height) = image_dimensions();
+    assert_eq!(body["name"], "shot.png");
+    assert_eq!(body["size_bytes"], IMAGE.len() as u64);
+    assert_eq!(body["format"], "png");
+    assert_eq!(body["width"], width);
+    assert_eq!(body["height"], height);
+    assert!(body["created_at"].is_string());
+}
+
+#[tokio::test]
+async fn meta_reports_unknown_format_for_non_image() {
+    let dir = storage_dir("meta-text");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("notes.png"), b"not an image").unwrap();
+
+    let response = routes(&dir, false)
+        .oneshot(meta_request("notes.png"))
+        .await
+        .expect("meta response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = read_json(response).await;
+    assert_eq!(body["size_bytes"], 12);
+    assert!(body["format"].is_null());
+    assert!(body["width"].is_null());
+}
+
+#[tokio::test]
+async fn meta_rejects_missing_and_traversal() {
+    let dir = storage_dir("meta-missing");
+    let app = routes(&dir, false);
+
+    let missing = app
+        .clone()
+        .oneshot(meta_request("absent.png"))
+        .await
+        .expect("meta response");
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+    let traversal = app
+        .oneshot(meta_request("..%2Fetc%2Fpasswd"))
+        .await
+        .expect("meta response");
+    assert_eq!(traversal.status(), StatusCode::BAD_REQUEST);
+}
+
+/// PNG 的 IHDR 中读取尺寸
+fn image_dimensions() -> (u32, u32) {
+    let width = u32::from_be_bytes(IMAGE[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(IMAGE[20..24].try_into().unwrap());
+    (width, height)
+}
+
+#[tokio::test]
+async fn upload_over_capacity_evicts_oldest_file() {
+    let dir = storage_dir("evict");
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, age) in [("oldest.png", 7200), ("older.png", 3600)] {
+        std::fs::write(dir.join(name), IMAGE).unwrap();
+        let modified = std::time::SystemTime::now() - std::time::Duration::from_secs(age);
+        std::fs::File::options()
+            .write(true)
+            .open(dir.join(name))
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    let app = apiserver::image::create_routes(ImageHostingConfig {
+        storage_dir: dir.to_string_lossy().into_owned(),
+        allow_client_key: true,
+        max_total_bytes: IMAGE.len() as u64 * 2,
+        strip_metadata: false,
+        ..Default::default()
+    });
+    let response = app
+        .oneshot(upload_request(Some("newest.png"), ""))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert!(!dir.join("oldest.png").exists());
+    assert!(dir.join("older.png").exists());
+    assert!(dir.join("newest.png").exists());
+}
+
+#[tokio::test]
+async fn upload_with_same_idempotency_key_stores_once() {
+    let dir = storage_dir("idempotent");
+    let app = routes(&dir, false);
+    let upload = |idempotency_key: &str| {
+        let mut request = upload_request(None, "");
+        request
+            .headers_mut()
+            .insert("idempotency-key", idempotency_key.parse().unwrap());
+        app.clone().oneshot(request)
+    };
+
+    let first = read_json(upload("retry-1").await.expect("first upload")).await;
+    let second = read_json(upload("retry-1").await.expect("retried upload")).await;
+    assert_eq!(first["success"], true);
+    assert_eq!(first, second);
+    assert_eq!(stored_files(&dir).len(), 1);
+
+    let other = read_json(upload("retry-2").await.expect("other upload")).await;
+    assert_ne!(other["path"], first["path"]);
+    assert_eq!(stored_files(&dir).len(), 2);
+}
+
+#[tokio::test]
+async fn concurrent_upload_with_same_idempotency_key_conflicts() {
+    let dir = storage_dir("idempotent-concurrent");
+    let app = routes(&dir, false);
+
+    // 首次上传的请求体分两次到达，第二部分发送前上传一直在进行
+    let body = upload_body(None, IMAGE);
+    let (body_tx, body_rx) = futures::channel::mpsc::unbounded::<Result<Vec<u8>, std::io::Error>>();
+    body_tx.unbounded_send(Ok(body[..64].to_vec())).unwrap();
+    let first = Request::builder()
+        .method("POST")
+        .uri("/upload")
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .header("idempotency-key", "concurrent")
+        .body(Body::from_stream(body_rx))
+        .expect("request");
+    let first = tokio::spawn(app.clone().oneshot(first));
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let mut duplicate = upload_request(None, "");
+    duplicate
+        .headers_mut()
+        .insert("idempotency-key", "concurrent".parse().unwrap());
+    let duplicate = app.clone().oneshot(duplicate).await.expect("duplicate");
+    assert_eq!(duplicate.status(), StatusCode::CONFLICT);
+
+    body_tx.unbounded_send(Ok(body[64..].to_vec())).unwrap();
+    drop(body_tx);
+    let first = read_json(first.await.unwrap().expect("first upload")).await;
+    assert_eq!(first["success"], true);
+    assert_eq!(stored_files(&dir).len(), 1);
+
+    // 首次上传完成后，重试返回同一个文件
+    let mut retry = upload_request(None, "");
+    retry
+        .headers_mut()
+        .insert("idempotency-key", "concurrent".parse().unwrap());
+    let retry = read_json(app.oneshot(retry).await.expect("retry")).await;
+    assert_eq!(retry, first);
+    assert_eq!(stored_files(&dir).len(), 1);
+}
+
+#[tokio::test]
+async fn upload_streams_large_file_in_chunks() {
+    let dir = storage_dir("streamed");
+    // 超过 axum 默认的 2MB 请求体限制
+    let payload: Vec<u8> = (0..3 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    let response = routes(&dir, false)
+        .oneshot(streamed_upload_request(&payload, 64 * 1024))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let path = read_json(response).await["path"]
+        .as_str()
+        .expect("path")
+        .to_string();
+    assert_eq!(std::fs::read(dir.join(path)).unwrap(), payload);
+    assert_eq!(stored_files(&dir).len(), 1);
+}
+
+#[tokio::test]
+async fn upload_over_file_limit_is_aborted() {
+    let dir = storage_dir("over-limit");
+    let app = apiserver::image::create_routes(ImageHostingConfig {
+        storage_dir: dir.to_string_lossy().into_owned(),
+        max_file_bytes: 1024 * 1024,
+        ..Default::default()
+    });
+    // 未超过请求体限制（单文件上限另加 1MB），由逐块写入时的检查中止
+    let payload = vec![7u8; 3 * 512 * 1024];
+
+    let response = app
+        .oneshot(streamed_upload_request(&payload, 64 * 1024))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&bytes).contains("文件超出大小限制"));
+
+    assert!(stored_files(&dir).is_empty());
+    assert!(stored_files(&dir.join(".partial")).is_empty());
+}
+
+fn sharded_config(dir: &Path) -> ImageHostingConfig {
+    ImageHostingConfig {
+        storage_dir: dir.to_string_lossy().into_owned(),
+        shard_by_date: true,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn upload_with_shard_by_date_lands_in_dated_dir() {
+    let dir = storage_dir("sharded");
+    let app = apiserver::image::create_routes(sharded_config(&dir));
+    let shard = chrono::Utc::now().format("%Y/%m/%d/").to_string();
+
+    let response = app
+        .clone()
+        .oneshot(upload_request(None, ""))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::OK);
+    let path = read_json(response).await["path"]
+        .as_str()
+        .expect("path")
+        .to_string();
+    assert!(path.starts_with(&shard), "{path}");
+    assert!(dir.join(&path).is_file());
+
+    let meta = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/{path}/meta"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("meta response");
+    assert_eq!(meta.status(), StatusCode::OK);
+    let meta = read_json(meta).await;
+    assert_eq!(meta["name"], path.as_str());
+    assert_eq!(meta["format"], "png");
+}
+
+#[tokio::test]
+async fn cleanup_prunes_sharded_files() {
+    let dir = storage_dir("sharded-cleanup");
+    let config = ImageHostingConfig {
+        file_expire_secs: 1,
+        ..sharded_config(&dir)
+    };
+    let response = apiserver::image::create_routes(config.clone())
+        .oneshot(upload_request(None, ""))
+        .await
+        .expect("upload response");
+    let path = read_json(response).await["path"]
+        .as_str()
+        .expect("path")
+        .to_string();
+    assert!(dir.join(&path).is_file());
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    apiserver::image::start_cleanup_task(config).stop().await;
+
+    assert!(!dir.join(&path).exists());
+    // 变空的日期目录一并删除，存储目录保留
+    let year = path.split('/').next().expect("year");
+    assert!(!dir.join(year).exists());
+    assert!(dir.is_dir());
+}
+
+#[tokio::test]
+async fn cleanup_sweeps_stale_partial_files() {
+    let dir = storage_dir("partial-sweep");
+    let partial_dir = dir.join(".partial");
+    std::fs::create_dir_all(&partial_dir).unwrap();
+    for (name, age) in [("stale.part", 7200), ("active.part", 0)] {
+        std::fs::write(partial_dir.join(name), b"partial").unwrap();
+        let modified = std::time::SystemTime::now() - std::time::Duration::from_secs(age);
+        std::fs::File::options()
+            .write(true)
+            .open(partial_dir.join(name))
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    apiserver::image::start_cleanup_task(ImageHostingConfig {
+        storage_dir: dir.to_string_lossy().into_owned(),
+        ..Default::default()
+    })
+    .stop()
+    .await;
+
+    assert!(!partial_dir.join("stale.part").exists());
+    assert!(partial_dir.join("active.part").exists());
+}
+
+fn converting_routes(dir: &Path, convert_to: &str) -> axum::Router {
+    apiserver::image::create_routes(ImageHostingConfig {
+        storage_dir: dir.to_string_lossy().into_owned(),
+        convert_to: Some(convert_to.to_string()),
+        strip_metadata: false,
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn upload_converts_png_to_jpeg() {
+    let dir = storage_dir("convert-jpeg");
+    let response = converting_routes(&dir, "jpg")
+        .oneshot(upload_request(None, ""))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::OK);
+    let path = read_json(response).await["path"]
+        .as_str()
+        .expect("path")
+        .to_string();
+    assert!(path.ends_with(".jpg"));
+
+    let meta = read_json(
+        routes(&dir, false)
+            .oneshot(meta_request(&path))
+            .await
+            .expect("meta response"),
+    )
+    .await;
+    let (width, height) = image_dimensions();
+    assert_eq!(meta["format"], "jpg");
+    assert_eq!(meta["width"], width);
+    assert_eq!(meta["height"], height);
+    assert_eq!(stored_files(&dir).len(), 1);
+    assert!(stored_files(&dir.join(".partial")).is_empty());
+}
+
+#[tokio::test]
+async fn upload_skips_conversion_when_format_matches() {
+    let dir = storage_dir("convert-noop");
+    let response = converting_routes(&dir, "png")
+        .oneshot(upload_request(None, ""))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::OK);
+    let path = read_json(response).await["path"]
+        .as_str()
+        .expect("path")
+        .to_string();
+    assert!(path.ends_with(".png"));
+    assert_eq!(std::fs::read(dir.join(path)).unwrap(), IMAGE);
+}
+
+#[tokio::test]
+async fn upload_rejects_undecodable_image_when_converting() {
+    let dir = storage_dir("convert-invalid");
+    let response = converting_routes(&dir, "jpg")
+        .oneshot(streamed_upload_request(b"definitely not an image", 8))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(stored_files(&dir).is_empty());
+    assert!(stored_files(&dir.join(".partial")).is_empty());
+}
+
+/// 带 EXIF 与 ICC 配置的 4x2 JPEG：IFD0 含方向（顺时针旋转 90 度）与指向 GPS IFD 的指针
+fn jpeg_with_gps_exif() -> Vec<u8> {
+    let mut jpeg = Vec::new();
+    image::RgbImage::from_pixel(4, 2, image::Rgb([200, 30, 30]))
+        .write_to(
+            &mut std::io::Cursor::new(&mut jpeg),
+            image::ImageFormat::Jpeg,
+        )
+        .unwrap();
+
+    let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08".to_vec();
+    // IFD0: Orientation = 6, GPSInfo -> 偏移 38
+    exif.extend_from_slice(&[0, 2]);
+    exif.extend_from_slice(&[0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0]);
+    exif.extend_from_slice(&[0x88, 0x25, 0, 4, 0, 0, 0, 1, 0, 0, 0, 38]);
+    exif.extend_from_slice(&[0, 0, 0, 0]);
+    // GPS IFD: GPSLatitudeRef = "N"
+    exif.extend_from_slice(&[0, 1]);
+    exif.extend_from_slice(&[0x00, 0x01, 0, 2, 0, 0, 0, 2, b'N', 0, 0, 0]);
+    exif.extend_from_slice(&[0, 0, 0, 0]);
+
+    // 紧跟在 SOI 之后插入 APP1（EXIF）与 APP2（ICC 配置，内容不需要是有效的配置）
+    let mut segments = jpeg_segment(0xE1, &exif);
+    segments.extend(jpeg_segment(
+        0xE2,
+        &[b"ICC_PROFILE\0\x01\x01".as_slice(), ICC].concat(),
+    ));
+    jpeg.splice(2..2, segments);
+    jpeg
+}
+
+const ICC: &[u8] = b"test icc profile";
+
+fn jpeg_segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+    let mut segment = vec![0xFF, marker];
+    segment.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+    segment.extend_from_slice(payload);
+    segment
+}
+
+/// 从 SOS 标记开始的扫描数据
+fn jpeg_scan(jpeg: &[u8]) -> &[u8] {
+    let start = jpeg
+        .windows(2)
+        .position(|window| window == [0xFF, 0xDA])
+        .expect("SOS marker");
+    &jpeg[start..]
+}
+
+#[tokio::test]
+async fn upload_strips_exif_metadata_by_default() {
+    let dir = storage_dir("strip-exif");
+    let photo = jpeg_with_gps_exif();
+    let mut decoder = image::ImageReader::new(std::io::Cursor::new(&photo))
+        .with_guessed_format()
+        .unwrap()
+        .into_decoder()
+        .unwrap();
+    assert!(
+        image::ImageDecoder::exif_metadata(&mut decoder)
+            .unwrap()
+            .is_some()
+    );
+
+    let app = apiserver::image::create_routes(ImageHostingConfig {
+        storage_dir: dir.to_string_lossy().into_owned(),
+        ..Default::default()
+    });
+    let response = app
+        .oneshot(streamed_upload_request(&photo, 64))
+        .await
+        .expect("upload response");
+    assert_eq!(response.status(), StatusCode::OK);
+    let path = read_json(response).await["path"]
+        .as_str()
+        .expect("path")
+        .to_string();
+    assert!(path.ends_with(".jpg"));
+
+    let stored = std::fs::read(dir.join(&path)).unwrap();
+    // 像素数据原样保留，没有重新编码
+    assert_eq!(jpeg_scan(&stored), jpeg_scan(&photo));
+    let mut decoder = image::ImageReader::new(std::io::Cursor::new(&stored))
+        .with_guessed_format()
+        .unwrap()
+        .into_decoder()
+        .unwrap();
+    assert_eq!(image::ImageDecoder::dimensions(&decoder), (4, 2));
+    // 只保留方向，GPS 等其他 EXIF 已去除
+    assert_eq!(
+        image::ImageDecoder::orientation(&mut decoder).unwrap(),
+        image::metadata::Orientation::Rotate90
+    );
+    let exif = image::ImageDecoder::exif_metadata(&mut decoder)
+        .unwrap()
+        .expect("orientation exif");
+    assert!(!exif.windows(2).any(|window| window == [0x88, 0x25]));
+    assert!(!stored.windows(2).any(|window| window == [0x88, 0x25]));
+    assert_eq!(
+        image::ImageDecoder::icc_profile(&mut decoder).unwrap(),
+        Some(ICC.to_vec())
+    );
+}
+
+#[tokio::test]
+async fn upload_rejects_formats_whose_metadata_cannot_be_stripped() {
+    let dir = storage_dir("strip-unsupported");
+    let app = apiserver::image::create_routes(ImageHostingConfig {
+        storage_dir: dir.to_string_lossy().into_owned(),
+        ..Default::default()
+    });
+    let heic = b"\0\0\0\x18ftypheic\0\0\0\0mif1heic";
+    let tiff = b"II*\0\x08\0\0\0\0\0\0\0\0\0";
+    for payload in [heic.as_slice(), tiff.as_slice()] {
+        let response = app
+            .clone()
+            .oneshot(streamed_upload_request(payload, 8))
+            .await
+            .expect("upload response");
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+    assert!(stored_files(&dir).is_empty());
+    assert!(stored_files(&dir.join(".partial")).is_empty());
+}
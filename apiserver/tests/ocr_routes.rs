@@ -0,0 +1,345 @@
+use apiserver::ocr::OcrCache;
+use async_trait::async_trait;
+use axum::{
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use config::ocr::RemoteOcrConfig;
+use pic_recog::test_support::{MockResponse, MockServer};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::time::Duration;
+use tower::ServiceExt;
+
+const IMAGE: &[u8] = include_bytes!("../../manifest/dev/tm_1.png");
+const BOUNDARY: &str = "rsde-ocr-test-boundary";
+
+/// 启动模拟的远程 OCR 服务：任务 ID 即上传的文件名，识别结果为 `text:<文件名>`
+///
+/// 同时在 `/images/*` 下提供图片下载，用于按 URL 识别的测试。
+/// 返回服务地址和 `/start` 被调用的次数。
+fn spawn_mock_engine() -> (String, Arc<AtomicUsize>) {
+    let started = Arc::new(AtomicUsize::new(0));
+    let start_counter = started.clone();
+    let server = MockServer::spawn(move |request| match request.path.as_str() {
+        "/images/shot.png" => MockResponse::bytes("image/png", IMAGE),
+        "/images/redirect.png" => {
+            MockResponse::status(302).with_header("Location", "/images/shot.png")
+        }
+        "/images/huge.png" => {
+            MockResponse::bytes("application/octet-stream", vec![0u8; 2 * 1024 * 1024])
+        }
+        "/perm" => MockResponse::json(json!({ "data": { "token": "mock-token" } })),
+        "/start" => {
+            start_counter.fetch_add(1, Ordering::SeqCst);
+            let name = request.json().map(|body| body["name"].clone());
+            MockResponse::json(json!({ "data": { "jobStatusId": name } }))
+        }
+        "/status" => {
+            let job_id = request.query_param("jobStatusId").unwrap_or_default();
+            MockResponse::json(json!({
+                "code": 1,
+                "data": {
+                    "isEnded": true,
+                    "ydResp": { "words_result": [{ "words": format!("text:{job_id}") }] }
+                }
+            }))
+        }
+        _ => MockResponse::status(404),
+    });
+    (server.base_url().to_string(), started)
+}
+
+/// 内存版识别结果缓存
+#[derive(Default)]
+struct MemoryOcrCache {
+    entries: Mutex<HashMap<String, String>>,
+}
+
+#[async_trait]
+impl OcrCache for MemoryOcrCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    async fn set(&self, key: &str, value: &str, _ttl: Duration) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+    }
+}
+
+fn remote_config(base_url: &str) -> RemoteOcrConfig {
+    RemoteOcrConfig {
+        perm_url: format!("{base_url}/perm"),
+        start_url: format!("{base_url}/start"),
+        status_url: format!("{base_url}/status"),
+        auth_token: "token".to_string(),
+        auth_uuid: "uuid".to_string(),
+        auth_cookie: "cookie".to_string(),
+        origin: "https://example.com".to_string(),
+        mode: "single".to_string(),
+        timeout_secs: 5,
+        poll_interval_ms: 50,
+        poll_max_attempts: 3,
+        poll_initial_delay_ms: 0,
+        accept_invalid_certs: false,
+        batch_max_files: 10,
+        batch_max_total_bytes: 1024 * 1024,
+        batch_concurrency: 2,
+        requests_per_second: 0.0,
+        download_max_bytes: 1024 * 1024,
+        download_allow_private_hosts: true,
+        cache_enabled: false,
+        cache_ttl_secs: 3600,
+        language: None,
+        reject_heic: false,
+        auto_orient: false,
+        extra_headers: Default::default(),
+        user_agent: None,
+    }
+}
+
+fn multipart_request(files: &[(&str, &[u8])]) -> Request<Body> {
+    let mut body = Vec::new();
+    for (filename, bytes) in files {
+        body.extend_from_slice(
+            format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"files\"; filename=\"{filename}\"\r\nContent-Type: image/png\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(bytes);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+
+    Request::builder()
+        .method("POST")
+        .uri("/batch")
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(body))
+        .expect("request")
+}
+
+fn from_url_request(url: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/from_url")
+        .header("content-type", "application/json")
+        .body(Body::from(json!({ "url": url }).to_string()))
+        .expect("request")
+}
+
+async fn read_json(response: axum::response::Response) -> Value {
+    let bytes = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+#[tokio::test]
+async fn batch_recognizes_each_uploaded_file() {
+    let (base_url, _) = spawn_mock_engine();
+    let app = apiserver::ocr::create_routes(remote_config(&base_url), "/tmp".to_string());
+
+    let response = app
+        .oneshot(multipart_request(&[("a.png", IMAGE), ("b.png", IMAGE)]))
+        .await
+        .expect("batch response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = read_json(response).await;
+    assert_eq!(
+        body,
+        json!([
+            { "filename": "a.png", "text": "text:a.png" },
+            { "filename": "b.png", "text": "text:b.png" }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn batch_reports_per_file_errors() {
+    let (base_url, _) = spawn_mock_engine();
+    let app = apiserver::ocr::create_routes(remote_config(&base_url), "/tmp".to_string());
+
+    let response = app
+        .oneshot(multipart_request(&[
+            ("a.png", IMAGE),
+            ("notes.txt", b"hello"),
+        ]))
+        .await
+        .expect("batch response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = read_json(response).await;
+    assert_eq!(body[0]["text"], "text:a.png");
+    assert!(body[1]["error"].is_string());
+    assert_eq!(body[1]["code"], "INVALID_IMAGE");
+}
+
+#[tokio::test]
+async fn batch_rejects_too_many_files() {
+    let mut config = remote_config("http://127.0.0.1:9");
+    config.batch_max_files = 1;
+    let app = apiserver::ocr::create_routes(config, "/tmp".to_string());
+
+    let response = app
+        .oneshot(multipart_request(&[("a.png", IMAGE), ("b.png", IMAGE)]))
+        .await
+        .expect("batch response");
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn batch_rejects_oversized_upload() {
+    let mut config = remote_config("http://127.0.0.1:9");
+    config.batch_max_total_bytes = IMAGE.len() as u64;
+    let app = apiserver::ocr::create_routes(config, "/tmp".to_string());
+
+    let response = app
+        .oneshot(multipart_request(&[("a.png", IMAGE), ("b.png", IMAGE)]))
+        .await
+        .expect("batch response");
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn engines_lists_remote_capabilities() {
+    let app =
+        apiserver::ocr::create_routes(remote_config("http://127.0.0.1:9"), "/tmp".to_string());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/engines")
+                .body(Body::empty())
+                .expect("request"),
+        )
+        .await
+        .expect("engines response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = read_json(response).await;
+    assert_eq!(body[0]["name"], "remote");
+    assert_eq!(body[0]["supports_position"], true);
+}
+
+#[tokio::test]
+async fn from_url_downloads_and_recognizes() {
+    let (base_url, _) = spawn_mock_engine();
+    let app = apiserver::ocr::create_routes(remote_config(&base_url), "/tmp".to_string());
+
+    let image_url = format!("{base_url}/images/shot.png");
+    let response = app
+        .oneshot(from_url_request(&image_url))
+        .await
+        .expect("from_url response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = read_json(response).await;
+    assert_eq!(body["success"], true);
+    assert_eq!(body["text"], "text:shot.png");
+    assert_eq!(body["image_path"], image_url);
+}
+
+#[tokio::test]
+async fn from_url_rejects_non_http_scheme() {
+    let app =
+        apiserver::ocr::create_routes(remote_config("http://127.0.0.1:9"), "/tmp".to_string());
+
+    let response = app
+        .oneshot(from_url_request("file:///etc/passwd"))
+        .await
+        .expect("from_url response");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn from_url_rejects_private_hosts_by_default() {
+    let mut config = remote_config("http://127.0.0.1:9");
+    config.download_allow_private_hosts = false;
+    let app = apiserver::ocr::create_routes(config, "/tmp".to_string());
+
+    for url in [
+        "http://127.0.0.1:8080/a.png",
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]/a.png",
+        "http://10.0.0.1/a.png",
+        "http://localhost/a.png",
+    ] {
+        let response = app
+            .clone()
+            .oneshot(from_url_request(url))
+            .await
+            .expect("from_url response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn from_url_does_not_follow_redirects() {
+    let (base_url, started) = spawn_mock_engine();
+    let app = apiserver::ocr::create_routes(remote_config(&base_url), "/tmp".to_string());
+
+    let response = app
+        .oneshot(from_url_request(&format!("{base_url}/images/redirect.png")))
+        .await
+        .expect("from_url response");
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(started.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn from_url_rejects_oversized_image() {
+    let (base_url, _) = spawn_mock_engine();
+    let app = apiserver::ocr::create_routes(remote_config(&base_url), "/tmp".to_string());
+
+    let response = app
+        .oneshot(from_url_request(&format!("{base_url}/images/huge.png")))
+        .await
+        .expect("from_url response");
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        read_json(response).await["error"]["code"],
+        "PAYLOAD_TOO_LARGE"
+    );
+}
+
+#[tokio::test]
+async fn identical_image_is_served_from_cache() {
+    let (base_url, started) = spawn_mock_engine();
+    let cache: Arc<dyn OcrCache> = Arc::new(MemoryOcrCache::default());
+    let app = apiserver::ocr::create_routes_with_cache(
+        remote_config(&base_url),
+        "/tmp".to_string(),
+        Some(cache),
+    );
+    let image_url = format!("{base_url}/images/shot.png");
+
+    let first = app
+        .clone()
+        .oneshot(from_url_request(&image_url))
+        .await
+        .expect("first response");
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(first.headers()["x-cache"], "MISS");
+
+    let second = app
+        .oneshot(from_url_request(&image_url))
+        .await
+        .expect("second response");
+    assert_eq!(second.status(), StatusCode::OK);
+    assert_eq!(second.headers()["x-cache"], "HIT");
+    assert_eq!(read_json(second).await["text"], "text:shot.png");
+    assert_eq!(started.load(Ordering::SeqCst), 1);
+}
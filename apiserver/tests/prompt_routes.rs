@@ -0,0 +1,341 @@
+use apiserver::prompt::{PromptState, create_routes_with_state};
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use prompt::InMemoryPromptStore;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn app() -> Router {
+    create_routes_with_state(PromptState::with_store(
+        Arc::new(InMemoryPromptStore::new()),
+    ))
+}
+
+fn json_request(method: &str, uri: &str, body: Value) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("request")
+}
+
+fn get_request(uri: &str) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .body(Body::empty())
+        .expect("request")
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(request).await.expect("response");
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    (status, serde_json::from_slice(&bytes).expect("json body"))
+}
+
+async fn create(app: &Router, name: &str) -> String {
+    let (status, body) = send(
+        app,
+        json_request(
+            "POST",
+            "/template",
+            json!({ "name": name, "content": "Hello {{name}}", "category": "agent" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    body["data"]["id"].as_str().expect("id").to_string()
+}
+
+#[tokio::test]
+async fn create_then_get_template() {
+    let app = app();
+    let id = create(&app, "greeting").await;
+
+    let (status, body) = send(&app, get_request(&format!("/template/{id}"))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["name"], "greeting");
+    assert_eq!(body["data"]["category"], "agent");
+    assert_eq!(body["data"]["version"], 1);
+
+    let (status, _) = send(&app, get_request("/template/missing")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn update_bumps_version() {
+    let app = app();
+    let id = create(&app, "greeting").await;
+
+    let (status, body) = send(
+        &app,
+        json_request(
+            "PUT",
+            &format!("/template/{id}"),
+            json!({ "name": "greeting-v2", "content": "Hi {{name}}" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["version"], 2);
+
+    let (_, body) = send(&app, get_request(&format!("/template/{id}"))).await;
+    assert_eq!(body["data"]["name"], "greeting-v2");
+    assert_eq!(body["data"]["content"], "Hi {{name}}");
+    assert_eq!(body["data"]["category"], "agent");
+    assert_eq!(body["data"]["version"], 2);
+}
+
+#[tokio::test]
+async fn list_searches_and_paginates() {
+    let app = app();
+    for name in ["alpha", "beta", "alphabet"] {
+        create(&app, name).await;
+    }
+
+    let (status, body) = send(&app, get_request("/template?page=1&page_size=2")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["total"], 3);
+    assert_eq!(body["data"]["total_pages"], 2);
+    assert_eq!(body["data"]["items"].as_array().unwrap().len(), 2);
+
+    let (_, body) = send(&app, get_request("/template?name=alpha")).await;
+    assert_eq!(body["data"]["total"], 2);
+    let names: Vec<_> = body["data"]["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["name"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(names, ["alphabet", "alpha"]);
+}
+
+#[tokio::test]
+async fn soft_delete_then_restore() {
+    let app = app();
+    let id = create(&app, "soft").await;
+
+    let (status, _) = send(
+        &app,
+        json_request("DELETE", &format!("/template/{id}?soft=true"), json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, get_request("/template")).await;
+    assert_eq!(body["data"]["total"], 0);
+    let (status, _) = send(&app, get_request(&format!("/template/{id}"))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(
+        &app,
+        json_request(
+            "PUT",
+            &format!("/template/{id}"),
+            json!({ "name": "soft", "content": "edited" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = send(
+        &app,
+        json_request("POST", &format!("/template/{id}/restore"), json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["is_active"], true);
+}
+
+#[tokio::test]
+async fn invalid_template_is_bad_request() {
+    let app = app();
+    let (status, body) = send(
+        &app,
+        json_request("POST", "/template", json!({ "name": " ", "content": "Hi" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["success"], false);
+}
+
+#[tokio::test]
+async fn create_rejects_empty_required_fields() {
+    let app = app();
+    for (request, field) in [
+        (json!({ "name": "", "content": "Hi" }), "name"),
+        (json!({ "name": "greeting", "content": "" }), "content"),
+    ] {
+        let (status, body) = send(&app, json_request("POST", "/template", request)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["success"], false);
+        let error = body["error"].as_str().expect("error");
+        assert!(
+            error.contains(&format!("{field} must not be empty")),
+            "{error}"
+        );
+    }
+
+    let (_, body) = send(&app, get_request("/template")).await;
+    assert_eq!(body["data"]["total"], 0);
+
+    create(&app, "greeting").await;
+    let (_, body) = send(&app, get_request("/template")).await;
+    assert_eq!(body["data"]["total"], 1);
+}
+
+#[tokio::test]
+async fn import_duplicate_id_conflicts() {
+    let app = app();
+    let id = create(&app, "existing").await;
+    let (_, existing) = send(&app, get_request(&format!("/template/{id}"))).await;
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/template/import")
+        .body(Body::from(format!("{}\n", existing["data"])))
+        .expect("request");
+    let (status, body) = send(&app, request).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["imported"], 0);
+}
+
+#[tokio::test]
+async fn import_malformed_line_is_bad_request() {
+    let app = app();
+    let request = Request::builder()
+        .method("POST")
+        .uri("/template/import")
+        .body(Body::from("{not json\n"))
+        .expect("request");
+    let (status, body) = send(&app, request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("line 1"));
+}
+
+#[tokio::test]
+async fn health_reports_backend_state() {
+    let store = InMemoryPromptStore::new();
+    let app = create_routes_with_state(PromptState::with_store(Arc::new(store.clone())));
+
+    let (status, body) = send(&app, get_request("/health")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["backend"], "up");
+
+    store.set_offline(true);
+    let (status, body) = send(&app, get_request("/health")).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["backend"], "down");
+    assert!(body["error"].as_str().unwrap().contains("offline"));
+}
+
+#[tokio::test]
+async fn list_sorts_by_created_or_updated() {
+    let app = app();
+    let first = create(&app, "first").await;
+    create(&app, "second").await;
+    let (status, _) = send(
+        &app,
+        json_request(
+            "PUT",
+            &format!("/template/{first}"),
+            json!({ "name": "first", "content": "edited" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let names = |body: &Value| -> Vec<String> {
+        body["data"]["items"]
+            .as_array()
+            .expect("items")
+            .iter()
+            .map(|t| t["name"].as_str().expect("name").to_string())
+            .collect()
+    };
+    let (_, by_created) = send(&app, get_request("/template")).await;
+    assert_eq!(names(&by_created), ["second", "first"]);
+    let (_, by_updated) = send(&app, get_request("/template?sort=updated")).await;
+    assert_eq!(names(&by_updated), ["first", "second"]);
+
+    let response = app
+        .oneshot(get_request("/template?sort=views"))
+        .await
+        .expect("response");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn add_and_remove_tags() {
+    let app = app();
+    let id = create(&app, "tagged").await;
+
+    let (status, body) = send(
+        &app,
+        json_request(
+            "POST",
+            &format!("/template/{id}/tags"),
+            json!({ "tag": "ops" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["tags"], json!(["ops"]));
+    assert_eq!(body["data"]["version"], 2);
+
+    // 重复添加是空操作，版本不变
+    let (status, body) = send(
+        &app,
+        json_request(
+            "POST",
+            &format!("/template/{id}/tags"),
+            json!({ "tag": "ops" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["tags"], json!(["ops"]));
+    assert_eq!(body["data"]["version"], 2);
+
+    // 移除不存在的标签也是空操作
+    let (status, body) = send(
+        &app,
+        json_request("DELETE", &format!("/template/{id}/tags/missing"), json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["version"], 2);
+
+    let (status, body) = send(
+        &app,
+        json_request("DELETE", &format!("/template/{id}/tags/ops"), json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["tags"], json!([]));
+    assert_eq!(body["data"]["version"], 3);
+    assert_eq!(body["data"]["content"], "Hello {{name}}");
+
+    let (status, _) = send(
+        &app,
+        json_request(
+            "POST",
+            &format!("/template/{id}/tags"),
+            json!({ "tag": "" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        &app,
+        json_request("POST", "/template/missing/tags", json!({ "tag": "ops" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -13,6 +13,26 @@ pub struct AnyboxConfig {
     /// 清理过期内容的间隔时间（秒）
     #[serde(default = "default_cleanup_interval")]
     pub cleanup_interval_secs: u64,
+
+    /// 渲染 HTML 格式帖子时是否原样输出（默认 false，始终清洗）
+    #[serde(default)]
+    pub render_allow_raw_html: bool,
+
+    /// 未指定过期时间的帖子默认多少小时后过期，不配置表示默认永不过期
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_expire_hours: Option<u64>,
+
+    /// 管理接口的访问令牌，请求需携带 `Authorization: Bearer <token>`；不配置时不开放管理接口
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_token: Option<String>,
+
+    /// Redis 连接级错误的最大重试次数，0 表示不重试
+    #[serde(default = "default_retry_max")]
+    pub retry_max: u32,
+
+    /// 首次重试前的等待时间（毫秒），之后每次翻倍
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
 }
 
 fn default_key_prefix() -> String {
@@ -23,12 +43,25 @@ fn default_cleanup_interval() -> u64 {
     3600 // 1 小时
 }
 
+fn default_retry_max() -> u32 {
+    2
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    100
+}
+
 impl Default for AnyboxConfig {
     fn default() -> Self {
         Self {
             redis_url: "redis://127.0.0.1:6379".to_string(),
             key_prefix: default_key_prefix(),
             cleanup_interval_secs: default_cleanup_interval(),
+            render_allow_raw_html: false,
+            default_expire_hours: None,
+            admin_token: None,
+            retry_max: default_retry_max(),
+            retry_backoff_ms: default_retry_backoff_ms(),
         }
     }
 }
@@ -16,6 +16,22 @@ pub struct ApiServerConfig {
     /// 是否启用 CORS
     #[serde(default = "default_cors_enabled")]
     pub cors_enabled: bool,
+
+    /// 允许跨域的来源，如 `https://app.example.com`；为空时允许任意来源，`*` 显式允许任意来源
+    #[serde(default)]
+    pub cors_allowed_origins: Vec<String>,
+
+    /// 允许跨域的请求方法，为空时允许任意方法
+    #[serde(default)]
+    pub cors_allowed_methods: Vec<String>,
+
+    /// 允许跨域的请求头，为空时允许任意请求头
+    #[serde(default)]
+    pub cors_allowed_headers: Vec<String>,
+
+    /// OCR 与图片上传接口每个 IP 每分钟允许的写请求数，GET 请求不计入（0 表示不限流）
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
 }
 
 fn default_listen_address() -> String {
@@ -30,12 +46,20 @@ fn default_cors_enabled() -> bool {
     true
 }
 
+fn default_requests_per_minute() -> u32 {
+    60
+}
+
 impl Default for ApiServerConfig {
     fn default() -> Self {
         Self {
             listen_address: default_listen_address(),
             log_level: default_log_level(),
             cors_enabled: default_cors_enabled(),
+            cors_allowed_origins: Vec::new(),
+            cors_allowed_methods: Vec::new(),
+            cors_allowed_headers: Vec::new(),
+            requests_per_minute: default_requests_per_minute(),
         }
     }
 }
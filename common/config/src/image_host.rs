@@ -12,8 +12,46 @@ pub struct ImageHostingConfig {
     /// 文件过期时间, 单位秒, 默认 3600 秒 (1 小时)
     #[serde(default = "default_file_expire")]
     pub file_expire_secs: u64,
+
+    /// 是否允许客户端通过 `key` 字段指定存储文件名, 默认 false
+    #[serde(default)]
+    pub allow_client_key: bool,
+
+    /// 存储目录占用上限, 单位字节, 超出时按修改时间淘汰最旧的文件, 默认 0 (不限制)
+    #[serde(default)]
+    pub max_total_bytes: u64,
+
+    /// 是否按上传日期分目录存储 (`storage_dir/YYYY/MM/DD/`), 避免单个目录文件过多, 默认 false (平铺)
+    #[serde(default)]
+    pub shard_by_date: bool,
+
+    /// 单个上传文件的大小上限, 单位字节, 上传时边接收边检查, 默认 20MB, 0 表示不限制
+    #[serde(default = "default_max_file_bytes")]
+    pub max_file_bytes: u64,
+
+    /// 上传 `Idempotency-Key` 的有效期, 单位秒, 窗口内重复的键直接返回首次上传结果, 默认 600 秒, 0 表示不启用
+    #[serde(default = "default_idempotency_window")]
+    pub idempotency_window_secs: u64,
+
+    /// 上传后统一转换成的图片格式 (jpg、png、webp), 格式已相同时不转换, 默认不转换
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub convert_to: Option<String>,
+
+    /// 重新编码为 JPEG 时的质量 (1-100), 默认 85; WebP 只支持无损编码, 不受此项影响
+    #[serde(default = "default_convert_quality")]
+    pub convert_quality: u8,
+
+    /// 是否去除上传图片的 EXIF 等元数据 (GPS、设备信息), 默认 true
+    ///
+    /// 开启后 JPEG、PNG、WebP 只改写文件结构去除元数据, 像素数据与 ICC 色彩配置原样保留,
+    /// EXIF 方向只保留方向一项; 无法去除元数据的 TIFF、HEIC、AVIF 拒绝上传; 其他格式原样保存
+    #[serde(default = "default_strip_metadata")]
+    pub strip_metadata: bool,
 }
 
+/// `convert_to` 支持的目标格式
+pub const CONVERT_FORMATS: &[&str] = &["jpg", "jpeg", "png", "webp"];
+
 fn default_cleanup_interval() -> u64 {
     3600 // 1 小时
 }
@@ -22,12 +60,56 @@ fn default_file_expire() -> u64 {
     3600 // 1 小时
 }
 
+fn default_max_file_bytes() -> u64 {
+    20 * 1024 * 1024 // 20MB
+}
+
+fn default_idempotency_window() -> u64 {
+    600 // 10 分钟
+}
+
+fn default_convert_quality() -> u8 {
+    85
+}
+
+fn default_strip_metadata() -> bool {
+    true
+}
+
 impl Default for ImageHostingConfig {
     fn default() -> Self {
         Self {
             storage_dir: String::new(),
             cleanup_interval_secs: default_cleanup_interval(),
             file_expire_secs: default_file_expire(),
+            allow_client_key: false,
+            max_total_bytes: 0,
+            shard_by_date: false,
+            max_file_bytes: default_max_file_bytes(),
+            idempotency_window_secs: default_idempotency_window(),
+            convert_to: None,
+            convert_quality: default_convert_quality(),
+            strip_metadata: default_strip_metadata(),
+        }
+    }
+}
+
+impl ImageHostingConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(target) = &self.convert_to
+            && !CONVERT_FORMATS.contains(&target.to_lowercase().as_str())
+        {
+            return Err(anyhow::anyhow!(
+                "image_hosting: convert_to '{}' is not supported. Supported formats: {}",
+                target,
+                CONVERT_FORMATS.join(", ")
+            ));
+        }
+        if !(1..=100).contains(&self.convert_quality) {
+            return Err(anyhow::anyhow!(
+                "image_hosting: convert_quality must be between 1 and 100"
+            ));
         }
+        Ok(())
     }
 }
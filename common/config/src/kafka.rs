@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// Kafka 连接配置
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct KafkaConfig {
+    /// Broker 地址列表
+    #[serde(default)]
+    pub brokers: Vec<String>,
+
+    /// SASL 用户名，配置后启用 SASL 认证
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// SASL 密码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    /// SASL 安全协议 (SASL_PLAINTEXT 或 SASL_SSL)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security_protocol: Option<String>,
+
+    /// SASL 机制 (PLAIN, SCRAM-SHA-256, SCRAM-SHA-512)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mechanism: Option<String>,
+}
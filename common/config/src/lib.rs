@@ -6,11 +6,13 @@ pub mod anybox;
 pub mod apiserver;
 pub mod datalink_engine;
 pub mod image_host;
+pub mod kafka;
 pub mod mysql;
 pub mod nodemanage;
 pub mod object_storage;
 pub mod ocr;
 pub mod prompt;
+pub mod rc;
 pub mod redis;
 pub mod rsagent;
 pub mod rsync;
@@ -83,6 +85,12 @@ impl GlobalConfig {
         if let Some(ref storage) = self.object_storage {
             storage.validate()?;
         }
+        if let Some(ref remote_ocr) = self.remote_ocr {
+            remote_ocr.validate()?;
+        }
+        if let Some(ref image_hosting) = self.image_hosting {
+            image_hosting.validate()?;
+        }
         Ok(())
     }
 }
@@ -383,5 +391,24 @@ mod tests {
         assert_eq!(hosting.storage_dir, "");
         assert_eq!(hosting.cleanup_interval_secs, 3600);
         assert_eq!(hosting.file_expire_secs, 3600);
+        assert_eq!(hosting.convert_to, None);
+        assert_eq!(hosting.convert_quality, 85);
+        assert!(hosting.strip_metadata);
+    }
+
+    #[test]
+    fn image_hosting_config_validates_convert_format() {
+        let mut hosting = image_host::ImageHostingConfig {
+            convert_to: Some("webp".to_string()),
+            ..Default::default()
+        };
+        assert!(hosting.validate().is_ok());
+
+        hosting.convert_to = Some("gif".to_string());
+        assert!(hosting.validate().is_err());
+
+        hosting.convert_to = Some("jpg".to_string());
+        hosting.convert_quality = 0;
+        assert!(hosting.validate().is_err());
     }
 }
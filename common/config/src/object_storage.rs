@@ -375,7 +375,7 @@ mod tests {
         let config: ObjectStorageConfig = toml::from_str(toml_str).unwrap();
         assert_eq!(config.upload_token_ttl_secs, 3600);
         assert_eq!(config.private_url_ttl_secs, 3600);
-        assert_eq!(config.use_https, true);
-        assert_eq!(config.bucket_is_private, false);
+        assert!(config.use_https);
+        assert!(!config.bucket_is_private);
     }
 }
@@ -1,6 +1,7 @@
 //! OCR 相关配置
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::time::Duration;
@@ -98,8 +99,7 @@ pub struct RemoteOcrConfig {
     pub auth_uuid: String,
     /// 会话 cookie（抓包获取）
     pub auth_cookie: String,
-    /// 请求来源 origin & referer 头
-    #[serde(default = "default_origin")]
+    /// 请求来源 origin & referer 头（必填）
     pub origin: String,
     /// perm 接口所需的模式参数
     #[serde(default = "default_mode")]
@@ -119,16 +119,98 @@ pub struct RemoteOcrConfig {
     /// 是否忽略 TLS 证书校验（默认 false）
     #[serde(default)]
     pub accept_invalid_certs: bool,
+    /// 批量识别单次请求允许的最大文件数
+    #[serde(default = "default_batch_max_files")]
+    pub batch_max_files: usize,
+    /// 批量识别单次请求允许的文件总字节数
+    #[serde(default = "default_batch_max_total_bytes")]
+    pub batch_max_total_bytes: u64,
+    /// 批量识别的最大并发数
+    #[serde(default = "default_batch_concurrency")]
+    pub batch_concurrency: usize,
+    /// 每秒允许发起的 token 与启动任务请求数，进程内所有识别共享（0 表示不限流）
+    #[serde(default)]
+    pub requests_per_second: f64,
+    /// 按 URL 识别时允许下载的最大字节数
+    #[serde(default = "default_download_max_bytes")]
+    pub download_max_bytes: u64,
+    /// 按 URL 识别时是否允许访问回环、内网与链路本地地址（默认 false，仅用于测试或受信环境）
+    #[serde(default)]
+    pub download_allow_private_hosts: bool,
+    /// 是否启用识别结果缓存（需要同时配置 [redis]）
+    #[serde(default)]
+    pub cache_enabled: bool,
+    /// 识别结果缓存的过期时间（秒）
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    /// 识别语言（可选），支持 Tesseract 风格代码（如 "chi_sim"、"eng"），
+    /// 发送前会转换为远程服务的语言代码；不配置时由服务端自动判断
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// 远程服务不接受 HEIC/HEIF 时启用：HEIC 图片在上传前直接被拒绝，不会转码。
+    /// 兼容旧配置名 `heic_transcode_to_jpeg`
+    #[serde(default, alias = "heic_transcode_to_jpeg")]
+    pub reject_heic: bool,
+    /// 上传前按 EXIF 方向标记摆正图片（并去除 EXIF），改善侧拍照片的识别效果
+    #[serde(default)]
+    pub auto_orient: bool,
+    /// 附加到每个请求上的自定义请求头（如网关要求的 `X-Api-Key`），
+    /// 不能覆盖鉴权相关的请求头
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra_headers: HashMap<String, String>,
+    /// 请求使用的 User-Agent（可选），部分 WAF 会拦截缺少 UA 的请求
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
 }
 
+/// 限流时允许的最低速率（每 1000 秒一个请求）
+pub const MIN_REQUESTS_PER_SECOND: f64 = 0.001;
+
+/// 由鉴权流程设置、不允许通过 `extra_headers` 覆盖的请求头
+const RESERVED_HEADERS: &[&str] = &["x-auth-token", "x-auth-uuid", "cookie"];
+
 impl RemoteOcrConfig {
     /// 从 TOML 配置加载远程 OCR 设置
     pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
         let content = fs::read_to_string(&path)?;
         let config: Self = toml::from_str(&content)?;
+        config.validate()?;
         Ok(config)
     }
 
+    /// 校验配置：`origin` 不能为空，`requests_per_second` 为 0 或不低于 [`MIN_REQUESTS_PER_SECOND`]，`extra_headers` 的名称与取值须为合法的 HTTP 请求头
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.origin.trim().is_empty() {
+            anyhow::bail!("remote_ocr.origin must not be empty");
+        }
+        if !self.requests_per_second.is_finite() || self.requests_per_second < 0.0 {
+            anyhow::bail!("remote_ocr.requests_per_second must be a non-negative number");
+        }
+        if self.requests_per_second > 0.0 && self.requests_per_second < MIN_REQUESTS_PER_SECOND {
+            anyhow::bail!(
+                "remote_ocr.requests_per_second must be 0 (unlimited) or at least {MIN_REQUESTS_PER_SECOND}"
+            );
+        }
+        for (name, value) in &self.extra_headers {
+            let is_token = !name.is_empty()
+                && name
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b));
+            if !is_token {
+                anyhow::bail!("remote_ocr.extra_headers: invalid header name '{name}'");
+            }
+            if RESERVED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                anyhow::bail!(
+                    "remote_ocr.extra_headers: header '{name}' is managed by auth settings"
+                );
+            }
+            if value.bytes().any(|b| (b < 0x20 && b != b'\t') || b == 0x7f) {
+                anyhow::bail!("remote_ocr.extra_headers: invalid value for header '{name}'");
+            }
+        }
+        Ok(())
+    }
+
     /// 请求超时时间
     pub fn request_timeout(&self) -> Duration {
         Duration::from_secs(self.timeout_secs.max(1))
@@ -144,6 +226,42 @@ impl RemoteOcrConfig {
         Duration::from_millis(self.poll_initial_delay_ms)
     }
 
+    /// 指向 `base_url` 的测试配置，用于对接 mock 服务
+    ///
+    /// 接口地址为 `{base_url}/perm`、`/start`、`/status`，凭证为非占位符的固定值，
+    /// 轮询间隔 50ms、最多 5 次，请求超时 5 秒，其余字段取默认值
+    pub fn for_testing(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        Self {
+            perm_url: format!("{base_url}/perm"),
+            start_url: format!("{base_url}/start"),
+            status_url: format!("{base_url}/status"),
+            auth_token: "test-token".to_string(),
+            auth_uuid: "test-uuid".to_string(),
+            auth_cookie: "test-cookie".to_string(),
+            origin: base_url.to_string(),
+            mode: default_mode(),
+            timeout_secs: 5,
+            poll_interval_ms: 50,
+            poll_max_attempts: 5,
+            poll_initial_delay_ms: 0,
+            accept_invalid_certs: false,
+            batch_max_files: default_batch_max_files(),
+            batch_max_total_bytes: default_batch_max_total_bytes(),
+            batch_concurrency: default_batch_concurrency(),
+            requests_per_second: 0.0,
+            download_max_bytes: default_download_max_bytes(),
+            download_allow_private_hosts: false,
+            cache_enabled: false,
+            cache_ttl_secs: default_cache_ttl_secs(),
+            language: None,
+            reject_heic: false,
+            auto_orient: false,
+            extra_headers: HashMap::new(),
+            user_agent: None,
+        }
+    }
+
     /// 判断配置是否仍为占位符（未填入真实凭证）
     pub fn is_placeholder(&self) -> bool {
         self.auth_token.contains("changeme")
@@ -152,10 +270,6 @@ impl RemoteOcrConfig {
     }
 }
 
-fn default_origin() -> String {
-    "https://web.xxxxapp.com".to_string()
-}
-
 fn default_mode() -> String {
     "single".to_string()
 }
@@ -172,6 +286,26 @@ fn default_poll_max_attempts() -> u32 {
     20
 }
 
+fn default_batch_max_files() -> usize {
+    10
+}
+
+fn default_batch_max_total_bytes() -> u64 {
+    20 * 1024 * 1024
+}
+
+fn default_batch_concurrency() -> usize {
+    4
+}
+
+fn default_download_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    24 * 3600
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,19 +313,10 @@ mod tests {
     #[test]
     fn test_remote_ocr_config_is_placeholder() {
         let placeholder_config = RemoteOcrConfig {
-            perm_url: "https://example.com/perm".to_string(),
-            start_url: "https://example.com/start".to_string(),
-            status_url: "https://example.com/status".to_string(),
             auth_token: "changeme".to_string(),
             auth_uuid: "changeme".to_string(),
             auth_cookie: "changeme".to_string(),
-            origin: default_origin(),
-            mode: default_mode(),
-            timeout_secs: default_timeout_secs(),
-            poll_interval_ms: default_poll_interval_ms(),
-            poll_max_attempts: default_poll_max_attempts(),
-            poll_initial_delay_ms: 0,
-            accept_invalid_certs: false,
+            ..RemoteOcrConfig::for_testing("https://example.com")
         };
         assert!(placeholder_config.is_placeholder());
 
@@ -203,4 +328,98 @@ mod tests {
         };
         assert!(!valid_config.is_placeholder());
     }
+
+    #[test]
+    fn test_for_testing_config_is_valid() {
+        let config = RemoteOcrConfig::for_testing("http://127.0.0.1:8080/");
+        assert!(config.validate().is_ok());
+        assert!(!config.is_placeholder());
+        assert_eq!(config.perm_url, "http://127.0.0.1:8080/perm");
+        assert_eq!(config.start_url, "http://127.0.0.1:8080/start");
+        assert_eq!(config.status_url, "http://127.0.0.1:8080/status");
+        assert_eq!(config.poll_interval(), Duration::from_millis(50));
+    }
+
+    fn config_with_header(name: &str, value: &str) -> RemoteOcrConfig {
+        let mut config: RemoteOcrConfig = toml::from_str(
+            r#"
+            perm_url = "https://example.com/perm"
+            start_url = "https://example.com/start"
+            status_url = "https://example.com/status"
+            auth_token = "token"
+            auth_uuid = "uuid"
+            auth_cookie = "cookie"
+            origin = "https://example.com"
+            "#,
+        )
+        .unwrap();
+        config
+            .extra_headers
+            .insert(name.to_string(), value.to_string());
+        config
+    }
+
+    #[test]
+    fn test_reject_heic_accepts_legacy_name() {
+        let base = r#"
+            perm_url = "https://example.com/perm"
+            start_url = "https://example.com/start"
+            status_url = "https://example.com/status"
+            auth_token = "token"
+            auth_uuid = "uuid"
+            auth_cookie = "cookie"
+            origin = "https://example.com"
+            "#;
+        for key in ["reject_heic", "heic_transcode_to_jpeg"] {
+            let config: RemoteOcrConfig = toml::from_str(&format!("{key} = true\n{base}")).unwrap();
+            assert!(config.reject_heic, "{key}");
+        }
+    }
+
+    #[test]
+    fn test_validate_extra_headers() {
+        assert!(config_with_header("X-Api-Key", "secret").validate().is_ok());
+        assert!(config_with_header("Bad Header", "v").validate().is_err());
+        assert!(
+            config_with_header("X-Api-Key", "a\r\nb")
+                .validate()
+                .is_err()
+        );
+        assert!(config_with_header("Cookie", "v").validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_requests_per_second() {
+        let mut config = config_with_header("X-Api-Key", "secret");
+        for (rate, valid) in [
+            (0.0, true),
+            (MIN_REQUESTS_PER_SECOND, true),
+            (10.0, true),
+            (-1.0, false),
+            (f64::NAN, false),
+            (1e-300, false),
+        ] {
+            config.requests_per_second = rate;
+            assert_eq!(config.validate().is_ok(), valid, "{rate}");
+        }
+    }
+
+    #[test]
+    fn test_origin_is_required() {
+        let missing = toml::from_str::<RemoteOcrConfig>(
+            r#"
+            perm_url = "https://example.com/perm"
+            start_url = "https://example.com/start"
+            status_url = "https://example.com/status"
+            auth_token = "token"
+            auth_uuid = "uuid"
+            auth_cookie = "cookie"
+            "#,
+        );
+        assert!(missing.unwrap_err().to_string().contains("origin"));
+
+        let mut empty = config_with_header("X-Api-Key", "secret");
+        empty.origin = " ".to_string();
+        assert!(empty.validate().is_err());
+    }
 }
@@ -11,17 +11,44 @@ pub struct PromptConfig {
     /// 表名前缀
     #[serde(default = "default_table_prefix")]
     pub table_prefix: String,
+
+    /// 建表使用的存储引擎
+    #[serde(default = "default_engine")]
+    pub engine: String,
+
+    /// 建表使用的默认字符集
+    #[serde(default = "default_charset")]
+    pub charset: String,
+
+    /// 建表使用的排序规则，为空时使用字符集的默认排序规则
+    #[serde(default = "default_collation")]
+    pub collation: String,
 }
 
 fn default_table_prefix() -> String {
     "prompt_".to_string()
 }
 
+fn default_engine() -> String {
+    "InnoDB".to_string()
+}
+
+fn default_charset() -> String {
+    "utf8mb4".to_string()
+}
+
+fn default_collation() -> String {
+    "utf8mb4_unicode_ci".to_string()
+}
+
 impl Default for PromptConfig {
     fn default() -> Self {
         Self {
             mysql: MysqlConfig::default(),
             table_prefix: default_table_prefix(),
+            engine: default_engine(),
+            charset: default_charset(),
+            collation: default_collation(),
         }
     }
 }
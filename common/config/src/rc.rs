@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::ConfigLoader;
+use crate::kafka::KafkaConfig;
+use crate::mysql::MysqlConfig;
+use crate::redis::RedisConfig;
+
+/// rc 命令行工具的连接默认值，按服务分节
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct RcConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redis: Option<RedisConfig>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mysql: Option<MysqlConfig>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kafka: Option<KafkaConfig>,
+}
+
+impl ConfigLoader for RcConfig {
+    fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+}
@@ -1,11 +1,18 @@
+use rdkafka::TopicPartitionList;
 use rdkafka::config::ClientConfig;
-use rdkafka::consumer::{Consumer, StreamConsumer};
-use rdkafka::message::{BorrowedMessage, Message};
+use rdkafka::consumer::{BaseConsumer, Consumer, StreamConsumer};
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::message::{BorrowedMessage, Header, Message, OwnedHeaders};
+use rdkafka::metadata::Metadata;
 use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
 use rdkafka::util::Timeout;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Mutex;
 use std::time::Duration;
 
+pub use rdkafka::Offset;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KafkaClientConfig {
     /// Kafka 服务器地址列表
@@ -22,6 +29,28 @@ pub struct KafkaClientConfig {
     pub enable_auto_commit: Option<bool>,
     /// SASL 认证配置
     pub sasl_config: Option<SaslConfig>,
+    /// CA 证书路径（SSL / SASL_SSL 时用于校验 broker）
+    #[serde(default)]
+    pub ssl_ca_location: Option<String>,
+    /// 客户端证书路径（SSL 双向认证时使用）
+    #[serde(default)]
+    pub ssl_certificate_location: Option<String>,
+    /// 生产者确认级别（0、1、all），未设置时使用 librdkafka 默认值
+    #[serde(default)]
+    pub acks: Option<String>,
+    /// 是否启用幂等生产，启用时要求 acks=all
+    #[serde(default)]
+    pub enable_idempotence: Option<bool>,
+}
+
+/// 支持的 acks 取值，`-1` 与 `all` 等价
+pub const SUPPORTED_ACKS: &[&str] = &["0", "1", "all", "-1"];
+
+/// 支持的 SASL 机制
+pub const SUPPORTED_SASL_MECHANISMS: &[&str] = &["PLAIN", "SCRAM-SHA-256", "SCRAM-SHA-512"];
+
+fn is_acks_all(acks: &str) -> bool {
+    acks == "all" || acks == "-1"
 }
 
 /// SASL 认证配置
@@ -63,6 +92,21 @@ impl SaslConfig {
         self.mechanism = mechanism.into();
         self
     }
+
+    /// 校验 SASL 机制是否受支持
+    ///
+    /// 机制名称区分大小写，与 librdkafka 保持一致
+    pub fn validate(&self) -> Result<(), String> {
+        if SUPPORTED_SASL_MECHANISMS.contains(&self.mechanism.as_str()) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Unsupported SASL mechanism '{}', expected one of: {}",
+                self.mechanism,
+                SUPPORTED_SASL_MECHANISMS.join(", ")
+            ))
+        }
+    }
 }
 
 impl KafkaClientConfig {
@@ -76,6 +120,10 @@ impl KafkaClientConfig {
             session_timeout_ms: Some(6000),
             enable_auto_commit: Some(true),
             sasl_config: None,
+            ssl_ca_location: None,
+            ssl_certificate_location: None,
+            acks: None,
+            enable_idempotence: None,
         }
     }
 
@@ -119,6 +167,90 @@ impl KafkaClientConfig {
         self
     }
 
+    /// 设置 CA 证书路径
+    pub fn with_ssl_ca_location(mut self, location: impl Into<String>) -> Self {
+        self.ssl_ca_location = Some(location.into());
+        self
+    }
+
+    /// 设置客户端证书路径
+    pub fn with_ssl_certificate_location(mut self, location: impl Into<String>) -> Self {
+        self.ssl_certificate_location = Some(location.into());
+        self
+    }
+
+    /// 设置生产者确认级别（0、1、all）
+    pub fn with_acks(mut self, acks: impl Into<String>) -> Self {
+        self.acks = Some(acks.into());
+        self
+    }
+
+    /// 设置是否启用幂等生产
+    pub fn with_idempotence(mut self, enable: bool) -> Self {
+        self.enable_idempotence = Some(enable);
+        self
+    }
+
+    /// 校验配置：SASL 机制、acks 取值，以及幂等生产与 acks 的组合
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(sasl) = &self.sasl_config {
+            sasl.validate()?;
+        }
+        if let Some(acks) = &self.acks {
+            if !SUPPORTED_ACKS.contains(&acks.as_str()) {
+                return Err(format!(
+                    "Unsupported acks '{acks}', expected one of: {}",
+                    SUPPORTED_ACKS.join(", ")
+                ));
+            }
+            if self.enable_idempotence == Some(true) && !is_acks_all(acks) {
+                return Err(format!(
+                    "Idempotent producer requires acks=all, got acks={acks}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 构建生产者使用的 ClientConfig
+    ///
+    /// 启用幂等生产但未指定 acks 时自动使用 acks=all
+    fn producer_client_config(&self) -> Result<ClientConfig, String> {
+        self.validate()?;
+
+        let mut client_config = ClientConfig::new();
+        client_config
+            .set("bootstrap.servers", self.broker_string())
+            .set("client.id", &self.client_id)
+            .set("message.timeout.ms", "5000")
+            .set("queue.buffering.max.messages", "100000")
+            .set("queue.buffering.max.kbytes", "1048576")
+            .set("batch.num.messages", "10000");
+
+        if let Some(timeout) = self.timeout {
+            client_config.set("socket.timeout.ms", (timeout * 1000).to_string());
+        }
+
+        if let Some(enable) = self.enable_idempotence {
+            client_config.set("enable.idempotence", enable.to_string());
+        }
+        match &self.acks {
+            Some(acks) => {
+                client_config.set("acks", acks);
+            }
+            None if self.enable_idempotence == Some(true) => {
+                client_config.set("acks", "all");
+            }
+            None => {}
+        }
+
+        // 应用 SASL 认证与 SSL 证书配置
+        self.apply_sasl_config(&mut client_config);
+        self.apply_ssl_config(&mut client_config);
+
+        Ok(client_config)
+    }
+
     /// 获取 broker 地址字符串
     fn broker_string(&self) -> String {
         self.brokers.join(",")
@@ -134,6 +266,24 @@ impl KafkaClientConfig {
                 .set("sasl.password", &sasl.password);
         }
     }
+
+    /// 应用 SSL 证书配置到 ClientConfig
+    ///
+    /// 未配置 SASL 时，指定证书即表示使用纯 SSL 协议
+    fn apply_ssl_config(&self, client_config: &mut ClientConfig) {
+        if self.ssl_ca_location.is_none() && self.ssl_certificate_location.is_none() {
+            return;
+        }
+        if self.sasl_config.is_none() {
+            client_config.set("security.protocol", "SSL");
+        }
+        if let Some(ca) = &self.ssl_ca_location {
+            client_config.set("ssl.ca.location", ca);
+        }
+        if let Some(cert) = &self.ssl_certificate_location {
+            client_config.set("ssl.certificate.location", cert);
+        }
+    }
 }
 
 /// Kafka 生产者客户端
@@ -144,23 +294,8 @@ pub struct KafkaProducer {
 impl KafkaProducer {
     /// 创建一个新的 Kafka 生产者
     pub fn new(config: &KafkaClientConfig) -> Result<Self, String> {
-        let mut client_config = ClientConfig::new();
-        client_config
-            .set("bootstrap.servers", config.broker_string())
-            .set("client.id", &config.client_id)
-            .set("message.timeout.ms", "5000")
-            .set("queue.buffering.max.messages", "100000")
-            .set("queue.buffering.max.kbytes", "1048576")
-            .set("batch.num.messages", "10000");
-
-        if let Some(timeout) = config.timeout {
-            client_config.set("socket.timeout.ms", (timeout * 1000).to_string());
-        }
-
-        // 应用 SASL 认证配置
-        config.apply_sasl_config(&mut client_config);
-
-        let producer = client_config
+        let producer = config
+            .producer_client_config()?
             .create()
             .map_err(|e| format!("Failed to create Kafka producer: {e}"))?;
 
@@ -169,12 +304,37 @@ impl KafkaProducer {
 
     /// 发送消息到指定的 topic
     pub async fn send(&self, topic: &str, key: Option<&str>, payload: &[u8]) -> Result<(), String> {
+        self.send_with_headers(topic, key, payload, &[]).await
+    }
+
+    /// 发送带 headers 的消息到指定的 topic
+    ///
+    /// headers 常用于传递 trace id、content-type 等元信息
+    pub async fn send_with_headers(
+        &self,
+        topic: &str,
+        key: Option<&str>,
+        payload: &[u8],
+        headers: &[(&str, &[u8])],
+    ) -> Result<(), String> {
         let mut record = FutureRecord::to(topic).payload(payload);
 
         if let Some(k) = key {
             record = record.key(k);
         }
 
+        if !headers.is_empty() {
+            let owned = headers
+                .iter()
+                .fold(OwnedHeaders::new(), |acc, (name, value)| {
+                    acc.insert(Header {
+                        key: name,
+                        value: Some(*value),
+                    })
+                });
+            record = record.headers(owned);
+        }
+
         self.producer
             .send(record, Timeout::After(Duration::from_secs(5)))
             .await
@@ -195,6 +355,40 @@ impl KafkaProducer {
         self.send(topic, key, &payload).await
     }
 
+    /// 批量发送消息到指定的 topic
+    ///
+    /// 先把所有消息放入发送队列，再并发等待投递结果，以便 librdkafka 合批发送。
+    /// 返回结果与输入顺序一致，单条失败不影响其它消息；调用方需要落盘保证时可随后调用 `flush`
+    pub async fn send_batch(
+        &self,
+        topic: &str,
+        messages: Vec<(Option<String>, Vec<u8>)>,
+    ) -> Result<Vec<Result<(), String>>, String> {
+        if topic.is_empty() {
+            return Err("Topic must not be empty".to_string());
+        }
+
+        let deliveries = messages.iter().map(|(key, payload)| {
+            let mut record = FutureRecord::to(topic).payload(payload.as_slice());
+            if let Some(k) = key {
+                record = record.key(k.as_str());
+            }
+            // send_result 只入队不等待，入队失败（如队列已满、消息过大）直接记为该条的结果
+            let enqueued = self.producer.send_result(record);
+            async move {
+                let delivery =
+                    enqueued.map_err(|(e, _)| format!("Failed to enqueue message: {e}"))?;
+                match delivery.await {
+                    Ok(Ok(_)) => Ok(()),
+                    Ok(Err((e, _))) => Err(format!("Failed to send message: {e}")),
+                    Err(_) => Err("Failed to send message: delivery canceled".to_string()),
+                }
+            }
+        });
+
+        Ok(futures::future::join_all(deliveries).await)
+    }
+
     /// 刷新缓冲区，确保所有消息已发送
     pub fn flush(&self, timeout: Duration) -> Result<(), String> {
         self.producer
@@ -215,29 +409,42 @@ impl KafkaProducer {
     }
 
     /// 获取指定 topic 的 metadata
-    pub fn get_topic_metadata(&self, topic: &str, timeout: Duration) -> Result<String, String> {
+    pub fn get_topic_metadata(
+        &self,
+        topic: &str,
+        timeout: Duration,
+    ) -> Result<ClusterMetadata, String> {
         let metadata = self
             .producer
             .client()
             .fetch_metadata(Some(topic), Timeout::After(timeout))
             .map_err(|e| format!("Failed to fetch metadata: {e}"))?;
 
-        let mut result = format!("Cluster: {}\n", metadata.orig_broker_name());
-        result.push_str(&format!("Brokers: {}\n", metadata.brokers().len()));
-        result.push_str(&format!("Topics: {}\n", metadata.topics().len()));
-
-        Ok(result)
+        Ok(ClusterMetadata::from(&metadata))
     }
 }
 
 /// Kafka 消费者客户端
 pub struct KafkaConsumer {
     consumer: StreamConsumer,
+    /// 创建时使用的配置，查询其它消费者组的 offset 时复用
+    client_config: ClientConfig,
+    /// 最近一次 subscribe 的 topics，订阅丢失时用于重新订阅
+    subscribed_topics: Mutex<Vec<String>>,
 }
 
+/// recv_resilient 重试的初始与最大等待时间
+///
+/// 重连由 librdkafka 自身完成，这里的退避只用于避免空转；
+/// 宕机期间积压的错误会逐条返回，上限过大会拖慢恢复后的消费
+const RECV_RETRY_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const RECV_RETRY_MAX_BACKOFF: Duration = Duration::from_secs(2);
+
 impl KafkaConsumer {
     /// 创建一个新的 Kafka 消费者
     pub fn new(config: &KafkaClientConfig) -> Result<Self, String> {
+        config.validate()?;
+
         let group_id = config
             .group_id
             .as_ref()
@@ -263,14 +470,19 @@ impl KafkaConsumer {
             client_config.set("socket.timeout.ms", (timeout * 1000).to_string());
         }
 
-        // 应用 SASL 认证配置
+        // 应用 SASL 认证与 SSL 证书配置
         config.apply_sasl_config(&mut client_config);
+        config.apply_ssl_config(&mut client_config);
 
         let consumer: StreamConsumer = client_config
             .create()
             .map_err(|e| format!("Failed to create Kafka consumer: {e}"))?;
 
-        Ok(Self { consumer })
+        Ok(Self {
+            consumer,
+            client_config,
+            subscribed_topics: Mutex::new(Vec::new()),
+        })
     }
 
     /// 订阅指定的 topics
@@ -278,6 +490,41 @@ impl KafkaConsumer {
         self.consumer
             .subscribe(topics)
             .map_err(|e| format!("Failed to subscribe to topics: {e}"))?;
+        *self.subscribed_topics.lock().unwrap() = topics.iter().map(|t| t.to_string()).collect();
+        Ok(())
+    }
+
+    /// 手动分配指定分区并从给定 offset 开始消费
+    ///
+    /// 注意：assign 与 subscribe 互斥，同一个消费者只能使用其中一种方式；
+    /// 手动分配的分区不参与消费者组的 rebalance
+    pub fn assign_partition(
+        &self,
+        topic: &str,
+        partition: i32,
+        offset: Offset,
+    ) -> Result<(), String> {
+        let mut tpl = TopicPartitionList::new();
+        tpl.add_partition_offset(topic, partition, offset)
+            .map_err(|e| format!("Invalid partition offset: {e}"))?;
+        self.consumer
+            .assign(&tpl)
+            .map_err(|e| format!("Failed to assign partition: {e}"))?;
+        Ok(())
+    }
+
+    /// 将已分配分区的消费位置移动到指定 offset
+    ///
+    /// 分区必须已经通过 assign_partition 或订阅分配给当前消费者
+    pub fn seek(&self, topic: &str, partition: i32, offset: Offset) -> Result<(), String> {
+        self.consumer
+            .seek(
+                topic,
+                partition,
+                offset,
+                Timeout::After(Duration::from_secs(5)),
+            )
+            .map_err(|e| format!("Failed to seek: {e}"))?;
         Ok(())
     }
 
@@ -289,6 +536,54 @@ impl KafkaConsumer {
             .map_err(|e| format!("Failed to receive message: {e}"))
     }
 
+    /// 接收下一条消息，遇到可恢复的错误时自动重试
+    ///
+    /// 分区读到末尾（`PartitionEOF`，开启 `enable.partition.eof` 时出现）不是错误，直接继续接收。
+    /// 可恢复错误（见 `is_transient_error`）会以指数退避（100ms 起，最长 2s）无限重试，
+    /// 每次重试前通过 `on_error` 回调报告错误；若之前通过 `subscribe` 订阅的 topics
+    /// 已丢失，会在重试前重新订阅。其它错误（如认证失败、fatal 错误）直接返回
+    pub async fn recv_resilient(
+        &self,
+        on_error: impl Fn(&str),
+    ) -> Result<BorrowedMessage<'_>, String> {
+        let mut backoff = RECV_RETRY_INITIAL_BACKOFF;
+        loop {
+            let err = match self.consumer.recv().await {
+                Ok(msg) => return Ok(msg),
+                Err(KafkaError::PartitionEOF(_)) => continue,
+                Err(e) => e,
+            };
+            if !is_transient_error(&err) {
+                return Err(format!("Failed to receive message: {err}"));
+            }
+            on_error(&format!("Transient error while receiving message: {err}"));
+
+            if self.resubscribe_if_lost()? {
+                on_error("Subscription was lost, resubscribed");
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(RECV_RETRY_MAX_BACKOFF);
+        }
+    }
+
+    /// 之前订阅过 topics 但当前订阅为空时重新订阅，返回是否进行了重新订阅
+    fn resubscribe_if_lost(&self) -> Result<bool, String> {
+        let topics = self.subscribed_topics.lock().unwrap().clone();
+        if topics.is_empty() {
+            return Ok(false);
+        }
+        let current = self
+            .consumer
+            .subscription()
+            .map_err(|e| format!("Failed to fetch subscription: {e}"))?;
+        if current.count() > 0 {
+            return Ok(false);
+        }
+        let topics: Vec<&str> = topics.iter().map(String::as_str).collect();
+        self.subscribe(&topics)?;
+        Ok(true)
+    }
+
     /// 提交当前偏移量
     pub fn commit(&self) -> Result<(), String> {
         self.consumer
@@ -313,27 +608,293 @@ impl KafkaConsumer {
     }
 
     /// 获取指定 topic 的 metadata
-    pub fn get_topic_metadata(&self, topic: &str, timeout: Duration) -> Result<String, String> {
+    pub fn get_topic_metadata(
+        &self,
+        topic: &str,
+        timeout: Duration,
+    ) -> Result<ClusterMetadata, String> {
         let metadata = self
             .consumer
             .fetch_metadata(Some(topic), Timeout::After(timeout))
             .map_err(|e| format!("Failed to fetch metadata: {e}"))?;
 
-        let mut result = format!("Cluster: {}\n", metadata.orig_broker_name());
-        result.push_str(&format!("Brokers: {}\n", metadata.brokers().len()));
-        result.push_str(&format!("Topics: {}\n", metadata.topics().len()));
+        Ok(ClusterMetadata::from(&metadata))
+    }
 
-        for topic_meta in metadata.topics() {
-            if topic_meta.name() == topic {
-                result.push_str(&format!(
-                    "  Topic '{}': {} partitions\n",
-                    topic_meta.name(),
-                    topic_meta.partitions().len()
-                ));
-            }
+    /// 列出集群中的消费者组
+    ///
+    /// 阻塞调用，在异步上下文中需通过 `spawn_blocking` 执行
+    pub fn list_groups(&self, timeout: Duration) -> Result<Vec<ConsumerGroupInfo>, String> {
+        let groups = self
+            .consumer
+            .fetch_group_list(None, Timeout::After(timeout))
+            .map_err(|e| format!("Failed to list consumer groups: {e}"))?;
+
+        Ok(groups
+            .groups()
+            .iter()
+            .map(|group| ConsumerGroupInfo {
+                name: group.name().to_string(),
+                state: group.state().to_string(),
+                protocol_type: group.protocol_type().to_string(),
+                members: group.members().len(),
+            })
+            .collect())
+    }
+
+    /// 计算消费者组在指定 topic 上每个分区的 lag（已提交 offset 与 high watermark 之差）
+    ///
+    /// 阻塞调用，在异步上下文中需通过 `spawn_blocking` 执行
+    pub fn group_lag(
+        &self,
+        group: &str,
+        topic: &str,
+        timeout: Duration,
+    ) -> Result<GroupLag, String> {
+        // committed_offsets 按消费者自身的 group.id 查询，这里用该组的临时消费者，
+        // 不订阅也不提交，不会加入该组触发 rebalance
+        let mut client_config = self.client_config.clone();
+        client_config
+            .set("group.id", group)
+            .set("enable.auto.commit", "false");
+        let group_consumer: BaseConsumer = client_config
+            .create()
+            .map_err(|e| format!("Failed to create Kafka consumer: {e}"))?;
+
+        let metadata = group_consumer
+            .fetch_metadata(Some(topic), Timeout::After(timeout))
+            .map_err(|e| format!("Failed to fetch metadata: {e}"))?;
+        let topic_metadata = metadata
+            .topics()
+            .iter()
+            .find(|t| t.name() == topic)
+            .ok_or_else(|| format!("Topic '{topic}' not found"))?;
+        if let Some(err) = topic_metadata.error() {
+            return Err(format!(
+                "Failed to fetch metadata for topic '{topic}': {err:?}"
+            ));
+        }
+
+        let mut tpl = TopicPartitionList::new();
+        for partition in topic_metadata.partitions() {
+            tpl.add_partition(topic, partition.id());
+        }
+        let committed = group_consumer
+            .committed_offsets(tpl, Timeout::After(timeout))
+            .map_err(|e| format!("Failed to fetch committed offsets: {e}"))?;
+
+        let mut partitions = Vec::new();
+        for element in committed.elements_for_topic(topic) {
+            let (low, high) = group_consumer
+                .fetch_watermarks(topic, element.partition(), Timeout::After(timeout))
+                .map_err(|e| format!("Failed to fetch watermarks: {e}"))?;
+            let committed = match element.offset() {
+                Offset::Offset(offset) => Some(offset),
+                _ => None,
+            };
+            partitions.push(PartitionLag::new(element.partition(), committed, low, high));
         }
 
-        Ok(result)
+        Ok(GroupLag::new(group, topic, partitions))
+    }
+}
+
+/// 判断接收消息时的错误是否可以通过重试恢复
+///
+/// 视为可恢复的错误：
+/// - broker 连接中断或全部不可用、DNS 解析失败、请求或操作超时、网络异常
+/// - 分区 leader 切换或暂不可用、topic 元数据尚未就绪
+/// - 消费者组协调者不可用、正在加载或切换，以及组正在 rebalance
+///
+/// 其余错误（包括 `MessageConsumptionFatal`）均视为不可恢复。
+/// 分区读到末尾（`PartitionEOF`）只是通知而不是错误，不在此判断，调用方应直接忽略
+pub fn is_transient_error(err: &KafkaError) -> bool {
+    match err {
+        KafkaError::MessageConsumption(code) => matches!(
+            code,
+            RDKafkaErrorCode::BrokerTransportFailure
+                | RDKafkaErrorCode::AllBrokersDown
+                | RDKafkaErrorCode::Resolve
+                | RDKafkaErrorCode::OperationTimedOut
+                | RDKafkaErrorCode::RequestTimedOut
+                | RDKafkaErrorCode::NetworkException
+                | RDKafkaErrorCode::LeaderNotAvailable
+                | RDKafkaErrorCode::NotLeaderForPartition
+                | RDKafkaErrorCode::UnknownTopicOrPartition
+                | RDKafkaErrorCode::CoordinatorLoadInProgress
+                | RDKafkaErrorCode::CoordinatorNotAvailable
+                | RDKafkaErrorCode::NotCoordinator
+                | RDKafkaErrorCode::RebalanceInProgress
+        ),
+        _ => false,
+    }
+}
+
+/// 消费者组信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsumerGroupInfo {
+    pub name: String,
+    /// 组状态，如 Stable、Empty
+    pub state: String,
+    pub protocol_type: String,
+    /// 当前成员数
+    pub members: usize,
+}
+
+/// 单个分区的消费 lag
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartitionLag {
+    pub partition: i32,
+    /// 已提交的 offset，该组从未提交过时为空
+    pub committed: Option<i64>,
+    pub low_watermark: i64,
+    pub high_watermark: i64,
+    pub lag: i64,
+}
+
+impl PartitionLag {
+    /// 未提交过 offset 时按从 low watermark 开始消费计算 lag
+    pub fn new(
+        partition: i32,
+        committed: Option<i64>,
+        low_watermark: i64,
+        high_watermark: i64,
+    ) -> Self {
+        let position = committed.unwrap_or(low_watermark);
+        Self {
+            partition,
+            committed,
+            low_watermark,
+            high_watermark,
+            lag: (high_watermark - position).max(0),
+        }
+    }
+}
+
+/// 消费者组在某个 topic 上的 lag，`Display` 输出供命令行展示的文本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupLag {
+    pub group: String,
+    pub topic: String,
+    /// 按分区号排序
+    pub partitions: Vec<PartitionLag>,
+    pub total_lag: i64,
+}
+
+impl GroupLag {
+    pub fn new(group: &str, topic: &str, mut partitions: Vec<PartitionLag>) -> Self {
+        partitions.sort_by_key(|p| p.partition);
+        let total_lag = partitions.iter().map(|p| p.lag).sum();
+        Self {
+            group: group.to_string(),
+            topic: topic.to_string(),
+            partitions,
+            total_lag,
+        }
+    }
+}
+
+impl fmt::Display for GroupLag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Group: {}, Topic: {}", self.group, self.topic)?;
+        for partition in &self.partitions {
+            let committed = partition
+                .committed
+                .map(|offset| offset.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                f,
+                "  Partition {}: committed={} end={} lag={}",
+                partition.partition, committed, partition.high_watermark, partition.lag
+            )?;
+        }
+        writeln!(f, "Total lag: {}", self.total_lag)
+    }
+}
+
+/// Kafka 集群 metadata，`Display` 输出供命令行展示的文本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClusterMetadata {
+    /// 返回 metadata 的 broker 名称
+    pub cluster_name: String,
+    pub brokers: Vec<BrokerInfo>,
+    pub topics: Vec<TopicMetadata>,
+}
+
+/// Broker 信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrokerInfo {
+    pub id: i32,
+    pub host: String,
+    pub port: i32,
+}
+
+/// Topic 信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicMetadata {
+    pub name: String,
+    pub partition_count: usize,
+}
+
+impl ClusterMetadata {
+    /// 按名称查找 topic
+    pub fn topic(&self, name: &str) -> Option<&TopicMetadata> {
+        self.topics.iter().find(|topic| topic.name == name)
+    }
+}
+
+impl From<&Metadata> for ClusterMetadata {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            cluster_name: metadata.orig_broker_name().to_string(),
+            brokers: metadata
+                .brokers()
+                .iter()
+                .map(|broker| BrokerInfo {
+                    id: broker.id(),
+                    host: broker.host().to_string(),
+                    port: broker.port(),
+                })
+                .collect(),
+            topics: metadata
+                .topics()
+                .iter()
+                .map(|topic| TopicMetadata {
+                    name: topic.name().to_string(),
+                    partition_count: topic.partitions().len(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for ClusterMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cluster: {}", self.cluster_name)?;
+        writeln!(f, "Brokers: {}", self.brokers.len())?;
+        writeln!(f, "Topics: {}", self.topics.len())?;
+        for topic in &self.topics {
+            writeln!(
+                f,
+                "  Topic '{}': {} partitions",
+                topic.name, topic.partition_count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 辅助函数：解析 offset 描述（earliest / latest / 具体数值）
+pub fn parse_offset(raw: &str) -> Result<Offset, String> {
+    match raw.trim().to_lowercase().as_str() {
+        "earliest" | "beginning" => Ok(Offset::Beginning),
+        "latest" | "end" => Ok(Offset::End),
+        value => value
+            .parse::<i64>()
+            .ok()
+            .filter(|n| *n >= 0)
+            .map(Offset::Offset)
+            .ok_or_else(|| format!("Invalid offset '{raw}', expected earliest, latest or <n>")),
     }
 }
 
@@ -388,4 +949,310 @@ mod tests {
         assert_eq!(sasl.username, "user");
         assert_eq!(sasl.password, "pass");
     }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("earliest"), Ok(Offset::Beginning));
+        assert_eq!(parse_offset("LATEST"), Ok(Offset::End));
+        assert_eq!(parse_offset("42"), Ok(Offset::Offset(42)));
+        assert!(parse_offset("-1").is_err());
+        assert!(parse_offset("first").is_err());
+    }
+
+    #[test]
+    fn test_sasl_mechanism_validation() {
+        for mechanism in SUPPORTED_SASL_MECHANISMS {
+            let sasl = SaslConfig::plaintext("user", "pass").with_mechanism(*mechanism);
+            assert!(sasl.validate().is_ok(), "{mechanism} should be accepted");
+        }
+
+        let err = SaslConfig::plaintext("user", "pass")
+            .with_mechanism("SCRAM-SHA256")
+            .validate()
+            .unwrap_err();
+        assert!(err.contains("SCRAM-SHA256"));
+        assert!(err.contains("SCRAM-SHA-512"));
+    }
+
+    #[test]
+    fn test_client_rejects_unknown_mechanism() {
+        let config = KafkaClientConfig::new(vec!["localhost:9092".to_string()], "test-client")
+            .with_sasl(SaslConfig::plaintext("user", "pass").with_mechanism("GSSAPI"))
+            .with_group_id("test-group");
+
+        assert!(KafkaProducer::new(&config).is_err());
+        assert!(KafkaConsumer::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_config_with_ssl_locations() {
+        let config = KafkaClientConfig::new(vec!["localhost:9093".to_string()], "test-client")
+            .with_ssl_ca_location("/etc/kafka/ca.pem")
+            .with_ssl_certificate_location("/etc/kafka/client.pem");
+
+        let mut client_config = ClientConfig::new();
+        config.apply_ssl_config(&mut client_config);
+        assert_eq!(client_config.get("security.protocol"), Some("SSL"));
+        assert_eq!(
+            client_config.get("ssl.ca.location"),
+            Some("/etc/kafka/ca.pem")
+        );
+        assert_eq!(
+            client_config.get("ssl.certificate.location"),
+            Some("/etc/kafka/client.pem")
+        );
+    }
+
+    #[test]
+    fn test_producer_config_applies_acks_and_idempotence() {
+        let config = KafkaClientConfig::new(vec!["localhost:9092".to_string()], "test-client")
+            .with_acks("1");
+        let client_config = config.producer_client_config().unwrap();
+        assert_eq!(client_config.get("acks"), Some("1"));
+        assert_eq!(client_config.get("enable.idempotence"), None);
+
+        let config = KafkaClientConfig::new(vec!["localhost:9092".to_string()], "test-client")
+            .with_acks("all")
+            .with_idempotence(true);
+        let client_config = config.producer_client_config().unwrap();
+        assert_eq!(client_config.get("acks"), Some("all"));
+        assert_eq!(client_config.get("enable.idempotence"), Some("true"));
+
+        // 默认不覆盖 librdkafka 的设置
+        let config = KafkaClientConfig::new(vec!["localhost:9092".to_string()], "test-client");
+        let client_config = config.producer_client_config().unwrap();
+        assert_eq!(client_config.get("acks"), None);
+        assert_eq!(client_config.get("enable.idempotence"), None);
+    }
+
+    #[test]
+    fn test_idempotence_defaults_acks_to_all() {
+        let config = KafkaClientConfig::new(vec!["localhost:9092".to_string()], "test-client")
+            .with_idempotence(true);
+        let client_config = config.producer_client_config().unwrap();
+        assert_eq!(client_config.get("acks"), Some("all"));
+        assert!(KafkaProducer::new(&config).is_ok());
+    }
+
+    #[test]
+    fn test_idempotence_rejects_weaker_acks() {
+        let config = KafkaClientConfig::new(vec!["localhost:9092".to_string()], "test-client")
+            .with_acks("1")
+            .with_idempotence(true);
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("acks=all"));
+        assert!(KafkaProducer::new(&config).is_err());
+
+        let config = KafkaClientConfig::new(vec!["localhost:9092".to_string()], "test-client")
+            .with_acks("-1")
+            .with_idempotence(true);
+        assert!(config.validate().is_ok());
+
+        let config = KafkaClientConfig::new(vec!["localhost:9092".to_string()], "test-client")
+            .with_acks("2");
+        assert!(config.validate().unwrap_err().contains("Unsupported acks"));
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_keeps_result_order() {
+        // 无可用 broker：正常消息在 message.timeout.ms 后投递失败，超大消息入队即失败
+        let config = KafkaClientConfig::new(vec!["127.0.0.1:9".to_string()], "test-client");
+        let producer = KafkaProducer::new(&config).unwrap();
+
+        let results = producer
+            .send_batch(
+                "test-topic",
+                vec![
+                    (Some("a".to_string()), b"first".to_vec()),
+                    (None, vec![0u8; 2 * 1024 * 1024]),
+                    (Some("c".to_string()), b"third".to_vec()),
+                ],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].as_ref().unwrap_err().contains("Failed to send"));
+        assert!(
+            results[1]
+                .as_ref()
+                .unwrap_err()
+                .contains("Failed to enqueue")
+        );
+        assert!(results[2].as_ref().unwrap_err().contains("Failed to send"));
+
+        assert!(producer.send_batch("", Vec::new()).await.is_err());
+    }
+
+    #[test]
+    fn test_transient_error_classification() {
+        assert!(!is_transient_error(&KafkaError::PartitionEOF(0)));
+        assert!(is_transient_error(&KafkaError::MessageConsumption(
+            RDKafkaErrorCode::AllBrokersDown
+        )));
+        assert!(is_transient_error(&KafkaError::MessageConsumption(
+            RDKafkaErrorCode::BrokerTransportFailure
+        )));
+        assert!(!is_transient_error(&KafkaError::MessageConsumption(
+            RDKafkaErrorCode::SaslAuthenticationFailed
+        )));
+        assert!(!is_transient_error(&KafkaError::MessageConsumptionFatal(
+            RDKafkaErrorCode::AllBrokersDown
+        )));
+        assert!(!is_transient_error(&KafkaError::Subscription(
+            "bad".to_string()
+        )));
+    }
+
+    /// 用 librdkafka 内置的 mock 集群模拟 broker 宕机后恢复
+    #[tokio::test]
+    #[ignore] // 依赖 broker 重连时序，耗时较长
+    async fn test_recv_resilient_recovers_after_broker_restart() {
+        use rdkafka::mocking::MockCluster;
+
+        let cluster = MockCluster::new(1).expect("mock cluster");
+        cluster
+            .create_topic("resilient", 1, 1)
+            .expect("create topic");
+        let brokers = vec![cluster.bootstrap_servers()];
+
+        let producer = KafkaProducer::new(&KafkaClientConfig::new(brokers.clone(), "producer"))
+            .expect("producer");
+        let consumer = KafkaConsumer::new(
+            &KafkaClientConfig::new(brokers, "consumer").with_group_id("resilient-group"),
+        )
+        .expect("consumer");
+        consumer.subscribe(&["resilient"]).expect("subscribe");
+
+        producer.send("resilient", None, b"before").await.unwrap();
+        let errors = std::cell::RefCell::new(Vec::new());
+        let msg = consumer
+            .recv_resilient(|e| errors.borrow_mut().push(e.to_string()))
+            .await
+            .expect("recv before restart");
+        assert_eq!(extract_payload(&msg), Some(&b"before"[..]));
+        drop(msg);
+
+        cluster.broker_down(1).expect("broker down");
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        cluster.broker_up(1).expect("broker up");
+
+        producer.send("resilient", None, b"after").await.unwrap();
+        let msg = tokio::time::timeout(
+            Duration::from_secs(30),
+            consumer.recv_resilient(|e| errors.borrow_mut().push(e.to_string())),
+        )
+        .await
+        .unwrap_or_else(|_| panic!("not recovered: {:?}", errors.borrow()))
+        .expect("recv after restart");
+        assert_eq!(extract_payload(&msg), Some(&b"after"[..]));
+    }
+
+    fn sample_metadata() -> ClusterMetadata {
+        ClusterMetadata {
+            cluster_name: "kafka-0:9092/0".to_string(),
+            brokers: vec![
+                BrokerInfo {
+                    id: 0,
+                    host: "kafka-0".to_string(),
+                    port: 9092,
+                },
+                BrokerInfo {
+                    id: 1,
+                    host: "kafka-1".to_string(),
+                    port: 9092,
+                },
+            ],
+            topics: vec![TopicMetadata {
+                name: "events".to_string(),
+                partition_count: 3,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_cluster_metadata_display() {
+        assert_eq!(
+            sample_metadata().to_string(),
+            "Cluster: kafka-0:9092/0\nBrokers: 2\nTopics: 1\n  Topic 'events': 3 partitions\n"
+        );
+
+        let empty = ClusterMetadata {
+            topics: vec![],
+            ..sample_metadata()
+        };
+        assert_eq!(
+            empty.to_string(),
+            "Cluster: kafka-0:9092/0\nBrokers: 2\nTopics: 0\n"
+        );
+    }
+
+    #[test]
+    fn test_cluster_metadata_topic_lookup() {
+        let metadata = sample_metadata();
+        assert_eq!(metadata.topic("events").map(|t| t.partition_count), Some(3));
+        assert!(metadata.topic("missing").is_none());
+    }
+
+    #[test]
+    fn test_group_lag_totals_partitions() {
+        let lag = GroupLag::new(
+            "billing",
+            "events",
+            vec![
+                PartitionLag::new(1, None, 2, 5),
+                PartitionLag::new(0, Some(7), 0, 10),
+                // 已提交 offset 超过 high watermark（如 topic 被截断）时不出现负 lag
+                PartitionLag::new(2, Some(12), 0, 10),
+            ],
+        );
+
+        let partitions: Vec<_> = lag
+            .partitions
+            .iter()
+            .map(|p| (p.partition, p.lag))
+            .collect();
+        assert_eq!(partitions, [(0, 3), (1, 3), (2, 0)]);
+        assert_eq!(lag.total_lag, 6);
+        assert_eq!(
+            lag.to_string(),
+            "Group: billing, Topic: events\n  Partition 0: committed=7 end=10 lag=3\n  Partition 1: committed=- end=5 lag=3\n  Partition 2: committed=12 end=10 lag=0\nTotal lag: 6\n"
+        );
+    }
+
+    /// 需要 TEST_KAFKA_BROKERS 中存在 topic `test-topic` 与消费者组 `test-group`
+    #[tokio::test]
+    #[ignore]
+    async fn test_list_groups_includes_known_group() {
+        let config = KafkaClientConfig::new(vec![TEST_KAFKA_BROKERS.to_string()], "test-client")
+            .with_sasl_plaintext(USERNAME, PASSWORD)
+            .with_group_id("test-lag-reader");
+        let consumer = KafkaConsumer::new(&config).expect("consumer");
+
+        let groups = consumer
+            .list_groups(Duration::from_secs(10))
+            .expect("list groups");
+        assert!(groups.iter().any(|group| group.name == "test-group"));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_group_lag_for_known_group() {
+        let config = KafkaClientConfig::new(vec![TEST_KAFKA_BROKERS.to_string()], "test-client")
+            .with_sasl_plaintext(USERNAME, PASSWORD)
+            .with_group_id("test-lag-reader");
+        let consumer = KafkaConsumer::new(&config).expect("consumer");
+
+        let lag = consumer
+            .group_lag("test-group", "test-topic", Duration::from_secs(10))
+            .expect("group lag");
+        assert!(!lag.partitions.is_empty());
+        assert_eq!(
+            lag.total_lag,
+            lag.partitions.iter().map(|p| p.lag).sum::<i64>()
+        );
+        for partition in &lag.partitions {
+            assert!(partition.high_watermark >= partition.low_watermark);
+        }
+    }
 }
@@ -23,9 +23,9 @@
 //! let rows_affected = client.execute_dml("INSERT INTO test VALUES (1, 'test')").await?;
 //! ```
 
-use mysql_async::{Opts, OptsBuilder, Params, Pool, prelude::*};
+use mysql_async::{Conn, Opts, OptsBuilder, Params, Pool, Row, Value, from_value_opt, prelude::*};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// MySQL 客户端配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +44,13 @@ pub struct MySqlClientConfig {
     pub timeout: Option<u64>,
     /// 是否启用 SSL/TLS
     pub ssl: bool,
+    /// 从连接池取出连接时先 PING，失效则丢弃并重取一次（默认：true）
+    #[serde(default = "default_validate_on_checkout")]
+    pub validate_on_checkout: bool,
+}
+
+fn default_validate_on_checkout() -> bool {
+    true
 }
 
 impl MySqlClientConfig {
@@ -72,6 +79,7 @@ impl MySqlClientConfig {
             database: None,
             timeout: Some(10),
             ssl: false,
+            validate_on_checkout: default_validate_on_checkout(),
         }
     }
 
@@ -105,6 +113,12 @@ impl MySqlClientConfig {
         self
     }
 
+    /// 设置取出连接时是否校验连接有效性
+    pub fn with_validate_on_checkout(mut self, validate: bool) -> Self {
+        self.validate_on_checkout = validate;
+        self
+    }
+
     /// 构建 MySQL 连接选项
     fn build_opts(&self) -> Opts {
         let mut builder = OptsBuilder::default()
@@ -169,23 +183,52 @@ impl MySqlClient {
         })
     }
 
-    /// 检查与 MySQL 的连接是否正常（PING）
-    pub async fn ping(&mut self) -> Result<(), String> {
+    /// 从连接池取出连接
+    ///
+    /// 连接长时间空闲后可能已被服务端断开（wait_timeout），开启
+    /// `validate_on_checkout` 时先 PING，失败则丢弃该连接并重取一次
+    async fn get_conn(&self) -> Result<Conn, String> {
         let mut conn = self
             .pool
             .get_conn()
             .await
             .map_err(|e| format!("Failed to get connection: {e}"))?;
-        conn.ping().await.map_err(|e| format!("Ping failed: {e}"))
-    }
+        if !self.config.validate_on_checkout || conn.ping().await.is_ok() {
+            return Ok(conn);
+        }
 
-    /// 获取 MySQL 服务器信息
-    pub async fn info(&mut self) -> Result<String, String> {
+        // 失效的连接直接断开，不放回连接池
+        let _ = conn.disconnect().await;
         let mut conn = self
             .pool
             .get_conn()
             .await
             .map_err(|e| format!("Failed to get connection: {e}"))?;
+        conn.ping()
+            .await
+            .map_err(|e| format!("Connection validation failed: {e}"))?;
+        Ok(conn)
+    }
+
+    /// 检查与 MySQL 的连接是否正常（PING）
+    pub async fn ping(&mut self) -> Result<(), String> {
+        let mut conn = self.get_conn().await?;
+        conn.ping().await.map_err(|e| format!("Ping failed: {e}"))
+    }
+
+    /// PING 并返回往返耗时
+    ///
+    /// 只统计 PING 本身的耗时，不包含从连接池获取连接的时间
+    pub async fn ping_timed(&mut self) -> Result<Duration, String> {
+        let mut conn = self.get_conn().await?;
+        let start = Instant::now();
+        conn.ping().await.map_err(|e| format!("Ping failed: {e}"))?;
+        Ok(start.elapsed())
+    }
+
+    /// 获取 MySQL 服务器信息
+    pub async fn info(&mut self) -> Result<String, String> {
+        let mut conn = self.get_conn().await?;
 
         let result: Vec<(String, String)> =
             conn.exec("SHOW VARIABLES LIKE 'version%'", ())
@@ -201,11 +244,7 @@ impl MySqlClient {
 
     /// 获取 MySQL 服务器版本
     pub async fn version(&mut self) -> Result<String, String> {
-        let mut conn = self
-            .pool
-            .get_conn()
-            .await
-            .map_err(|e| format!("Failed to get connection: {e}"))?;
+        let mut conn = self.get_conn().await?;
 
         let version: String = conn
             .exec_first("SELECT VERSION()", ())
@@ -220,11 +259,7 @@ impl MySqlClient {
     ///
     /// 适用于 CREATE, DROP, ALTER 等数据定义语言
     pub async fn execute_ddl(&mut self, query: &str) -> Result<(), String> {
-        let mut conn = self
-            .pool
-            .get_conn()
-            .await
-            .map_err(|e| format!("Failed to get connection: {e}"))?;
+        let mut conn = self.get_conn().await?;
 
         conn.exec_drop(query, ())
             .await
@@ -235,11 +270,7 @@ impl MySqlClient {
     ///
     /// 适用于 INSERT, UPDATE, DELETE 等数据操作语言
     pub async fn execute_dml(&mut self, query: &str) -> Result<u64, String> {
-        let mut conn = self
-            .pool
-            .get_conn()
-            .await
-            .map_err(|e| format!("Failed to get connection: {e}"))?;
+        let mut conn = self.get_conn().await?;
 
         let result = conn
             .exec_iter(query, ())
@@ -254,11 +285,7 @@ impl MySqlClient {
     where
         T: FromRow + Send + 'static,
     {
-        let mut conn = self
-            .pool
-            .get_conn()
-            .await
-            .map_err(|e| format!("Failed to get connection: {e}"))?;
+        let mut conn = self.get_conn().await?;
 
         let result = conn
             .exec(query, ())
@@ -278,11 +305,7 @@ impl MySqlClient {
         T: FromRow + Send + 'static,
         P: Into<Params> + Send,
     {
-        let mut conn = self
-            .pool
-            .get_conn()
-            .await
-            .map_err(|e| format!("Failed to get connection: {e}"))?;
+        let mut conn = self.get_conn().await?;
 
         let result = conn
             .exec(query, params)
@@ -292,6 +315,41 @@ impl MySqlClient {
         Ok(result)
     }
 
+    /// 查询单个值（COUNT、MAX 等），返回第一行第一列
+    ///
+    /// 无结果行或值为 NULL 时返回 None
+    pub async fn query_scalar<T>(&self, query: &str) -> Result<Option<T>, String>
+    where
+        T: FromValue,
+    {
+        self.query_scalar_with_params(query, ()).await
+    }
+
+    /// 参数化查询单个值
+    pub async fn query_scalar_with_params<T, P>(
+        &self,
+        query: &str,
+        params: P,
+    ) -> Result<Option<T>, String>
+    where
+        T: FromValue,
+        P: Into<Params> + Send,
+    {
+        let mut conn = self.get_conn().await?;
+
+        let row: Option<Row> = conn
+            .exec_first(query, params)
+            .await
+            .map_err(|e| format!("Failed to execute query: {e}"))?;
+
+        match row.and_then(|mut row| row.take::<Value, _>(0)) {
+            None | Some(Value::NULL) => Ok(None),
+            Some(value) => from_value_opt(value)
+                .map(Some)
+                .map_err(|e| format!("Failed to convert scalar value: {e}")),
+        }
+    }
+
     /// 插入数据并返回插入的行数
     pub async fn insert(&mut self, query: &str) -> Result<u64, String> {
         self.execute_dml(query).await
@@ -307,13 +365,59 @@ impl MySqlClient {
         self.execute_dml(query).await
     }
 
+    /// 列出当前数据库中的表
+    pub async fn list_tables(&self) -> Result<Vec<String>, String> {
+        self.require_database()?;
+        let mut conn = self.get_conn().await?;
+
+        conn.query(
+            "SELECT TABLE_NAME FROM information_schema.TABLES \
+             WHERE TABLE_SCHEMA = DATABASE() ORDER BY TABLE_NAME",
+        )
+        .await
+        .map_err(|e| format!("Failed to list tables: {e}"))
+    }
+
+    /// 查询当前数据库中表的列定义，按列顺序返回
+    pub async fn describe_table(&self, table: &str) -> Result<Vec<ColumnInfo>, String> {
+        self.require_database()?;
+        validate_identifier(table)?;
+        let mut conn = self.get_conn().await?;
+
+        let rows: Vec<(String, String, String, String)> = conn
+            .exec(
+                "SELECT COLUMN_NAME, COLUMN_TYPE, IS_NULLABLE, COLUMN_KEY FROM information_schema.COLUMNS \
+                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+                (table,),
+            )
+            .await
+            .map_err(|e| format!("Failed to describe table: {e}"))?;
+
+        if rows.is_empty() {
+            return Err(format!("Table '{table}' not found"));
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|(name, column_type, nullable, key)| ColumnInfo {
+                name,
+                column_type,
+                nullable: nullable.eq_ignore_ascii_case("YES"),
+                key: (!key.is_empty()).then_some(key),
+            })
+            .collect())
+    }
+
+    fn require_database(&self) -> Result<(), String> {
+        match self.config.database {
+            Some(_) => Ok(()),
+            None => Err("No database selected".to_string()),
+        }
+    }
+
     /// 验证连接池中的连接是否有效
     pub async fn validate_connection(&self) -> Result<(), String> {
-        let mut conn = self
-            .pool
-            .get_conn()
-            .await
-            .map_err(|e| format!("Failed to get connection: {e}"))?;
+        let mut conn = self.get_conn().await?;
         conn.ping()
             .await
             .map_err(|e| format!("Connection validation failed: {e}"))
@@ -330,6 +434,33 @@ impl MySqlClient {
     }
 }
 
+/// 校验表名等标识符，只允许字母、数字、`_` 和 `$`，长度不超过 64
+fn validate_identifier(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid table name: {name:?}"))
+    }
+}
+
+/// 表的列信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// 列类型，如 `varchar(255)`
+    #[serde(rename = "type")]
+    pub column_type: String,
+    pub nullable: bool,
+    /// 索引类型：PRI、UNI、MUL，无索引时为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
 /// MySQL 连接测试结果
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MySqlPingResult {
@@ -339,6 +470,9 @@ pub struct MySqlPingResult {
     pub database: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    /// PING 往返耗时（毫秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
@@ -377,6 +511,23 @@ mod tests {
         assert!(config.ssl);
     }
 
+    #[test]
+    fn test_validate_on_checkout_defaults_to_true() {
+        assert!(MySqlClientConfig::new("localhost").validate_on_checkout);
+        assert!(
+            !MySqlClientConfig::new("localhost")
+                .with_validate_on_checkout(false)
+                .validate_on_checkout
+        );
+
+        // 旧配置中没有该字段时同样默认开启
+        let config: MySqlClientConfig = serde_json::from_str(
+            r#"{"host":"localhost","port":3306,"username":null,"password":null,"database":null,"timeout":10,"ssl":false}"#,
+        )
+        .unwrap();
+        assert!(config.validate_on_checkout);
+    }
+
     #[test]
     fn test_build_opts() {
         let config = MySqlClientConfig::new("localhost:3306")
@@ -388,4 +539,108 @@ mod tests {
         // We can't easily test the internal opts, but this ensures it doesn't panic
         assert_eq!(opts.ip_or_hostname(), "localhost");
     }
+
+    #[tokio::test]
+    #[ignore] // 需要真实 MySQL 环境
+    async fn test_ping_timed_measures_round_trip() {
+        let config = MySqlClientConfig::new("localhost:3306")
+            .with_username("root")
+            .with_password("root");
+        let mut client = MySqlClient::new(&config).await.expect("connect failed");
+
+        let latency = client.ping_timed().await.expect("ping failed");
+        assert!(latency > Duration::ZERO);
+        // 本地服务的往返耗时应远小于连接超时
+        assert!(latency < Duration::from_secs(5), "{latency:?}");
+        // 连接复用后再次 PING 仍能计时
+        assert!(client.ping_timed().await.is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要真实 MySQL 环境
+    async fn test_query_scalar() {
+        let config = MySqlClientConfig::new("localhost:3306")
+            .with_username("root")
+            .with_password("root");
+        let client = MySqlClient::new(&config).await.expect("connect failed");
+
+        let count: Option<u64> = client
+            .query_scalar("SELECT COUNT(*) FROM (SELECT 1 UNION ALL SELECT 2 UNION ALL SELECT 3) t")
+            .await
+            .unwrap();
+        assert_eq!(count, Some(3));
+
+        let count: Option<u64> = client
+            .query_scalar_with_params(
+                "SELECT COUNT(*) FROM (SELECT 1 AS n UNION ALL SELECT 2 UNION ALL SELECT 3) t WHERE n > ?",
+                (1,),
+            )
+            .await
+            .unwrap();
+        assert_eq!(count, Some(2));
+
+        let empty: Option<u64> = client
+            .query_scalar("SELECT 1 FROM DUAL WHERE 1 = 0")
+            .await
+            .unwrap();
+        assert_eq!(empty, None);
+    }
+
+    #[test]
+    fn test_validate_identifier() {
+        assert!(validate_identifier("prompt_templates").is_ok());
+        assert!(validate_identifier("t$1").is_ok());
+        assert!(validate_identifier("").is_err());
+        assert!(validate_identifier("users; DROP TABLE users").is_err());
+        assert!(validate_identifier("a`b").is_err());
+        assert!(validate_identifier(&"a".repeat(65)).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要真实 MySQL 环境
+    async fn test_list_and_describe_tables() {
+        let config = MySqlClientConfig::new("localhost:3306")
+            .with_username("root")
+            .with_password("root")
+            .with_database("mysql");
+        let client = MySqlClient::new(&config).await.expect("connect failed");
+
+        let tables = client.list_tables().await.unwrap();
+        assert!(tables.iter().any(|t| t == "user"));
+
+        let columns = client.describe_table("user").await.unwrap();
+        let host = columns.iter().find(|c| c.name == "Host").unwrap();
+        assert_eq!(host.key.as_deref(), Some("PRI"));
+        assert!(!host.nullable);
+
+        let err = client.describe_table("no_such_table").await.unwrap_err();
+        assert!(err.contains("not found"));
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要真实 MySQL 环境
+    async fn test_stale_connection_is_replaced_on_checkout() {
+        let config = MySqlClientConfig::new("localhost:3306")
+            .with_username("root")
+            .with_password("root");
+        let client = MySqlClient::new(&config).await.expect("connect failed");
+
+        // 让池中的空闲连接在 1 秒后被服务端断开
+        client
+            .query_scalar::<u64>("SELECT 1")
+            .await
+            .expect("warm up failed");
+        let mut conn = client.pool.get_conn().await.unwrap();
+        conn.query_drop("SET SESSION wait_timeout = 1")
+            .await
+            .unwrap();
+        drop(conn);
+        tokio::time::sleep(Duration::from_secs(3)).await;
+
+        let value: Option<u64> = client
+            .query_scalar("SELECT 1")
+            .await
+            .expect("stale connection was not replaced");
+        assert_eq!(value, Some(1));
+    }
 }
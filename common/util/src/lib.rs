@@ -3,4 +3,7 @@ pub mod db;
 pub mod log;
 pub mod metrics;
 pub mod net;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod task;
 pub use metrics::{counter, gauge, histogram};
@@ -23,6 +23,22 @@ impl ImageMetrics {
         counter!("image_cleanup_freed_bytes").increment(freed_bytes);
     }
 
+    /// 记录上传时的格式转换
+    pub fn record_conversion(target_format: &str) {
+        let labels = [("format", target_format.to_string())];
+        counter!("image_convert_total", &labels).increment(1);
+    }
+
+    /// 记录上传时去除元数据的图片数
+    pub fn record_metadata_stripped() {
+        counter!("image_metadata_stripped_total").increment(1);
+    }
+
+    /// 记录超出容量上限时淘汰的文件数
+    pub fn record_eviction(evicted_files: u64) {
+        counter!("image_evicted_total").increment(evicted_files);
+    }
+
     /// 记录存储空间使用情况
     pub fn record_storage_usage(bytes: u64) {
         gauge!("image_storage_usage_bytes").set(bytes as f64);
@@ -1,4 +1,4 @@
-/// HttpRequest 使用示例和测试
+// HttpRequest 使用示例和测试
 
 #[cfg(test)]
 mod tests {
@@ -11,7 +11,7 @@ mod tests {
         assert_eq!(request.url, "https://example.com");
         assert_eq!(request.method, HttpMethod::GET);
         assert_eq!(request.timeout, Some(30));
-        assert_eq!(request.follow_redirects, true);
+        assert!(request.follow_redirects);
     }
 
     #[test]
@@ -120,9 +120,9 @@ mod tests {
 
         assert_eq!(request.method, HttpMethod::POST);
         assert_eq!(request.timeout, Some(15));
-        assert_eq!(request.follow_redirects, false);
+        assert!(!request.follow_redirects);
         assert_eq!(request.max_redirects, 5);
-        assert_eq!(request.verify_ssl, true);
+        assert!(request.verify_ssl);
     }
 
     #[test]
@@ -1,4 +1,4 @@
-/// DialTcpRequest 使用示例
+// DialTcpRequest 使用示例
 
 #[cfg(test)]
 mod tests {
@@ -83,6 +83,6 @@ mod tests {
         assert_eq!(request.timeout, Some(10));
         assert_eq!(request.read_timeout, Some(15));
         assert_eq!(request.write_timeout, Some(15));
-        assert_eq!(request.nodelay, true);
+        assert!(request.nodelay);
     }
 }
@@ -0,0 +1,339 @@
+//! axum 服务共用的中间件栈
+//!
+//! 各 HTTP 服务通过 [`default_layers`] 统一请求 ID、访问日志、CORS、超时和并发限制，
+//! 业务相关的中间件（如指标采集）由各服务在此之外自行叠加。
+
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, header};
+use std::time::Duration;
+use tower::ServiceBuilder;
+use tower::layer::util::{Identity, Stack};
+use tower::limit::GlobalConcurrencyLimitLayer;
+use tower_http::classify::{ServerErrorsAsFailures, SharedClassifier};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::timeout::TimeoutLayer;
+use tower_http::trace::TraceLayer;
+
+/// 请求 ID 头：客户端未携带时生成 UUID，并原样写回响应
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// 通用中间件配置
+#[derive(Debug, Clone)]
+pub struct HttpMiddlewareConfig {
+    /// 处理超时（到返回响应头为止），超时返回 408
+    pub request_timeout: Duration,
+    /// 同时处理的最大请求数，超出的请求排队等待
+    pub concurrency_limit: usize,
+    /// 是否启用 CORS，关闭时不返回任何跨域响应头
+    pub cors_enabled: bool,
+    /// 允许跨域的来源，为空或包含 `*` 时允许任意来源
+    pub allowed_origins: Vec<String>,
+    /// 允许的请求方法，为空或包含 `*` 时允许任意方法
+    pub allowed_methods: Vec<String>,
+    /// 允许的请求头，为空或包含 `*` 时允许任意请求头
+    pub allowed_headers: Vec<String>,
+}
+
+impl Default for HttpMiddlewareConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(300),
+            concurrency_limit: 1024,
+            cors_enabled: true,
+            allowed_origins: Vec::new(),
+            allowed_methods: Vec::new(),
+            allowed_headers: Vec::new(),
+        }
+    }
+}
+
+impl HttpMiddlewareConfig {
+    /// 设置请求处理超时
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// 设置最大并发请求数
+    pub fn with_concurrency_limit(mut self, limit: usize) -> Self {
+        self.concurrency_limit = limit;
+        self
+    }
+
+    /// 设置是否启用 CORS
+    pub fn with_cors_enabled(mut self, enabled: bool) -> Self {
+        self.cors_enabled = enabled;
+        self
+    }
+
+    /// 设置允许跨域的来源
+    pub fn with_allowed_origins(mut self, origins: Vec<String>) -> Self {
+        self.allowed_origins = origins;
+        self
+    }
+
+    /// 设置允许的请求方法
+    pub fn with_allowed_methods(mut self, methods: Vec<String>) -> Self {
+        self.allowed_methods = methods;
+        self
+    }
+
+    /// 设置允许的请求头
+    pub fn with_allowed_headers(mut self, headers: Vec<String>) -> Self {
+        self.allowed_headers = headers;
+        self
+    }
+
+    /// 是否允许任意来源（未配置来源列表或显式配置了 `*`）
+    pub fn allows_any_origin(&self) -> bool {
+        is_wildcard(&self.allowed_origins)
+    }
+
+    fn cors_layer(&self) -> CorsLayer {
+        if !self.cors_enabled {
+            return CorsLayer::new();
+        }
+
+        let origin = if self.allows_any_origin() {
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::list(
+                self.allowed_origins
+                    .iter()
+                    .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+            )
+        };
+        let methods = if is_wildcard(&self.allowed_methods) {
+            AllowMethods::any()
+        } else {
+            AllowMethods::list(
+                self.allowed_methods
+                    .iter()
+                    .filter_map(|method| Method::from_bytes(method.as_bytes()).ok()),
+            )
+        };
+        let headers = if is_wildcard(&self.allowed_headers) {
+            AllowHeaders::any()
+        } else {
+            AllowHeaders::list(
+                self.allowed_headers
+                    .iter()
+                    .filter_map(|header| HeaderName::from_bytes(header.as_bytes()).ok()),
+            )
+        };
+        CorsLayer::new()
+            .allow_origin(origin)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)])
+    }
+}
+
+fn is_wildcard(values: &[String]) -> bool {
+    values.is_empty() || values.iter().any(|value| value == "*")
+}
+
+/// [`default_layers`] 返回的中间件栈类型，由外到内依次为：
+/// 请求 ID 生成、访问日志、请求 ID 回写、CORS、超时、并发限制
+pub type DefaultLayers = Stack<
+    GlobalConcurrencyLimitLayer,
+    Stack<
+        TimeoutLayer,
+        Stack<
+            CorsLayer,
+            Stack<
+                PropagateRequestIdLayer,
+                Stack<
+                    TraceLayer<SharedClassifier<ServerErrorsAsFailures>>,
+                    Stack<SetRequestIdLayer<MakeRequestUuid>, Identity>,
+                >,
+            >,
+        >,
+    >,
+>;
+
+/// 构建通用中间件栈，用于 `Router::layer`
+///
+/// 并发限制在所有路由间共享，而不是每个路由单独计数。
+pub fn default_layers(config: &HttpMiddlewareConfig) -> ServiceBuilder<DefaultLayers> {
+    let request_id = HeaderName::from_static(REQUEST_ID_HEADER);
+    ServiceBuilder::new()
+        .layer(SetRequestIdLayer::new(request_id.clone(), MakeRequestUuid))
+        .layer(TraceLayer::new_for_http())
+        .layer(PropagateRequestIdLayer::new(request_id))
+        .layer(config.cors_layer())
+        .layer(TimeoutLayer::new(config.request_timeout))
+        .layer(GlobalConcurrencyLimitLayer::new(config.concurrency_limit))
+}
+
+/// 校验请求是否携带 `Authorization: Bearer <expected>`
+///
+/// 按常量时间比较令牌内容，避免通过响应耗时逐字节猜出令牌。
+pub fn bearer_token_matches(headers: &HeaderMap, expected: &str) -> bool {
+    let Some(provided) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode, header},
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    fn router(config: &HttpMiddlewareConfig) -> Router {
+        Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    "slow"
+                }),
+            )
+            .layer(default_layers(config))
+    }
+
+    #[tokio::test]
+    async fn test_default_layers_set_request_id_and_cors() {
+        let app = router(&HttpMiddlewareConfig::default());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(header::ORIGIN, "http://example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(REQUEST_ID_HEADER));
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+
+        // 客户端携带的请求 ID 原样返回
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(REQUEST_ID_HEADER, "req-42")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-42");
+    }
+
+    #[tokio::test]
+    async fn test_cors_allow_list_only_echoes_allowed_origins() {
+        let config = HttpMiddlewareConfig::default()
+            .with_allowed_origins(vec!["https://app.example.com".to_string()])
+            .with_allowed_methods(vec!["GET".to_string(), "POST".to_string()]);
+        let app = router(&config);
+        let request = |origin: &str| {
+            Request::builder()
+                .uri("/")
+                .header(header::ORIGIN, origin)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let allowed = app
+            .clone()
+            .oneshot(request("https://app.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(
+            allowed.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.example.com"
+        );
+
+        let denied = app
+            .oneshot(request("https://evil.example.com"))
+            .await
+            .unwrap();
+        assert!(
+            !denied
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cors_explicit_wildcard_and_disabled() {
+        let config = HttpMiddlewareConfig::default().with_allowed_origins(vec!["*".to_string()]);
+        assert!(config.allows_any_origin());
+        let response = router(&config)
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(header::ORIGIN, "http://example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+
+        let config = HttpMiddlewareConfig::default().with_cors_enabled(false);
+        let response = router(&config)
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(header::ORIGIN, "http://example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(
+            !response
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        );
+    }
+
+    #[test]
+    fn test_bearer_token_matches() {
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::AUTHORIZATION, value.parse().unwrap());
+            headers
+        };
+        assert!(bearer_token_matches(&headers("Bearer secret"), "secret"));
+        assert!(!bearer_token_matches(&headers("Bearer secreT"), "secret"));
+        assert!(!bearer_token_matches(&headers("Bearer secret2"), "secret"));
+        assert!(!bearer_token_matches(&headers("secret"), "secret"));
+        assert!(!bearer_token_matches(&HeaderMap::new(), "secret"));
+    }
+
+    #[tokio::test]
+    async fn test_default_layers_time_out_slow_requests() {
+        let config =
+            HttpMiddlewareConfig::default().with_request_timeout(Duration::from_millis(50));
+
+        let response = router(&config)
+            .oneshot(Request::builder().uri("/slow").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+}
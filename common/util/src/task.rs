@@ -0,0 +1,178 @@
+//! 周期性后台任务
+//!
+//! 统一清理类定时任务的调度方式：启动后立即执行一次，之后按间隔（可叠加随机抖动）执行，
+//! 通过 `PeriodicHandle` 在优雅退出时停止，可选择在停止前再执行最后一次。
+
+use rand::Rng;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// 周期任务配置
+#[derive(Debug, Clone)]
+pub struct PeriodicTask {
+    /// 执行间隔
+    interval: Duration,
+    /// 每次间隔额外叠加的最大随机抖动，避免多实例同时执行
+    jitter: Duration,
+    /// 收到停止通知后是否再执行最后一次
+    final_run: bool,
+}
+
+impl PeriodicTask {
+    /// 创建周期任务配置，间隔为 0 时按 1 毫秒处理
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval: interval.max(Duration::from_millis(1)),
+            jitter: Duration::ZERO,
+            final_run: false,
+        }
+    }
+
+    /// 设置最大随机抖动
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// 停止时再执行最后一次，适用于退出前需要收尾的清理任务
+    pub fn with_final_run(mut self, final_run: bool) -> Self {
+        self.final_run = final_run;
+        self
+    }
+
+    /// 下一次执行前的等待时间
+    fn next_delay(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.interval;
+        }
+        let jitter_ms = self.jitter.as_millis() as u64;
+        self.interval + Duration::from_millis(rand::thread_rng().gen_range(0..=jitter_ms))
+    }
+
+    /// 在后台启动任务
+    pub fn spawn<F, Fut>(self, mut task: F) -> PeriodicHandle
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let cancel = Arc::new(Notify::new());
+        let cancelled = cancel.clone();
+
+        let handle = tokio::spawn(async move {
+            loop {
+                // 正在执行的一轮不会被打断，取消在本轮结束后生效
+                task().await;
+                tokio::select! {
+                    _ = tokio::time::sleep(self.next_delay()) => {}
+                    _ = cancelled.notified() => break,
+                }
+            }
+            if self.final_run {
+                task().await;
+            }
+        });
+
+        PeriodicHandle { cancel, handle }
+    }
+}
+
+/// 按固定间隔在后台执行任务，启动后立即执行第一次
+pub fn spawn_periodic<F, Fut>(interval: Duration, task: F) -> PeriodicHandle
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    PeriodicTask::new(interval).spawn(task)
+}
+
+/// 周期任务句柄
+///
+/// 丢弃句柄不会停止任务，需要显式调用 `cancel` 或 `stop`。
+#[derive(Debug)]
+pub struct PeriodicHandle {
+    cancel: Arc<Notify>,
+    handle: JoinHandle<()>,
+}
+
+impl PeriodicHandle {
+    /// 通知任务停止，不等待其退出
+    pub fn cancel(&self) {
+        self.cancel.notify_one();
+    }
+
+    /// 停止任务并等待正在执行的一轮（以及启用时的最后一次）结束
+    pub async fn stop(self) {
+        self.cancel();
+        let _ = self.handle.await;
+    }
+
+    /// 任务是否已退出
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_spawn_periodic_fires_until_stopped() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let handle = spawn_periodic(Duration::from_millis(40), move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        // 立即执行一次，之后约每 40ms 一次
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let fired = runs.load(Ordering::SeqCst);
+        assert!((3..=5).contains(&fired), "fired {fired} times");
+
+        handle.stop().await;
+        let stopped_at = runs.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), stopped_at);
+    }
+
+    #[tokio::test]
+    async fn test_final_run_fires_once_on_stop() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let handle = PeriodicTask::new(Duration::from_secs(3600))
+            .with_final_run(true)
+            .spawn(move || {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        handle.stop().await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_next_delay_stays_within_jitter() {
+        let task =
+            PeriodicTask::new(Duration::from_millis(100)).with_jitter(Duration::from_millis(20));
+        for _ in 0..100 {
+            let delay = task.next_delay();
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(120));
+        }
+        assert_eq!(
+            PeriodicTask::new(Duration::ZERO).next_delay(),
+            Duration::from_millis(1)
+        );
+    }
+}
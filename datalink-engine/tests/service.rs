@@ -8,6 +8,7 @@ use datalink_engine::{
 };
 use serde_json::json;
 
+#[allow(clippy::too_many_arguments)]
 fn spec(
     name: &str,
     domain: &str,
@@ -48,7 +48,7 @@ impl NodeRepository for MemoryNodeRepository {
     async fn list(&self, pagination: PaginationParams) -> Result<PaginatedResult<Node>> {
         let nodes = self.nodes.lock().await;
         let mut items: Vec<_> = nodes.values().cloned().collect();
-        items.sort_by(|left, right| right.created_at.cmp(&left.created_at));
+        items.sort_by_key(|node| std::cmp::Reverse(node.created_at));
 
         let total = items.len() as u64;
         let start = pagination.offset();
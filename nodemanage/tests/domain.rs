@@ -182,7 +182,7 @@ fn one_node_can_have_multiple_agent_bindings() {
     assert_eq!(binding_a.node_id, binding_b.node_id);
     assert_ne!(binding_a.agent_id, binding_b.agent_id);
 
-    let bindings = vec![binding_a, binding_b];
+    let bindings = [binding_a, binding_b];
     assert_eq!(bindings.len(), 2);
 
     let bound_count = bindings
@@ -0,0 +1,55 @@
+//! 远程 OCR 凭证
+//!
+//! 远程服务的 token/uuid/cookie 通常靠抓包获取且会过期。通过 [`CredentialProvider`]
+//! 获取凭证后，调用方可以实现登录流程，在凭证失效时返回新的凭证。
+
+use crate::error::Result;
+use config::ocr::RemoteOcrConfig;
+
+/// 远程 OCR 鉴权信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// 授权 token（x-auth-token）
+    pub auth_token: String,
+    /// 授权 uuid（x-auth-uuid）
+    pub auth_uuid: String,
+    /// 会话 cookie
+    pub auth_cookie: String,
+}
+
+/// 凭证提供者
+///
+/// 每次识别开始时调用一次 `fetch`；远程服务返回 401/403 或拒绝发放 token 时
+/// 会再调用一次并用新凭证重试，实现方可以在此时重新登录。
+pub trait CredentialProvider: Send + Sync {
+    /// 获取当前可用的凭证
+    fn fetch(&self) -> Result<Credentials>;
+}
+
+/// 使用配置文件中固定凭证的提供者
+#[derive(Debug, Clone)]
+pub struct StaticProvider {
+    credentials: Credentials,
+}
+
+impl StaticProvider {
+    /// 使用给定凭证创建
+    pub fn new(credentials: Credentials) -> Self {
+        Self { credentials }
+    }
+
+    /// 从远程 OCR 配置中读取凭证
+    pub fn from_config(config: &RemoteOcrConfig) -> Self {
+        Self::new(Credentials {
+            auth_token: config.auth_token.clone(),
+            auth_uuid: config.auth_uuid.clone(),
+            auth_cookie: config.auth_cookie.clone(),
+        })
+    }
+}
+
+impl CredentialProvider for StaticProvider {
+    fn fetch(&self) -> Result<Credentials> {
+        Ok(self.credentials.clone())
+    }
+}
@@ -3,10 +3,10 @@
 //! 包含不同的 OCR 识别引擎实现
 
 pub mod remote;
+pub mod tesseract;
 
 // 未来可以添加其他引擎，例如:
 // pub mod paddleocr;
 // pub mod easyocr;
-// pub mod tesseract;
 // pub mod azure_vision;
 // pub mod google_vision;
@@ -2,17 +2,25 @@
 //!
 //! 实现通过 HTTP 调用 web.xxxxapp.com 的 OCR 服务
 
-use crate::error::ImageRecognitionError;
-use crate::utils::{RemoteImagePayload, load_and_validate_remote_image};
+use crate::credentials::{CredentialProvider, Credentials, StaticProvider};
+use crate::error::{ImageRecognitionError, Result};
+use crate::rate_limit::shared_limiter;
+use crate::utils::{
+    RemoteImagePayload, auto_orient, is_heif_format, load_and_validate_remote_image,
+    load_and_validate_remote_image_bytes, sha1_hex,
+};
 use base64::Engine as _;
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use config::ocr::RemoteOcrConfig;
 use reqwest::blocking::{Client, RequestBuilder};
 use reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
 use serde_json::{Value, json};
-use sha1::{Digest, Sha1};
 use std::path::Path;
-use std::thread::sleep;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, sleep};
+use std::time::{Duration, Instant};
+use tracing::warn;
 
 const ACCEPT_HEADER_VALUE: &str = "application/json, text/plain, */*";
 const CONTENT_TYPE_JSON: &str = "application/json;charset=UTF-8";
@@ -30,40 +38,457 @@ pub fn recognize(
     image_path: &str,
     config: &RemoteOcrConfig,
     include_position: bool,
-) -> Result<String, ImageRecognitionError> {
+) -> Result<String> {
+    recognize_with_progress(image_path, config, include_position, |_| {})
+}
+
+/// 单次轮询的进度信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PollProgress {
+    /// 当前轮询次数（从 1 开始）
+    pub attempt: u32,
+    /// 自开始轮询以来经过的时间
+    pub elapsed: Duration,
+    /// 最近一次查询到的任务状态
+    pub status: String,
+}
+
+/// 带审计信息的识别结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecognitionReport {
+    /// 远程服务分配的任务 ID，可用于与服务端日志对照
+    pub job_id: String,
+    /// 状态查询次数
+    pub attempts: u32,
+    /// 从开始识别到拿到结果的总耗时
+    pub elapsed: Duration,
+    /// 各阶段耗时
+    pub timings: RecognitionTimings,
+    /// 识别结果，格式与 [`recognize`] 相同
+    pub text: String,
+}
+
+/// 远程识别各阶段耗时
+///
+/// 凭证被拒绝而重试时只统计最后一次尝试，总耗时中另含图片预处理与结果解析的时间。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecognitionTimings {
+    /// 获取 token 耗时
+    pub perm_token: Duration,
+    /// 上传图片并启动任务耗时
+    pub start_job: Duration,
+    /// 轮询任务状态直到完成的耗时（含轮询间隔）
+    pub poll: Duration,
+}
+
+impl RecognitionTimings {
+    /// 各阶段耗时之和
+    pub fn total(&self) -> Duration {
+        self.perm_token + self.start_job + self.poll
+    }
+
+    /// 启用 `metrics` feature 时记录到直方图
+    fn record(&self, elapsed: Duration) {
+        #[cfg(feature = "metrics")]
+        {
+            use metrics::histogram;
+            let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+            histogram!("ocr_remote_perm_token_duration_ms").record(ms(self.perm_token));
+            histogram!("ocr_remote_start_job_duration_ms").record(ms(self.start_job));
+            histogram!("ocr_remote_poll_duration_ms").record(ms(self.poll));
+            histogram!("ocr_remote_total_duration_ms").record(ms(elapsed));
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = elapsed;
+    }
+}
+
+/// 调用远程 OCR 服务并返回任务 ID、轮询次数与耗时
+pub fn recognize_reported(
+    image_path: &str,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+) -> Result<RecognitionReport> {
+    let payload = load_and_validate_remote_image(image_path)?;
+    run_job_reported(
+        &payload,
+        image_name(image_path),
+        config,
+        include_position,
+        &mut |_| {},
+        &AtomicBool::new(false),
+        &StaticProvider::from_config(config),
+    )
+}
+
+/// 调用远程 OCR 服务并在每次轮询后回调进度，便于界面展示识别进度
+pub fn recognize_with_progress(
+    image_path: &str,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+    mut on_progress: impl FnMut(PollProgress),
+) -> Result<String> {
+    let payload = load_and_validate_remote_image(image_path)?;
+    run_job(
+        &payload,
+        image_name(image_path),
+        config,
+        include_position,
+        &mut on_progress,
+        &AtomicBool::new(false),
+        &StaticProvider::from_config(config),
+    )
+}
+
+/// 通过凭证提供者获取鉴权信息进行识别
+///
+/// 远程服务拒绝凭证时会再次调用 `provider.fetch()` 并重试一次，
+/// 便于实现自动重新登录。
+pub fn recognize_with_credentials(
+    image_path: &str,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+    provider: &dyn CredentialProvider,
+) -> Result<String> {
     let payload = load_and_validate_remote_image(image_path)?;
+    run_job(
+        &payload,
+        image_name(image_path),
+        config,
+        include_position,
+        &mut |_| {},
+        &AtomicBool::new(false),
+        provider,
+    )
+}
+
+/// 可取消的识别：`cancel` 被置为 true 后不再发起新的请求，立即返回
+/// [`ImageRecognitionError::Cancelled`]
+pub fn recognize_cancellable(
+    image_path: &str,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+    cancel: &AtomicBool,
+) -> Result<String> {
+    let payload = load_and_validate_remote_image(image_path)?;
+    run_job(
+        &payload,
+        image_name(image_path),
+        config,
+        include_position,
+        &mut |_| {},
+        cancel,
+        &StaticProvider::from_config(config),
+    )
+}
+
+/// 识别内存中的图片数据，`file_name` 用于推断格式并作为任务名上报
+pub fn recognize_bytes(
+    bytes: Vec<u8>,
+    file_name: &str,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+) -> Result<String> {
+    let payload = load_and_validate_remote_image_bytes(bytes, file_name)?;
+    recognize_payload(&payload, image_name(file_name), config, include_position)
+}
+
+/// 批量识别中的单张图片
+#[derive(Debug, Clone)]
+pub struct BatchImage {
+    /// 文件名（用于推断格式）
+    pub file_name: String,
+    /// 图片数据
+    pub bytes: Vec<u8>,
+}
+
+/// 以有限并发批量识别图片，结果顺序与输入一致
+///
+/// 单张图片失败不会影响其他图片，`max_concurrency` 为 0 时按 1 处理。
+pub fn recognize_batch(
+    images: Vec<BatchImage>,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+    max_concurrency: usize,
+) -> Vec<Result<String>> {
+    let total = images.len();
+    let workers = max_concurrency.clamp(1, total.max(1));
+    let queue = Mutex::new(images.into_iter().enumerate());
+    let results: Mutex<Vec<Option<Result<String>>>> =
+        Mutex::new((0..total).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let next = queue.lock().expect("batch queue poisoned").next();
+                    let Some((index, image)) = next else {
+                        break;
+                    };
+                    let result =
+                        recognize_bytes(image.bytes, &image.file_name, config, include_position);
+                    results.lock().expect("batch results poisoned")[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .expect("batch results poisoned")
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| {
+                Err(ImageRecognitionError::EngineError(
+                    "批量识别任务未执行".to_string(),
+                ))
+            })
+        })
+        .collect()
+}
+
+/// 识别已通过校验的图片负载
+pub fn recognize_payload(
+    payload: &RemoteImagePayload,
+    image_name: &str,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+) -> Result<String> {
+    recognize_payload_cancellable(
+        payload,
+        image_name,
+        config,
+        include_position,
+        &AtomicBool::new(false),
+    )
+}
+
+/// 可取消地识别已通过校验的图片负载
+pub fn recognize_payload_cancellable(
+    payload: &RemoteImagePayload,
+    image_name: &str,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+    cancel: &AtomicBool,
+) -> Result<String> {
+    run_job(
+        payload,
+        image_name,
+        config,
+        include_position,
+        &mut |_| {},
+        cancel,
+        &StaticProvider::from_config(config),
+    )
+}
+
+fn run_job(
+    payload: &RemoteImagePayload,
+    image_name: &str,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+    on_progress: &mut dyn FnMut(PollProgress),
+    cancel: &AtomicBool,
+    provider: &dyn CredentialProvider,
+) -> Result<String> {
+    run_job_reported(
+        payload,
+        image_name,
+        config,
+        include_position,
+        on_progress,
+        cancel,
+        provider,
+    )
+    .map(|report| report.text)
+}
+
+fn run_job_reported(
+    payload: &RemoteImagePayload,
+    image_name: &str,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+    on_progress: &mut dyn FnMut(PollProgress),
+    cancel: &AtomicBool,
+    provider: &dyn CredentialProvider,
+) -> Result<RecognitionReport> {
+    let started = Instant::now();
+    check_heic_accepted(payload, config)?;
+    let oriented = if config.auto_orient {
+        auto_orient(payload)?
+    } else {
+        None
+    };
+    let payload = oriented.as_ref().unwrap_or(payload);
     let client = build_http_client(config)?;
 
-    let perm_token = request_perm_token(&client, config)?;
-    let job_id = start_job(&client, config, &payload, image_path, &perm_token)?;
-    let final_snapshot = poll_for_completion(&client, config, &job_id)?;
+    let credentials = provider.fetch()?;
+    let attempt = |credentials: &Credentials, on_progress: &mut dyn FnMut(PollProgress)| {
+        run_attempt(
+            &client,
+            config,
+            credentials,
+            payload,
+            image_name,
+            on_progress,
+            cancel,
+        )
+    };
+    let outcome = match attempt(&credentials, on_progress) {
+        Err(ImageRecognitionError::AuthError(reason)) => {
+            warn!("远程 OCR 凭证被拒绝，重新获取凭证后重试: {reason}");
+            attempt(&provider.fetch()?, on_progress)?
+        }
+        result => result?,
+    };
 
-    if include_position {
+    let text = if include_position {
         // 返回完整的结果（包含坐标信息）
-        extract_full_result(&final_snapshot)
+        extract_full_result(&outcome.snapshot)?
     } else {
         // 仅返回纯文本
-        extract_text(&final_snapshot)
-            .ok_or_else(|| ImageRecognitionError::EngineError("无法从响应中提取文本".to_string()))
+        extract_text(&outcome.snapshot)
+            .ok_or_else(|| ImageRecognitionError::EngineError("无法从响应中提取文本".to_string()))?
+    };
+
+    let elapsed = started.elapsed();
+    outcome.timings.record(elapsed);
+    Ok(RecognitionReport {
+        job_id: outcome.job_id,
+        attempts: outcome.polls,
+        elapsed,
+        timings: outcome.timings,
+        text,
+    })
+}
+
+/// 单次完整流程的结果
+struct JobOutcome {
+    job_id: String,
+    /// 状态查询次数
+    polls: u32,
+    /// 任务完成时的状态快照
+    snapshot: Value,
+    /// 各阶段耗时
+    timings: RecognitionTimings,
+}
+
+/// 使用一组凭证完成 获取 token → 启动任务 → 轮询 的完整流程
+fn run_attempt(
+    client: &Client,
+    config: &RemoteOcrConfig,
+    credentials: &Credentials,
+    payload: &RemoteImagePayload,
+    image_name: &str,
+    on_progress: &mut dyn FnMut(PollProgress),
+    cancel: &AtomicBool,
+) -> Result<JobOutcome> {
+    check_cancelled(cancel)?;
+    // 限流按 token 接口地址共享，同一服务的所有识别一起计数
+    let limiter = shared_limiter(&config.perm_url, config.requests_per_second);
+    let throttle = || match &limiter {
+        Some(limiter) => limiter.acquire(cancel),
+        None => Ok(()),
+    };
+    let mut timings = RecognitionTimings::default();
+    throttle()?;
+    let stage = Instant::now();
+    let perm_token = request_perm_token(client, config, credentials)?;
+    timings.perm_token = stage.elapsed();
+    check_cancelled(cancel)?;
+    throttle()?;
+    let stage = Instant::now();
+    let job_id = start_job(
+        client,
+        config,
+        credentials,
+        payload,
+        image_name,
+        &perm_token,
+    )?;
+    timings.start_job = stage.elapsed();
+    let stage = Instant::now();
+    let mut polls = 0;
+    let snapshot = poll_for_completion(
+        client,
+        config,
+        credentials,
+        &job_id,
+        &mut |progress| {
+            polls = progress.attempt;
+            on_progress(progress)
+        },
+        cancel,
+    )?;
+    timings.poll = stage.elapsed();
+    Ok(JobOutcome {
+        job_id,
+        polls,
+        snapshot,
+        timings,
+    })
+}
+
+/// 配置了 `reject_heic` 时，在发起任何请求前拒绝 HEIC/HEIF 图片
+///
+/// image crate 没有 HEIC 解码器，无法在本地转码，只能避免把远程服务不接受的格式上传上去。
+fn check_heic_accepted(payload: &RemoteImagePayload, config: &RemoteOcrConfig) -> Result<()> {
+    if config.reject_heic && is_heif_format(&payload.format) {
+        return Err(ImageRecognitionError::UnsupportedFormat(format!(
+            "{}（远程服务不接受该格式，请先转换为 JPEG 或 PNG）",
+            payload.format
+        )));
     }
+    Ok(())
+}
+
+fn check_cancelled(cancel: &AtomicBool) -> Result<()> {
+    if cancel.load(Ordering::Relaxed) {
+        Err(ImageRecognitionError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
+
+/// 分段等待，期间被取消时提前返回
+pub(crate) fn sleep_unless_cancelled(duration: Duration, cancel: &AtomicBool) -> Result<()> {
+    const SLICE: Duration = Duration::from_millis(50);
+    let deadline = Instant::now() + duration;
+    loop {
+        check_cancelled(cancel)?;
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(());
+        }
+        sleep(remaining.min(SLICE));
+    }
+}
+
+fn image_name(image_path: &str) -> &str {
+    Path::new(image_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("image")
 }
 
-fn build_http_client(config: &RemoteOcrConfig) -> Result<Client, ImageRecognitionError> {
+fn build_http_client(config: &RemoteOcrConfig) -> Result<Client> {
     let mut builder = Client::builder().timeout(config.request_timeout());
     if config.accept_invalid_certs {
         builder = builder.danger_accept_invalid_certs(true);
     }
+    if let Some(user_agent) = config.user_agent.as_deref() {
+        builder = builder.user_agent(user_agent);
+    }
 
-    builder
-        .build()
-        .map_err(|err| ImageRecognitionError::EngineError(format!("构建 HTTP 客户端失败: {err}")))
+    Ok(builder.build()?)
 }
 
 fn request_perm_token(
     client: &Client,
     config: &RemoteOcrConfig,
-) -> Result<String, ImageRecognitionError> {
-    let headers = build_perm_headers(config)?;
+    credentials: &Credentials,
+) -> Result<String> {
+    let headers = build_perm_headers(config, credentials)?;
     let response = execute_json_request(
         client
             .post(&config.perm_url)
@@ -74,35 +499,20 @@ fn request_perm_token(
 
     first_string(&response, &["/data/token", "/token"]).ok_or_else(|| {
         let brief = extract_brief(&response);
-        ImageRecognitionError::EngineError(format!("远程 OCR token 缺失: {brief}"))
+        ImageRecognitionError::AuthError(format!("远程 OCR token 缺失: {brief}"))
     })
 }
 
 fn start_job(
     client: &Client,
     config: &RemoteOcrConfig,
+    credentials: &Credentials,
     payload: &RemoteImagePayload,
-    image_path: &str,
+    image_name: &str,
     perm_token: &str,
-) -> Result<String, ImageRecognitionError> {
-    let headers = build_job_headers(config)?;
-    let data_url = build_data_url(payload)?;
-    let hash = sha1_hex(&data_url);
-    let image_name = Path::new(image_path)
-        .file_name()
-        .and_then(|name| name.to_str())
-        .unwrap_or("image");
-
-    let body = json!({
-        "token": perm_token,
-        "hash": hash,
-        "name": image_name,
-        "size": payload.bytes.len(),
-        "dataUrl": data_url,
-        "result": {},
-        "status": "processing",
-        "isSuccess": false,
-    });
+) -> Result<String> {
+    let headers = build_job_headers(config, credentials)?;
+    let body = build_start_body(config, payload, image_name, perm_token)?;
 
     let response = execute_json_request(
         client.post(&config.start_url).headers(headers).json(&body),
@@ -124,19 +534,85 @@ fn start_job(
     })
 }
 
+/// 构造启动任务的请求体，配置了语言时附带 `langType` 字段
+fn build_start_body(
+    config: &RemoteOcrConfig,
+    payload: &RemoteImagePayload,
+    image_name: &str,
+    perm_token: &str,
+) -> Result<Value> {
+    let data_url = build_data_url(payload)?;
+    let hash = sha1_hex(data_url.as_bytes());
+
+    let mut body = json!({
+        "token": perm_token,
+        "hash": hash,
+        "name": image_name,
+        "size": payload.bytes.len(),
+        "dataUrl": data_url,
+        "result": {},
+        "status": "processing",
+        "isSuccess": false,
+    });
+
+    if let Some(language) = config.language.as_deref() {
+        body["langType"] = Value::String(remote_language_code(language).to_string());
+    }
+
+    Ok(body)
+}
+
+/// 将语言代码转换为远程服务使用的格式
+///
+/// | 配置值              | 远程服务 |
+/// |---------------------|----------|
+/// | `chi_sim` / `zh`    | `zh-CHS` |
+/// | `chi_tra`           | `zh-CHT` |
+/// | `eng` / `en`        | `en`     |
+/// | `jpn` / `ja`        | `ja`     |
+/// | `kor` / `ko`        | `ko`     |
+/// | `fra` / `fr`        | `fr`     |
+/// | `deu` / `de`        | `de`     |
+/// | `rus` / `ru`        | `ru`     |
+///
+/// 未列出的代码原样透传，便于直接使用服务端格式（如 `auto`）。
+pub fn remote_language_code(language: &str) -> &str {
+    match language {
+        "chi_sim" | "zh" | "zh-CN" => "zh-CHS",
+        "chi_tra" | "zh-TW" => "zh-CHT",
+        "eng" => "en",
+        "jpn" => "ja",
+        "kor" => "ko",
+        "fra" => "fr",
+        "deu" => "de",
+        "rus" => "ru",
+        other => other,
+    }
+}
+
 fn poll_for_completion(
     client: &Client,
     config: &RemoteOcrConfig,
+    credentials: &Credentials,
     job_id: &str,
-) -> Result<Value, ImageRecognitionError> {
+    on_progress: &mut dyn FnMut(PollProgress),
+    cancel: &AtomicBool,
+) -> Result<Value> {
+    let started = Instant::now();
     if config.poll_initial_delay_ms > 0 {
-        sleep(config.poll_initial_delay());
+        sleep_unless_cancelled(config.poll_initial_delay(), cancel)?;
     }
 
     let mut attempts: u32 = 0;
 
     loop {
-        let snapshot = fetch_status(client, config, job_id)?;
+        check_cancelled(cancel)?;
+        let snapshot = fetch_status(client, config, credentials, job_id)?;
+        on_progress(PollProgress {
+            attempt: attempts + 1,
+            elapsed: started.elapsed(),
+            status: observed_status(&snapshot),
+        });
 
         if let Some(done) = job_is_finished(&snapshot) {
             if done {
@@ -153,22 +629,23 @@ fn poll_for_completion(
 
         if attempts >= config.poll_max_attempts {
             let detail = extract_brief(&snapshot);
-            return Err(ImageRecognitionError::EngineError(format!(
+            return Err(ImageRecognitionError::Timeout(format!(
                 "远程 OCR 轮询超时 (尝试 {} 次): {detail}",
                 config.poll_max_attempts
             )));
         }
 
-        sleep(config.poll_interval());
+        sleep_unless_cancelled(config.poll_interval(), cancel)?;
     }
 }
 
 fn fetch_status(
     client: &Client,
     config: &RemoteOcrConfig,
+    credentials: &Credentials,
     job_id: &str,
-) -> Result<Value, ImageRecognitionError> {
-    let headers = build_job_headers(config)?;
+) -> Result<Value> {
+    let headers = build_job_headers(config, credentials)?;
 
     execute_json_request(
         client
@@ -179,18 +656,24 @@ fn fetch_status(
     )
 }
 
-fn execute_json_request(
-    builder: RequestBuilder,
-    context: &str,
-) -> Result<Value, ImageRecognitionError> {
+fn execute_json_request(builder: RequestBuilder, context: &str) -> Result<Value> {
     let response = builder
         .send()
-        .map_err(|err| ImageRecognitionError::EngineError(format!("{context} 请求失败: {err}")))?;
+        .map_err(|e| with_context(e.into(), context))?;
 
     let status = response.status();
-    let json_value: Value = response.json().map_err(|err| {
-        ImageRecognitionError::EngineError(format!("{context} 响应解析失败: {err}"))
-    })?;
+    if matches!(
+        status,
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN
+    ) {
+        return Err(ImageRecognitionError::AuthError(format!(
+            "{context} 被拒绝，状态码 {status}"
+        )));
+    }
+
+    let json_value: Value = response
+        .json()
+        .map_err(|e| with_context(e.into(), context))?;
 
     if !status.is_success() {
         let brief = extract_brief(&json_value);
@@ -202,34 +685,67 @@ fn execute_json_request(
     Ok(json_value)
 }
 
-fn build_data_url(payload: &RemoteImagePayload) -> Result<String, ImageRecognitionError> {
-    let mime = payload.mime_type()?;
-    let encoded = BASE64_STANDARD.encode(&payload.bytes);
-    Ok(format!("data:{mime};base64,{encoded}"))
+/// 在错误信息前加上请求的上下文，错误类型（是否可重试）保持不变
+fn with_context(err: ImageRecognitionError, context: &str) -> ImageRecognitionError {
+    match err {
+        ImageRecognitionError::Network(msg) => {
+            ImageRecognitionError::Network(format!("{context} 失败: {msg}"))
+        }
+        ImageRecognitionError::Timeout(msg) => {
+            ImageRecognitionError::Timeout(format!("{context} 超时: {msg}"))
+        }
+        ImageRecognitionError::Parse(msg) => {
+            ImageRecognitionError::Parse(format!("{context} 响应解析失败: {msg}"))
+        }
+        other => other,
+    }
 }
 
-fn sha1_hex(content: &str) -> String {
-    let mut hasher = Sha1::new();
-    hasher.update(content.as_bytes());
-    let digest = hasher.finalize();
-    format!("{digest:x}")
+/// 估算 data URL 的长度：`data:{mime};base64,` 前缀加上 base64 编码后的长度
+pub fn data_url_len_estimate(mime: &str, byte_len: usize) -> usize {
+    "data:".len() + mime.len() + ";base64,".len() + byte_len.div_ceil(3) * 4
 }
 
-fn build_perm_headers(config: &RemoteOcrConfig) -> Result<HeaderMap, ImageRecognitionError> {
+/// 构造 `data:{mime};base64,...` 格式的 data URL
+///
+/// 直接编码进按最终长度预分配的字符串。原先先编码出一份 base64 再用 `format!`
+/// 拼接，会同时持有两份编码结果：10MB 图片的峰值约为 10 + 13.3 × 2 ≈ 36.7MB，
+/// 现在约为 10 + 13.3 ≈ 23.3MB，且编码过程中不会重新分配。
+fn build_data_url(payload: &RemoteImagePayload) -> Result<String> {
+    let mime = payload.mime_type()?;
+    let mut data_url = String::with_capacity(data_url_len_estimate(mime, payload.bytes.len()));
+    data_url.push_str("data:");
+    data_url.push_str(mime);
+    data_url.push_str(";base64,");
+    BASE64_STANDARD.encode_string(&payload.bytes, &mut data_url);
+    Ok(data_url)
+}
+
+fn build_perm_headers(config: &RemoteOcrConfig, credentials: &Credentials) -> Result<HeaderMap> {
     let mut headers = basic_headers(config)?;
-    insert_header(&mut headers, "x-auth-token", &config.auth_token)?;
-    insert_header(&mut headers, "x-auth-uuid", &config.auth_uuid)?;
+    insert_header(&mut headers, "x-auth-token", &credentials.auth_token)?;
+    insert_header(&mut headers, "x-auth-uuid", &credentials.auth_uuid)?;
     Ok(headers)
 }
 
-fn build_job_headers(config: &RemoteOcrConfig) -> Result<HeaderMap, ImageRecognitionError> {
-    let mut headers = build_perm_headers(config)?;
-    insert_header(&mut headers, "cookie", &config.auth_cookie)?;
+fn build_job_headers(config: &RemoteOcrConfig, credentials: &Credentials) -> Result<HeaderMap> {
+    let mut headers = build_perm_headers(config, credentials)?;
+    insert_header(&mut headers, "cookie", &credentials.auth_cookie)?;
     Ok(headers)
 }
 
-fn basic_headers(config: &RemoteOcrConfig) -> Result<HeaderMap, ImageRecognitionError> {
+/// 基础请求头，自定义请求头先写入，随后的固定请求头与鉴权请求头会覆盖同名项
+fn basic_headers(config: &RemoteOcrConfig) -> Result<HeaderMap> {
     let mut headers = HeaderMap::new();
+    for (name, value) in &config.extra_headers {
+        let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|err| {
+            ImageRecognitionError::EngineError(format!("自定义请求头名称 {name} 无效: {err}"))
+        })?;
+        let header_value = HeaderValue::from_str(value).map_err(|err| {
+            ImageRecognitionError::EngineError(format!("自定义请求头 {name} 的值无效: {err}"))
+        })?;
+        headers.insert(header_name, header_value);
+    }
     headers.insert(ACCEPT, HeaderValue::from_static(ACCEPT_HEADER_VALUE));
     headers.insert(CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE_JSON));
     insert_header(&mut headers, "origin", &config.origin)?;
@@ -238,11 +754,7 @@ fn basic_headers(config: &RemoteOcrConfig) -> Result<HeaderMap, ImageRecognition
     Ok(headers)
 }
 
-fn insert_header(
-    headers: &mut HeaderMap,
-    name: &'static str,
-    value: &str,
-) -> Result<(), ImageRecognitionError> {
+fn insert_header(headers: &mut HeaderMap, name: &'static str, value: &str) -> Result<()> {
     let header_name = HeaderName::from_static(name);
     let header_value = HeaderValue::from_str(value).map_err(|err| {
         ImageRecognitionError::EngineError(format!("设置请求头 {name} 失败: {err}"))
@@ -294,44 +806,51 @@ fn job_is_finished(snapshot: &Value) -> Option<bool> {
     None
 }
 
+/// 从状态快照中提取可读的任务状态
+fn observed_status(snapshot: &Value) -> String {
+    if let Some(status) = snapshot
+        .pointer("/data/jobStatus/status")
+        .or_else(|| snapshot.pointer("/data/status"))
+        .and_then(Value::as_str)
+    {
+        return status.to_string();
+    }
+
+    match job_is_finished(snapshot) {
+        Some(true) => "success".to_string(),
+        Some(false) => "failed".to_string(),
+        None => "processing".to_string(),
+    }
+}
+
 fn referer_from_origin(origin: &str) -> String {
     let trimmed = origin.trim_end_matches('/');
     format!("{trimmed}/")
 }
 
 /// 提取包含坐标信息的完整结果
-fn extract_full_result(snapshot: &Value) -> Result<String, ImageRecognitionError> {
+fn extract_full_result(snapshot: &Value) -> Result<String> {
     // 优先处理 ydResp.words_result 结构（包含坐标）
     if let Some(words_result) = snapshot.pointer("/data/ydResp/words_result") {
-        return serde_json::to_string_pretty(words_result).map_err(|err| {
-            ImageRecognitionError::EngineError(format!("序列化完整结果失败: {err}"))
-        });
+        return Ok(serde_json::to_string_pretty(words_result)?);
     }
 
     // 备用：返回 data.result 或 data.jobStatus.result
     if let Some(result) = snapshot.pointer("/data/jobStatus/result") {
-        return serde_json::to_string_pretty(result).map_err(|err| {
-            ImageRecognitionError::EngineError(format!("序列化完整结果失败: {err}"))
-        });
+        return Ok(serde_json::to_string_pretty(result)?);
     }
 
     if let Some(result) = snapshot.pointer("/data/result") {
-        return serde_json::to_string_pretty(result).map_err(|err| {
-            ImageRecognitionError::EngineError(format!("序列化完整结果失败: {err}"))
-        });
+        return Ok(serde_json::to_string_pretty(result)?);
     }
 
     // 如果没有找到特定字段，返回整个 data 部分
     if let Some(data) = snapshot.pointer("/data") {
-        return serde_json::to_string_pretty(data).map_err(|err| {
-            ImageRecognitionError::EngineError(format!("序列化完整结果失败: {err}"))
-        });
+        return Ok(serde_json::to_string_pretty(data)?);
     }
 
     // 最后兜底：返回整个响应
-    serde_json::to_string_pretty(snapshot).map_err(|err| {
-        ImageRecognitionError::EngineError(format!("序列化远程 OCR 结果失败: {err}"))
-    })
+    Ok(serde_json::to_string_pretty(snapshot)?)
 }
 
 fn extract_text(snapshot: &Value) -> Option<String> {
@@ -402,10 +921,8 @@ fn extract_text(snapshot: &Value) -> Option<String> {
 
 fn collect_text_nodes(node: &Value, acc: &mut Vec<String>) {
     match node {
-        Value::String(text) => {
-            if !text.is_empty() {
-                acc.push(text.to_string());
-            }
+        Value::String(text) if !text.is_empty() => {
+            acc.push(text.to_string());
         }
         Value::Array(items) => {
             for item in items {
@@ -482,3 +999,351 @@ fn extract_brief(value: &Value) -> String {
         Err(_) => "<无法解析的响应>".to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer, ocr_engine};
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+
+    fn test_config(language: Option<&str>) -> RemoteOcrConfig {
+        toml::from_str::<RemoteOcrConfig>(
+            r#"
+            perm_url = "http://127.0.0.1/perm"
+            start_url = "http://127.0.0.1/start"
+            status_url = "http://127.0.0.1/status"
+            auth_token = "token"
+            auth_uuid = "uuid"
+            auth_cookie = "cookie"
+            origin = "https://example.com"
+            "#,
+        )
+        .map(|config| RemoteOcrConfig {
+            language: language.map(str::to_string),
+            ..config
+        })
+        .unwrap()
+    }
+
+    fn test_payload() -> RemoteImagePayload {
+        load_and_validate_remote_image_bytes(
+            include_bytes!("../../../manifest/dev/tm_1.png").to_vec(),
+            "tm_1.png",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_json_request_errors_keep_context() {
+        let server = MockServer::spawn(|_| MockResponse::bytes("application/json", "not json"));
+        let client = Client::new();
+        let err =
+            execute_json_request(client.get(server.base_url()), "查询远程 OCR 状态").unwrap_err();
+        assert!(matches!(err, ImageRecognitionError::Parse(_)), "{err:?}");
+        assert!(err.to_string().contains("查询远程 OCR 状态"), "{err}");
+
+        // 端口 1 上没有服务，连接会被拒绝
+        let err = execute_json_request(client.get("http://127.0.0.1:1/"), "获取远程 OCR 凭证")
+            .unwrap_err();
+        assert!(matches!(err, ImageRecognitionError::Network(_)), "{err:?}");
+        assert!(err.to_string().contains("获取远程 OCR 凭证"), "{err}");
+    }
+
+    #[test]
+    fn test_start_body_includes_mapped_language() {
+        let body =
+            build_start_body(&test_config(Some("chi_sim")), &test_payload(), "a.png", "t").unwrap();
+        assert_eq!(body["langType"], "zh-CHS");
+        assert_eq!(body["name"], "a.png");
+    }
+
+    #[test]
+    fn test_start_body_omits_language_when_unset() {
+        let body = build_start_body(&test_config(None), &test_payload(), "a.png", "t").unwrap();
+        assert!(body.get("langType").is_none());
+    }
+
+    #[test]
+    fn test_remote_language_code_mapping() {
+        assert_eq!(remote_language_code("eng"), "en");
+        assert_eq!(remote_language_code("chi_tra"), "zh-CHT");
+        assert_eq!(remote_language_code("auto"), "auto");
+    }
+
+    #[test]
+    fn test_data_url_round_trips_without_reallocation() {
+        let payload = test_payload();
+        let data_url = build_data_url(&payload).unwrap();
+
+        let encoded = data_url.strip_prefix("data:image/png;base64,").unwrap();
+        assert_eq!(BASE64_STANDARD.decode(encoded).unwrap(), payload.bytes);
+        assert_eq!(
+            data_url.len(),
+            data_url_len_estimate("image/png", payload.bytes.len())
+        );
+        assert_eq!(data_url.capacity(), data_url.len());
+    }
+
+    #[test]
+    fn test_extra_headers_added_without_overriding_auth() {
+        let mut config = test_config(None);
+        config
+            .extra_headers
+            .insert("X-Api-Key".to_string(), "gateway-key".to_string());
+        config
+            .extra_headers
+            .insert("x-auth-token".to_string(), "spoofed".to_string());
+
+        let credentials = StaticProvider::from_config(&config).fetch().unwrap();
+        let headers = build_job_headers(&config, &credentials).unwrap();
+
+        assert_eq!(headers["x-api-key"], "gateway-key");
+        assert_eq!(headers["x-auth-token"], "token");
+        assert_eq!(headers["cookie"], "cookie");
+    }
+
+    #[test]
+    fn test_configured_user_agent_is_sent() {
+        let server = MockServer::spawn(|_| MockResponse::json(json!({})));
+
+        let config = RemoteOcrConfig {
+            user_agent: Some("rsde-test/1.0".to_string()),
+            ..mock_config(server.base_url())
+        };
+        let credentials = StaticProvider::from_config(&config).fetch().unwrap();
+        let client = build_http_client(&config).unwrap();
+        // 响应中没有 token，这里只关心请求头
+        let _ = request_perm_token(&client, &config, &credentials);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("user-agent"), Some("rsde-test/1.0"));
+    }
+
+    /// 启动模拟的远程 OCR 服务：前 `processing_polls` 次查询返回 processing，之后返回识别完成
+    ///
+    /// 携带 `x-auth-token: expired` 的请求返回 401。
+    fn spawn_mock_engine(processing_polls: usize) -> MockServer {
+        spawn_mock_engine_with_delay(processing_polls, Duration::ZERO)
+    }
+
+    /// 同 [`spawn_mock_engine`]，每个请求延迟 `delay` 后再响应
+    fn spawn_mock_engine_with_delay(processing_polls: usize, delay: Duration) -> MockServer {
+        let mut polls = 0;
+        let mut finished = ocr_engine(json!([{ "words": "done" }]));
+        MockServer::spawn(move |request| {
+            sleep(delay);
+            if request.header("x-auth-token") == Some("expired") {
+                return MockResponse::status(401);
+            }
+            let is_poll = !request.path.starts_with("/perm") && !request.path.starts_with("/start");
+            if is_poll && polls < processing_polls {
+                polls += 1;
+                return MockResponse::json(
+                    json!({ "data": { "jobStatus": { "status": "processing" } } }),
+                );
+            }
+            finished(request)
+        })
+    }
+
+    fn mock_config(base_url: &str) -> RemoteOcrConfig {
+        RemoteOcrConfig::for_testing(base_url)
+    }
+
+    #[test]
+    fn test_recognize_with_progress_reports_each_poll() {
+        let server = spawn_mock_engine(2);
+        let config = mock_config(server.base_url());
+
+        let mut progress = Vec::new();
+        let text = recognize_with_progress("../manifest/dev/tm_1.png", &config, false, |update| {
+            progress.push(update)
+        })
+        .unwrap();
+
+        assert_eq!(text, "done");
+        assert_eq!(progress.len(), 3);
+        assert_eq!(
+            progress.iter().map(|p| p.attempt).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(progress[0].status, "processing");
+        assert_eq!(progress[2].status, "success");
+        assert!(progress[2].elapsed >= progress[0].elapsed);
+    }
+
+    #[test]
+    fn test_heic_rejected_before_upload_when_configured() {
+        let server = spawn_mock_engine(0);
+        let config = RemoteOcrConfig {
+            reject_heic: true,
+            ..mock_config(server.base_url())
+        };
+        let mut bytes = vec![0, 0, 0, 16];
+        bytes.extend_from_slice(b"ftypheic");
+        bytes.extend_from_slice(&[0u8; 20]);
+
+        let result = recognize_bytes(bytes, "IMG_0001.heic", &config, false);
+
+        assert!(matches!(
+            result,
+            Err(ImageRecognitionError::UnsupportedFormat(_))
+        ));
+        assert_eq!(server.request_count(), 0);
+    }
+
+    #[test]
+    fn test_cancel_before_start_issues_no_requests() {
+        let server = spawn_mock_engine(0);
+        let cancel = AtomicBool::new(true);
+
+        let result = recognize_cancellable(
+            "../manifest/dev/tm_1.png",
+            &mock_config(server.base_url()),
+            false,
+            &cancel,
+        );
+
+        assert!(matches!(result, Err(ImageRecognitionError::Cancelled)));
+        assert_eq!(server.request_count(), 0);
+    }
+
+    #[test]
+    fn test_cancel_during_polling_returns_promptly() {
+        let server = spawn_mock_engine(usize::MAX);
+        let config = RemoteOcrConfig {
+            poll_interval_ms: 10_000,
+            poll_max_attempts: 100,
+            ..mock_config(server.base_url())
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        let trigger = cancel.clone();
+        thread::spawn(move || {
+            sleep(Duration::from_millis(200));
+            trigger.store(true, Ordering::SeqCst);
+        });
+
+        let started = Instant::now();
+        let result = recognize_cancellable("../manifest/dev/tm_1.png", &config, false, &cancel);
+
+        assert!(matches!(result, Err(ImageRecognitionError::Cancelled)));
+        assert!(started.elapsed() < Duration::from_secs(2));
+        // perm + start + 第一次状态查询，取消后不再发起请求
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[test]
+    fn test_recognize_reported_exposes_job_id() {
+        let server = spawn_mock_engine(1);
+
+        let report = recognize_reported(
+            "../manifest/dev/tm_1.png",
+            &mock_config(server.base_url()),
+            false,
+        )
+        .unwrap();
+
+        // 与 mock 服务 /start 返回的 jobStatusId 一致
+        assert_eq!(report.job_id, "job-1");
+        assert_eq!(report.attempts, 2);
+        assert_eq!(report.text, "done");
+        assert!(report.elapsed >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_report_timings_add_up_to_elapsed() {
+        let delay = Duration::from_millis(100);
+        let server = spawn_mock_engine_with_delay(1, delay);
+
+        let report = recognize_reported(
+            "../manifest/dev/tm_1.png",
+            &mock_config(server.base_url()),
+            false,
+        )
+        .unwrap();
+        let timings = report.timings;
+
+        assert!(timings.perm_token >= delay);
+        assert!(timings.start_job >= delay);
+        // 两次状态查询加一次轮询间隔
+        assert!(timings.poll >= delay * 2 + Duration::from_millis(50));
+        // 各阶段之外的耗时受调度影响，只检查阶段总和不超过整体耗时
+        assert!(
+            timings.total() <= report.elapsed,
+            "elapsed {:?}, stages {:?}",
+            report.elapsed,
+            timings
+        );
+    }
+
+    #[test]
+    fn test_batch_requests_are_spaced_by_rate_limit() {
+        let server = spawn_mock_engine(0);
+        let config = RemoteOcrConfig {
+            requests_per_second: 10.0,
+            ..mock_config(server.base_url())
+        };
+        let images = (0..3)
+            .map(|i| BatchImage {
+                file_name: format!("{i}.png"),
+                bytes: include_bytes!("../../../manifest/dev/tm_1.png").to_vec(),
+            })
+            .collect();
+
+        let started = Instant::now();
+        let results = recognize_batch(images, &config, false, 3);
+        assert!(results.iter().all(Result::is_ok));
+
+        // 只有 token 与启动任务请求受限流约束，状态查询不受影响
+        let gated: Vec<Instant> = server
+            .requests()
+            .iter()
+            .filter(|request| !request.path.starts_with("/status"))
+            .map(|request| request.arrived)
+            .collect();
+        assert_eq!(gated.len(), 6);
+        // 6 个请求按 100ms 间隔放行，最后一个最早在开始后 500ms 到达；只检查下界，不受调度抖动影响
+        let last = gated.into_iter().max().unwrap();
+        assert!(last - started >= Duration::from_millis(500));
+    }
+
+    /// 第一次返回过期凭证，之后返回新凭证
+    struct RefreshingProvider {
+        fetches: AtomicUsize,
+    }
+
+    impl CredentialProvider for RefreshingProvider {
+        fn fetch(&self) -> Result<Credentials> {
+            let token = if self.fetches.fetch_add(1, Ordering::SeqCst) == 0 {
+                "expired"
+            } else {
+                "fresh"
+            };
+            Ok(Credentials {
+                auth_token: token.to_string(),
+                auth_uuid: "uuid".to_string(),
+                auth_cookie: "cookie".to_string(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_credentials_refreshed_after_unauthorized() {
+        let server = spawn_mock_engine(0);
+        let provider = RefreshingProvider {
+            fetches: AtomicUsize::new(0),
+        };
+
+        let text = recognize_with_credentials(
+            "../manifest/dev/tm_1.png",
+            &mock_config(server.base_url()),
+            false,
+            &provider,
+        )
+        .unwrap();
+
+        assert_eq!(text, "done");
+        assert_eq!(provider.fetches.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,116 @@
+//! Tesseract 本地 OCR 引擎
+//!
+//! 调用本机 `tesseract` 命令行完成识别，需要预先安装 tesseract-ocr 及语言数据
+//! （见 `make dep-install` 与 `make traindata-deploy`）
+
+use crate::error::{ImageRecognitionError, Result};
+use crate::utils::validate_image_path;
+use config::ocr::OcrConfig;
+use serde_json::{Value, json};
+use std::process::Command;
+
+const TESSERACT_BIN: &str = "tesseract";
+
+/// tsv 输出中表示单词的层级
+const WORD_LEVEL: &str = "5";
+
+/// 使用本地 Tesseract 识别图片
+///
+/// `include_position` 为 true 时返回与远程 OCR `words_result` 结构一致的 JSON，
+/// 否则返回纯文本。
+pub fn recognize(image_path: &str, config: &OcrConfig, include_position: bool) -> Result<String> {
+    validate_image_path(image_path)?;
+
+    let mut command = Command::new(TESSERACT_BIN);
+    command
+        .arg(image_path)
+        .arg("stdout")
+        .arg("-l")
+        .arg(&config.language);
+    if let Some(data_path) = &config.data_path {
+        command.arg("--tessdata-dir").arg(data_path);
+    }
+    if let Some(psm) = config.page_segmentation_mode {
+        command.arg("--psm").arg(psm.to_string());
+    }
+    if let Some(oem) = config.engine_mode {
+        command.arg("--oem").arg(oem.to_string());
+    }
+    if include_position {
+        command.arg("tsv");
+    }
+
+    let output = command.output().map_err(|err| {
+        ImageRecognitionError::TesseractError(format!("无法启动 {TESSERACT_BIN}: {err}"))
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ImageRecognitionError::TesseractError(format!(
+            "识别失败 ({}): {}",
+            output.status,
+            stderr.trim()
+        )));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if include_position {
+        tsv_to_words_result(&stdout)
+    } else {
+        Ok(stdout.trim().to_string())
+    }
+}
+
+/// 将 tsv 输出转换为 `[{ "words", "location": { left, top, width, height } }]`
+fn tsv_to_words_result(tsv: &str) -> Result<String> {
+    let words: Vec<Value> = tsv
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split('\t').collect();
+            if columns.len() < 12 || columns[0] != WORD_LEVEL {
+                return None;
+            }
+            let text = columns[11].trim();
+            if text.is_empty() {
+                return None;
+            }
+            let number = |index: usize| columns[index].parse::<i64>().unwrap_or_default();
+            Some(json!({
+                "words": text,
+                "location": {
+                    "left": number(6),
+                    "top": number(7),
+                    "width": number(8),
+                    "height": number(9),
+                },
+                "confidence": columns[10].parse::<f64>().unwrap_or_default(),
+            }))
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&words)
+        .map_err(|err| ImageRecognitionError::TesseractError(format!("序列化识别结果失败: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tsv_to_words_result() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+                   1\t1\t0\t0\t0\t0\t0\t0\t100\t50\t-1\t\n\
+                   5\t1\t1\t1\t1\t1\t10\t20\t30\t12\t96.5\tHello\n\
+                   5\t1\t1\t1\t1\t2\t45\t20\t28\t12\t91\t \n";
+
+        let result: Value = serde_json::from_str(&tsv_to_words_result(tsv).unwrap()).unwrap();
+        assert_eq!(
+            result,
+            json!([{
+                "words": "Hello",
+                "location": { "left": 10, "top": 20, "width": 30, "height": 12 },
+                "confidence": 96.5
+            }])
+        );
+    }
+}
@@ -21,8 +21,21 @@ pub enum ImageRecognitionError {
     ConfigError(String),
     /// 其他引擎错误（预留给未来的识别引擎）
     EngineError(String),
+    /// 识别被调用方取消
+    Cancelled,
+    /// 远程服务请求或轮询超时
+    Timeout(String),
+    /// 远程服务鉴权失败（凭证失效或缺失）
+    AuthError(String),
+    /// 网络请求失败（连接失败、请求发送失败）
+    Network(String),
+    /// JSON 解析或序列化失败
+    Parse(String),
 }
 
+/// 图片识别结果类型
+pub type Result<T> = std::result::Result<T, ImageRecognitionError>;
+
 impl fmt::Display for ImageRecognitionError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -47,10 +60,42 @@ impl fmt::Display for ImageRecognitionError {
             ImageRecognitionError::EngineError(msg) => {
                 write!(f, "识别引擎错误: {msg}")
             }
+            ImageRecognitionError::Cancelled => {
+                write!(f, "识别已取消")
+            }
+            ImageRecognitionError::Timeout(msg) => {
+                write!(f, "远程服务超时: {msg}")
+            }
+            ImageRecognitionError::AuthError(msg) => {
+                write!(f, "远程服务鉴权失败: {msg}")
+            }
+            ImageRecognitionError::Network(msg) => {
+                write!(f, "网络请求失败: {msg}")
+            }
+            ImageRecognitionError::Parse(msg) => {
+                write!(f, "解析失败: {msg}")
+            }
         }
     }
 }
 
+impl ImageRecognitionError {
+    /// 是否值得换一个引擎重试（远程服务不可用、响应异常、超时或鉴权失败）
+    ///
+    /// 图片本身的问题（不存在、格式或尺寸不符）换引擎也无法解决，不重试。
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ImageRecognitionError::EngineError(_)
+                | ImageRecognitionError::Timeout(_)
+                | ImageRecognitionError::AuthError(_)
+                | ImageRecognitionError::TesseractError(_)
+                | ImageRecognitionError::Network(_)
+                | ImageRecognitionError::Parse(_)
+        )
+    }
+}
+
 impl std::error::Error for ImageRecognitionError {}
 
 impl From<std::io::Error> for ImageRecognitionError {
@@ -58,3 +103,68 @@ impl From<std::io::Error> for ImageRecognitionError {
         ImageRecognitionError::IoError(err)
     }
 }
+
+/// 超时归为 [`ImageRecognitionError::Timeout`]，响应体解码失败归为 [`ImageRecognitionError::Parse`]
+impl From<reqwest::Error> for ImageRecognitionError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            ImageRecognitionError::Timeout(err.to_string())
+        } else if err.is_decode() {
+            ImageRecognitionError::Parse(err.to_string())
+        } else {
+            ImageRecognitionError::Network(err.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for ImageRecognitionError {
+    fn from(err: serde_json::Error) -> Self {
+        ImageRecognitionError::Parse(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_error_converts_to_io_variant() {
+        let err: ImageRecognitionError = std::io::Error::other("disk full").into();
+        assert!(matches!(err, ImageRecognitionError::IoError(_)));
+        assert!(err.to_string().contains("disk full"));
+    }
+
+    #[test]
+    fn test_serde_json_error_converts_to_parse() {
+        let err: ImageRecognitionError = serde_json::from_str::<serde_json::Value>("{not json")
+            .unwrap_err()
+            .into();
+        assert!(matches!(err, ImageRecognitionError::Parse(_)));
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn test_reqwest_error_converts_to_network() {
+        // 端口 1 上没有服务，连接会被拒绝
+        let err: ImageRecognitionError = reqwest::blocking::get("http://127.0.0.1:1/")
+            .unwrap_err()
+            .into();
+        assert!(matches!(err, ImageRecognitionError::Network(_)), "{err:?}");
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn test_reqwest_timeout_converts_to_timeout() {
+        // 响应晚于客户端超时
+        let server = crate::test_support::MockServer::spawn(|_| {
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            crate::test_support::MockResponse::status(200)
+        });
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let err: ImageRecognitionError = client.get(server.base_url()).send().unwrap_err().into();
+        assert!(matches!(err, ImageRecognitionError::Timeout(_)), "{err:?}");
+    }
+}
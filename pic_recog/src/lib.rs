@@ -4,7 +4,7 @@
 //!
 //! ## 功能特性
 //!
-//! - 🔍 多引擎支持：Remote OCR（更多引擎即将支持）
+//! - 🔍 多引擎支持：Remote OCR、本地 Tesseract，支持按顺序降级
 //! - 🌍 多语言识别：支持英文、中文、日文等多种语言
 //! - ⚙️ 灵活配置：自定义识别参数
 //! - 🛡️ 错误处理：完善的错误类型和处理机制
@@ -33,22 +33,50 @@
 //! println!("识别结果（含坐标）: {}", result);
 //! ```
 //!
+//! ### 引擎降级
+//!
+//! ```no_run
+//! use pic_recog::{Engine, OcrConfig, RemoteOcrConfig, recognize_with_fallback};
+//!
+//! let remote = RemoteOcrConfig::from_file("config.toml").unwrap();
+//! let engines = [Engine::Remote(Box::new(remote)), Engine::Tesseract(OcrConfig::default())];
+//! // 远程服务不可用时自动改用本地 Tesseract
+//! let text = recognize_with_fallback(&engines, "image.png", false).unwrap();
+//! ```
+//!
 //! ## 模块结构
 //!
 //! - `config` - 配置类型
+//! - `credentials` - 远程 OCR 凭证提供者
 //! - `error` - 错误类型定义
+//! - `rate_limit` - 远程 OCR 请求限流
+//! - `result` - 结构化识别结果（单词及其位置）
 //! - `engines` - 不同的识别引擎实现
 //!   - `remote` - Remote OCR 引擎
+//!   - `tesseract` - 本地 Tesseract 引擎
 //! - `utils` - 通用工具函数
+//! - `test_support` - 测试用的模拟远程 OCR 服务（`test-support` feature）
 
 // 模块声明
+pub mod credentials;
 pub mod engines;
 pub mod error;
+pub mod rate_limit;
+pub mod result;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 pub mod utils;
 
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{info, warn};
+
 // 重新导出常用类型
-pub use config::ocr::RemoteOcrConfig;
-pub use error::ImageRecognitionError;
+pub use config::ocr::{OcrConfig, RemoteOcrConfig};
+pub use credentials::{CredentialProvider, Credentials, StaticProvider};
+pub use engines::remote::{BatchImage, PollProgress, RecognitionReport, RecognitionTimings};
+pub use error::{ImageRecognitionError, Result};
+pub use result::{Location, OcrWord};
 
 // ============================================================================
 // 公共 API - Remote OCR 引擎
@@ -66,10 +94,7 @@ pub use error::ImageRecognitionError;
 ///
 /// # 返回
 /// 远程 OCR 服务返回的识别文本（不包含坐标信息）
-pub fn recognize_image_by_remote(
-    image_path: &str,
-    config: &RemoteOcrConfig,
-) -> Result<String, ImageRecognitionError> {
+pub fn recognize_image_by_remote(image_path: &str, config: &RemoteOcrConfig) -> Result<String> {
     engines::remote::recognize(image_path, config, false)
 }
 
@@ -87,6 +112,289 @@ pub fn recognize_image_by_remote(
 pub fn recognize_image_by_remote_with_position(
     image_path: &str,
     config: &RemoteOcrConfig,
-) -> Result<String, ImageRecognitionError> {
+) -> Result<String> {
     engines::remote::recognize(image_path, config, true)
 }
+
+/// 使用远程 OCR 服务识别图片，返回带位置的结构化结果
+///
+/// 位置可能是矩形或多边形（旋转文本），可通过 [`Location::bounding_rect`]
+/// 统一换算为外接矩形。
+pub fn recognize_image_by_remote_structured(
+    image_path: &str,
+    config: &RemoteOcrConfig,
+) -> Result<Vec<OcrWord>> {
+    let json = engines::remote::recognize(image_path, config, true)?;
+    result::parse_words_result(&json)
+}
+
+/// 使用远程 OCR 服务批量识别图片
+///
+/// 最多同时发起 `max_concurrency` 个远程任务，返回结果与输入顺序一一对应，
+/// 单张图片失败不会中断其他图片的识别。
+///
+/// # 参数
+/// * `images` - 待识别的图片（文件名 + 数据）
+/// * `config` - 远程 OCR 配置
+/// * `include_position` - 是否返回包含坐标信息的完整 JSON 结果
+/// * `max_concurrency` - 最大并发数
+pub fn recognize_batch_remote(
+    images: Vec<BatchImage>,
+    config: &RemoteOcrConfig,
+    include_position: bool,
+    max_concurrency: usize,
+) -> Vec<Result<String>> {
+    engines::remote::recognize_batch(images, config, include_position, max_concurrency)
+}
+
+// ============================================================================
+// 公共 API - 多引擎降级
+// ============================================================================
+
+/// 远程 OCR 服务支持的语言（Tesseract 风格代码，`auto` 表示自动识别）
+const REMOTE_LANGUAGES: &[&str] = &[
+    "auto", "chi_sim", "chi_tra", "eng", "jpn", "kor", "fra", "deu", "rus",
+];
+
+/// 识别引擎能力描述
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EngineInfo {
+    /// 引擎名称
+    pub name: String,
+    /// 是否支持返回文字坐标
+    pub supports_position: bool,
+    /// 是否支持批量识别
+    pub supports_batch: bool,
+    /// 支持的语言代码
+    pub supported_languages: Vec<String>,
+}
+
+/// 自定义识别引擎，用于接入其他 OCR 实现
+pub trait OcrEngine: Send + Sync {
+    /// 引擎名称（用于日志）
+    fn name(&self) -> &str;
+
+    /// 引擎能力，默认不声明任何可选能力
+    fn capabilities(&self) -> EngineInfo {
+        EngineInfo {
+            name: self.name().to_string(),
+            supports_position: false,
+            supports_batch: false,
+            supported_languages: Vec::new(),
+        }
+    }
+
+    /// 识别图片，`include_position` 含义与远程 OCR 相同
+    fn recognize(&self, image_path: &str, include_position: bool) -> Result<String>;
+}
+
+/// 降级链中的识别引擎
+#[derive(Clone)]
+pub enum Engine {
+    /// 远程 OCR 服务
+    Remote(Box<RemoteOcrConfig>),
+    /// 本地 Tesseract
+    Tesseract(OcrConfig),
+    /// 自定义引擎
+    Custom(Arc<dyn OcrEngine>),
+}
+
+impl Engine {
+    /// 引擎名称
+    pub fn name(&self) -> &str {
+        match self {
+            Engine::Remote(_) => "remote",
+            Engine::Tesseract(_) => "tesseract",
+            Engine::Custom(engine) => engine.name(),
+        }
+    }
+
+    /// 引擎能力描述
+    pub fn capabilities(&self) -> EngineInfo {
+        match self {
+            Engine::Remote(_) => EngineInfo {
+                name: self.name().to_string(),
+                supports_position: true,
+                supports_batch: true,
+                supported_languages: REMOTE_LANGUAGES.iter().map(|l| l.to_string()).collect(),
+            },
+            Engine::Tesseract(config) => EngineInfo {
+                name: self.name().to_string(),
+                supports_position: true,
+                supports_batch: false,
+                // 只能识别已配置（已安装）的语言包
+                supported_languages: config
+                    .language
+                    .split('+')
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            },
+            Engine::Custom(engine) => engine.capabilities(),
+        }
+    }
+
+    /// 使用该引擎识别图片
+    pub fn recognize(&self, image_path: &str, include_position: bool) -> Result<String> {
+        match self {
+            Engine::Remote(config) => {
+                engines::remote::recognize(image_path, config, include_position)
+            }
+            Engine::Tesseract(config) => {
+                engines::tesseract::recognize(image_path, config, include_position)
+            }
+            Engine::Custom(engine) => engine.recognize(image_path, include_position),
+        }
+    }
+}
+
+/// 按顺序尝试多个引擎，返回第一个成功的结果
+///
+/// 只有可重试的错误（远程服务不可用、超时、鉴权失败等，见
+/// [`ImageRecognitionError::is_retryable`]）才会切换到下一个引擎；
+/// 全部失败时返回最后一个错误。
+///
+/// # 参数
+/// * `engines` - 按优先级排列的引擎
+/// * `image_path` - 图片文件路径
+/// * `include_position` - 是否返回包含坐标信息的完整 JSON 结果
+pub fn recognize_with_fallback(
+    engines: &[Engine],
+    image_path: &str,
+    include_position: bool,
+) -> Result<String> {
+    let mut last_error = None;
+
+    for engine in engines {
+        match engine.recognize(image_path, include_position) {
+            Ok(text) => {
+                info!("OCR 引擎 {} 识别成功: {image_path}", engine.name());
+                return Ok(text);
+            }
+            Err(err) if err.is_retryable() => {
+                warn!("OCR 引擎 {} 识别失败，尝试下一个引擎: {err}", engine.name());
+                last_error = Some(err);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Err(last_error
+        .unwrap_or_else(|| ImageRecognitionError::ConfigError("未配置识别引擎".to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const IMAGE: &str = "../manifest/dev/tm_1.png";
+
+    /// 模拟引擎：记录调用次数并返回预设结果
+    struct MockEngine {
+        result: fn() -> Result<String>,
+        calls: AtomicUsize,
+    }
+
+    impl MockEngine {
+        fn new(result: fn() -> Result<String>) -> Arc<Self> {
+            Arc::new(Self {
+                result,
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    impl OcrEngine for MockEngine {
+        fn name(&self) -> &str {
+            "mock-tesseract"
+        }
+
+        fn recognize(&self, _image_path: &str, _include_position: bool) -> Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            (self.result)()
+        }
+    }
+
+    /// 指向不可达地址的远程配置，请求会立即失败
+    fn unreachable_remote() -> RemoteOcrConfig {
+        toml::from_str(
+            r#"
+            perm_url = "http://127.0.0.1:9/perm"
+            start_url = "http://127.0.0.1:9/start"
+            status_url = "http://127.0.0.1:9/status"
+            auth_token = "token"
+            auth_uuid = "uuid"
+            auth_cookie = "cookie"
+            origin = "https://example.com"
+            timeout_secs = 1
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_falls_back_when_remote_is_down() {
+        let local = MockEngine::new(|| Ok("local text".to_string()));
+        let engines = [
+            Engine::Remote(Box::new(unreachable_remote())),
+            Engine::Custom(local.clone()),
+        ];
+
+        let text = recognize_with_fallback(&engines, IMAGE, false).unwrap();
+
+        assert_eq!(text, "local text");
+        assert_eq!(local.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_does_not_fall_back_on_invalid_image() {
+        let first = MockEngine::new(|| {
+            Err(ImageRecognitionError::ValidationError(
+                "too small".to_string(),
+            ))
+        });
+        let second = MockEngine::new(|| Ok("unused".to_string()));
+        let engines = [Engine::Custom(first), Engine::Custom(second.clone())];
+
+        let result = recognize_with_fallback(&engines, IMAGE, false);
+
+        assert!(matches!(
+            result,
+            Err(ImageRecognitionError::ValidationError(_))
+        ));
+        assert_eq!(second.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_returns_last_error_when_all_fail() {
+        let first = MockEngine::new(|| Err(ImageRecognitionError::Timeout("a".to_string())));
+        let second = MockEngine::new(|| Err(ImageRecognitionError::AuthError("b".to_string())));
+        let engines = [Engine::Custom(first), Engine::Custom(second)];
+
+        let result = recognize_with_fallback(&engines, IMAGE, false);
+
+        assert!(matches!(result, Err(ImageRecognitionError::AuthError(_))));
+    }
+
+    #[test]
+    fn test_engine_capabilities() {
+        let remote = Engine::Remote(Box::new(unreachable_remote())).capabilities();
+        assert!(remote.supports_position && remote.supports_batch);
+        assert!(remote.supported_languages.contains(&"chi_sim".to_string()));
+
+        let tesseract = Engine::Tesseract(OcrConfig {
+            language: "chi_sim+eng".to_string(),
+            ..Default::default()
+        })
+        .capabilities();
+        assert_eq!(tesseract.name, "tesseract");
+        assert!(!tesseract.supports_batch);
+        assert_eq!(tesseract.supported_languages, ["chi_sim", "eng"]);
+
+        let custom = Engine::Custom(MockEngine::new(|| Ok(String::new()))).capabilities();
+        assert_eq!(custom.name, "mock-tesseract");
+        assert!(!custom.supports_position);
+    }
+}
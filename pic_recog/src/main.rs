@@ -0,0 +1,213 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use pic_recog::utils::remote_supported_formats;
+use pic_recog::{
+    BatchImage, ImageRecognitionError, OcrWord, RemoteOcrConfig, recognize_batch_remote,
+    recognize_image_by_remote, recognize_image_by_remote_with_position,
+};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(name = "pic-recog")]
+#[command(about = "Recognize text in images with the remote OCR engine", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Recognize a single image or every image in a directory
+    Recognize(RecognizeArgs),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args)]
+struct RecognizeArgs {
+    /// Remote OCR config file (TOML)
+    #[arg(long, env = "PIC_RECOG_CONFIG")]
+    config: PathBuf,
+
+    /// Image to recognize
+    #[arg(long, required_unless_present = "batch", conflicts_with = "batch")]
+    image: Option<PathBuf>,
+
+    /// Recognize every supported image in this directory
+    #[arg(long)]
+    batch: Option<PathBuf>,
+
+    /// Keep word positions (raw result JSON for text output, words for JSON output)
+    #[arg(long)]
+    position: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "text")]
+    format: OutputFormat,
+
+    /// Maximum concurrent jobs for --batch, defaults to batch_concurrency in the config
+    #[arg(long)]
+    concurrency: Option<usize>,
+}
+
+/// 单张图片的输出
+#[derive(Serialize)]
+struct ImageOutput {
+    image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    words: Option<Vec<OcrWord>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ImageOutput {
+    fn new(
+        image: String,
+        result: Result<String, ImageRecognitionError>,
+        args: &RecognizeArgs,
+    ) -> Self {
+        let mut output = Self {
+            image,
+            text: None,
+            words: None,
+            error: None,
+        };
+        match result {
+            Ok(text) if args.position && args.format == OutputFormat::Json => {
+                match pic_recog::result::parse_words_result(&text) {
+                    Ok(words) => output.words = Some(words),
+                    Err(e) => output.error = Some(e.to_string()),
+                }
+            }
+            Ok(text) => output.text = Some(text),
+            Err(e) => output.error = Some(e.to_string()),
+        }
+        output
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Commands::Recognize(args) => handle_recognize(&args),
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// 识别并输出结果，有图片识别失败时返回 false
+fn handle_recognize(args: &RecognizeArgs) -> anyhow::Result<bool> {
+    let config = RemoteOcrConfig::from_file(&args.config)
+        .map_err(|e| anyhow::anyhow!("Failed to load config {}: {e}", args.config.display()))?;
+
+    let outputs = match (&args.image, &args.batch) {
+        (Some(image), _) => {
+            let path = image.to_string_lossy();
+            let result = if args.position {
+                recognize_image_by_remote_with_position(&path, &config)
+            } else {
+                recognize_image_by_remote(&path, &config)
+            };
+            vec![ImageOutput::new(path.into_owned(), result, args)]
+        }
+        (None, Some(dir)) => {
+            let images = read_batch_dir(dir)?;
+            let names: Vec<String> = images.iter().map(|i| i.file_name.clone()).collect();
+            let concurrency = args.concurrency.unwrap_or(config.batch_concurrency);
+            let results = recognize_batch_remote(images, &config, args.position, concurrency);
+            names
+                .into_iter()
+                .zip(results)
+                .map(|(name, result)| ImageOutput::new(name, result, args))
+                .collect()
+        }
+        (None, None) => anyhow::bail!("Either --image or --batch is required"),
+    };
+
+    let all_ok = outputs.iter().all(|output| output.error.is_none());
+    print_outputs(&outputs, args)?;
+    Ok(all_ok)
+}
+
+/// 读取目录下所有远程 OCR 支持的图片，按文件名排序
+fn read_batch_dir(dir: &Path) -> anyhow::Result<Vec<BatchImage>> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| anyhow::anyhow!("Failed to read directory {}: {e}", dir.display()))?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && is_supported_image(path))
+        .collect();
+    paths.sort();
+
+    if paths.is_empty() {
+        anyhow::bail!("No supported images found in {}", dir.display());
+    }
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let bytes = fs::read(&path)
+                .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Ok(BatchImage { file_name, bytes })
+        })
+        .collect()
+}
+
+fn is_supported_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| remote_supported_formats().contains(&ext.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+fn print_outputs(outputs: &[ImageOutput], args: &RecognizeArgs) -> anyhow::Result<()> {
+    match args.format {
+        OutputFormat::Json if args.batch.is_some() => {
+            println!("{}", serde_json::to_string_pretty(outputs)?)
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&outputs[0])?),
+        OutputFormat::Text => {
+            for output in outputs {
+                if args.batch.is_some() {
+                    println!("==> {} <==", output.image);
+                }
+                match (&output.text, &output.error) {
+                    (Some(text), _) => println!("{text}"),
+                    (None, Some(error)) => eprintln!("{}: {error}", output.image),
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,136 @@
+//! 远程 OCR 请求限流
+//!
+//! 远程服务对调用频率有全局限制，批量识别即使限制了并发也可能触发 429。
+//! 这里按配置的速率为 token 与启动任务请求分配发送时刻，同一服务的所有识别共享一个限流器。
+
+use crate::error::Result;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// 相邻请求的最长间隔，对应配置允许的最低速率
+const MAX_INTERVAL: Duration = Duration::from_secs(1000);
+
+/// 令牌桶限流器（容量为 1），请求按 `1 / requests_per_second` 的间隔依次放行
+#[derive(Debug)]
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    interval: Duration,
+    /// 下一个请求最早可以发出的时刻
+    next_slot: Instant,
+}
+
+/// 两次请求的间隔，速率过低时按 [`MAX_INTERVAL`] 计算
+fn interval_for(requests_per_second: f64) -> Duration {
+    Duration::try_from_secs_f64(1.0 / requests_per_second)
+        .unwrap_or(MAX_INTERVAL)
+        .min(MAX_INTERVAL)
+}
+
+impl RateLimiter {
+    /// 创建限流器，`requests_per_second` 须大于 0
+    pub fn new(requests_per_second: f64) -> Self {
+        Self {
+            state: Mutex::new(LimiterState {
+                interval: interval_for(requests_per_second),
+                next_slot: Instant::now(),
+            }),
+        }
+    }
+
+    /// 调整速率，已分配的发送时刻不受影响
+    pub fn set_rate(&self, requests_per_second: f64) {
+        self.state.lock().expect("rate limiter poisoned").interval =
+            interval_for(requests_per_second);
+    }
+
+    /// 获取发送许可，必要时阻塞等待；等待期间被取消时返回 [`crate::ImageRecognitionError::Cancelled`]
+    pub fn acquire(&self, cancel: &AtomicBool) -> Result<()> {
+        let wait = {
+            let mut state = self.state.lock().expect("rate limiter poisoned");
+            let now = Instant::now();
+            let slot = state.next_slot.max(now);
+            state.next_slot = slot + state.interval;
+            slot - now
+        };
+        crate::engines::remote::sleep_unless_cancelled(wait, cancel)
+    }
+}
+
+/// 按服务地址获取共享的限流器，`requests_per_second` 不大于 0 时不限流
+///
+/// 同一服务只有一个限流器，速率以最近一次传入的配置为准，
+/// 避免不同配置各自限流后叠加超出服务的全局限制。
+pub fn shared_limiter(endpoint: &str, requests_per_second: f64) -> Option<Arc<RateLimiter>> {
+    static LIMITERS: OnceLock<Mutex<HashMap<String, Arc<RateLimiter>>>> = OnceLock::new();
+
+    if requests_per_second <= 0.0 || !requests_per_second.is_finite() {
+        return None;
+    }
+    let mut limiters = LIMITERS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("rate limiter registry poisoned");
+    let limiter = match limiters.get(endpoint) {
+        Some(limiter) => {
+            limiter.set_rate(requests_per_second);
+            limiter.clone()
+        }
+        None => {
+            let limiter = Arc::new(RateLimiter::new(requests_per_second));
+            limiters.insert(endpoint.to_string(), limiter.clone());
+            limiter
+        }
+    };
+    Some(limiter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_acquire_spaces_requests_across_threads() {
+        let limiter = Arc::new(RateLimiter::new(20.0));
+        let started = Instant::now();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let limiter = limiter.clone();
+                thread::spawn(move || {
+                    limiter.acquire(&AtomicBool::new(false)).unwrap();
+                    started.elapsed()
+                })
+            })
+            .collect();
+        let mut times: Vec<Duration> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        times.sort();
+
+        // 第一个立即放行，之后每 50ms 放行一个，第四个至少等待 150ms
+        assert!(times[3] >= Duration::from_millis(150));
+    }
+
+    #[test]
+    fn test_shared_limiter_is_keyed_by_endpoint_only() {
+        let a = shared_limiter("http://ocr.test/perm", 5.0).unwrap();
+        let b = shared_limiter("http://ocr.test/perm", 8.0).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(
+            a.state.lock().unwrap().interval,
+            Duration::from_secs_f64(1.0 / 8.0)
+        );
+        assert!(shared_limiter("http://ocr.test/perm", 0.0).is_none());
+    }
+
+    #[test]
+    fn test_tiny_rate_does_not_panic() {
+        let limiter = RateLimiter::new(f64::MIN_POSITIVE);
+        assert_eq!(limiter.state.lock().unwrap().interval, MAX_INTERVAL);
+    }
+}
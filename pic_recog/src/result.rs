@@ -0,0 +1,214 @@
+//! 结构化识别结果
+//!
+//! 将 `include_position = true` 时返回的 `words_result` JSON 解析为带位置的单词列表。
+//! 位置可能是轴对齐矩形（`location`），也可能是旋转文本的多边形顶点
+//! （`vertexes_location` 或 `boundingBox`）。
+
+use crate::error::{ImageRecognitionError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 识别出的单个文本片段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OcrWord {
+    /// 文本内容
+    pub text: String,
+    /// 位置信息，响应中没有时为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+}
+
+/// 文本位置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Location {
+    /// 轴对齐矩形
+    Rect {
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+    /// 多边形顶点（旋转文本），按响应中的顺序排列
+    Polygon { points: Vec<(i32, i32)> },
+}
+
+impl Location {
+    /// 外接矩形，矩形直接返回自身
+    pub fn bounding_rect(&self) -> Location {
+        match self {
+            Location::Rect { .. } => self.clone(),
+            Location::Polygon { points } => polygon_bounding_rect(points),
+        }
+    }
+}
+
+/// 计算多边形的外接矩形，空多边形返回零大小矩形
+pub fn polygon_bounding_rect(points: &[(i32, i32)]) -> Location {
+    let Some(&(first_x, first_y)) = points.first() else {
+        return Location::Rect {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        };
+    };
+
+    let (min_x, min_y, max_x, max_y) = points.iter().fold(
+        (first_x, first_y, first_x, first_y),
+        |(min_x, min_y, max_x, max_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        },
+    );
+    Location::Rect {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x,
+        height: max_y - min_y,
+    }
+}
+
+/// 解析 `words_result` JSON（数组）为结构化结果
+pub fn parse_words_result(json: &str) -> Result<Vec<OcrWord>> {
+    let value: Value = serde_json::from_str(json)?;
+    let items = value.as_array().ok_or_else(|| {
+        ImageRecognitionError::Parse("识别结果不是 words_result 数组".to_string())
+    })?;
+
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            let text = item
+                .get("words")
+                .or_else(|| item.get("text"))
+                .and_then(Value::as_str)?;
+            Some(OcrWord {
+                text: text.to_string(),
+                location: parse_location(item),
+            })
+        })
+        .collect())
+}
+
+/// 优先使用多边形顶点，其次是矩形
+fn parse_location(item: &Value) -> Option<Location> {
+    if let Some(points) = item
+        .get("vertexes_location")
+        .or_else(|| item.get("polygon"))
+        .and_then(parse_points)
+    {
+        return Some(Location::Polygon { points });
+    }
+
+    // 形如 "x1,y1,x2,y2,x3,y3,x4,y4" 的顶点字符串
+    if let Some(points) = item
+        .get("boundingBox")
+        .and_then(Value::as_str)
+        .and_then(parse_point_string)
+    {
+        return Some(Location::Polygon { points });
+    }
+
+    let location = item.get("location")?;
+    let number = |key: &str| location.get(key).and_then(Value::as_i64).map(|v| v as i32);
+    Some(Location::Rect {
+        x: number("left")?,
+        y: number("top")?,
+        width: number("width")?,
+        height: number("height")?,
+    })
+}
+
+/// 解析 `[{"x":1,"y":2}, ...]` 或 `[[1,2], ...]`
+fn parse_points(value: &Value) -> Option<Vec<(i32, i32)>> {
+    let points = value
+        .as_array()?
+        .iter()
+        .map(|point| {
+            let (x, y) = match point {
+                Value::Object(map) => (map.get("x")?, map.get("y")?),
+                Value::Array(pair) if pair.len() == 2 => (&pair[0], &pair[1]),
+                _ => return None,
+            };
+            Some((x.as_i64()? as i32, y.as_i64()? as i32))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!points.is_empty()).then_some(points)
+}
+
+fn parse_point_string(value: &str) -> Option<Vec<(i32, i32)>> {
+    let numbers = value
+        .split(',')
+        .map(|n| n.trim().parse::<i32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if numbers.is_empty() || numbers.len() % 2 != 0 {
+        return None;
+    }
+    Some(numbers.chunks_exact(2).map(|p| (p[0], p[1])).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rect_response() {
+        let json = r#"[{"words": "hello", "location": {"left": 10, "top": 20, "width": 30, "height": 12}}]"#;
+        let words = parse_words_result(json).unwrap();
+        assert_eq!(
+            words,
+            vec![OcrWord {
+                text: "hello".to_string(),
+                location: Some(Location::Rect {
+                    x: 10,
+                    y: 20,
+                    width: 30,
+                    height: 12
+                }),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_polygon_response() {
+        let json = r#"[
+            {"words": "rotated", "vertexes_location": [{"x": 10, "y": 5}, {"x": 50, "y": 15}, {"x": 45, "y": 35}, {"x": 5, "y": 25}]},
+            {"words": "youdao", "boundingBox": "1,2,9,2,9,8,1,8"}
+        ]"#;
+        let words = parse_words_result(json).unwrap();
+
+        let Some(Location::Polygon { points }) = &words[0].location else {
+            panic!("expected polygon: {:?}", words[0].location);
+        };
+        assert_eq!(points, &vec![(10, 5), (50, 15), (45, 35), (5, 25)]);
+        assert_eq!(
+            words[0].location.as_ref().unwrap().bounding_rect(),
+            Location::Rect {
+                x: 5,
+                y: 5,
+                width: 45,
+                height: 30
+            }
+        );
+        assert_eq!(
+            words[1].location,
+            Some(Location::Polygon {
+                points: vec![(1, 2), (9, 2), (9, 8), (1, 8)]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_word_without_location() {
+        let words = parse_words_result(r#"[{"words": "plain"}]"#).unwrap();
+        assert_eq!(words[0].location, None);
+    }
+
+    #[test]
+    fn test_parse_invalid_json_is_parse_error() {
+        let err = parse_words_result("{not json").unwrap_err();
+        assert!(matches!(err, ImageRecognitionError::Parse(_)), "{err:?}");
+        let err = parse_words_result(r#"{"words": "plain"}"#).unwrap_err();
+        assert!(matches!(err, ImageRecognitionError::Parse(_)), "{err:?}");
+    }
+}
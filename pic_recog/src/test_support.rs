@@ -0,0 +1,221 @@
+//! 测试用的模拟 HTTP 服务
+//!
+//! 在后台线程中顺序处理请求，每个响应后关闭连接，足以模拟远程 OCR 服务。
+//! 本 crate 的单元测试直接使用，其他 crate 的测试需要开启 `test-support` feature。
+
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+/// 模拟服务收到的请求
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    /// 不含查询参数的路径
+    pub path: String,
+    pub query: String,
+    /// 请求头，名称已转为小写
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub arrived: Instant,
+}
+
+impl MockRequest {
+    /// 按名称（不区分大小写）读取请求头
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// 读取查询参数（不做 URL 解码）
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// 按 JSON 解析请求体
+    pub fn json(&self) -> Option<Value> {
+        serde_json::from_slice(&self.body).ok()
+    }
+}
+
+/// 模拟服务的响应
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// 空响应体
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// 200 JSON 响应
+    pub fn json(value: Value) -> Self {
+        Self::bytes("application/json", value.to_string())
+    }
+
+    /// 200 响应，使用给定的 content-type
+    pub fn bytes(content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            body: body.into(),
+            ..Self::status(200)
+        }
+        .with_header("Content-Type", content_type)
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn write_to(&self, mut stream: impl Write) -> std::io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\n",
+            self.status,
+            reason(self.status)
+        )?;
+        for (name, value) in &self.headers {
+            write!(stream, "{name}: {value}\r\n")?;
+        }
+        write!(
+            stream,
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.body.len()
+        )?;
+        stream.write_all(&self.body)?;
+        stream.flush()
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        302 => "Found",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
+}
+
+/// 监听 127.0.0.1 随机端口的模拟 HTTP 服务
+pub struct MockServer {
+    base_url: String,
+    received: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    /// 启动服务，每个请求交给 `handler` 生成响应
+    pub fn spawn(mut handler: impl FnMut(&MockRequest) -> MockResponse + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let recorded = received.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let mut reader = BufReader::new(stream);
+                let Some(request) = read_request(&mut reader) else {
+                    continue;
+                };
+                recorded.lock().unwrap().push(request.clone());
+                let _ = handler(&request).write_to(reader.into_inner());
+            }
+        });
+
+        Self {
+            base_url: format!("http://{addr}"),
+            received,
+        }
+    }
+
+    /// 服务地址，如 `http://127.0.0.1:12345`
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// 已收到的请求数
+    pub fn request_count(&self) -> usize {
+        self.received.lock().unwrap().len()
+    }
+
+    /// 已收到的请求，按到达顺序
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+fn read_request(reader: &mut impl BufRead) -> Option<MockRequest> {
+    let arrived = Instant::now();
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(MockRequest {
+        method,
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body,
+        arrived,
+    })
+}
+
+/// 远程 OCR 服务的标准流程：`/perm` 返回 token，`/start` 返回任务 `job-1`，
+/// 其余请求视为状态查询，直接返回识别完成与给定的 `words_result`
+pub fn ocr_engine(words_result: Value) -> impl FnMut(&MockRequest) -> MockResponse + Send {
+    move |request| {
+        if request.path.starts_with("/perm") {
+            MockResponse::json(json!({ "data": { "token": "mock-token" } }))
+        } else if request.path.starts_with("/start") {
+            MockResponse::json(json!({ "data": { "jobStatusId": "job-1" } }))
+        } else {
+            MockResponse::json(json!({
+                "code": 1,
+                "data": { "isEnded": true, "ydResp": { "words_result": words_result } }
+            }))
+        }
+    }
+}
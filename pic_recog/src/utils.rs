@@ -2,9 +2,13 @@
 //!
 //! 提供跨引擎使用的工具函数
 
-use crate::error::ImageRecognitionError;
-use image::image_dimensions;
+use crate::error::{ImageRecognitionError, Result};
+use image::codecs::jpeg::JpegEncoder;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use sha1::{Digest, Sha1};
 use std::fs;
+use std::io::Cursor;
 use std::path::Path;
 
 /// 支持的图片格式
@@ -12,13 +16,20 @@ const SUPPORTED_FORMATS: &[&str] = &["jpg", "jpeg", "png", "bmp", "tiff", "tif",
 
 /// 远程 OCR 支持的图片及文档格式
 const REMOTE_SUPPORTED_FORMATS: &[&str] = &[
-    "png", "jpg", "jpeg", "bmp", "gif", "tiff", "tif", "webp", "pdf",
+    "png", "jpg", "jpeg", "bmp", "gif", "tiff", "tif", "webp", "heic", "heif", "pdf",
+];
+
+/// HEIC/HEIF 文件 `ftyp` 盒中可能出现的品牌
+const HEIF_BRANDS: &[&[u8; 4]] = &[
+    b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis", b"mif1", b"msf1",
 ];
 
 const MAX_REMOTE_PAYLOAD_BYTES: u64 = 10 * 1024 * 1024; // 10MB
 const MIN_DIMENSION: u32 = 16;
 const MAX_DIMENSION: u32 = 8192;
 const MAX_ASPECT_RATIO: f32 = 50.0;
+/// 摆正后重新编码 JPEG 使用的质量
+const AUTO_ORIENT_JPEG_QUALITY: u8 = 90;
 
 /// 远程 OCR 通过校验的图片负载
 pub struct RemoteImagePayload {
@@ -30,7 +41,16 @@ pub struct RemoteImagePayload {
 
 impl RemoteImagePayload {
     /// 获取与文件扩展名对应的 MIME 类型
-    pub fn mime_type(&self) -> Result<&'static str, ImageRecognitionError> {
+    pub fn mime_type(&self) -> Result<&'static str> {
+        // 内容与扩展名不一致时以内容为准（如扩展名为 png 的 WebP 截图）
+        if self.format != "pdf"
+            && !is_heif_format(&self.format)
+            && let Ok(format) = image::guess_format(&self.bytes)
+            && let Some(mime) = image_format_mime(format)
+        {
+            return Ok(mime);
+        }
+
         match self.format.as_str() {
             "png" => Ok("image/png"),
             "jpg" | "jpeg" => Ok("image/jpeg"),
@@ -38,12 +58,31 @@ impl RemoteImagePayload {
             "gif" => Ok("image/gif"),
             "tiff" | "tif" => Ok("image/tiff"),
             "webp" => Ok("image/webp"),
+            "heic" => Ok("image/heic"),
+            "heif" => Ok("image/heif"),
             "pdf" => Ok("application/pdf"),
             other => Err(ImageRecognitionError::UnsupportedFormat(other.to_string())),
         }
     }
 }
 
+/// 是否为 HEIC/HEIF 格式
+pub fn is_heif_format(format: &str) -> bool {
+    matches!(format, "heic" | "heif")
+}
+
+fn image_format_mime(format: ImageFormat) -> Option<&'static str> {
+    match format {
+        ImageFormat::Png => Some("image/png"),
+        ImageFormat::Jpeg => Some("image/jpeg"),
+        ImageFormat::Bmp => Some("image/bmp"),
+        ImageFormat::Gif => Some("image/gif"),
+        ImageFormat::Tiff => Some("image/tiff"),
+        ImageFormat::WebP => Some("image/webp"),
+        _ => None,
+    }
+}
+
 /// 验证图片文件是否存在且格式支持
 ///
 /// # 参数
@@ -52,7 +91,7 @@ impl RemoteImagePayload {
 /// # 返回
 /// * `Ok(())` - 文件存在且格式支持
 /// * `Err(ImageRecognitionError)` - 文件不存在或格式不支持
-pub fn validate_image_path(image_path: &str) -> Result<(), ImageRecognitionError> {
+pub fn validate_image_path(image_path: &str) -> Result<()> {
     let path = Path::new(image_path);
 
     // 检查文件是否存在
@@ -75,22 +114,79 @@ pub fn validate_image_path(image_path: &str) -> Result<(), ImageRecognitionError
     Ok(())
 }
 
+/// 计算内容的 SHA-1 十六进制摘要
+pub fn sha1_hex(content: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(content);
+    let digest = hasher.finalize();
+    format!("{digest:x}")
+}
+
 /// 获取支持的图片格式列表
 pub fn supported_formats() -> &'static [&'static str] {
     SUPPORTED_FORMATS
 }
 
+/// 获取远程 OCR 支持的格式列表
+pub fn remote_supported_formats() -> &'static [&'static str] {
+    REMOTE_SUPPORTED_FORMATS
+}
+
 /// 加载并校验远程 OCR 图片输入
-pub fn load_and_validate_remote_image(
-    image_path: &str,
-) -> Result<RemoteImagePayload, ImageRecognitionError> {
+pub fn load_and_validate_remote_image(image_path: &str) -> Result<RemoteImagePayload> {
     let path = Path::new(image_path);
 
     if !path.exists() {
         return Err(ImageRecognitionError::FileNotFound(image_path.to_string()));
     }
 
-    let ext = path
+    let ext = remote_extension(image_path)?;
+
+    // 先根据文件大小和文件头中的尺寸快速拒绝，避免读入整张超限图片
+    validate_payload_size(fs::metadata(path)?.len())?;
+    if ext != "pdf" && !is_heif_format(&ext) {
+        let (width, height) = probe_file_dimensions(path, &ext).map_err(|err| {
+            ImageRecognitionError::ValidationError(format!("读取图片尺寸失败: {err}"))
+        })?;
+        validate_dimensions(width, height)?;
+    }
+
+    let bytes = fs::read(path)?;
+    validate_remote_bytes(bytes, ext)
+}
+
+/// 只读取文件头获取图片尺寸，不解码像素数据
+pub fn probe_file_dimensions(path: &Path, ext: &str) -> image::ImageResult<(u32, u32)> {
+    let mut reader = ImageReader::open(path)?.with_guessed_format()?;
+    if reader.format().is_none()
+        && let Some(format) = ImageFormat::from_extension(ext)
+    {
+        reader.set_format(format);
+    }
+    reader.into_dimensions()
+}
+
+/// 根据文件头探测图片格式与尺寸，无法识别为图片时返回 `None`
+pub fn probe_file_format(path: &Path) -> Option<(&'static str, u32, u32)> {
+    let reader = ImageReader::open(path).ok()?.with_guessed_format().ok()?;
+    let name = reader.format()?.extensions_str().first().copied()?;
+    let (width, height) = reader.into_dimensions().ok()?;
+    Some((name, width, height))
+}
+
+/// 校验内存中的远程 OCR 图片输入
+///
+/// 适用于上传或下载得到的图片数据，`file_name` 仅用于推断格式。
+pub fn load_and_validate_remote_image_bytes(
+    bytes: Vec<u8>,
+    file_name: &str,
+) -> Result<RemoteImagePayload> {
+    let ext = remote_extension(file_name)?;
+    validate_remote_bytes(bytes, ext)
+}
+
+fn remote_extension(file_name: &str) -> Result<String> {
+    let ext = Path::new(file_name)
         .extension()
         .and_then(|ext| ext.to_str())
         .map(|ext| ext.to_lowercase())
@@ -100,17 +196,21 @@ pub fn load_and_validate_remote_image(
         return Err(ImageRecognitionError::UnsupportedFormat(ext));
     }
 
-    let bytes = fs::read(path)?;
+    Ok(ext)
+}
 
-    if bytes.len() as u64 > MAX_REMOTE_PAYLOAD_BYTES {
-        return Err(ImageRecognitionError::ValidationError(format!(
-            "图片体积超出限制 (最大 10MB)，当前大小: {:.2}MB",
-            bytes.len() as f64 / 1_048_576.0
-        )));
-    }
+fn validate_remote_bytes(bytes: Vec<u8>, ext: String) -> Result<RemoteImagePayload> {
+    validate_payload_size(bytes.len() as u64)?;
 
-    if ext != "pdf" {
-        let (width, height) = image_dimensions(path).map_err(|err| {
+    // image crate 无法解码 HEIC/HEIF，只校验文件头，尺寸交由远程服务判断
+    if is_heif_format(&ext) {
+        if !has_heif_signature(&bytes) {
+            return Err(ImageRecognitionError::ValidationError(
+                "文件内容不是有效的 HEIC/HEIF 图片".to_string(),
+            ));
+        }
+    } else if ext != "pdf" {
+        let (width, height) = read_dimensions(&bytes, &ext).map_err(|err| {
             ImageRecognitionError::ValidationError(format!("读取图片尺寸失败: {err}"))
         })?;
 
@@ -132,7 +232,41 @@ pub fn load_and_validate_remote_image(
     })
 }
 
-fn validate_dimensions(width: u32, height: u32) -> Result<(), ImageRecognitionError> {
+fn validate_payload_size(size: u64) -> Result<()> {
+    if size > MAX_REMOTE_PAYLOAD_BYTES {
+        return Err(ImageRecognitionError::ValidationError(format!(
+            "图片体积超出限制 (最大 10MB)，当前大小: {:.2}MB",
+            size as f64 / 1_048_576.0
+        )));
+    }
+    Ok(())
+}
+
+/// 检查 ISO BMFF `ftyp` 盒中的主品牌及兼容品牌
+fn has_heif_signature(bytes: &[u8]) -> bool {
+    if bytes.len() < 16 || &bytes[4..8] != b"ftyp" {
+        return false;
+    }
+    let box_size = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+    let end = box_size.clamp(16, bytes.len());
+    // 主品牌位于 8..12，12..16 为版本号，之后是兼容品牌列表
+    std::iter::once(&bytes[8..12])
+        .chain(bytes[16..end].chunks_exact(4))
+        .any(|brand| HEIF_BRANDS.iter().any(|known| brand == known.as_slice()))
+}
+
+/// 优先根据内容识别格式，无法识别时退回扩展名，只解析文件头
+fn read_dimensions(bytes: &[u8], ext: &str) -> image::ImageResult<(u32, u32)> {
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    if reader.format().is_none()
+        && let Some(format) = ImageFormat::from_extension(ext)
+    {
+        reader.set_format(format);
+    }
+    reader.into_dimensions()
+}
+
+fn validate_dimensions(width: u32, height: u32) -> Result<()> {
     if width < MIN_DIMENSION || height < MIN_DIMENSION {
         return Err(ImageRecognitionError::ValidationError(format!(
             "图片尺寸过小: {width}x{height}，要求最小边 >= {MIN_DIMENSION} 像素"
@@ -167,6 +301,52 @@ fn validate_dimensions(width: u32, height: u32) -> Result<(), ImageRecognitionEr
     Ok(())
 }
 
+/// 按 EXIF 方向标记把图片摆正
+///
+/// 解码后旋转/翻转像素并按原格式重新编码，重新编码的数据不再携带 EXIF，
+/// 避免下游再次旋转。没有方向标记（或已是正向）、以及 PDF/HEIC 等无法在本地
+/// 解码的格式返回 `None`，调用方继续使用原始数据。
+pub fn auto_orient(payload: &RemoteImagePayload) -> Result<Option<RemoteImagePayload>> {
+    if payload.format == "pdf" || is_heif_format(&payload.format) {
+        return Ok(None);
+    }
+
+    let reader = ImageReader::new(Cursor::new(&payload.bytes)).with_guessed_format()?;
+    let Some(format) = reader.format() else {
+        return Ok(None);
+    };
+    let mut decoder = reader.into_decoder().map_err(decode_error)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    if orientation == Orientation::NoTransforms {
+        return Ok(None);
+    }
+
+    let mut image = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+    image.apply_orientation(orientation);
+
+    let mut bytes = Vec::new();
+    if format == ImageFormat::Jpeg {
+        // 默认质量偏低，影响小字识别
+        let encoder = JpegEncoder::new_with_quality(&mut bytes, AUTO_ORIENT_JPEG_QUALITY);
+        image.to_rgb8().write_with_encoder(encoder)
+    } else {
+        image.write_to(&mut Cursor::new(&mut bytes), format)
+    }
+    .map_err(|err| ImageRecognitionError::EngineError(format!("图片摆正后重新编码失败: {err}")))?;
+    validate_payload_size(bytes.len() as u64)?;
+
+    Ok(Some(RemoteImagePayload {
+        bytes,
+        width: Some(image.width()),
+        height: Some(image.height()),
+        format: payload.format.clone(),
+    }))
+}
+
+fn decode_error(err: image::ImageError) -> ImageRecognitionError {
+    ImageRecognitionError::ValidationError(format!("解码图片失败: {err}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,6 +359,11 @@ mod tests {
         assert!(formats.contains(&"jpeg"));
     }
 
+    #[test]
+    fn test_sha1_hex() {
+        assert_eq!(sha1_hex(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    }
+
     #[test]
     fn test_validate_dimensions_ok() {
         assert!(validate_dimensions(1024, 768).is_ok());
@@ -198,4 +383,149 @@ mod tests {
     fn test_validate_dimensions_rejects_ratio() {
         assert!(validate_dimensions(8000, 100).is_err());
     }
+
+    #[test]
+    fn test_load_remote_image_bytes() {
+        let mut bytes = Vec::new();
+        image::RgbImage::new(32, 24)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+
+        let payload = load_and_validate_remote_image_bytes(bytes, "shot.png").unwrap();
+        assert_eq!(payload.width, Some(32));
+        assert_eq!(payload.height, Some(24));
+        assert_eq!(payload.format, "png");
+    }
+
+    #[test]
+    fn test_load_remote_image_bytes_rejects_unknown_extension() {
+        assert!(matches!(
+            load_and_validate_remote_image_bytes(vec![0u8; 8], "shot.xyz"),
+            Err(ImageRecognitionError::UnsupportedFormat(_))
+        ));
+    }
+
+    /// 构造最小的 HEIC 文件头（仅包含 ftyp 盒）
+    fn heic_header() -> Vec<u8> {
+        let mut bytes = vec![0, 0, 0, 24];
+        bytes.extend_from_slice(b"ftypheic");
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+        bytes.extend_from_slice(b"mif1heic");
+        bytes.extend_from_slice(&[0u8; 32]);
+        bytes
+    }
+
+    #[test]
+    fn test_load_remote_webp_bytes() {
+        let mut bytes = Vec::new();
+        image::DynamicImage::ImageRgba8(image::RgbaImage::new(40, 30))
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::WebP)
+            .unwrap();
+
+        let payload = load_and_validate_remote_image_bytes(bytes.clone(), "shot.webp").unwrap();
+        assert_eq!(payload.width, Some(40));
+        assert_eq!(payload.mime_type().unwrap(), "image/webp");
+
+        // 扩展名错误时按内容推断 MIME
+        let payload = load_and_validate_remote_image_bytes(bytes, "shot.png").unwrap();
+        assert_eq!(payload.mime_type().unwrap(), "image/webp");
+    }
+
+    #[test]
+    fn test_load_remote_heic_bytes() {
+        let payload = load_and_validate_remote_image_bytes(heic_header(), "IMG_0001.HEIC").unwrap();
+        assert_eq!(payload.format, "heic");
+        assert_eq!(payload.width, None);
+        assert_eq!(payload.mime_type().unwrap(), "image/heic");
+    }
+
+    #[test]
+    fn test_load_remote_heic_rejects_invalid_signature() {
+        assert!(matches!(
+            load_and_validate_remote_image_bytes(vec![0u8; 64], "IMG_0001.heic"),
+            Err(ImageRecognitionError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_oversized_dimensions_rejected_from_header_only() {
+        let mut bytes = Vec::new();
+        image::GrayImage::new(9000, 20)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        // 截断到 IDAT 数据开头：像素数据不完整，完整解码必然失败，
+        // 因此能得到尺寸错误说明只读取了文件头
+        let idat = bytes.windows(4).position(|w| w == b"IDAT").unwrap();
+        bytes.truncate(idat + 8);
+
+        let path = std::env::temp_dir().join(format!("pic_recog_probe_{}.png", std::process::id()));
+        fs::write(&path, &bytes).unwrap();
+
+        let started = std::time::Instant::now();
+        let result = load_and_validate_remote_image(path.to_str().unwrap());
+        let elapsed = started.elapsed();
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(ImageRecognitionError::ValidationError(msg)) => assert!(msg.contains("尺寸过大")),
+            other => panic!("unexpected result: {:?}", other.map(|p| p.format)),
+        }
+        assert!(elapsed < std::time::Duration::from_millis(500));
+    }
+
+    /// 在 JPEG 的 SOI 之后插入只包含方向标记的 EXIF（APP1）段
+    fn with_exif_orientation(jpeg: &[u8], orientation: u8) -> Vec<u8> {
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01".to_vec();
+        // 0x0112 Orientation，SHORT，数量 1，值左对齐；之后为下一个 IFD 偏移 0
+        exif.extend_from_slice(&[0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, orientation, 0, 0]);
+        exif.extend_from_slice(&[0, 0, 0, 0]);
+
+        let mut bytes = jpeg[..2].to_vec();
+        bytes.extend_from_slice(&[0xFF, 0xE1]);
+        bytes.extend_from_slice(&((exif.len() + 2) as u16).to_be_bytes());
+        bytes.extend_from_slice(&exif);
+        bytes.extend_from_slice(&jpeg[2..]);
+        bytes
+    }
+
+    /// 横向存储的 64x32 图片：左半黑、右半白
+    fn sideways_jpeg() -> Vec<u8> {
+        let image =
+            image::GrayImage::from_fn(64, 32, |x, _| image::Luma([if x < 32 { 0 } else { 255 }]));
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Jpeg)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_auto_orient_rotates_upright() {
+        // 方向 6：显示时需顺时针旋转 90°，原左侧（黑）转到上方
+        let bytes = with_exif_orientation(&sideways_jpeg(), 6);
+        let payload = load_and_validate_remote_image_bytes(bytes, "photo.jpg").unwrap();
+
+        let upright = auto_orient(&payload).unwrap().expect("should rotate");
+        assert_eq!((upright.width, upright.height), (Some(32), Some(64)));
+
+        let mut decoder = ImageReader::new(Cursor::new(&upright.bytes))
+            .with_guessed_format()
+            .unwrap()
+            .into_decoder()
+            .unwrap();
+        assert_eq!(decoder.orientation().unwrap(), Orientation::NoTransforms);
+        let image = DynamicImage::from_decoder(decoder).unwrap().to_luma8();
+        assert!(image.get_pixel(16, 8).0[0] < 64);
+        assert!(image.get_pixel(16, 56).0[0] > 192);
+    }
+
+    #[test]
+    fn test_auto_orient_without_exif_is_noop() {
+        let payload = load_and_validate_remote_image_bytes(sideways_jpeg(), "photo.jpg").unwrap();
+        assert!(auto_orient(&payload).unwrap().is_none());
+
+        let upright = with_exif_orientation(&sideways_jpeg(), 1);
+        let payload = load_and_validate_remote_image_bytes(upright, "photo.jpg").unwrap();
+        assert!(auto_orient(&payload).unwrap().is_none());
+    }
 }
@@ -0,0 +1,116 @@
+use pic_recog::test_support::{MockServer, ocr_engine};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const IMAGE_PATH: &str = "../manifest/dev/tm_1.png";
+
+/// 启动模拟的远程 OCR 服务，每个任务第一次查询状态即返回识别完成
+fn spawn_mock_engine() -> MockServer {
+    MockServer::spawn(ocr_engine(json!([
+        { "words": "hello", "location": { "left": 1, "top": 2, "width": 30, "height": 10 } },
+        { "words": "world", "location": { "left": 1, "top": 20, "width": 30, "height": 10 } }
+    ])))
+}
+
+/// 在临时目录中写入指向模拟服务的配置，返回该目录
+fn write_config(name: &str, base_url: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pic-recog-cli-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("remote_ocr.toml"),
+        format!(
+            r#"
+            perm_url = "{base_url}/perm"
+            start_url = "{base_url}/start"
+            status_url = "{base_url}/status"
+            auth_token = "token"
+            auth_uuid = "uuid"
+            auth_cookie = "cookie"
+            origin = "https://example.com"
+            poll_interval_ms = 10
+            "#
+        ),
+    )
+    .unwrap();
+    dir
+}
+
+fn pic_recog(config_dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_pic-recog"));
+    command.env("PIC_RECOG_CONFIG", config_dir.join("remote_ocr.toml"));
+    command
+}
+
+#[test]
+fn recognize_prints_text() {
+    let config_dir = write_config("text", spawn_mock_engine().base_url());
+
+    let output = pic_recog(&config_dir)
+        .args(["recognize", "--image", IMAGE_PATH])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\nworld\n");
+}
+
+#[test]
+fn recognize_prints_words_as_json() {
+    let config_dir = write_config("json", spawn_mock_engine().base_url());
+
+    let output = pic_recog(&config_dir)
+        .args([
+            "recognize",
+            "--image",
+            IMAGE_PATH,
+            "--position",
+            "--format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{output:?}");
+    let body: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(body["image"], IMAGE_PATH);
+    assert_eq!(body["words"][0]["text"], "hello");
+    assert_eq!(body["words"][1]["location"]["type"], "rect");
+}
+
+#[test]
+fn recognize_batch_covers_every_image() {
+    let config_dir = write_config("batch", spawn_mock_engine().base_url());
+    let images = config_dir.join("images");
+    std::fs::create_dir_all(&images).unwrap();
+    for name in ["a.png", "b.png"] {
+        std::fs::copy(IMAGE_PATH, images.join(name)).unwrap();
+    }
+    std::fs::write(images.join("notes.txt"), "not an image").unwrap();
+
+    let output = pic_recog(&config_dir)
+        .args(["recognize", "--format", "json", "--batch"])
+        .arg(&images)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{output:?}");
+    let body: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let results = body.as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["image"], "a.png");
+    assert_eq!(results[1]["image"], "b.png");
+    assert_eq!(results[1]["text"], "hello\nworld");
+}
+
+#[test]
+fn missing_config_reports_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pic-recog"))
+        .args(["recognize", "--image", IMAGE_PATH])
+        .env("PIC_RECOG_CONFIG", "/nonexistent/remote_ocr.toml")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to load config"));
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+
+/// MySQL 唯一键冲突（ER_DUP_ENTRY）
+const ER_DUP_ENTRY: u16 = 1062;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptError {
+    NotFound(String),
+    Conflict(String),
+    Database(String),
+    Validation(String),
+}
+
+impl fmt::Display for PromptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(f, "PromptTemplate not found: id={id}"),
+            Self::Conflict(message) => write!(f, "PromptTemplate conflict: {message}"),
+            Self::Database(message) => write!(f, "Prompt database error: {message}"),
+            Self::Validation(message) => write!(f, "Invalid PromptTemplate: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for PromptError {}
+
+pub type Result<T> = std::result::Result<T, PromptError>;
+
+impl PromptError {
+    /// 在错误信息前补充上下文，不改变错误类型
+    fn with_context(self, context: &str) -> Self {
+        match self {
+            Self::NotFound(id) => Self::NotFound(id),
+            Self::Conflict(message) => Self::Conflict(format!("{context}: {message}")),
+            Self::Database(message) => Self::Database(format!("{context}: {message}")),
+            Self::Validation(message) => Self::Validation(format!("{context}: {message}")),
+        }
+    }
+}
+
+impl From<mysql_async::Error> for PromptError {
+    fn from(err: mysql_async::Error) -> Self {
+        match &err {
+            mysql_async::Error::Server(server) if server.code == ER_DUP_ENTRY => {
+                Self::Conflict(server.message.clone())
+            }
+            _ => Self::Database(err.to_string()),
+        }
+    }
+}
+
+/// 存储层只对已入库的 tags/variables 做 JSON 编解码，失败说明数据损坏而不是请求有误；
+/// 请求体的解析错误由路由层单独返回 400
+impl From<serde_json::Error> for PromptError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Database(format!("invalid stored JSON: {err}"))
+    }
+}
+
+/// 为存储层的 `Result`/`Option` 补充错误上下文
+pub(crate) trait Context<T> {
+    fn context(self, context: &str) -> Result<T>;
+
+    fn with_context(self, context: impl FnOnce() -> String) -> Result<T>;
+}
+
+impl<T, E: Into<PromptError>> Context<T> for std::result::Result<T, E> {
+    fn context(self, context: &str) -> Result<T> {
+        self.map_err(|err| err.into().with_context(context))
+    }
+
+    fn with_context(self, context: impl FnOnce() -> String) -> Result<T> {
+        self.map_err(|err| err.into().with_context(&context()))
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context(self, context: &str) -> Result<T> {
+        self.ok_or_else(|| PromptError::Database(context.to_string()))
+    }
+
+    fn with_context(self, context: impl FnOnce() -> String) -> Result<T> {
+        self.ok_or_else(|| PromptError::Database(context()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_error(code: u16) -> mysql_async::Error {
+        mysql_async::Error::Server(mysql_async::ServerError {
+            code,
+            message: "Duplicate entry 'abc' for key 'PRIMARY'".to_string(),
+            state: "23000".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_duplicate_entry_maps_to_conflict() {
+        assert!(matches!(
+            PromptError::from(server_error(ER_DUP_ENTRY)),
+            PromptError::Conflict(_)
+        ));
+        assert!(matches!(
+            PromptError::from(server_error(1146)),
+            PromptError::Database(_)
+        ));
+    }
+
+    #[test]
+    fn test_stored_json_error_maps_to_database() {
+        let err: PromptError = serde_json::from_str::<Vec<String>>("[not json")
+            .unwrap_err()
+            .into();
+        assert!(matches!(err, PromptError::Database(_)), "{err:?}");
+    }
+
+    #[test]
+    fn test_context_keeps_variant() {
+        let err = Err::<(), _>(server_error(ER_DUP_ENTRY))
+            .context("Failed to insert prompt template")
+            .unwrap_err();
+        assert_eq!(
+            err,
+            PromptError::Conflict(
+                "Failed to insert prompt template: Duplicate entry 'abc' for key 'PRIMARY'"
+                    .to_string()
+            )
+        );
+        assert!(matches!(
+            None::<String>.context("Missing id"),
+            Err(PromptError::Database(_))
+        ));
+    }
+}
@@ -1,5 +1,9 @@
+pub mod error;
 pub mod models;
 pub mod storage;
+pub mod store;
 
-pub use models::{PaginatedResult, PaginationParams, PromptCategory, PromptTemplate};
+pub use error::{PromptError, Result};
+pub use models::{PaginatedResult, PaginationParams, PromptCategory, PromptTemplate, SortBy};
 pub use storage::PromptTemplateManager;
+pub use store::{InMemoryPromptStore, PromptStore};
@@ -1,10 +1,17 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+use crate::error::{PromptError, Result};
+
+/// 模板名称的最大长度
+const MAX_NAME_LEN: usize = 255;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum PromptCategory {
+    #[default]
     Chat,
     Completion,
     Assistant,
@@ -12,13 +19,18 @@ pub enum PromptCategory {
     Custom,
 }
 
-impl Default for PromptCategory {
-    fn default() -> Self {
-        Self::Chat
+impl PromptCategory {
+    /// 全部分类，按声明顺序排列
+    pub fn all() -> &'static [PromptCategory] {
+        &[
+            Self::Chat,
+            Self::Completion,
+            Self::Assistant,
+            Self::Agent,
+            Self::Custom,
+        ]
     }
-}
 
-impl PromptCategory {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Chat => "chat",
@@ -41,6 +53,12 @@ impl PromptCategory {
     }
 }
 
+impl fmt::Display for PromptCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PromptTemplate {
     pub id: String,
@@ -55,6 +73,9 @@ pub struct PromptTemplate {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub created_by: Option<String>,
+    /// 软删除时间，未删除时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl PromptTemplate {
@@ -73,6 +94,7 @@ impl PromptTemplate {
             created_at: now,
             updated_at: now,
             created_by: None,
+            deleted_at: None,
         }
     }
 
@@ -106,6 +128,83 @@ impl PromptTemplate {
         self.version += 1;
         self.updated_at = Utc::now();
     }
+
+    /// 添加标签并递增版本，标签已存在时不做修改；返回是否发生了变化
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        if self.tags.iter().any(|t| t == tag) {
+            return false;
+        }
+        self.tags.push(tag.to_string());
+        self.version += 1;
+        self.updated_at = Utc::now();
+        true
+    }
+
+    /// 移除标签并递增版本，标签不存在时不做修改；返回是否发生了变化
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let before = self.tags.len();
+        self.tags.retain(|t| t != tag);
+        if self.tags.len() == before {
+            return false;
+        }
+        self.version += 1;
+        self.updated_at = Utc::now();
+        true
+    }
+
+    /// 是否已被软删除
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// 校验写入前的必填字段，名称长度受 `name` 列 VARCHAR(255) 限制
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(PromptError::Validation(
+                "name must not be empty".to_string(),
+            ));
+        }
+        if self.name.chars().count() > MAX_NAME_LEN {
+            return Err(PromptError::Validation(format!(
+                "name must be at most {MAX_NAME_LEN} characters"
+            )));
+        }
+        if self.content.is_empty() {
+            return Err(PromptError::Validation(
+                "content must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 校验单个标签，不能为空白
+pub fn validate_tag(tag: &str) -> Result<()> {
+    if tag.trim().is_empty() {
+        return Err(PromptError::Validation("tag must not be empty".to_string()));
+    }
+    Ok(())
+}
+
+/// 列表排序字段，均按时间倒序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortBy {
+    /// 按创建时间
+    #[default]
+    Created,
+    /// 按最近修改时间
+    Updated,
+}
+
+impl SortBy {
+    /// 对应的数据库列名
+    pub fn column(&self) -> &'static str {
+        match self {
+            SortBy::Created => "created_at",
+            SortBy::Updated => "updated_at",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,6 +213,8 @@ pub struct PaginationParams {
     pub page: u32,
     #[serde(default = "default_page_size")]
     pub page_size: u32,
+    #[serde(default)]
+    pub sort: SortBy,
 }
 
 fn default_page() -> u32 {
@@ -129,6 +230,7 @@ impl Default for PaginationParams {
         Self {
             page: 1,
             page_size: 20,
+            sort: SortBy::default(),
         }
     }
 }
@@ -138,9 +240,15 @@ impl PaginationParams {
         Self {
             page: page.max(1),
             page_size: page_size.clamp(1, 100),
+            sort: SortBy::default(),
         }
     }
 
+    pub fn with_sort(mut self, sort: SortBy) -> Self {
+        self.sort = sort;
+        self
+    }
+
     pub fn offset(&self) -> u64 {
         ((self.page - 1) * self.page_size) as u64
     }
@@ -176,6 +284,41 @@ impl<T> PaginatedResult<T> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_add_and_remove_tag() {
+        let mut template = PromptTemplate::new("test".to_string(), "Hello".to_string())
+            .with_tags(vec!["a".to_string()]);
+
+        assert!(template.add_tag("b"));
+        assert_eq!(template.tags, ["a", "b"]);
+        assert_eq!(template.version, 2);
+
+        // 重复添加与移除不存在的标签都不改变版本
+        assert!(!template.add_tag("a"));
+        assert!(!template.remove_tag("missing"));
+        assert_eq!(template.version, 2);
+
+        assert!(template.remove_tag("a"));
+        assert_eq!(template.tags, ["b"]);
+        assert_eq!(template.version, 3);
+
+        assert!(validate_tag(" ").is_err());
+        assert!(validate_tag("b").is_ok());
+    }
+
+    #[test]
+    fn test_prompt_category_all_round_trips() {
+        assert_eq!(PromptCategory::all().len(), 5);
+        for category in PromptCategory::all() {
+            assert_eq!(
+                PromptCategory::parse(category.as_str()).as_ref(),
+                Some(category)
+            );
+            assert_eq!(category.to_string(), category.as_str());
+        }
+        assert_eq!(PromptCategory::default(), PromptCategory::Chat);
+    }
+
     #[test]
     fn test_prompt_template_creation() {
         let template = PromptTemplate::new("test".to_string(), "Hello {{name}}!".to_string());
@@ -204,6 +347,19 @@ mod tests {
         assert_eq!(template.tags.len(), 2);
     }
 
+    #[test]
+    fn test_prompt_template_validate() {
+        let template = PromptTemplate::new("ok".to_string(), "content".to_string());
+        assert!(template.validate().is_ok());
+
+        let blank = PromptTemplate::new("  ".to_string(), "content".to_string());
+        assert!(matches!(blank.validate(), Err(PromptError::Validation(_))));
+        let long = PromptTemplate::new("n".repeat(MAX_NAME_LEN + 1), "content".to_string());
+        assert!(matches!(long.validate(), Err(PromptError::Validation(_))));
+        let empty = PromptTemplate::new("ok".to_string(), String::new());
+        assert!(matches!(empty.validate(), Err(PromptError::Validation(_))));
+    }
+
     #[test]
     fn test_pagination_params() {
         let params = PaginationParams::new(2, 50);
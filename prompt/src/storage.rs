@@ -1,14 +1,37 @@
-use anyhow::{Context, Result};
 use chrono::NaiveDateTime;
-use mysql_async::{Pool, Row, params, prelude::*};
-use tracing::{debug, info};
-
-use crate::models::{PaginatedResult, PaginationParams, PromptCategory, PromptTemplate};
+use futures::{Stream, StreamExt};
+use mysql_async::{Conn, Params, Pool, Row, TxOpts, params, prelude::*};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info};
+
+use crate::error::{Context, PromptError, Result};
+use crate::models::{
+    PaginatedResult, PaginationParams, PromptCategory, PromptTemplate, validate_tag,
+};
+
+/// 模板表应包含的列，启动时校验以便尽早发现表结构与代码不一致
+const EXPECTED_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "description",
+    "category",
+    "content",
+    "variables",
+    "tags",
+    "version",
+    "is_active",
+    "created_at",
+    "updated_at",
+    "created_by",
+    "deleted_at",
+];
 
 #[derive(Clone)]
 pub struct PromptTemplateManager {
     pool: Pool,
     table_name: String,
+    /// 建表语句的表选项（ENGINE/CHARSET/COLLATE）
+    table_options: String,
 }
 
 impl PromptTemplateManager {
@@ -19,11 +42,16 @@ impl PromptTemplateManager {
             config.mysql.host, config.mysql.port
         );
 
-        let pool = Pool::new(mysql_async::Opts::from_url(&url).context("Invalid MySQL URL")?);
+        let table_options = table_options(&config)?;
+        let pool = Pool::new(
+            mysql_async::Opts::from_url(&url)
+                .map_err(|e| PromptError::Validation(format!("Invalid MySQL URL: {e}")))?,
+        );
 
         let manager = Self {
             pool,
             table_name: format!("{}templates", config.table_prefix),
+            table_options,
         };
 
         manager.init_table().await?;
@@ -32,6 +60,17 @@ impl PromptTemplateManager {
         Ok(manager)
     }
 
+    /// 取一个连接并 ping，检查 MySQL 是否可用
+    pub async fn ping(&self) -> Result<()> {
+        let mut conn = self
+            .pool
+            .get_conn()
+            .await
+            .context("Failed to get MySQL connection")?;
+        conn.ping().await.context("Failed to ping MySQL")?;
+        Ok(())
+    }
+
     async fn init_table(&self) -> Result<()> {
         let mut conn = self
             .pool
@@ -53,59 +92,80 @@ impl PromptTemplateManager {
                 `created_at` DATETIME NOT NULL,
                 `updated_at` DATETIME NOT NULL,
                 `created_by` VARCHAR(255),
+                `deleted_at` DATETIME NULL,
                 INDEX `idx_name` (`name`),
                 INDEX `idx_category` (`category`),
                 INDEX `idx_is_active` (`is_active`),
-                INDEX `idx_created_at` (`created_at`)
-            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci"#,
-            self.table_name
+                INDEX `idx_created_at` (`created_at`),
+                INDEX `idx_updated_at` (`updated_at`)
+            ) {}"#,
+            self.table_name, self.table_options
         );
 
         conn.query_drop(&create_table_sql)
             .await
             .context("Failed to create table")?;
 
+        // 旧表没有 deleted_at 列，补齐软删除所需的列
+        let has_deleted_at: Option<u64> = conn
+            .exec_first(
+                "SELECT COUNT(*) FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = :table AND COLUMN_NAME = 'deleted_at'",
+                params! { "table" => &self.table_name },
+            )
+            .await
+            .context("Failed to inspect table columns")?;
+        if has_deleted_at.unwrap_or(0) == 0 {
+            conn.query_drop(format!(
+                "ALTER TABLE `{}` ADD COLUMN `deleted_at` DATETIME NULL",
+                self.table_name
+            ))
+            .await
+            .context("Failed to add deleted_at column")?;
+            info!("Added deleted_at column to '{}'", self.table_name);
+        }
+
+        self.check_columns(&mut conn).await?;
+
         debug!("Table '{}' initialized", self.table_name);
         Ok(())
     }
 
+    /// 校验表中包含全部预期列，缺失时列出缺少的列名
+    async fn check_columns(&self, conn: &mut Conn) -> Result<()> {
+        let columns: Vec<String> = conn
+            .exec(
+                "SELECT COLUMN_NAME FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = :table",
+                params! { "table" => &self.table_name },
+            )
+            .await
+            .context("Failed to inspect table columns")?;
+
+        let missing: Vec<&str> = EXPECTED_COLUMNS
+            .iter()
+            .copied()
+            .filter(|expected| !columns.iter().any(|c| c.eq_ignore_ascii_case(expected)))
+            .collect();
+        if !missing.is_empty() {
+            return Err(PromptError::Database(format!(
+                "Table '{}' is missing columns: {}",
+                self.table_name,
+                missing.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
     pub async fn create(&self, template: PromptTemplate) -> Result<PromptTemplate> {
+        template.validate()?;
         let mut conn = self
             .pool
             .get_conn()
             .await
             .context("Failed to get MySQL connection")?;
 
-        let variables_json = serde_json::to_string(&template.variables)?;
-        let tags_json = serde_json::to_string(&template.tags)?;
-        let created_at = template.created_at.naive_utc();
-        let updated_at = template.updated_at.naive_utc();
-
-        let insert_sql = format!(
-            r#"INSERT INTO `{}` (id, name, description, category, content, variables, tags, version, is_active, created_at, updated_at, created_by)
-               VALUES (:id, :name, :description, :category, :content, :variables, :tags, :version, :is_active, :created_at, :updated_at, :created_by)"#,
-            self.table_name
-        );
-
-        conn.exec_drop(
-            &insert_sql,
-            params! {
-                "id" => &template.id,
-                "name" => &template.name,
-                "description" => &template.description,
-                "category" => template.category.as_str(),
-                "content" => &template.content,
-                "variables" => &variables_json,
-                "tags" => &tags_json,
-                "version" => template.version,
-                "is_active" => template.is_active,
-                "created_at" => created_at,
-                "updated_at" => updated_at,
-                "created_by" => &template.created_by,
-            },
-        )
-        .await
-        .context("Failed to insert prompt template")?;
+        conn.exec_drop(self.insert_sql(), Self::insert_params(&template)?)
+            .await
+            .context("Failed to insert prompt template")?;
 
         info!(
             "✅ Created PromptTemplate: id={}, name={}",
@@ -114,6 +174,36 @@ impl PromptTemplateManager {
         Ok(template)
     }
 
+    fn insert_sql(&self) -> String {
+        format!(
+            r#"INSERT INTO `{}` (id, name, description, category, content, variables, tags, version, is_active, created_at, updated_at, created_by, deleted_at)
+               VALUES (:id, :name, :description, :category, :content, :variables, :tags, :version, :is_active, :created_at, :updated_at, :created_by, :deleted_at)"#,
+            self.table_name
+        )
+    }
+
+    fn insert_params(template: &PromptTemplate) -> Result<Params> {
+        let variables_json = serde_json::to_string(&template.variables)?;
+        let tags_json = serde_json::to_string(&template.tags)?;
+
+        Ok(params! {
+            "id" => &template.id,
+            "name" => &template.name,
+            "description" => &template.description,
+            "category" => template.category.as_str(),
+            "content" => &template.content,
+            "variables" => variables_json,
+            "tags" => tags_json,
+            "version" => template.version,
+            "is_active" => template.is_active,
+            "created_at" => template.created_at.naive_utc(),
+            "updated_at" => template.updated_at.naive_utc(),
+            "created_by" => &template.created_by,
+            "deleted_at" => template.deleted_at.map(|t| t.naive_utc()),
+        })
+    }
+
+    /// 按 id 获取未删除的模板，软删除的模板视为不存在
     pub async fn get(&self, id: &str) -> Result<Option<PromptTemplate>> {
         let mut conn = self
             .pool
@@ -122,7 +212,7 @@ impl PromptTemplateManager {
             .context("Failed to get MySQL connection")?;
 
         let select_sql = format!(
-            "SELECT id, name, description, category, content, variables, tags, version, is_active, created_at, updated_at, created_by FROM `{}` WHERE id = :id",
+            "SELECT id, name, description, category, content, variables, tags, version, is_active, created_at, updated_at, created_by, deleted_at FROM `{}` WHERE id = :id AND deleted_at IS NULL",
             self.table_name
         );
 
@@ -151,7 +241,10 @@ impl PromptTemplateManager {
             .await
             .context("Failed to get MySQL connection")?;
 
-        let count_sql = format!("SELECT COUNT(*) FROM `{}`", self.table_name);
+        let count_sql = format!(
+            "SELECT COUNT(*) FROM `{}` WHERE deleted_at IS NULL",
+            self.table_name
+        );
         let total: u64 = conn
             .query_first(&count_sql)
             .await
@@ -163,8 +256,9 @@ impl PromptTemplateManager {
         }
 
         let select_sql = format!(
-            "SELECT id, name, description, category, content, variables, tags, version, is_active, created_at, updated_at, created_by FROM `{}` ORDER BY created_at DESC LIMIT :limit OFFSET :offset",
-            self.table_name
+            "SELECT id, name, description, category, content, variables, tags, version, is_active, created_at, updated_at, created_by, deleted_at FROM `{}` WHERE deleted_at IS NULL ORDER BY {} DESC LIMIT :limit OFFSET :offset",
+            self.table_name,
+            params.sort.column()
         );
 
         let rows: Vec<Row> = conn
@@ -187,7 +281,9 @@ impl PromptTemplateManager {
         Ok(PaginatedResult::new(items, total, &params))
     }
 
+    /// 更新未删除的模板，软删除的模板返回 NotFound
     pub async fn update(&self, template: PromptTemplate) -> Result<PromptTemplate> {
+        template.validate()?;
         let mut conn = self
             .pool
             .get_conn()
@@ -201,7 +297,7 @@ impl PromptTemplateManager {
         let update_sql = format!(
             r#"UPDATE `{}` SET name = :name, description = :description, category = :category, content = :content, 
                variables = :variables, tags = :tags, version = :version, is_active = :is_active, updated_at = :updated_at, created_by = :created_by
-               WHERE id = :id"#,
+               WHERE id = :id AND deleted_at IS NULL"#,
             self.table_name
         );
 
@@ -227,14 +323,79 @@ impl PromptTemplateManager {
             .affected_rows();
 
         if affected == 0 {
-            anyhow::bail!("PromptTemplate not found: id={}", template.id);
+            return Err(PromptError::NotFound(template.id));
         }
 
         info!("✏️ Updated PromptTemplate: id={}", template.id);
         Ok(template)
     }
 
-    pub async fn delete(&self, id: &str) -> Result<bool> {
+    /// 删除模板，`soft` 为 true 时仅标记删除（可通过 `restore` 恢复）
+    pub async fn delete(&self, id: &str, soft: bool) -> Result<bool> {
+        if soft {
+            self.soft_delete(id).await
+        } else {
+            self.hard_delete(id).await
+        }
+    }
+
+    /// 软删除：置 is_active = false 并记录 deleted_at，默认列表中不再可见
+    pub async fn soft_delete(&self, id: &str) -> Result<bool> {
+        let mut conn = self
+            .pool
+            .get_conn()
+            .await
+            .context("Failed to get MySQL connection")?;
+
+        let update_sql = format!(
+            "UPDATE `{}` SET is_active = FALSE, deleted_at = :deleted_at WHERE id = :id AND deleted_at IS NULL",
+            self.table_name
+        );
+        let affected = conn
+            .exec_iter(
+                &update_sql,
+                params! { "id" => id, "deleted_at" => chrono::Utc::now().naive_utc() },
+            )
+            .await
+            .context("Failed to soft delete prompt template")?
+            .affected_rows();
+
+        let success = affected > 0;
+        if success {
+            info!("🗑️ Soft deleted PromptTemplate: id={}", id);
+        }
+
+        Ok(success)
+    }
+
+    /// 恢复软删除的模板
+    pub async fn restore(&self, id: &str) -> Result<bool> {
+        let mut conn = self
+            .pool
+            .get_conn()
+            .await
+            .context("Failed to get MySQL connection")?;
+
+        let update_sql = format!(
+            "UPDATE `{}` SET is_active = TRUE, deleted_at = NULL WHERE id = :id AND deleted_at IS NOT NULL",
+            self.table_name
+        );
+        let affected = conn
+            .exec_iter(&update_sql, params! { "id" => id })
+            .await
+            .context("Failed to restore prompt template")?
+            .affected_rows();
+
+        let success = affected > 0;
+        if success {
+            info!("♻️ Restored PromptTemplate: id={}", id);
+        }
+
+        Ok(success)
+    }
+
+    /// 永久删除模板
+    pub async fn hard_delete(&self, id: &str) -> Result<bool> {
         let mut conn = self
             .pool
             .get_conn()
@@ -256,6 +417,183 @@ impl PromptTemplateManager {
         Ok(success)
     }
 
+    /// 追加标签，只更新 tags、version 与 updated_at；标签已存在时不修改，软删除的模板返回 NotFound
+    pub async fn add_tag(&self, id: &str, tag: &str) -> Result<PromptTemplate> {
+        validate_tag(tag)?;
+        let mut conn = self
+            .pool
+            .get_conn()
+            .await
+            .context("Failed to get MySQL connection")?;
+
+        // 标签已存在时条件不成立，不会匹配任何行
+        let update_sql = format!(
+            r#"UPDATE `{}` SET tags = JSON_ARRAY_APPEND(COALESCE(tags, JSON_ARRAY()), '$', :tag),
+               version = version + 1, updated_at = :updated_at
+               WHERE id = :id AND deleted_at IS NULL AND NOT JSON_CONTAINS(COALESCE(tags, JSON_ARRAY()), JSON_QUOTE(:tag))"#,
+            self.table_name
+        );
+        let affected = conn
+            .exec_iter(
+                &update_sql,
+                params! {
+                    "id" => id,
+                    "tag" => tag,
+                    "updated_at" => chrono::Utc::now().naive_utc(),
+                },
+            )
+            .await
+            .context("Failed to add prompt template tag")?
+            .affected_rows();
+        drop(conn);
+
+        let template = self
+            .get(id)
+            .await?
+            .ok_or_else(|| PromptError::NotFound(id.to_string()))?;
+        if affected > 0 {
+            info!("🏷️ Added tag to PromptTemplate: id={}, tag={}", id, tag);
+        }
+        Ok(template)
+    }
+
+    /// 移除标签，在事务中锁定该行后重建 tags；标签不存在时不修改，软删除的模板返回 NotFound
+    pub async fn remove_tag(&self, id: &str, tag: &str) -> Result<PromptTemplate> {
+        let mut conn = self
+            .pool
+            .get_conn()
+            .await
+            .context("Failed to get MySQL connection")?;
+
+        let mut tx = conn
+            .start_transaction(TxOpts::default())
+            .await
+            .context("Failed to start transaction")?;
+
+        let select_sql = format!(
+            "SELECT tags FROM `{}` WHERE id = :id AND deleted_at IS NULL FOR UPDATE",
+            self.table_name
+        );
+        let row: Option<Option<String>> = tx
+            .exec_first(&select_sql, params! { "id" => id })
+            .await
+            .context("Failed to query prompt template")?;
+        let tags_json = row.ok_or_else(|| PromptError::NotFound(id.to_string()))?;
+        let mut tags: Vec<String> = match tags_json {
+            Some(json) => serde_json::from_str(&json)?,
+            None => Vec::new(),
+        };
+
+        let before = tags.len();
+        tags.retain(|t| t != tag);
+        let removed = tags.len() < before;
+        if removed {
+            let update_sql = format!(
+                "UPDATE `{}` SET tags = :tags, version = version + 1, updated_at = :updated_at WHERE id = :id",
+                self.table_name
+            );
+            tx.exec_drop(
+                &update_sql,
+                params! {
+                    "id" => id,
+                    "tags" => serde_json::to_string(&tags)?,
+                    "updated_at" => chrono::Utc::now().naive_utc(),
+                },
+            )
+            .await
+            .context("Failed to remove prompt template tag")?;
+        }
+        tx.commit().await.context("Failed to commit tag removal")?;
+        drop(conn);
+
+        let template = self
+            .get(id)
+            .await?
+            .ok_or_else(|| PromptError::NotFound(id.to_string()))?;
+        if removed {
+            info!("🏷️ Removed tag from PromptTemplate: id={}, tag={}", id, tag);
+        }
+        Ok(template)
+    }
+
+    /// 流式导出全部模板（包括 is_active=false 的行），内存占用与表大小无关
+    pub fn export_stream(&self) -> impl Stream<Item = Result<PromptTemplate>> + Send + 'static {
+        let (tx, mut rx) = mpsc::channel(64);
+        let manager = self.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = manager.export_into(&tx).await {
+                error!("Failed to export prompt templates: {}", e);
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+
+        futures::stream::poll_fn(move |cx| rx.poll_recv(cx))
+    }
+
+    async fn export_into(&self, tx: &mpsc::Sender<Result<PromptTemplate>>) -> Result<()> {
+        let mut conn = self
+            .pool
+            .get_conn()
+            .await
+            .context("Failed to get MySQL connection")?;
+
+        let select_sql = format!(
+            "SELECT id, name, description, category, content, variables, tags, version, is_active, created_at, updated_at, created_by, deleted_at FROM `{}` ORDER BY created_at ASC",
+            self.table_name
+        );
+
+        let mut rows = conn
+            .query_stream::<Row, _>(select_sql)
+            .await
+            .context("Failed to export templates")?;
+
+        while let Some(row) = rows.next().await {
+            let template = row
+                .context("Failed to read template row")
+                .and_then(|row| self.row_to_template(row));
+            if tx.send(template).await.is_err() {
+                // 接收方已断开（客户端取消下载）
+                debug!("Export stream receiver dropped");
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 在单个事务中导入模板，任一条失败则整体回滚
+    pub async fn import(&self, templates: Vec<PromptTemplate>) -> Result<usize> {
+        for template in &templates {
+            template
+                .validate()
+                .with_context(|| format!("Failed to import prompt template: id={}", template.id))?;
+        }
+
+        let mut conn = self
+            .pool
+            .get_conn()
+            .await
+            .context("Failed to get MySQL connection")?;
+
+        let mut tx = conn
+            .start_transaction(TxOpts::default())
+            .await
+            .context("Failed to start transaction")?;
+
+        let insert_sql = self.insert_sql();
+        for template in &templates {
+            tx.exec_drop(&insert_sql, Self::insert_params(template)?)
+                .await
+                .with_context(|| format!("Failed to import prompt template: id={}", template.id))?;
+        }
+
+        tx.commit().await.context("Failed to commit import")?;
+
+        info!("📥 Imported {} PromptTemplates", templates.len());
+        Ok(templates.len())
+    }
+
     pub async fn search_by_name(
         &self,
         name: &str,
@@ -270,7 +608,7 @@ impl PromptTemplateManager {
         let search_pattern = format!("%{name}%");
 
         let count_sql = format!(
-            "SELECT COUNT(*) FROM `{}` WHERE name LIKE :pattern",
+            "SELECT COUNT(*) FROM `{}` WHERE name LIKE :pattern AND deleted_at IS NULL",
             self.table_name
         );
         let total: u64 = conn
@@ -284,8 +622,9 @@ impl PromptTemplateManager {
         }
 
         let select_sql = format!(
-            "SELECT id, name, description, category, content, variables, tags, version, is_active, created_at, updated_at, created_by FROM `{}` WHERE name LIKE :pattern ORDER BY created_at DESC LIMIT :limit OFFSET :offset",
-            self.table_name
+            "SELECT id, name, description, category, content, variables, tags, version, is_active, created_at, updated_at, created_by, deleted_at FROM `{}` WHERE name LIKE :pattern AND deleted_at IS NULL ORDER BY {} DESC LIMIT :limit OFFSET :offset",
+            self.table_name,
+            params.sort.column()
         );
 
         let rows: Vec<Row> = conn
@@ -314,6 +653,8 @@ impl PromptTemplateManager {
         let created_at: NaiveDateTime = row.get("created_at").context("Missing created_at")?;
         let updated_at: NaiveDateTime = row.get("updated_at").context("Missing updated_at")?;
         let created_by: Option<String> = row.get("created_by");
+        let deleted_at: Option<NaiveDateTime> =
+            row.get::<Option<NaiveDateTime>, _>("deleted_at").flatten();
 
         let variables: Vec<String> = serde_json::from_str(&variables_json).unwrap_or_default();
         let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
@@ -332,10 +673,44 @@ impl PromptTemplateManager {
             created_at: chrono::DateTime::from_naive_utc_and_offset(created_at, chrono::Utc),
             updated_at: chrono::DateTime::from_naive_utc_and_offset(updated_at, chrono::Utc),
             created_by,
+            deleted_at: deleted_at
+                .map(|t| chrono::DateTime::from_naive_utc_and_offset(t, chrono::Utc)),
         })
     }
 }
 
+/// 根据配置生成建表的表选项，引擎、字符集与排序规则只允许字母、数字和下划线
+fn table_options(config: &config::prompt::PromptConfig) -> Result<String> {
+    let is_identifier = |value: &str| {
+        !value.is_empty()
+            && value
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+    };
+    for (field, value) in [("engine", &config.engine), ("charset", &config.charset)] {
+        if !is_identifier(value) {
+            return Err(PromptError::Validation(format!(
+                "Invalid prompt.{field}: '{value}'"
+            )));
+        }
+    }
+
+    let mut options = format!(
+        "ENGINE={} DEFAULT CHARSET={}",
+        config.engine, config.charset
+    );
+    if !config.collation.is_empty() {
+        if !is_identifier(&config.collation) {
+            return Err(PromptError::Validation(format!(
+                "Invalid prompt.collation: '{}'",
+                config.collation
+            )));
+        }
+        options.push_str(&format!(" COLLATE={}", config.collation));
+    }
+    Ok(options)
+}
+
 impl std::fmt::Debug for PromptTemplateManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PromptTemplateManager")
@@ -363,9 +738,63 @@ mod tests {
                 ..Default::default()
             },
             table_prefix: "test_prompt_".to_string(),
+            ..Default::default()
         }
     }
 
+    #[test]
+    fn test_table_options() -> Result<()> {
+        let config = create_test_config();
+        assert_eq!(
+            table_options(&config)?,
+            "ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci"
+        );
+
+        let mut config = create_test_config();
+        config.engine = "MyISAM".to_string();
+        config.charset = "utf8".to_string();
+        config.collation = String::new();
+        assert_eq!(
+            table_options(&config)?,
+            "ENGINE=MyISAM DEFAULT CHARSET=utf8"
+        );
+
+        config.charset = "utf8mb4; DROP TABLE x".to_string();
+        assert!(matches!(
+            table_options(&config),
+            Err(PromptError::Validation(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_missing_column_is_reported() -> Result<()> {
+        let mut config = create_test_config();
+        config.table_prefix = format!("test_schema_{}_", uuid::Uuid::new_v4().simple());
+        let table = format!("{}templates", config.table_prefix);
+
+        let pool = Pool::new(
+            mysql_async::Opts::from_url(&config.mysql.connection_url()).expect("mysql url"),
+        );
+        let mut conn = pool.get_conn().await?;
+        conn.query_drop(format!(
+            "CREATE TABLE `{table}` (`id` VARCHAR(36) NOT NULL PRIMARY KEY, `name` VARCHAR(255) NOT NULL, `content` LONGTEXT NOT NULL)"
+        ))
+        .await?;
+
+        let err = PromptTemplateManager::new(config).await.unwrap_err();
+        conn.query_drop(format!("DROP TABLE `{table}`")).await?;
+
+        let message = err.to_string();
+        assert!(matches!(err, PromptError::Database(_)));
+        assert!(message.contains("missing columns"), "{message}");
+        assert!(message.contains("category"), "{message}");
+        assert!(message.contains("created_by"), "{message}");
+        assert!(!message.contains("deleted_at"), "{message}");
+        Ok(())
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_create_and_get() -> Result<()> {
@@ -377,13 +806,129 @@ mod tests {
             .with_variables(vec!["name".to_string()]);
 
         let id = template.id.clone();
-        let created = manager.create(template).await?;
+        manager.create(template).await?;
 
         let fetched = manager.get(&id).await?;
         assert!(fetched.is_some());
         assert_eq!(fetched.unwrap().name, "test");
 
-        manager.delete(&id).await?;
+        manager.hard_delete(&id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_export_import_round_trip() -> Result<()> {
+        let source = PromptTemplateManager::new(create_test_config()).await?;
+        let active = PromptTemplate::new("export-a".to_string(), "A".to_string());
+        let mut inactive = PromptTemplate::new("export-b".to_string(), "B".to_string());
+        inactive.is_active = false;
+        let ids = [active.id.clone(), inactive.id.clone()];
+        source.create(active).await?;
+        source.create(inactive).await?;
+
+        let exported: Vec<PromptTemplate> = source
+            .export_stream()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_>>()?;
+        let exported: Vec<PromptTemplate> = exported
+            .into_iter()
+            .filter(|t| ids.contains(&t.id))
+            .collect();
+        assert_eq!(exported.len(), 2);
+
+        let mut target_config = create_test_config();
+        target_config.table_prefix = format!("test_import_{}_", uuid::Uuid::new_v4().simple());
+        let target = PromptTemplateManager::new(target_config).await?;
+        assert_eq!(target.import(exported).await?, 2);
+
+        let imported = target.get(&ids[1]).await?.expect("imported template");
+        assert!(!imported.is_active);
+        assert_eq!(target.list(PaginationParams::default()).await?.total, 2);
+
+        for id in &ids {
+            source.hard_delete(id).await?;
+            target.hard_delete(id).await?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_soft_delete_hides_and_restore_brings_back() -> Result<()> {
+        let mut config = create_test_config();
+        config.table_prefix = format!("test_soft_{}_", uuid::Uuid::new_v4().simple());
+        let manager = PromptTemplateManager::new(config).await?;
+
+        let template = PromptTemplate::new("soft".to_string(), "content".to_string());
+        let id = template.id.clone();
+        manager.create(template).await?;
+
+        assert!(manager.delete(&id, true).await?);
+        assert_eq!(manager.list(PaginationParams::default()).await?.total, 0);
+        assert!(manager.get(&id).await?.is_none());
+        let mut edited = PromptTemplate::new("soft".to_string(), "edited".to_string());
+        edited.id = id.clone();
+        assert!(matches!(
+            manager.update(edited).await,
+            Err(PromptError::NotFound(_))
+        ));
+
+        assert!(manager.restore(&id).await?);
+        let listed = manager.list(PaginationParams::default()).await?;
+        assert_eq!(listed.total, 1);
+        assert!(listed.items[0].is_active);
+        assert!(!listed.items[0].is_deleted());
+
+        manager.hard_delete(&id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_hard_delete_removes_row() -> Result<()> {
+        let manager = PromptTemplateManager::new(create_test_config()).await?;
+
+        let template = PromptTemplate::new("hard".to_string(), "content".to_string());
+        let id = template.id.clone();
+        manager.create(template).await?;
+
+        assert!(manager.delete(&id, false).await?);
+        assert!(manager.get(&id).await?.is_none());
+        assert!(!manager.restore(&id).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_add_and_remove_tag() -> Result<()> {
+        let manager = PromptTemplateManager::new(create_test_config()).await?;
+
+        let template = PromptTemplate::new("tags".to_string(), "content".to_string())
+            .with_tags(vec!["a".to_string()]);
+        let id = template.id.clone();
+        manager.create(template).await?;
+
+        let added = manager.add_tag(&id, "b").await?;
+        assert_eq!(added.tags, ["a", "b"]);
+        assert_eq!(added.version, 2);
+
+        // 重复添加与移除不存在的标签都是空操作
+        assert_eq!(manager.add_tag(&id, "a").await?.version, 2);
+        assert_eq!(manager.remove_tag(&id, "missing").await?.version, 2);
+
+        let removed = manager.remove_tag(&id, "a").await?;
+        assert_eq!(removed.tags, ["b"]);
+        assert_eq!(removed.version, 3);
+        assert_eq!(removed.name, "tags");
+
+        assert!(matches!(
+            manager.add_tag("missing-id", "a").await,
+            Err(PromptError::NotFound(_))
+        ));
+        manager.hard_delete(&id).await?;
         Ok(())
     }
 }
@@ -0,0 +1,454 @@
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Mutex;
+
+use crate::error::{PromptError, Result};
+use crate::models::{PaginatedResult, PaginationParams, PromptTemplate, SortBy, validate_tag};
+use crate::storage::PromptTemplateManager;
+
+/// 模板存储抽象，MySQL 实现为 `PromptTemplateManager`，测试可使用 `InMemoryPromptStore`
+#[async_trait]
+pub trait PromptStore: Send + Sync + 'static {
+    async fn create(&self, template: PromptTemplate) -> Result<PromptTemplate>;
+    /// 按 id 获取未删除的模板，软删除的模板视为不存在
+    async fn get(&self, id: &str) -> Result<Option<PromptTemplate>>;
+    /// 分页列出未删除的模板，按 `params.sort` 对应的时间倒序
+    async fn list(&self, params: PaginationParams) -> Result<PaginatedResult<PromptTemplate>>;
+    async fn search_by_name(
+        &self,
+        name: &str,
+        params: PaginationParams,
+    ) -> Result<PaginatedResult<PromptTemplate>>;
+    /// 更新未删除的模板，软删除的模板返回 NotFound
+    async fn update(&self, template: PromptTemplate) -> Result<PromptTemplate>;
+    async fn soft_delete(&self, id: &str) -> Result<bool>;
+    async fn restore(&self, id: &str) -> Result<bool>;
+    async fn hard_delete(&self, id: &str) -> Result<bool>;
+    /// 添加标签，只修改标签列表并递增版本；标签已存在时原样返回模板
+    async fn add_tag(&self, id: &str, tag: &str) -> Result<PromptTemplate>;
+    /// 移除标签，只修改标签列表并递增版本；标签不存在时原样返回模板
+    async fn remove_tag(&self, id: &str, tag: &str) -> Result<PromptTemplate>;
+    /// 导出全部模板（包括已停用和软删除的）
+    fn export_stream(&self) -> BoxStream<'static, Result<PromptTemplate>>;
+    /// 导入模板，任一条失败则整体不生效
+    async fn import(&self, templates: Vec<PromptTemplate>) -> Result<usize>;
+    /// 检查后端是否可用，用于健康检查
+    async fn ping(&self) -> Result<()>;
+
+    /// 删除模板，`soft` 为 true 时仅标记删除（可通过 `restore` 恢复）
+    async fn delete(&self, id: &str, soft: bool) -> Result<bool> {
+        if soft {
+            self.soft_delete(id).await
+        } else {
+            self.hard_delete(id).await
+        }
+    }
+}
+
+#[async_trait]
+impl PromptStore for PromptTemplateManager {
+    async fn create(&self, template: PromptTemplate) -> Result<PromptTemplate> {
+        PromptTemplateManager::create(self, template).await
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<PromptTemplate>> {
+        PromptTemplateManager::get(self, id).await
+    }
+
+    async fn list(&self, params: PaginationParams) -> Result<PaginatedResult<PromptTemplate>> {
+        PromptTemplateManager::list(self, params).await
+    }
+
+    async fn search_by_name(
+        &self,
+        name: &str,
+        params: PaginationParams,
+    ) -> Result<PaginatedResult<PromptTemplate>> {
+        PromptTemplateManager::search_by_name(self, name, params).await
+    }
+
+    async fn update(&self, template: PromptTemplate) -> Result<PromptTemplate> {
+        PromptTemplateManager::update(self, template).await
+    }
+
+    async fn soft_delete(&self, id: &str) -> Result<bool> {
+        PromptTemplateManager::soft_delete(self, id).await
+    }
+
+    async fn restore(&self, id: &str) -> Result<bool> {
+        PromptTemplateManager::restore(self, id).await
+    }
+
+    async fn hard_delete(&self, id: &str) -> Result<bool> {
+        PromptTemplateManager::hard_delete(self, id).await
+    }
+
+    async fn add_tag(&self, id: &str, tag: &str) -> Result<PromptTemplate> {
+        PromptTemplateManager::add_tag(self, id, tag).await
+    }
+
+    async fn remove_tag(&self, id: &str, tag: &str) -> Result<PromptTemplate> {
+        PromptTemplateManager::remove_tag(self, id, tag).await
+    }
+
+    fn export_stream(&self) -> BoxStream<'static, Result<PromptTemplate>> {
+        PromptTemplateManager::export_stream(self).boxed()
+    }
+
+    async fn import(&self, templates: Vec<PromptTemplate>) -> Result<usize> {
+        PromptTemplateManager::import(self, templates).await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        PromptTemplateManager::ping(self).await
+    }
+}
+
+/// 内存版模板存储，按插入顺序保存，用于测试与本地开发
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryPromptStore {
+    templates: Arc<Mutex<Vec<PromptTemplate>>>,
+    /// 模拟后端不可用，仅影响 `ping`
+    offline: Arc<AtomicBool>,
+}
+
+impl InMemoryPromptStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 模拟后端断开或恢复，用于测试健康检查
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
+    /// 过滤出未删除的模板，按排序字段倒序（同一时间后插入的在前）并分页
+    async fn page_where(
+        &self,
+        params: &PaginationParams,
+        filter: impl Fn(&PromptTemplate) -> bool,
+    ) -> PaginatedResult<PromptTemplate> {
+        let templates = self.templates.lock().await;
+        let mut matched: Vec<&PromptTemplate> = templates
+            .iter()
+            .rev()
+            .filter(|t| !t.is_deleted() && filter(t))
+            .collect();
+        matched.sort_by_key(|t| {
+            std::cmp::Reverse(match params.sort {
+                SortBy::Created => t.created_at,
+                SortBy::Updated => t.updated_at,
+            })
+        });
+
+        let items = matched
+            .iter()
+            .skip(params.offset() as usize)
+            .take(params.limit() as usize)
+            .map(|t| (*t).clone())
+            .collect();
+        PaginatedResult::new(items, matched.len() as u64, params)
+    }
+}
+
+#[async_trait]
+impl PromptStore for InMemoryPromptStore {
+    async fn create(&self, template: PromptTemplate) -> Result<PromptTemplate> {
+        template.validate()?;
+        let mut templates = self.templates.lock().await;
+        if templates.iter().any(|t| t.id == template.id) {
+            return Err(PromptError::Conflict(format!(
+                "duplicate id={}",
+                template.id
+            )));
+        }
+        templates.push(template.clone());
+        Ok(template)
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<PromptTemplate>> {
+        let templates = self.templates.lock().await;
+        Ok(templates
+            .iter()
+            .find(|t| t.id == id && !t.is_deleted())
+            .cloned())
+    }
+
+    async fn list(&self, params: PaginationParams) -> Result<PaginatedResult<PromptTemplate>> {
+        Ok(self.page_where(&params, |_| true).await)
+    }
+
+    async fn search_by_name(
+        &self,
+        name: &str,
+        params: PaginationParams,
+    ) -> Result<PaginatedResult<PromptTemplate>> {
+        Ok(self.page_where(&params, |t| t.name.contains(name)).await)
+    }
+
+    async fn update(&self, template: PromptTemplate) -> Result<PromptTemplate> {
+        template.validate()?;
+        let mut templates = self.templates.lock().await;
+        let existing = templates
+            .iter_mut()
+            .find(|t| t.id == template.id && !t.is_deleted())
+            .ok_or_else(|| PromptError::NotFound(template.id.clone()))?;
+
+        // 与 MySQL 实现一致：更新不改变创建时间与删除状态
+        *existing = PromptTemplate {
+            created_at: existing.created_at,
+            deleted_at: existing.deleted_at,
+            ..template.clone()
+        };
+        Ok(template)
+    }
+
+    async fn soft_delete(&self, id: &str) -> Result<bool> {
+        let mut templates = self.templates.lock().await;
+        match templates.iter_mut().find(|t| t.id == id && !t.is_deleted()) {
+            Some(template) => {
+                template.is_active = false;
+                template.deleted_at = Some(chrono::Utc::now());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn restore(&self, id: &str) -> Result<bool> {
+        let mut templates = self.templates.lock().await;
+        match templates.iter_mut().find(|t| t.id == id && t.is_deleted()) {
+            Some(template) => {
+                template.is_active = true;
+                template.deleted_at = None;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn hard_delete(&self, id: &str) -> Result<bool> {
+        let mut templates = self.templates.lock().await;
+        let before = templates.len();
+        templates.retain(|t| t.id != id);
+        Ok(templates.len() < before)
+    }
+
+    async fn add_tag(&self, id: &str, tag: &str) -> Result<PromptTemplate> {
+        validate_tag(tag)?;
+        let mut templates = self.templates.lock().await;
+        let template = templates
+            .iter_mut()
+            .find(|t| t.id == id && !t.is_deleted())
+            .ok_or_else(|| PromptError::NotFound(id.to_string()))?;
+        template.add_tag(tag);
+        Ok(template.clone())
+    }
+
+    async fn remove_tag(&self, id: &str, tag: &str) -> Result<PromptTemplate> {
+        let mut templates = self.templates.lock().await;
+        let template = templates
+            .iter_mut()
+            .find(|t| t.id == id && !t.is_deleted())
+            .ok_or_else(|| PromptError::NotFound(id.to_string()))?;
+        template.remove_tag(tag);
+        Ok(template.clone())
+    }
+
+    fn export_stream(&self) -> BoxStream<'static, Result<PromptTemplate>> {
+        let templates = self.templates.clone();
+        stream::once(async move { templates.lock().await.clone() })
+            .flat_map(|templates| stream::iter(templates.into_iter().map(Ok)))
+            .boxed()
+    }
+
+    async fn import(&self, imported: Vec<PromptTemplate>) -> Result<usize> {
+        let mut templates = self.templates.lock().await;
+        for (index, template) in imported.iter().enumerate() {
+            template.validate()?;
+            let duplicate = templates.iter().any(|t| t.id == template.id)
+                || imported[..index].iter().any(|t| t.id == template.id);
+            if duplicate {
+                return Err(PromptError::Conflict(format!(
+                    "Failed to import prompt template: duplicate id={}",
+                    template.id
+                )));
+            }
+        }
+
+        let count = imported.len();
+        templates.extend(imported);
+        Ok(count)
+    }
+
+    async fn ping(&self) -> Result<()> {
+        if self.offline.load(Ordering::Relaxed) {
+            return Err(PromptError::Database(
+                "in-memory store is offline".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(name: &str) -> PromptTemplate {
+        PromptTemplate::new(name.to_string(), format!("content of {name}"))
+    }
+
+    #[tokio::test]
+    async fn test_create_get_and_update() -> Result<()> {
+        let store = InMemoryPromptStore::new();
+        let created = store.create(template("greeting")).await?;
+        assert!(matches!(
+            store.create(created.clone()).await,
+            Err(PromptError::Conflict(_))
+        ));
+
+        let mut changed = store.get(&created.id).await?.expect("created template");
+        changed.update_content("Hi {{name}}".to_string());
+        store.update(changed).await?;
+
+        let fetched = store.get(&created.id).await?.expect("updated template");
+        assert_eq!(fetched.content, "Hi {{name}}");
+        assert_eq!(fetched.version, 2);
+        let missing = template("missing");
+        assert_eq!(
+            store.update(missing.clone()).await.unwrap_err(),
+            PromptError::NotFound(missing.id)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_add_and_remove_tag_only_touch_tags() -> Result<()> {
+        let store = InMemoryPromptStore::new();
+        let created = store
+            .create(template("tagged").with_tags(vec!["a".to_string()]))
+            .await?;
+
+        let added = store.add_tag(&created.id, "b").await?;
+        assert_eq!(added.tags, ["a", "b"]);
+        assert_eq!(added.version, 2);
+        assert_eq!(added.content, created.content);
+
+        let duplicate = store.add_tag(&created.id, "b").await?;
+        assert_eq!(duplicate.tags, ["a", "b"]);
+        assert_eq!(duplicate.version, 2);
+        assert_eq!(duplicate.updated_at, added.updated_at);
+
+        let unchanged = store.remove_tag(&created.id, "missing").await?;
+        assert_eq!(unchanged.version, 2);
+
+        let removed = store.remove_tag(&created.id, "a").await?;
+        assert_eq!(removed.tags, ["b"]);
+        assert_eq!(removed.version, 3);
+
+        assert!(matches!(
+            store.add_tag(&created.id, " ").await,
+            Err(PromptError::Validation(_))
+        ));
+        assert_eq!(
+            store.remove_tag("missing", "a").await.unwrap_err(),
+            PromptError::NotFound("missing".to_string())
+        );
+
+        // 软删除的模板不能再修改标签
+        assert!(store.soft_delete(&created.id).await?);
+        assert_eq!(
+            store.add_tag(&created.id, "c").await.unwrap_err(),
+            PromptError::NotFound(created.id.clone())
+        );
+        assert_eq!(
+            store.remove_tag(&created.id, "b").await.unwrap_err(),
+            PromptError::NotFound(created.id.clone())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_and_search_paginate_newest_first() -> Result<()> {
+        let store = InMemoryPromptStore::new();
+        for name in ["alpha", "beta", "alphabet"] {
+            store.create(template(name)).await?;
+        }
+
+        let page = store.list(PaginationParams::new(1, 2)).await?;
+        assert_eq!(page.total, 3);
+        assert_eq!(page.total_pages, 2);
+        let names: Vec<_> = page.items.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["alphabet", "beta"]);
+
+        let found = store
+            .search_by_name("alpha", PaginationParams::new(2, 1))
+            .await?;
+        assert_eq!(found.total, 2);
+        assert_eq!(found.items[0].name, "alpha");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_sorted_by_updated_at() -> Result<()> {
+        let store = InMemoryPromptStore::new();
+        let now = chrono::Utc::now();
+        for (name, minutes_ago) in [("older", 10), ("newer", 5)] {
+            let mut t = template(name);
+            t.created_at = now - chrono::Duration::minutes(minutes_ago);
+            t.updated_at = t.created_at;
+            store.create(t).await?;
+        }
+
+        let mut older = store.list(PaginationParams::new(1, 10)).await?.items[1].clone();
+        older.update_content("edited".to_string());
+        store.update(older).await?;
+
+        let names = |page: PaginatedResult<PromptTemplate>| -> Vec<String> {
+            page.items.into_iter().map(|t| t.name).collect()
+        };
+        let by_created = store.list(PaginationParams::new(1, 10)).await?;
+        assert_eq!(names(by_created), ["newer", "older"]);
+        let by_updated = store
+            .list(PaginationParams::new(1, 10).with_sort(SortBy::Updated))
+            .await?;
+        assert_eq!(names(by_updated), ["older", "newer"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_restore_and_import() -> Result<()> {
+        let store = InMemoryPromptStore::new();
+        let created = store.create(template("soft")).await?;
+        let id = created.id.clone();
+
+        assert!(store.delete(&id, true).await?);
+        assert_eq!(store.list(PaginationParams::default()).await?.total, 0);
+        assert!(store.get(&id).await?.is_none());
+        assert!(matches!(
+            store.update(created.clone()).await,
+            Err(PromptError::NotFound(_))
+        ));
+        assert!(store.restore(&id).await?);
+        assert!(store.get(&id).await?.is_some());
+        assert_eq!(store.list(PaginationParams::default()).await?.total, 1);
+
+        let exported: Vec<PromptTemplate> = store
+            .export_stream()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_>>()?;
+        assert_eq!(exported.len(), 1);
+        // 与已有 id 冲突时整体不导入
+        assert!(store.import(vec![template("new"), created]).await.is_err());
+        assert_eq!(store.list(PaginationParams::default()).await?.total, 1);
+
+        assert!(store.delete(&id, false).await?);
+        assert!(store.get(&id).await?.is_none());
+        Ok(())
+    }
+}
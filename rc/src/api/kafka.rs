@@ -7,7 +7,7 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tracing::{error, info};
-use util::client::kafka::{KafkaClientConfig, KafkaProducer, SaslConfig};
+use util::client::kafka::{ClusterMetadata, KafkaClientConfig, KafkaProducer, SaslConfig};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PingRequest {
@@ -162,7 +162,7 @@ async fn ping_kafka(
     if let Some(topic) = &req.topic {
         match producer.get_topic_metadata(topic, Duration::from_secs(req.timeout)) {
             Ok(metadata) => {
-                parse_metadata(&metadata, &mut result);
+                apply_metadata(&metadata, req.topic.as_deref(), &mut result);
             }
             Err(e) => {
                 error!("fetch topic metadata fail: {}", e);
@@ -171,7 +171,7 @@ async fn ping_kafka(
     } else {
         match producer.get_topic_metadata("", Duration::from_secs(req.timeout)) {
             Ok(metadata) => {
-                parse_metadata(&metadata, &mut result);
+                apply_metadata(&metadata, req.topic.as_deref(), &mut result);
             }
             Err(e) => {
                 error!("fetch cluster metadata fail: {}", e);
@@ -183,30 +183,14 @@ async fn ping_kafka(
     Ok(Json(result))
 }
 
-fn parse_metadata(metadata: &str, result: &mut PingResponse) {
-    if let Some(cluster_line) = metadata.lines().next()
-        && let Some(cluster) = cluster_line.strip_prefix("Cluster: ")
-    {
-        result.cluster_name = Some(cluster.to_string());
-    }
-    if let Some(brokers_line) = metadata.lines().nth(1)
-        && let Some(count_str) = brokers_line.strip_prefix("Brokers: ")
-    {
-        result.broker_count = count_str.parse().ok();
-    }
-    if let Some(topics_line) = metadata.lines().nth(2)
-        && let Some(count_str) = topics_line.strip_prefix("Topics: ")
-    {
-        result.topic_count = count_str.parse().ok();
-    }
-    for line in metadata.lines() {
-        if line.contains("partitions")
-            && let Some(parts) = line.split(':').nth(1)
-            && let Some(count_str) = parts.split_whitespace().next()
-        {
-            result.partition_count = count_str.parse().ok();
-        }
-    }
+/// 把 metadata 填入响应，指定了 topic 时附带其分区数
+fn apply_metadata(metadata: &ClusterMetadata, topic: Option<&str>, result: &mut PingResponse) {
+    result.cluster_name = Some(metadata.cluster_name.clone());
+    result.broker_count = Some(metadata.brokers.len());
+    result.topic_count = Some(metadata.topics.len());
+    result.partition_count = topic
+        .and_then(|name| metadata.topic(name))
+        .map(|topic| topic.partition_count);
 }
 
 pub fn create_routes() -> Router {
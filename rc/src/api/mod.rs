@@ -2,12 +2,283 @@ mod kafka;
 mod mysql;
 mod redis;
 
-use axum::Router;
+use axum::{Router, extract::Query, response::Json, routing::get};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use util::client::kafka::{KafkaClientConfig, KafkaProducer};
+use util::client::mysql::{MySqlClient, MySqlClientConfig};
+use util::client::redis::{RedisClient, RedisClientConfig};
+
+/// RC 服务挂载的子服务
+const SERVICES: [&str; 3] = ["kafka", "redis", "mysql"];
 
 /// 创建 RC 服务的所有路由
 pub fn create_routes() -> Router {
     Router::new()
+        .route("/health", get(health_check))
+        .route(
+            "/health/deep",
+            get(deep_health_check).post(deep_health_check_with_credentials),
+        )
         .nest("/kafka", kafka::create_routes())
         .nest("/redis", redis::create_routes())
         .nest("/mysql", mysql::create_routes())
 }
+
+async fn health_check() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "services": SERVICES,
+        "version": env!("CARGO_PKG_VERSION")
+    }))
+}
+
+/// 深度健康检查的目标，GET 时作为查询参数
+#[derive(Debug, Deserialize)]
+pub struct DeepHealthQuery {
+    /// 目标主机（三个服务共用）
+    pub host: String,
+    #[serde(default = "default_kafka_port")]
+    pub kafka_port: u16,
+    #[serde(default = "default_redis_port")]
+    pub redis_port: u16,
+    #[serde(default = "default_mysql_port")]
+    pub mysql_port: u16,
+    /// 每个服务的超时时间（秒）
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+}
+
+/// 深度健康检查使用的凭证，只通过 POST 请求体传入，避免出现在 URL 与访问日志中
+#[derive(Debug, Default, Deserialize)]
+pub struct DeepHealthCredentials {
+    #[serde(default)]
+    pub redis_password: Option<String>,
+    #[serde(default)]
+    pub mysql_username: Option<String>,
+    #[serde(default)]
+    pub mysql_password: Option<String>,
+}
+
+/// POST 深度健康检查的请求体：检查目标与凭证
+#[derive(Debug, Deserialize)]
+pub struct DeepHealthRequest {
+    #[serde(flatten)]
+    pub target: DeepHealthQuery,
+    #[serde(flatten)]
+    pub credentials: DeepHealthCredentials,
+}
+
+fn default_kafka_port() -> u16 {
+    9092
+}
+
+fn default_redis_port() -> u16 {
+    6379
+}
+
+fn default_mysql_port() -> u16 {
+    3306
+}
+
+fn default_timeout() -> u64 {
+    5
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceReachability {
+    pub reachable: bool,
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ServiceReachability {
+    fn from_result(target: String, result: Result<Duration, String>) -> Self {
+        match result {
+            Ok(latency) => Self {
+                reachable: true,
+                target,
+                latency_ms: Some(latency.as_secs_f64() * 1000.0),
+                error: None,
+            },
+            Err(e) => Self {
+                reachable: false,
+                target,
+                latency_ms: None,
+                error: Some(e),
+            },
+        }
+    }
+}
+
+/// 不带凭证的深度健康检查
+async fn deep_health_check(Query(query): Query<DeepHealthQuery>) -> Json<serde_json::Value> {
+    deep_health(query, DeepHealthCredentials::default()).await
+}
+
+/// 带凭证的深度健康检查
+async fn deep_health_check_with_credentials(
+    Json(req): Json<DeepHealthRequest>,
+) -> Json<serde_json::Value> {
+    deep_health(req.target, req.credentials).await
+}
+
+/// 并发 ping kafka/redis/mysql，汇总各服务的可达性
+async fn deep_health(
+    query: DeepHealthQuery,
+    credentials: DeepHealthCredentials,
+) -> Json<serde_json::Value> {
+    let timeout = Duration::from_secs(query.timeout);
+    let kafka_target = format!("{}:{}", query.host, query.kafka_port);
+    let redis_target = format!("{}:{}", query.host, query.redis_port);
+    let mysql_target = format!("{}:{}", query.host, query.mysql_port);
+
+    let (kafka, redis, mysql) = tokio::join!(
+        ping_kafka(kafka_target.clone(), timeout),
+        ping_redis(redis_target.clone(), credentials.redis_password, timeout),
+        ping_mysql(
+            mysql_target.clone(),
+            credentials.mysql_username,
+            credentials.mysql_password,
+            timeout,
+        ),
+    );
+
+    let kafka = ServiceReachability::from_result(kafka_target, kafka);
+    let redis = ServiceReachability::from_result(redis_target, redis);
+    let mysql = ServiceReachability::from_result(mysql_target, mysql);
+    let all_reachable = kafka.reachable && redis.reachable && mysql.reachable;
+
+    Json(serde_json::json!({
+        "status": if all_reachable { "ok" } else { "degraded" },
+        "services": {
+            "kafka": kafka,
+            "redis": redis,
+            "mysql": mysql,
+        },
+        "version": env!("CARGO_PKG_VERSION")
+    }))
+}
+
+async fn ping_kafka(target: String, timeout: Duration) -> Result<Duration, String> {
+    let config =
+        KafkaClientConfig::new(vec![target], "rc-health-check").with_timeout(timeout.as_secs());
+    // fetch_metadata 是阻塞调用
+    tokio::task::spawn_blocking(move || {
+        let producer = KafkaProducer::new(&config)?;
+        let start = Instant::now();
+        producer.ping(timeout)?;
+        Ok(start.elapsed())
+    })
+    .await
+    .map_err(|e| format!("kafka ping task failed: {e}"))?
+}
+
+async fn ping_redis(
+    target: String,
+    password: Option<String>,
+    timeout: Duration,
+) -> Result<Duration, String> {
+    let mut config = RedisClientConfig::new(target).with_timeout(timeout.as_secs());
+    if let Some(password) = password {
+        config = config.with_password(password);
+    }
+    tokio::time::timeout(timeout, async {
+        let mut client = RedisClient::new(&config).await?;
+        client.ping_timed().await
+    })
+    .await
+    .map_err(|_| "redis ping timed out".to_string())?
+}
+
+async fn ping_mysql(
+    target: String,
+    username: Option<String>,
+    password: Option<String>,
+    timeout: Duration,
+) -> Result<Duration, String> {
+    let mut config = MySqlClientConfig::new(target).with_timeout(timeout.as_secs());
+    if let Some(username) = username {
+        config = config.with_username(username);
+    }
+    if let Some(password) = password {
+        config = config.with_password(password);
+    }
+    tokio::time::timeout(timeout, async {
+        let mut client = MySqlClient::new(&config).await?;
+        client.ping_timed().await
+    })
+    .await
+    .map_err(|_| "mysql ping timed out".to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_shallow_health_route() {
+        let response = create_routes()
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["status"], "ok");
+        assert_eq!(
+            body["services"],
+            serde_json::json!(["kafka", "redis", "mysql"])
+        );
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    }
+
+    #[tokio::test]
+    async fn test_deep_health_takes_credentials_in_body() {
+        let body = serde_json::json!({
+            "host": "127.0.0.1",
+            "kafka_port": 1,
+            "redis_port": 1,
+            "mysql_port": 1,
+            "timeout": 1,
+            "redis_password": "secret",
+            "mysql_username": "root",
+            "mysql_password": "secret",
+        });
+        let response = create_routes()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/health/deep")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        // 端口 1 上没有服务
+        assert_eq!(body["status"], "degraded");
+        for service in SERVICES {
+            assert_eq!(body["services"][service]["reachable"], false, "{service}");
+            assert_eq!(body["services"][service]["target"], "127.0.0.1:1");
+        }
+    }
+}
@@ -104,6 +104,7 @@ async fn ping_mysql(
         port: 3306,
         database: req.database.clone(),
         version: None,
+        latency_ms: None,
         error: None,
     };
 
@@ -124,9 +125,10 @@ async fn ping_mysql(
     };
 
     // Ping
-    match client.ping().await {
-        Ok(()) => {
+    match client.ping_timed().await {
+        Ok(latency) => {
             result.success = true;
+            result.latency_ms = Some(latency.as_secs_f64() * 1000.0);
         }
         Err(e) => {
             result.error = Some(e);
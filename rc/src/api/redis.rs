@@ -184,6 +184,7 @@ async fn ping_redis(
         db: Some(req.db),
         version: None,
         dbsize: None,
+        latency_ms: None,
         error: None,
     };
 
@@ -197,9 +198,10 @@ async fn ping_redis(
     };
 
     // Ping
-    match client.ping().await {
-        Ok(_) => {
+    match client.ping_timed().await {
+        Ok(latency) => {
             result.success = true;
+            result.latency_ms = Some(latency.as_secs_f64() * 1000.0);
         }
         Err(e) => {
             result.error = Some(e);
@@ -0,0 +1,214 @@
+//! 连接参数默认值
+//!
+//! 每个连接参数按 命令行参数 > 环境变量 > `--config` 配置文件 的优先级取值，
+//! 配置文件格式见 [`config::rc::RcConfig`]。
+
+use config::ConfigLoader;
+use config::rc::RcConfig;
+use std::path::Path;
+
+/// 从命令行参数、环境变量、配置文件中取第一个有值的
+fn pick(flag: Option<String>, env: Option<String>, file: Option<String>) -> Option<String> {
+    flag.or(env).or(file)
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// 合并后的连接参数来源
+#[derive(Debug, Default)]
+pub struct ConnectionDefaults {
+    file: RcConfig,
+}
+
+impl ConnectionDefaults {
+    pub fn new(file: RcConfig) -> Self {
+        Self { file }
+    }
+
+    /// 加载配置文件，未指定时只使用命令行参数和环境变量
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        match path {
+            Some(path) => RcConfig::from_file(path)
+                .map(Self::new)
+                .map_err(|e| anyhow::anyhow!("Failed to load config {}: {e}", path.display())),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn redis_host(&self, flag: Option<String>) -> Option<String> {
+        let file = self.file.redis.as_ref().map(|c| c.address.clone());
+        pick(flag, env("RC_REDIS_HOST"), file)
+    }
+
+    pub fn redis_password(&self, flag: Option<String>) -> Option<String> {
+        let file = self.file.redis.as_ref().and_then(|c| c.password.clone());
+        pick(flag, env("RC_REDIS_PASSWORD"), file)
+    }
+
+    pub fn mysql_host(&self, flag: Option<String>) -> Option<String> {
+        let file = self
+            .file
+            .mysql
+            .as_ref()
+            .map(|c| format!("{}:{}", c.host, c.port));
+        pick(flag, env("RC_MYSQL_HOST"), file)
+    }
+
+    pub fn mysql_username(&self, flag: Option<String>) -> Option<String> {
+        let file = self.file.mysql.as_ref().map(|c| c.user.clone());
+        pick(flag, env("RC_MYSQL_USER"), file)
+    }
+
+    pub fn mysql_password(&self, flag: Option<String>) -> Option<String> {
+        let file = self
+            .file
+            .mysql
+            .as_ref()
+            .map(|c| c.password.clone())
+            .filter(|password| !password.is_empty());
+        pick(flag, env("RC_MYSQL_PASSWORD"), file)
+    }
+
+    pub fn mysql_database(&self, flag: Option<String>) -> Option<String> {
+        let file = self.file.mysql.as_ref().map(|c| c.database.clone());
+        pick(flag, env("RC_MYSQL_DATABASE"), file)
+    }
+
+    /// Broker 列表，环境变量 `RC_KAFKA_BROKERS` 以逗号分隔
+    pub fn kafka_brokers(&self, flag: Vec<String>) -> Vec<String> {
+        if !flag.is_empty() {
+            return flag;
+        }
+        if let Some(brokers) = env("RC_KAFKA_BROKERS") {
+            return brokers.split(',').map(|b| b.trim().to_string()).collect();
+        }
+        self.file
+            .kafka
+            .as_ref()
+            .map(|c| c.brokers.clone())
+            .unwrap_or_default()
+    }
+
+    /// 指定了 `--sasl` 或从环境变量、配置文件得到了用户名时启用 SASL
+    pub fn kafka_sasl(&self, flag: bool) -> bool {
+        flag || self.kafka_username(None).is_some()
+    }
+
+    pub fn kafka_username(&self, flag: Option<String>) -> Option<String> {
+        let file = self.file.kafka.as_ref().and_then(|c| c.username.clone());
+        pick(flag, env("RC_KAFKA_USERNAME"), file)
+    }
+
+    pub fn kafka_password(&self, flag: Option<String>) -> Option<String> {
+        let file = self.file.kafka.as_ref().and_then(|c| c.password.clone());
+        pick(flag, env("RC_KAFKA_PASSWORD"), file)
+    }
+
+    pub fn kafka_security_protocol(&self, flag: Option<String>) -> String {
+        let file = self
+            .file
+            .kafka
+            .as_ref()
+            .and_then(|c| c.security_protocol.clone());
+        pick(flag, env("RC_KAFKA_SECURITY_PROTOCOL"), file)
+            .unwrap_or_else(|| "SASL_PLAINTEXT".to_string())
+    }
+
+    pub fn kafka_mechanism(&self, flag: Option<String>) -> String {
+        let file = self.file.kafka.as_ref().and_then(|c| c.mechanism.clone());
+        pick(flag, env("RC_KAFKA_MECHANISM"), file).unwrap_or_else(|| "PLAIN".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults(raw: &str) -> ConnectionDefaults {
+        ConnectionDefaults::new(toml::from_str(raw).unwrap())
+    }
+
+    #[test]
+    fn test_file_value_used_when_flag_absent() {
+        let defaults = defaults(
+            r#"
+            [redis]
+            address = "redis.internal:6380"
+            password = "secret"
+
+            [mysql]
+            host = "db.internal"
+            port = 3307
+            user = "reader"
+            database = "app"
+
+            [kafka]
+            brokers = ["k1:9092", "k2:9092"]
+            username = "svc"
+            password = "pw"
+            "#,
+        );
+
+        assert_eq!(
+            defaults.redis_host(None).as_deref(),
+            Some("redis.internal:6380")
+        );
+        assert_eq!(defaults.redis_password(None).as_deref(), Some("secret"));
+        assert_eq!(
+            defaults.mysql_host(None).as_deref(),
+            Some("db.internal:3307")
+        );
+        assert_eq!(defaults.mysql_username(None).as_deref(), Some("reader"));
+        assert_eq!(defaults.mysql_password(None), None);
+        assert_eq!(
+            defaults.kafka_brokers(Vec::new()),
+            vec!["k1:9092", "k2:9092"]
+        );
+        assert!(defaults.kafka_sasl(false));
+        assert_eq!(defaults.kafka_mechanism(None), "PLAIN");
+    }
+
+    #[test]
+    fn test_flag_overrides_file_value() {
+        let defaults = defaults(
+            r#"
+            [redis]
+            address = "redis.internal:6380"
+
+            [kafka]
+            brokers = ["k1:9092"]
+            "#,
+        );
+
+        assert_eq!(
+            defaults
+                .redis_host(Some("127.0.0.1:6379".to_string()))
+                .as_deref(),
+            Some("127.0.0.1:6379")
+        );
+        assert_eq!(
+            defaults.kafka_brokers(vec!["local:9092".to_string()]),
+            vec!["local:9092"]
+        );
+        assert!(!defaults.kafka_sasl(false));
+        // 配置文件没有的服务不提供默认值
+        assert_eq!(defaults.mysql_host(None), None);
+    }
+
+    #[test]
+    fn test_precedence_flag_then_env_then_file() {
+        let flag = Some("flag".to_string());
+        let env = Some("env".to_string());
+        let file = Some("file".to_string());
+
+        assert_eq!(
+            pick(flag, env.clone(), file.clone()).as_deref(),
+            Some("flag")
+        );
+        assert_eq!(pick(None, env, file.clone()).as_deref(), Some("env"));
+        assert_eq!(pick(None, None, file).as_deref(), Some("file"));
+        assert_eq!(pick(None, None, None), None);
+    }
+}
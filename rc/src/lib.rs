@@ -1,3 +1,4 @@
 pub mod api;
+pub mod defaults;
 
 pub use api::create_routes;
@@ -1,14 +1,33 @@
 use clap::{Args, Parser, Subcommand};
+use rc::defaults::ConnectionDefaults;
+use rdkafka::message::Message;
 use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
-use util::client::kafka::{KafkaClientConfig, KafkaProducer, SaslConfig};
-use util::client::mysql::{MySqlClient, MySqlClientConfig};
+use util::client::kafka::{
+    ClusterMetadata, KafkaClientConfig, KafkaConsumer, KafkaProducer, SaslConfig, extract_payload,
+    parse_offset,
+};
+use util::client::mysql::{MySqlClient, MySqlClientConfig, MySqlPingResult};
 use util::client::redis::{RedisClient, RedisClientConfig, RedisPingResult};
 
 #[derive(Parser)]
 #[command(name = "rc")]
 #[command(about = "Remote Control CLI Tool", long_about = None)]
 struct Cli {
+    /// Connection defaults file (TOML); flags and RC_* env vars take precedence
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Write the command result to a file instead of stdout
+    #[arg(long, global = true)]
+    output: Option<PathBuf>,
+
+    /// Only print the final result, without connection progress messages
+    #[arg(long, global = true)]
+    quiet: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -33,12 +52,18 @@ struct KafkaArgs {
 enum KafkaCommands {
     /// Ping Kafka cluster to check connectivity
     Ping(PingArgs),
+    /// Produce a message to a topic
+    Produce(KafkaProduceArgs),
+    /// Consume messages from a topic
+    Consume(KafkaConsumeArgs),
+    /// Show consumer group lag for a topic
+    Lag(KafkaLagArgs),
 }
 
 #[derive(Args)]
 struct PingArgs {
     /// Kafka broker addresses (comma-separated)
-    #[arg(short, long, value_delimiter = ',', required = true)]
+    #[arg(short, long, value_delimiter = ',')]
     brokers: Vec<String>,
 
     /// Client ID
@@ -54,20 +79,20 @@ struct PingArgs {
     sasl: bool,
 
     /// SASL username (required if --sasl is set)
-    #[arg(long, required_if_eq("sasl", "true"))]
+    #[arg(long)]
     username: Option<String>,
 
     /// SASL password (required if --sasl is set)
-    #[arg(long, required_if_eq("sasl", "true"))]
+    #[arg(long)]
     password: Option<String>,
 
-    /// SASL security protocol (SASL_PLAINTEXT or SASL_SSL)
-    #[arg(long, default_value = "SASL_PLAINTEXT")]
-    security_protocol: String,
+    /// SASL security protocol (SASL_PLAINTEXT or SASL_SSL, default: SASL_PLAINTEXT)
+    #[arg(long)]
+    security_protocol: Option<String>,
 
-    /// SASL mechanism (PLAIN, SCRAM-SHA-256, SCRAM-SHA-512)
-    #[arg(long, default_value = "PLAIN")]
-    mechanism: String,
+    /// SASL mechanism (PLAIN, SCRAM-SHA-256, SCRAM-SHA-512, default: PLAIN)
+    #[arg(long)]
+    mechanism: Option<String>,
 
     /// Topic to check metadata (optional)
     #[arg(short, long)]
@@ -78,6 +103,176 @@ struct PingArgs {
     format: String,
 }
 
+#[derive(Args)]
+struct KafkaProduceArgs {
+    /// Kafka broker addresses (comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    brokers: Vec<String>,
+
+    /// Topic to produce to
+    #[arg(short, long, required = true)]
+    topic: String,
+
+    /// Message payload
+    #[arg(short, long, required = true)]
+    message: String,
+
+    /// Message key (optional)
+    #[arg(short, long)]
+    key: Option<String>,
+
+    /// Message header in key=value form (repeatable)
+    #[arg(long = "header", value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+
+    /// Client ID
+    #[arg(long, default_value = "rc-kafka-client")]
+    client_id: String,
+
+    /// Connection timeout in seconds
+    #[arg(long, default_value = "10")]
+    timeout: u64,
+
+    /// Enable SASL authentication
+    #[arg(long)]
+    sasl: bool,
+
+    /// SASL username (required if --sasl is set)
+    #[arg(long)]
+    username: Option<String>,
+
+    /// SASL password (required if --sasl is set)
+    #[arg(long)]
+    password: Option<String>,
+
+    /// SASL security protocol (SASL_PLAINTEXT or SASL_SSL, default: SASL_PLAINTEXT)
+    #[arg(long)]
+    security_protocol: Option<String>,
+
+    /// SASL mechanism (PLAIN, SCRAM-SHA-256, SCRAM-SHA-512, default: PLAIN)
+    #[arg(long)]
+    mechanism: Option<String>,
+
+    /// Output format (text or json)
+    #[arg(long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct KafkaConsumeArgs {
+    /// Kafka broker addresses (comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    brokers: Vec<String>,
+
+    /// Topic to consume from
+    #[arg(short, long, required = true)]
+    topic: String,
+
+    /// Consumer group ID
+    #[arg(short, long, default_value = "rc-kafka-consumer")]
+    group_id: String,
+
+    /// Start offset (earliest, latest or <n>). When set, the partition is
+    /// assigned directly instead of subscribing through the consumer group
+    #[arg(long)]
+    offset: Option<String>,
+
+    /// Partition to read from when --offset is set
+    #[arg(long, default_value = "0")]
+    partition: i32,
+
+    /// Number of messages to read before exiting
+    #[arg(short = 'n', long, default_value = "1")]
+    count: usize,
+
+    /// Client ID
+    #[arg(long, default_value = "rc-kafka-client")]
+    client_id: String,
+
+    /// Connection and receive timeout in seconds
+    #[arg(long, default_value = "10")]
+    timeout: u64,
+
+    /// Enable SASL authentication
+    #[arg(long)]
+    sasl: bool,
+
+    /// SASL username (required if --sasl is set)
+    #[arg(long)]
+    username: Option<String>,
+
+    /// SASL password (required if --sasl is set)
+    #[arg(long)]
+    password: Option<String>,
+
+    /// SASL security protocol (SASL_PLAINTEXT or SASL_SSL, default: SASL_PLAINTEXT)
+    #[arg(long)]
+    security_protocol: Option<String>,
+
+    /// SASL mechanism (PLAIN, SCRAM-SHA-256, SCRAM-SHA-512, default: PLAIN)
+    #[arg(long)]
+    mechanism: Option<String>,
+
+    /// Output format (text or json)
+    #[arg(long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct KafkaLagArgs {
+    /// Kafka broker addresses (comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    brokers: Vec<String>,
+
+    /// Consumer group to inspect
+    #[arg(short, long, required = true)]
+    group: String,
+
+    /// Topic to compute lag for
+    #[arg(short, long, required = true)]
+    topic: String,
+
+    /// Client ID
+    #[arg(long, default_value = "rc-kafka-client")]
+    client_id: String,
+
+    /// Request timeout in seconds
+    #[arg(long, default_value = "10")]
+    timeout: u64,
+
+    /// Enable SASL authentication
+    #[arg(long)]
+    sasl: bool,
+
+    /// SASL username (required if --sasl is set)
+    #[arg(long)]
+    username: Option<String>,
+
+    /// SASL password (required if --sasl is set)
+    #[arg(long)]
+    password: Option<String>,
+
+    /// SASL security protocol (SASL_PLAINTEXT or SASL_SSL, default: SASL_PLAINTEXT)
+    #[arg(long)]
+    security_protocol: Option<String>,
+
+    /// SASL mechanism (PLAIN, SCRAM-SHA-256, SCRAM-SHA-512, default: PLAIN)
+    #[arg(long)]
+    mechanism: Option<String>,
+
+    /// Output format (text or json)
+    #[arg(long, default_value = "text")]
+    format: String,
+}
+
+/// Parse a `key=value` header argument
+fn parse_header(raw: &str) -> Result<(String, String), String> {
+    match raw.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("invalid header '{raw}', expected key=value")),
+    }
+}
+
 #[derive(Args)]
 struct RedisArgs {
     #[command(subcommand)]
@@ -98,13 +293,15 @@ enum RedisCommands {
     Info(RedisInfoArgs),
     /// List keys matching pattern
     Keys(RedisKeysArgs),
+    /// Delete all keys matching pattern (uses SCAN, not KEYS)
+    Delp(RedisDelpArgs),
 }
 
 #[derive(Args)]
 struct RedisPingArgs {
     /// Redis server address (host:port or redis://host:port)
-    #[arg(short = 'H', long, required = true)]
-    host: String,
+    #[arg(short = 'H', long)]
+    host: Option<String>,
 
     /// Password for authentication
     #[arg(short, long)]
@@ -134,8 +331,8 @@ struct RedisPingArgs {
 #[derive(Args)]
 struct RedisGetArgs {
     /// Redis server address (host:port or redis://host:port)
-    #[arg(short = 'H', long, required = true)]
-    host: String,
+    #[arg(short = 'H', long)]
+    host: Option<String>,
 
     /// Key to get
     #[arg(short, long, required = true)]
@@ -165,8 +362,8 @@ struct RedisGetArgs {
 #[derive(Args)]
 struct RedisSetArgs {
     /// Redis server address (host:port or redis://host:port)
-    #[arg(short = 'H', long, required = true)]
-    host: String,
+    #[arg(short = 'H', long)]
+    host: Option<String>,
 
     /// Key to set
     #[arg(short, long, required = true)]
@@ -204,8 +401,8 @@ struct RedisSetArgs {
 #[derive(Args)]
 struct RedisDelArgs {
     /// Redis server address (host:port or redis://host:port)
-    #[arg(short = 'H', long, required = true)]
-    host: String,
+    #[arg(short = 'H', long)]
+    host: Option<String>,
 
     /// Key to delete
     #[arg(short, long, required = true)]
@@ -235,8 +432,8 @@ struct RedisDelArgs {
 #[derive(Args)]
 struct RedisInfoArgs {
     /// Redis server address (host:port or redis://host:port)
-    #[arg(short = 'H', long, required = true)]
-    host: String,
+    #[arg(short = 'H', long)]
+    host: Option<String>,
 
     /// Password for authentication
     #[arg(short, long)]
@@ -266,8 +463,8 @@ struct RedisInfoArgs {
 #[derive(Args)]
 struct RedisKeysArgs {
     /// Redis server address (host:port or redis://host:port)
-    #[arg(short = 'H', long, required = true)]
-    host: String,
+    #[arg(short = 'H', long)]
+    host: Option<String>,
 
     /// Pattern to match (default: *)
     #[arg(short = 'P', long, default_value = "*")]
@@ -294,6 +491,41 @@ struct RedisKeysArgs {
     format: String,
 }
 
+#[derive(Args)]
+struct RedisDelpArgs {
+    /// Redis server address (host:port or redis://host:port)
+    #[arg(short = 'H', long)]
+    host: Option<String>,
+
+    /// Pattern of keys to delete, e.g. "prefix:*"
+    #[arg(short = 'P', long, required = true)]
+    pattern: String,
+
+    /// Skip the confirmation prompt
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Password for authentication
+    #[arg(short, long)]
+    password: Option<String>,
+
+    /// Username for ACL authentication (Redis 6.0+)
+    #[arg(short, long)]
+    username: Option<String>,
+
+    /// Database index (default: 0)
+    #[arg(short, long, default_value = "0")]
+    db: i64,
+
+    /// Enable TLS
+    #[arg(long)]
+    tls: bool,
+
+    /// Output format (text or json)
+    #[arg(long, default_value = "text")]
+    format: String,
+}
+
 #[derive(Args)]
 struct MySqlArgs {
     #[command(subcommand)]
@@ -306,13 +538,82 @@ enum MySqlCommands {
     Ping(MySqlPingArgs),
     /// Execute SQL query
     Query(MySqlQueryArgs),
+    /// List tables in the database
+    Tables(MySqlTablesArgs),
+    /// Show the columns of a table
+    Describe(MySqlDescribeArgs),
 }
 
 #[derive(Args)]
 struct MySqlPingArgs {
     /// MySQL server address (host:port)
-    #[arg(short = 'H', long, required = true)]
-    host: String,
+    #[arg(short = 'H', long)]
+    host: Option<String>,
+
+    /// Username for authentication
+    #[arg(short, long)]
+    username: Option<String>,
+
+    /// Password for authentication
+    #[arg(short, long)]
+    password: Option<String>,
+
+    /// Database name
+    #[arg(short, long)]
+    database: Option<String>,
+
+    /// Connection timeout in seconds
+    #[arg(long, default_value = "10")]
+    timeout: u64,
+
+    /// Enable SSL/TLS
+    #[arg(long)]
+    ssl: bool,
+
+    /// Output format (text or json)
+    #[arg(long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct MySqlTablesArgs {
+    /// MySQL server address (host:port)
+    #[arg(short = 'H', long)]
+    host: Option<String>,
+
+    /// Username for authentication
+    #[arg(short, long)]
+    username: Option<String>,
+
+    /// Password for authentication
+    #[arg(short, long)]
+    password: Option<String>,
+
+    /// Database name
+    #[arg(short, long)]
+    database: Option<String>,
+
+    /// Connection timeout in seconds
+    #[arg(long, default_value = "10")]
+    timeout: u64,
+
+    /// Enable SSL/TLS
+    #[arg(long)]
+    ssl: bool,
+
+    /// Output format (text or json)
+    #[arg(long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct MySqlDescribeArgs {
+    /// Table name
+    table: String,
+
+    /// MySQL server address (host:port)
+    #[arg(short = 'H', long)]
+    host: Option<String>,
 
     /// Username for authentication
     #[arg(short, long)]
@@ -342,8 +643,8 @@ struct MySqlPingArgs {
 #[derive(Args)]
 struct MySqlQueryArgs {
     /// MySQL server address (host:port)
-    #[arg(short = 'H', long, required = true)]
-    host: String,
+    #[arg(short = 'H', long)]
+    host: Option<String>,
 
     /// SQL query to execute
     #[arg(short, long, required = true)]
@@ -404,62 +705,366 @@ struct PingResult {
     error: Option<String>,
 }
 
-/// MySQL ping result
-#[derive(Debug, Serialize, Deserialize)]
-struct MySqlPingResult {
-    success: bool,
-    host: String,
-    port: u16,
-    database: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    version: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<String>,
+/// 错误类别，决定 JSON 输出中的 `code` 和进程退出码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorKind {
+    Connection,
+    Auth,
+    NotFound,
+    Other,
+}
+
+impl ErrorKind {
+    fn code(self) -> &'static str {
+        match self {
+            ErrorKind::Connection => "connection",
+            ErrorKind::Auth => "auth",
+            ErrorKind::NotFound => "not_found",
+            ErrorKind::Other => "error",
+        }
+    }
+
+    fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Connection => 2,
+            ErrorKind::Auth => 3,
+            ErrorKind::NotFound => 4,
+            ErrorKind::Other => 1,
+        }
+    }
+
+    /// 根据错误信息识别类别，客户端只返回字符串错误，无法按类型区分
+    fn classify(message: &str, default: ErrorKind) -> ErrorKind {
+        const AUTH: &[&str] = &[
+            "auth",
+            "wrongpass",
+            "access denied",
+            "invalid password",
+            "invalid username-password",
+        ];
+        const NOT_FOUND: &[&str] = &[
+            "not found",
+            "unknown topic",
+            "unknowntopic",
+            "unknown database",
+            "doesn't exist",
+            "does not exist",
+        ];
+        const CONNECTION: &[&str] = &[
+            "connection refused",
+            "connection reset",
+            "timed out",
+            "unreachable",
+            "failed to lookup",
+            "all brokers down",
+        ];
+
+        let message = message.to_lowercase();
+        let matches = |patterns: &[&str]| patterns.iter().any(|p| message.contains(p));
+        if matches(AUTH) {
+            ErrorKind::Auth
+        } else if matches(NOT_FOUND) {
+            ErrorKind::NotFound
+        } else if matches(CONNECTION) {
+            ErrorKind::Connection
+        } else {
+            default
+        }
+    }
+}
+
+/// 命令执行失败，由 `main` 统一输出并设置退出码
+#[derive(Debug)]
+struct CliError {
+    kind: ErrorKind,
+    message: String,
+}
+
+impl CliError {
+    fn new(message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            kind: ErrorKind::classify(&message, ErrorKind::Other),
+            message,
+        }
+    }
+
+    /// 建立连接阶段的错误，无法进一步识别时归为连接错误
+    fn connection(message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            kind: ErrorKind::classify(&message, ErrorKind::Connection),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl Commands {
+    /// 当前子命令是否使用 JSON 输出
+    fn is_json(&self) -> bool {
+        let format = match self {
+            Commands::Kafka(args) => match &args.command {
+                KafkaCommands::Ping(args) => &args.format,
+                KafkaCommands::Produce(args) => &args.format,
+                KafkaCommands::Consume(args) => &args.format,
+                KafkaCommands::Lag(args) => &args.format,
+            },
+            Commands::MySql(args) => match &args.command {
+                MySqlCommands::Ping(args) => &args.format,
+                MySqlCommands::Query(args) => &args.format,
+                MySqlCommands::Tables(args) => &args.format,
+                MySqlCommands::Describe(args) => &args.format,
+            },
+            Commands::Redis(args) => match &args.command {
+                RedisCommands::Ping(args) => &args.format,
+                RedisCommands::Get(args) => &args.format,
+                RedisCommands::Set(args) => &args.format,
+                RedisCommands::Del(args) => &args.format,
+                RedisCommands::Info(args) => &args.format,
+                RedisCommands::Keys(args) => &args.format,
+                RedisCommands::Delp(args) => &args.format,
+            },
+        };
+        format.eq_ignore_ascii_case("json")
+    }
+}
+
+/// 命令输出：结果写到 stdout 或 `--output` 指定的文件，
+/// 连接过程等提示信息始终写 stdout，`--quiet` 时不输出
+#[derive(Default)]
+struct Output {
+    file: Option<std::fs::File>,
+    quiet: bool,
+}
+
+impl Output {
+    fn new(path: Option<&Path>, quiet: bool) -> anyhow::Result<Self> {
+        let file = match path {
+            Some(path) => Some(std::fs::File::create(path).map_err(|e| {
+                anyhow::anyhow!("Failed to create output file {}: {e}", path.display())
+            })?),
+            None => None,
+        };
+        Ok(Self { file, quiet })
+    }
+
+    /// 输出命令结果
+    fn result(&self, text: impl std::fmt::Display) -> io::Result<()> {
+        match self.file.as_ref() {
+            // &File 实现了 Write，无需可变借用
+            Some(mut file) => writeln!(file, "{text}"),
+            None => {
+                println!("{text}");
+                Ok(())
+            }
+        }
+    }
+
+    /// 输出过程提示信息
+    fn progress(&self, text: impl std::fmt::Display) {
+        if !self.quiet {
+            println!("{text}");
+        }
+    }
+}
+
+/// 输出错误并返回退出码：JSON 模式按结果输出 `{success, error, code}`
+fn report_error(err: &anyhow::Error, is_json: bool, out: &Output) -> i32 {
+    let kind = match err.downcast_ref::<CliError>() {
+        Some(cli_error) => cli_error.kind,
+        None => ErrorKind::classify(&err.to_string(), ErrorKind::Other),
+    };
+    if is_json {
+        let body = serde_json::json!({
+            "success": false,
+            "error": err.to_string(),
+            "code": kind.code(),
+        });
+        if out.result(&body).is_err() {
+            println!("{body}");
+        }
+    } else {
+        eprintln!("❌ Error: {err}");
+    }
+    kind.exit_code()
 }
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() {
     let cli = Cli::parse();
+    let is_json = cli.command.is_json();
+    let out = match Output::new(cli.output.as_deref(), cli.quiet) {
+        Ok(out) => out,
+        Err(err) => std::process::exit(report_error(&err, is_json, &Output::default())),
+    };
 
-    match cli.command {
-        Commands::Kafka(kafka_args) => handle_kafka_command(kafka_args).await?,
-        Commands::MySql(mysql_args) => handle_mysql_command(mysql_args).await?,
-        Commands::Redis(redis_args) => handle_redis_command(redis_args).await?,
+    let result = match ConnectionDefaults::load(cli.config.as_deref()) {
+        Ok(defaults) => match cli.command {
+            Commands::Kafka(kafka_args) => handle_kafka_command(kafka_args, &defaults, &out).await,
+            Commands::MySql(mysql_args) => handle_mysql_command(mysql_args, &defaults, &out).await,
+            Commands::Redis(redis_args) => handle_redis_command(redis_args, &defaults, &out).await,
+        },
+        Err(e) => Err(e),
+    };
+
+    if let Err(err) = result {
+        std::process::exit(report_error(&err, is_json, &out));
     }
+}
 
-    Ok(())
+/// 合并后的 MySQL 连接参数
+struct MySqlConnection {
+    host: String,
+    username: Option<String>,
+    password: Option<String>,
+    database: Option<String>,
 }
 
-async fn handle_mysql_command(args: MySqlArgs) -> anyhow::Result<()> {
+impl MySqlConnection {
+    fn resolve(
+        defaults: &ConnectionDefaults,
+        host: &Option<String>,
+        username: &Option<String>,
+        password: &Option<String>,
+        database: &Option<String>,
+    ) -> anyhow::Result<Self> {
+        let host = defaults.mysql_host(host.clone()).ok_or_else(|| {
+            anyhow::anyhow!("--host is required (or set mysql.host in the --config file)")
+        })?;
+        Ok(Self {
+            host,
+            username: defaults.mysql_username(username.clone()),
+            password: defaults.mysql_password(password.clone()),
+            database: defaults.mysql_database(database.clone()),
+        })
+    }
+
+    /// 建立连接，失败时返回连接类错误
+    async fn connect(&self, timeout: u64, ssl: bool) -> anyhow::Result<MySqlClient> {
+        let mut config = MySqlClientConfig::new(&self.host)
+            .with_timeout(timeout)
+            .with_ssl(ssl);
+        if let Some(username) = &self.username {
+            config = config.with_username(username);
+        }
+        if let Some(password) = &self.password {
+            config = config.with_password(password);
+        }
+        if let Some(database) = &self.database {
+            config = config.with_database(database);
+        }
+        MySqlClient::new(&config)
+            .await
+            .map_err(|e| CliError::connection(format!("Connection failed: {e}")).into())
+    }
+}
+
+async fn handle_mysql_command(
+    args: MySqlArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     match args.command {
-        MySqlCommands::Ping(ping_args) => handle_mysql_ping(ping_args).await?,
-        MySqlCommands::Query(query_args) => handle_mysql_query(query_args).await?,
+        MySqlCommands::Ping(ping_args) => handle_mysql_ping(ping_args, defaults, out).await?,
+        MySqlCommands::Query(query_args) => handle_mysql_query(query_args, defaults, out).await?,
+        MySqlCommands::Tables(tables_args) => {
+            handle_mysql_tables(tables_args, defaults, out).await?
+        }
+        MySqlCommands::Describe(describe_args) => {
+            handle_mysql_describe(describe_args, defaults, out).await?
+        }
     }
     Ok(())
 }
 
-async fn handle_kafka_command(args: KafkaArgs) -> anyhow::Result<()> {
+async fn handle_kafka_command(
+    args: KafkaArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     match args.command {
-        KafkaCommands::Ping(ping_args) => handle_ping(ping_args).await?,
+        KafkaCommands::Ping(ping_args) => handle_ping(ping_args, defaults, out).await?,
+        KafkaCommands::Produce(produce_args) => {
+            handle_kafka_produce(produce_args, defaults, out).await?
+        }
+        KafkaCommands::Consume(consume_args) => {
+            handle_kafka_consume(consume_args, defaults, out).await?
+        }
+        KafkaCommands::Lag(lag_args) => handle_kafka_lag(lag_args, defaults, out).await?,
     }
     Ok(())
 }
 
-async fn handle_ping(args: PingArgs) -> anyhow::Result<()> {
+/// 合并 broker 列表，命令行、环境变量和配置文件都未提供时报错
+fn kafka_brokers(defaults: &ConnectionDefaults, flag: Vec<String>) -> anyhow::Result<Vec<String>> {
+    let brokers = defaults.kafka_brokers(flag);
+    if brokers.is_empty() {
+        anyhow::bail!("--brokers is required (or set kafka.brokers in the --config file)");
+    }
+    Ok(brokers)
+}
+
+/// 合并 SASL 参数，未启用 SASL 时返回 None
+fn kafka_sasl(
+    defaults: &ConnectionDefaults,
+    sasl: bool,
+    username: Option<String>,
+    password: Option<String>,
+    security_protocol: Option<String>,
+    mechanism: Option<String>,
+) -> anyhow::Result<Option<SaslConfig>> {
+    if !defaults.kafka_sasl(sasl) {
+        return Ok(None);
+    }
+    let username = defaults
+        .kafka_username(username)
+        .ok_or_else(|| anyhow::anyhow!("Username is required when SASL is enabled"))?;
+    let password = defaults
+        .kafka_password(password)
+        .ok_or_else(|| anyhow::anyhow!("Password is required when SASL is enabled"))?;
+    Ok(Some(SaslConfig {
+        mechanism: defaults.kafka_mechanism(mechanism),
+        username,
+        password,
+        security_protocol: defaults.kafka_security_protocol(security_protocol),
+    }))
+}
+
+async fn handle_ping(
+    args: PingArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     let is_json = args.format.to_lowercase() == "json";
-    let brokers = args.brokers.clone();
+    let brokers = kafka_brokers(defaults, args.brokers)?;
     let client_id = args.client_id.clone();
-    let sasl_enabled = args.sasl;
+    let sasl = kafka_sasl(
+        defaults,
+        args.sasl,
+        args.username,
+        args.password,
+        args.security_protocol,
+        args.mechanism,
+    )?;
+    let sasl_enabled = sasl.is_some();
 
     if !is_json {
-        println!("🔌 Connecting to Kafka cluster...");
-        println!("   Brokers: {}", args.brokers.join(", "));
-        println!("   Client ID: {}", args.client_id);
+        out.progress(format_args!("🔌 Connecting to Kafka cluster..."));
+        out.progress(format_args!("   Brokers: {}", brokers.join(", ")));
+        out.progress(format_args!("   Client ID: {}", args.client_id));
     }
 
     // 创建配置
     let mut config =
-        KafkaClientConfig::new(args.brokers, args.client_id).with_timeout(args.timeout);
+        KafkaClientConfig::new(brokers.clone(), args.client_id).with_timeout(args.timeout);
 
     let mut result = PingResult {
         success: false,
@@ -478,31 +1083,21 @@ async fn handle_ping(args: PingArgs) -> anyhow::Result<()> {
     };
 
     // 如果启用 SASL，添加认证配置
-    if args.sasl {
-        let username = args
-            .username
-            .ok_or_else(|| anyhow::anyhow!("Username is required when SASL is enabled"))?;
-        let password = args
-            .password
-            .ok_or_else(|| anyhow::anyhow!("Password is required when SASL is enabled"))?;
-
-        result.username = Some(username.clone());
-        result.security_protocol = Some(args.security_protocol.clone());
-        result.mechanism = Some(args.mechanism.clone());
+    if let Some(sasl_config) = sasl {
+        result.username = Some(sasl_config.username.clone());
+        result.security_protocol = Some(sasl_config.security_protocol.clone());
+        result.mechanism = Some(sasl_config.mechanism.clone());
 
         if !is_json {
-            println!("   SASL: Enabled");
-            println!("   Username: {}", username);
-            println!("   Security Protocol: {}", args.security_protocol);
-            println!("   Mechanism: {}", args.mechanism);
+            out.progress(format_args!("   SASL: Enabled"));
+            out.progress(format_args!("   Username: {}", sasl_config.username));
+            out.progress(format_args!(
+                "   Security Protocol: {}",
+                sasl_config.security_protocol
+            ));
+            out.progress(format_args!("   Mechanism: {}", sasl_config.mechanism));
         }
 
-        let sasl_config = SaslConfig {
-            mechanism: args.mechanism,
-            username,
-            password,
-            security_protocol: args.security_protocol,
-        };
         config = config.with_sasl(sasl_config);
     }
 
@@ -510,73 +1105,67 @@ async fn handle_ping(args: PingArgs) -> anyhow::Result<()> {
     let producer = match KafkaProducer::new(&config) {
         Ok(p) => p,
         Err(e) => {
-            result.error = Some(format!("Failed to create Kafka producer: {}", e));
-            if is_json {
-                println!("{}", serde_json::to_string_pretty(&result)?);
-            } else {
-                println!("❌ Error: {}", result.error.as_ref().unwrap());
-            }
-            return Err(anyhow::anyhow!("Failed to create producer"));
+            return Err(
+                CliError::connection(format!("Failed to create Kafka producer: {e}")).into(),
+            );
         }
     };
 
     // 执行 ping
     if !is_json {
-        println!("\n⏳ Pinging Kafka cluster...");
+        out.progress(format_args!("\n⏳ Pinging Kafka cluster..."));
     }
 
     match producer.ping(Duration::from_secs(args.timeout)) {
         Ok(_) => {
             result.success = true;
             if !is_json {
-                println!("✅ Ping successful!\n");
+                out.result(format_args!("✅ Ping successful!\n"))?;
             }
         }
         Err(e) => {
-            result.error = Some(format!("Ping failed: {}", e));
-            if is_json {
-                println!("{}", serde_json::to_string_pretty(&result)?);
-            } else {
-                println!("❌ Ping failed: {}", e);
-            }
-            return Err(anyhow::anyhow!("Ping failed"));
+            return Err(CliError::connection(format!("Ping failed: {e}")).into());
         }
     }
 
     // 如果指定了 topic，获取 topic metadata
     if let Some(topic) = &args.topic {
         if !is_json {
-            println!("📊 Fetching metadata for topic '{}'...", topic);
+            out.progress(format_args!(
+                "📊 Fetching metadata for topic '{}'...",
+                topic
+            ));
         }
         match producer.get_topic_metadata(topic, Duration::from_secs(args.timeout)) {
             Ok(metadata) => {
-                // 解析 metadata 字符串
-                parse_metadata(&metadata, &mut result);
+                apply_metadata(&metadata, Some(topic), &mut result);
                 if !is_json {
-                    println!("\n{}", metadata);
+                    out.result(format_args!("\n{}", metadata))?;
                 }
             }
             Err(e) => {
                 if !is_json {
-                    println!("⚠️  Failed to fetch topic metadata: {}", e);
+                    out.progress(format_args!("⚠️  Failed to fetch topic metadata: {}", e));
                 }
             }
         }
     } else {
         // 获取集群整体 metadata
         if !is_json {
-            println!("📊 Fetching cluster metadata...");
+            out.progress(format_args!("📊 Fetching cluster metadata..."));
         }
         match producer.get_topic_metadata("", Duration::from_secs(args.timeout)) {
             Ok(metadata) => {
-                parse_metadata(&metadata, &mut result);
+                apply_metadata(&metadata, None, &mut result);
                 if !is_json {
-                    println!("\n{}", metadata);
+                    out.result(format_args!("\n{}", metadata))?;
                 }
             }
             Err(_) => {
                 if !is_json {
-                    println!("   Use --topic <name> to get specific topic metadata\n");
+                    out.progress(format_args!(
+                        "   Use --topic <name> to get specific topic metadata\n"
+                    ));
                 }
             }
         }
@@ -584,213 +1173,399 @@ async fn handle_ping(args: PingArgs) -> anyhow::Result<()> {
 
     // 输出 JSON 结果
     if is_json {
-        println!("{}", serde_json::to_string_pretty(&result)?);
+        out.result(format_args!("{}", serde_json::to_string_pretty(&result)?))?;
     }
 
     Ok(())
 }
 
-fn parse_metadata(metadata: &str, result: &mut PingResult) {
-    if let Some(cluster_line) = metadata.lines().next() {
-        if let Some(cluster) = cluster_line.strip_prefix("Cluster: ") {
-            result.cluster_name = Some(cluster.to_string());
-        }
+async fn handle_kafka_produce(
+    args: KafkaProduceArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
+    let is_json = args.format.to_lowercase() == "json";
+
+    let brokers = kafka_brokers(defaults, args.brokers)?;
+    let mut config = KafkaClientConfig::new(brokers, args.client_id).with_timeout(args.timeout);
+    if let Some(sasl_config) = kafka_sasl(
+        defaults,
+        args.sasl,
+        args.username,
+        args.password,
+        args.security_protocol,
+        args.mechanism,
+    )? {
+        config = config.with_sasl(sasl_config);
     }
-    if let Some(brokers_line) = metadata.lines().nth(1) {
-        if let Some(count_str) = brokers_line.strip_prefix("Brokers: ") {
-            result.broker_count = count_str.parse().ok();
+
+    let producer = KafkaProducer::new(&config)
+        .map_err(|e| CliError::connection(format!("Failed to create Kafka producer: {e}")))?;
+    let headers: Vec<(&str, &[u8])> = args
+        .headers
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_bytes()))
+        .collect();
+
+    let result = producer
+        .send_with_headers(
+            &args.topic,
+            args.key.as_deref(),
+            args.message.as_bytes(),
+            &headers,
+        )
+        .await
+        .and_then(|_| producer.flush(Duration::from_secs(args.timeout)));
+
+    match result {
+        Ok(()) => {
+            if is_json {
+                out.result(format_args!(
+                    "{}",
+                    serde_json::json!({
+                        "success": true,
+                        "topic": args.topic,
+                        "key": args.key,
+                        "headers": args.headers.len(),
+                    })
+                ))?;
+            } else {
+                out.result(format_args!("✅ Message sent to topic '{}'", args.topic))?;
+            }
+        }
+        Err(e) => {
+            return Err(CliError::new(format!("Failed to send message: {e}")).into());
         }
     }
-    if let Some(topics_line) = metadata.lines().nth(2) {
-        if let Some(count_str) = topics_line.strip_prefix("Topics: ") {
-            result.topic_count = count_str.parse().ok();
+
+    Ok(())
+}
+
+async fn handle_kafka_consume(
+    args: KafkaConsumeArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
+    let is_json = args.format.to_lowercase() == "json";
+
+    let brokers = kafka_brokers(defaults, args.brokers)?;
+    let mut config = KafkaClientConfig::new(brokers, args.client_id)
+        .with_timeout(args.timeout)
+        .with_group_id(args.group_id)
+        .with_auto_commit(false);
+    if let Some(sasl_config) = kafka_sasl(
+        defaults,
+        args.sasl,
+        args.username,
+        args.password,
+        args.security_protocol,
+        args.mechanism,
+    )? {
+        config = config.with_sasl(sasl_config);
+    }
+
+    let consumer = KafkaConsumer::new(&config)
+        .map_err(|e| CliError::connection(format!("Failed to create Kafka consumer: {e}")))?;
+
+    // assign 与 subscribe 互斥：指定 offset 时直接分配分区
+    match &args.offset {
+        Some(raw) => {
+            let offset = parse_offset(raw).map_err(|e| anyhow::anyhow!(e))?;
+            consumer
+                .assign_partition(&args.topic, args.partition, offset)
+                .map_err(CliError::new)?;
         }
+        None => consumer.subscribe(&[&args.topic]).map_err(CliError::new)?,
     }
-    for line in metadata.lines() {
-        if line.contains("partitions") {
-            if let Some(parts) = line.split(':').nth(1) {
-                if let Some(count_str) = parts.trim().split_whitespace().next() {
-                    result.partition_count = count_str.parse().ok();
+
+    for _ in 0..args.count {
+        let msg =
+            match tokio::time::timeout(Duration::from_secs(args.timeout), consumer.recv()).await {
+                Ok(Ok(msg)) => msg,
+                Ok(Err(e)) => {
+                    return Err(CliError::new(format!("Failed to receive message: {e}")).into());
                 }
-            }
+                Err(_) => {
+                    if !is_json {
+                        out.progress(format_args!(
+                            "⚠️  No more messages within {}s",
+                            args.timeout
+                        ));
+                    }
+                    break;
+                }
+            };
+
+        let key = msg.key().map(|k| String::from_utf8_lossy(k).into_owned());
+        let payload = extract_payload(&msg)
+            .map(|p| String::from_utf8_lossy(p).into_owned())
+            .unwrap_or_default();
+        if is_json {
+            out.result(format_args!(
+                "{}",
+                serde_json::json!({
+                    "topic": msg.topic(),
+                    "partition": msg.partition(),
+                    "offset": msg.offset(),
+                    "key": key,
+                    "payload": payload,
+                })
+            ))?;
+        } else {
+            out.result(format_args!(
+                "[{}:{}@{}] {}{}",
+                msg.topic(),
+                msg.partition(),
+                msg.offset(),
+                key.map(|k| format!("{k} => ")).unwrap_or_default(),
+                payload
+            ))?;
         }
     }
+
+    Ok(())
 }
 
-async fn handle_redis_command(args: RedisArgs) -> anyhow::Result<()> {
+async fn handle_kafka_lag(
+    args: KafkaLagArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
+    let is_json = args.format.to_lowercase() == "json";
+
+    let brokers = kafka_brokers(defaults, args.brokers)?;
+    let mut config = KafkaClientConfig::new(brokers, args.client_id)
+        .with_timeout(args.timeout)
+        .with_group_id(args.group.clone())
+        .with_auto_commit(false);
+    if let Some(sasl_config) = kafka_sasl(
+        defaults,
+        args.sasl,
+        args.username,
+        args.password,
+        args.security_protocol,
+        args.mechanism,
+    )? {
+        config = config.with_sasl(sasl_config);
+    }
+
+    let consumer = KafkaConsumer::new(&config)
+        .map_err(|e| CliError::connection(format!("Failed to create Kafka consumer: {e}")))?;
+    // metadata、已提交 offset 与 watermark 查询都是阻塞调用，放到阻塞线程池执行
+    let timeout = Duration::from_secs(args.timeout);
+    let lag = tokio::task::spawn_blocking(move || {
+        consumer
+            .group_lag(&args.group, &args.topic, timeout)
+            .map_err(CliError::connection)
+    })
+    .await??;
+
+    if is_json {
+        out.result(format_args!("{}", serde_json::to_string_pretty(&lag)?))?;
+    } else {
+        out.result(format_args!("{}", lag.to_string().trim_end()))?;
+    }
+    Ok(())
+}
+
+/// 把 metadata 填入 ping 结果，指定了 topic 时附带其分区数
+fn apply_metadata(metadata: &ClusterMetadata, topic: Option<&str>, result: &mut PingResult) {
+    result.cluster_name = Some(metadata.cluster_name.clone());
+    result.broker_count = Some(metadata.brokers.len());
+    result.topic_count = Some(metadata.topics.len());
+    result.partition_count = topic
+        .and_then(|name| metadata.topic(name))
+        .map(|topic| topic.partition_count);
+}
+
+async fn handle_redis_command(
+    args: RedisArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     match args.command {
-        RedisCommands::Ping(ping_args) => handle_redis_ping(ping_args).await?,
-        RedisCommands::Get(get_args) => handle_redis_get(get_args).await?,
-        RedisCommands::Set(set_args) => handle_redis_set(set_args).await?,
-        RedisCommands::Del(del_args) => handle_redis_del(del_args).await?,
-        RedisCommands::Info(info_args) => handle_redis_info(info_args).await?,
-        RedisCommands::Keys(keys_args) => handle_redis_keys(keys_args).await?,
+        RedisCommands::Ping(ping_args) => handle_redis_ping(ping_args, defaults, out).await?,
+        RedisCommands::Get(get_args) => handle_redis_get(get_args, defaults, out).await?,
+        RedisCommands::Set(set_args) => handle_redis_set(set_args, defaults, out).await?,
+        RedisCommands::Del(del_args) => handle_redis_del(del_args, defaults, out).await?,
+        RedisCommands::Info(info_args) => handle_redis_info(info_args, defaults, out).await?,
+        RedisCommands::Keys(keys_args) => handle_redis_keys(keys_args, defaults, out).await?,
+        RedisCommands::Delp(delp_args) => handle_redis_delp(delp_args, defaults, out).await?,
     }
     Ok(())
 }
 
+/// 合并连接参数，返回实际使用的地址和客户端配置
 fn build_redis_config(
-    host: &str,
-    password: Option<&str>,
+    defaults: &ConnectionDefaults,
+    host: Option<String>,
+    password: Option<String>,
     username: Option<&str>,
     db: i64,
     tls: bool,
-) -> RedisClientConfig {
-    let mut config = RedisClientConfig::new(host).with_db(db).with_tls(tls);
-    if let Some(pass) = password {
-        config = config.with_password(pass);
+) -> anyhow::Result<(String, RedisClientConfig)> {
+    let host = defaults.redis_host(host).ok_or_else(|| {
+        anyhow::anyhow!("--host is required (or set redis.address in the --config file)")
+    })?;
+    let mut config = RedisClientConfig::new(&host).with_db(db).with_tls(tls);
+    if let Some(pass) = defaults.redis_password(password) {
+        config = config.with_password(&pass);
     }
     if let Some(user) = username {
         config = config.with_username(user);
     }
-    config
+    Ok((host, config))
 }
 
-async fn handle_redis_ping(args: RedisPingArgs) -> anyhow::Result<()> {
+async fn handle_redis_ping(
+    args: RedisPingArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     let is_json = args.format.to_lowercase() == "json";
-    let config = build_redis_config(
-        &args.host,
-        args.password.as_deref(),
+    let (host, config) = build_redis_config(
+        defaults,
+        args.host.clone(),
+        args.password.clone(),
         args.username.as_deref(),
         args.db,
         args.tls,
-    );
+    )?;
+    let config = config.with_timeout(args.timeout);
 
     let mut result = RedisPingResult {
         success: false,
-        url: args.host.clone(),
+        url: host.clone(),
         db: Some(args.db),
         version: None,
         dbsize: None,
+        latency_ms: None,
         error: None,
     };
 
     if !is_json {
-        println!("🔌 Connecting to Redis...");
-        println!("   Host: {}", args.host);
-        println!("   Database: {}", args.db);
+        out.progress(format_args!("🔌 Connecting to Redis..."));
+        out.progress(format_args!("   Host: {}", host));
+        out.progress(format_args!("   Database: {}", args.db));
         if args.tls {
-            println!("   TLS: Enabled");
+            out.progress(format_args!("   TLS: Enabled"));
         }
     }
 
     let mut client = match RedisClient::new(&config).await {
         Ok(c) => c,
         Err(e) => {
-            result.error = Some(e.clone());
-            if is_json {
-                println!("{}", serde_json::to_string_pretty(&result)?);
-            } else {
-                println!("❌ Connection failed: {}", e);
-            }
-            return Err(anyhow::anyhow!(e));
+            return Err(CliError::connection(format!("Connection failed: {e}")).into());
         }
     };
 
     if !is_json {
-        println!("\n⏳ Pinging Redis...");
+        out.progress(format_args!("\n⏳ Pinging Redis..."));
     }
 
-    match client.ping().await {
-        Ok(pong) => {
+    match client.ping_timed().await {
+        Ok(latency) => {
             result.success = true;
+            result.latency_ms = Some(latency.as_secs_f64() * 1000.0);
             if !is_json {
-                println!("✅ Ping successful! Response: {}", pong);
+                out.result(format_args!(
+                    "✅ Ping successful! Latency: {:.2} ms",
+                    latency.as_secs_f64() * 1000.0
+                ))?;
             }
         }
         Err(e) => {
-            result.error = Some(e.clone());
-            if is_json {
-                println!("{}", serde_json::to_string_pretty(&result)?);
-            } else {
-                println!("❌ Ping failed: {}", e);
-            }
-            return Err(anyhow::anyhow!(e));
+            return Err(CliError::new(format!("Ping failed: {e}")).into());
         }
     }
 
     if let Ok(version) = client.version().await {
         result.version = Some(version.clone());
         if !is_json {
-            println!("   Version: {}", version);
+            out.result(format_args!("   Version: {}", version))?;
         }
     }
 
     if let Ok(dbsize) = client.dbsize().await {
         result.dbsize = Some(dbsize);
         if !is_json {
-            println!("   Keys in DB: {}", dbsize);
+            out.result(format_args!("   Keys in DB: {}", dbsize))?;
         }
     }
 
     if is_json {
-        println!("{}", serde_json::to_string_pretty(&result)?);
+        out.result(format_args!("{}", serde_json::to_string_pretty(&result)?))?;
     }
 
     Ok(())
 }
 
-async fn handle_redis_get(args: RedisGetArgs) -> anyhow::Result<()> {
+async fn handle_redis_get(
+    args: RedisGetArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     let is_json = args.format.to_lowercase() == "json";
-    let config = build_redis_config(
-        &args.host,
-        args.password.as_deref(),
+    let (_, config) = build_redis_config(
+        defaults,
+        args.host.clone(),
+        args.password.clone(),
         args.username.as_deref(),
         args.db,
         args.tls,
-    );
+    )?;
 
     let mut client = RedisClient::new(&config)
         .await
-        .map_err(|e| anyhow::anyhow!(e))?;
+        .map_err(|e| CliError::connection(format!("Connection failed: {e}")))?;
 
     match client.get(&args.key).await {
         Ok(Some(value)) => {
             if is_json {
-                println!(
+                out.result(format_args!(
                     "{}",
                     serde_json::json!({"key": args.key, "value": value, "exists": true})
-                );
+                ))?;
             } else {
-                println!("{}", value);
+                out.result(format_args!("{}", value))?;
             }
         }
         Ok(None) => {
             if is_json {
-                println!(
+                out.result(format_args!(
                     "{}",
                     serde_json::json!({"key": args.key, "value": null, "exists": false})
-                );
+                ))?;
             } else {
-                println!("(nil)");
+                out.result(format_args!("(nil)"))?;
             }
         }
         Err(e) => {
-            if is_json {
-                println!("{}", serde_json::json!({"error": e}));
-            } else {
-                println!("❌ Error: {}", e);
-            }
-            return Err(anyhow::anyhow!(e));
+            return Err(CliError::new(e).into());
         }
     }
 
     Ok(())
 }
 
-async fn handle_redis_set(args: RedisSetArgs) -> anyhow::Result<()> {
+async fn handle_redis_set(
+    args: RedisSetArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     let is_json = args.format.to_lowercase() == "json";
-    let config = build_redis_config(
-        &args.host,
-        args.password.as_deref(),
+    let (_, config) = build_redis_config(
+        defaults,
+        args.host.clone(),
+        args.password.clone(),
         args.username.as_deref(),
         args.db,
         args.tls,
-    );
+    )?;
 
     let mut client = RedisClient::new(&config)
         .await
-        .map_err(|e| anyhow::anyhow!(e))?;
+        .map_err(|e| CliError::connection(format!("Connection failed: {e}")))?;
 
     let result = if let Some(ttl) = args.ttl {
         client.set_ex(&args.key, &args.value, ttl).await
@@ -801,75 +1576,78 @@ async fn handle_redis_set(args: RedisSetArgs) -> anyhow::Result<()> {
     match result {
         Ok(()) => {
             if is_json {
-                println!(
+                out.result(format_args!(
                     "{}",
                     serde_json::json!({"success": true, "key": args.key, "ttl": args.ttl})
-                );
+                ))?;
             } else {
-                println!("OK");
+                out.result(format_args!("OK"))?;
             }
         }
         Err(e) => {
-            if is_json {
-                println!("{}", serde_json::json!({"success": false, "error": e}));
-            } else {
-                println!("❌ Error: {}", e);
-            }
-            return Err(anyhow::anyhow!(e));
+            return Err(CliError::new(e).into());
         }
     }
 
     Ok(())
 }
 
-async fn handle_redis_del(args: RedisDelArgs) -> anyhow::Result<()> {
+async fn handle_redis_del(
+    args: RedisDelArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     let is_json = args.format.to_lowercase() == "json";
-    let config = build_redis_config(
-        &args.host,
-        args.password.as_deref(),
+    let (_, config) = build_redis_config(
+        defaults,
+        args.host.clone(),
+        args.password.clone(),
         args.username.as_deref(),
         args.db,
         args.tls,
-    );
+    )?;
 
     let mut client = RedisClient::new(&config)
         .await
-        .map_err(|e| anyhow::anyhow!(e))?;
+        .map_err(|e| CliError::connection(format!("Connection failed: {e}")))?;
 
     match client.del(&args.key).await {
         Ok(count) => {
             if is_json {
-                println!("{}", serde_json::json!({"deleted": count, "key": args.key}));
+                out.result(format_args!(
+                    "{}",
+                    serde_json::json!({"deleted": count, "key": args.key})
+                ))?;
             } else {
-                println!("(integer) {}", count);
+                out.result(format_args!("(integer) {}", count))?;
             }
         }
         Err(e) => {
-            if is_json {
-                println!("{}", serde_json::json!({"error": e}));
-            } else {
-                println!("❌ Error: {}", e);
-            }
-            return Err(anyhow::anyhow!(e));
+            return Err(CliError::new(e).into());
         }
     }
 
     Ok(())
 }
 
-async fn handle_redis_info(args: RedisInfoArgs) -> anyhow::Result<()> {
+async fn handle_redis_info(
+    args: RedisInfoArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     let is_json = args.format.to_lowercase() == "json";
-    let config = build_redis_config(
-        &args.host,
-        args.password.as_deref(),
+    let (_, config) = build_redis_config(
+        defaults,
+        args.host.clone(),
+        args.password.clone(),
         args.username.as_deref(),
         args.db,
         args.tls,
-    );
+    )?;
 
     let mut client = RedisClient::new(&config)
         .await
-        .map_err(|e| anyhow::anyhow!(e))?;
+        .map_err(|e| CliError::connection(format!("Connection failed: {e}")))?;
 
     match client.info(args.section.as_deref()).await {
         Ok(info) => {
@@ -882,94 +1660,160 @@ async fn handle_redis_info(args: RedisInfoArgs) -> anyhow::Result<()> {
                         Some((parts.next()?.to_string(), parts.next()?.to_string()))
                     })
                     .collect();
-                println!("{}", serde_json::to_string_pretty(&info_map)?);
+                out.result(format_args!("{}", serde_json::to_string_pretty(&info_map)?))?;
             } else {
-                println!("{}", info);
+                out.result(format_args!("{}", info))?;
             }
         }
         Err(e) => {
-            if is_json {
-                println!("{}", serde_json::json!({"error": e}));
-            } else {
-                println!("❌ Error: {}", e);
-            }
-            return Err(anyhow::anyhow!(e));
+            return Err(CliError::new(e).into());
         }
     }
 
     Ok(())
 }
 
-async fn handle_redis_keys(args: RedisKeysArgs) -> anyhow::Result<()> {
+async fn handle_redis_keys(
+    args: RedisKeysArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     let is_json = args.format.to_lowercase() == "json";
-    let config = build_redis_config(
-        &args.host,
-        args.password.as_deref(),
+    let (_, config) = build_redis_config(
+        defaults,
+        args.host.clone(),
+        args.password.clone(),
         args.username.as_deref(),
         args.db,
         args.tls,
-    );
+    )?;
 
     let mut client = RedisClient::new(&config)
         .await
-        .map_err(|e| anyhow::anyhow!(e))?;
+        .map_err(|e| CliError::connection(format!("Connection failed: {e}")))?;
 
     match client.keys(&args.pattern).await {
         Ok(keys) => {
             if is_json {
-                println!(
+                out.result(format_args!(
                     "{}",
                     serde_json::json!({"pattern": args.pattern, "count": keys.len(), "keys": keys})
-                );
+                ))?;
             } else {
                 if keys.is_empty() {
-                    println!("(empty list)");
+                    out.result(format_args!("(empty list)"))?;
                 } else {
                     for (i, key) in keys.iter().enumerate() {
-                        println!("{}) \"{}\"", i + 1, key);
+                        out.result(format_args!("{}) \"{}\"", i + 1, key))?;
                     }
                 }
             }
         }
         Err(e) => {
+            return Err(CliError::new(e).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// 在 stderr 提示确认，仅输入 y/yes 时返回 true（stdin 关闭视为拒绝）
+fn confirm(prompt: &str) -> io::Result<bool> {
+    eprint!("{prompt} [y/N] ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+async fn handle_redis_delp(
+    args: RedisDelpArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
+    let is_json = args.format.to_lowercase() == "json";
+    let (_, config) = build_redis_config(
+        defaults,
+        args.host.clone(),
+        args.password.clone(),
+        args.username.as_deref(),
+        args.db,
+        args.tls,
+    )?;
+
+    if !args.yes
+        && !confirm(&format!(
+            "Delete all keys matching '{}' in db {}?",
+            args.pattern, args.db
+        ))?
+    {
+        return Err(CliError::new("Aborted, no keys were deleted").into());
+    }
+
+    let mut client = RedisClient::new(&config)
+        .await
+        .map_err(|e| CliError::connection(format!("Connection failed: {e}")))?;
+
+    match client.del_by_pattern(&args.pattern).await {
+        Ok(count) => {
             if is_json {
-                println!("{}", serde_json::json!({"error": e}));
+                out.result(format_args!(
+                    "{}",
+                    serde_json::json!({"deleted": count, "pattern": args.pattern})
+                ))?;
             } else {
-                println!("❌ Error: {}", e);
+                out.result(format_args!("(integer) {}", count))?;
             }
-            return Err(anyhow::anyhow!(e));
+        }
+        Err(e) => {
+            return Err(CliError::new(e).into());
         }
     }
 
     Ok(())
 }
 
-async fn handle_mysql_ping(args: MySqlPingArgs) -> anyhow::Result<()> {
+async fn handle_mysql_ping(
+    args: MySqlPingArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     let is_json = args.format.to_lowercase() == "json";
-    let host = args.host.clone();
+    let MySqlConnection {
+        host,
+        username,
+        password,
+        database,
+    } = MySqlConnection::resolve(
+        defaults,
+        &args.host,
+        &args.username,
+        &args.password,
+        &args.database,
+    )?;
 
     if !is_json {
-        println!("🔌 Connecting to MySQL...");
-        println!("   Host: {}", args.host);
-        if let Some(db) = &args.database {
-            println!("   Database: {}", db);
+        out.progress(format_args!("🔌 Connecting to MySQL..."));
+        out.progress(format_args!("   Host: {}", host));
+        if let Some(db) = &database {
+            out.progress(format_args!("   Database: {}", db));
         }
         if args.ssl {
-            println!("   TLS: Enabled");
+            out.progress(format_args!("   TLS: Enabled"));
         }
     }
 
-    let mut config = MySqlClientConfig::new(&args.host).with_timeout(args.timeout);
+    let mut config = MySqlClientConfig::new(&host).with_timeout(args.timeout);
 
-    if let Some(username) = &args.username {
+    if let Some(username) = &username {
         config = config.with_username(username);
     }
 
-    if let Some(password) = &args.password {
+    if let Some(password) = &password {
         config = config.with_password(password);
     }
 
-    if let Some(database) = &args.database {
+    if let Some(database) = &database {
         config = config.with_database(database);
     }
 
@@ -979,96 +1823,104 @@ async fn handle_mysql_ping(args: MySqlPingArgs) -> anyhow::Result<()> {
 
     let mut result = MySqlPingResult {
         success: false,
-        host: args.host.clone(),
+        host: host.clone(),
         port: 3306,
-        database: args.database.clone(),
+        database: database.clone(),
         version: None,
+        latency_ms: None,
         error: None,
     };
 
     // Parse port from host if present
-    if let Some(pos) = host.find(':') {
-        if let Ok(port) = host[pos + 1..].parse() {
-            result.port = port;
-        }
+    if let Some(pos) = host.find(':')
+        && let Ok(port) = host[pos + 1..].parse()
+    {
+        result.port = port;
     }
 
     let mut client = match MySqlClient::new(&config).await {
         Ok(c) => c,
         Err(e) => {
-            result.error = Some(e.clone());
-            if is_json {
-                println!("{}", serde_json::to_string_pretty(&result)?);
-            } else {
-                println!("❌ Connection failed: {}", e);
-            }
-            return Err(anyhow::anyhow!(e));
+            return Err(CliError::connection(format!("Connection failed: {e}")).into());
         }
     };
 
     if !is_json {
-        println!("\n⏳ Pinging MySQL...");
+        out.progress(format_args!("\n⏳ Pinging MySQL..."));
     }
 
-    match client.ping().await {
-        Ok(()) => {
+    match client.ping_timed().await {
+        Ok(latency) => {
             result.success = true;
+            result.latency_ms = Some(latency.as_secs_f64() * 1000.0);
             if !is_json {
-                println!("✅ Ping successful!");
+                out.result(format_args!(
+                    "✅ Ping successful! Latency: {:.2} ms",
+                    latency.as_secs_f64() * 1000.0
+                ))?;
             }
         }
         Err(e) => {
-            result.error = Some(e.clone());
-            if is_json {
-                println!("{}", serde_json::to_string_pretty(&result)?);
-            } else {
-                println!("❌ Ping failed: {}", e);
-            }
-            return Err(anyhow::anyhow!(e));
+            return Err(CliError::new(format!("Ping failed: {e}")).into());
         }
     }
 
     if let Ok(version) = client.version().await {
         result.version = Some(version.clone());
         if !is_json {
-            println!("   Version: {}", version);
+            out.result(format_args!("   Version: {}", version))?;
         }
     }
 
     if is_json {
-        println!("{}", serde_json::to_string_pretty(&result)?);
+        out.result(format_args!("{}", serde_json::to_string_pretty(&result)?))?;
     }
 
     Ok(())
 }
 
-async fn handle_mysql_query(args: MySqlQueryArgs) -> anyhow::Result<()> {
+async fn handle_mysql_query(
+    args: MySqlQueryArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
     let is_json = args.format.to_lowercase() == "json";
-    let _host = args.host.clone();
+    let MySqlConnection {
+        host,
+        username,
+        password,
+        database,
+    } = MySqlConnection::resolve(
+        defaults,
+        &args.host,
+        &args.username,
+        &args.password,
+        &args.database,
+    )?;
 
     if !is_json {
-        println!("🔌 Connecting to MySQL...");
-        println!("   Host: {}", args.host);
-        if let Some(db) = &args.database {
-            println!("   Database: {}", db);
+        out.progress(format_args!("🔌 Connecting to MySQL..."));
+        out.progress(format_args!("   Host: {}", host));
+        if let Some(db) = &database {
+            out.progress(format_args!("   Database: {}", db));
         }
         if args.ssl {
-            println!("   TLS: Enabled");
+            out.progress(format_args!("   TLS: Enabled"));
         }
-        println!("   Query: {}", args.query);
+        out.progress(format_args!("   Query: {}", args.query));
     }
 
-    let mut config = MySqlClientConfig::new(&args.host).with_timeout(args.timeout);
+    let mut config = MySqlClientConfig::new(&host).with_timeout(args.timeout);
 
-    if let Some(username) = &args.username {
+    if let Some(username) = &username {
         config = config.with_username(username);
     }
 
-    if let Some(password) = &args.password {
+    if let Some(password) = &password {
         config = config.with_password(password);
     }
 
-    if let Some(database) = &args.database {
+    if let Some(database) = &database {
         config = config.with_database(database);
     }
 
@@ -1079,17 +1931,12 @@ async fn handle_mysql_query(args: MySqlQueryArgs) -> anyhow::Result<()> {
     let mut client = match MySqlClient::new(&config).await {
         Ok(c) => c,
         Err(e) => {
-            if is_json {
-                println!("{}", serde_json::json!({"error": e}));
-            } else {
-                println!("❌ Connection failed: {}", e);
-            }
-            return Err(anyhow::anyhow!(e));
+            return Err(CliError::connection(format!("Connection failed: {e}")).into());
         }
     };
 
     if !is_json {
-        println!("\n⏳ Executing query...");
+        out.progress(format_args!("\n⏳ Executing query..."));
     }
 
     let query_type = args.query_type.to_lowercase();
@@ -1097,44 +1944,126 @@ async fn handle_mysql_query(args: MySqlQueryArgs) -> anyhow::Result<()> {
         "ddl" => match client.execute_ddl(&args.query).await {
             Ok(()) => {
                 if is_json {
-                    println!(
+                    out.result(format_args!(
                         "{}",
                         serde_json::json!({"success": true, "message": "DDL executed successfully"})
-                    );
+                    ))?;
                 } else {
-                    println!("✅ DDL query executed successfully!");
+                    out.result(format_args!("✅ DDL query executed successfully!"))?;
                 }
             }
             Err(e) => {
-                if is_json {
-                    println!("{}", serde_json::json!({"error": e}));
-                } else {
-                    println!("❌ DDL query failed: {}", e);
-                }
-                return Err(anyhow::anyhow!(e));
+                return Err(CliError::new(format!("DDL query failed: {e}")).into());
             }
         },
-        "dml" | _ => match client.execute_dml(&args.query).await {
+        _ => match client.execute_dml(&args.query).await {
             Ok(rows) => {
                 if is_json {
-                    println!(
+                    out.result(format_args!(
                         "{}",
                         serde_json::json!({"success": true, "rows_affected": rows})
-                    );
+                    ))?;
                 } else {
-                    println!("✅ Query executed successfully! Rows affected: {}", rows);
+                    out.result(format_args!(
+                        "✅ Query executed successfully! Rows affected: {}",
+                        rows
+                    ))?;
                 }
             }
             Err(e) => {
-                if is_json {
-                    println!("{}", serde_json::json!({"error": e}));
-                } else {
-                    println!("❌ Query failed: {}", e);
-                }
-                return Err(anyhow::anyhow!(e));
+                return Err(CliError::new(format!("Query failed: {e}")).into());
             }
         },
     }
 
     Ok(())
 }
+
+async fn handle_mysql_tables(
+    args: MySqlTablesArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
+    let is_json = args.format.to_lowercase() == "json";
+    let client = MySqlConnection::resolve(
+        defaults,
+        &args.host,
+        &args.username,
+        &args.password,
+        &args.database,
+    )?
+    .connect(args.timeout, args.ssl)
+    .await?;
+
+    let tables = client.list_tables().await.map_err(CliError::new)?;
+    if is_json {
+        out.result(format_args!(
+            "{}",
+            serde_json::json!({"count": tables.len(), "tables": tables})
+        ))?;
+    } else if tables.is_empty() {
+        out.result(format_args!("(no tables)"))?;
+    } else {
+        for table in &tables {
+            out.result(format_args!("{}", table))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_mysql_describe(
+    args: MySqlDescribeArgs,
+    defaults: &ConnectionDefaults,
+    out: &Output,
+) -> anyhow::Result<()> {
+    let is_json = args.format.to_lowercase() == "json";
+    let client = MySqlConnection::resolve(
+        defaults,
+        &args.host,
+        &args.username,
+        &args.password,
+        &args.database,
+    )?
+    .connect(args.timeout, args.ssl)
+    .await?;
+
+    let columns = client
+        .describe_table(&args.table)
+        .await
+        .map_err(CliError::new)?;
+    if is_json {
+        out.result(format_args!(
+            "{}",
+            serde_json::json!({"table": args.table, "columns": columns})
+        ))?;
+    } else {
+        out.result(format_args!(
+            "{:<32} {:<24} {:<8} {}",
+            "Field", "Type", "Null", "Key"
+        ))?;
+        for column in &columns {
+            out.result(format_args!(
+                "{:<32} {:<24} {:<8} {}",
+                column.name,
+                column.column_type,
+                if column.nullable { "YES" } else { "NO" },
+                column.key.as_deref().unwrap_or("")
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition_is_valid() {
+        // 全局参数的短选项不能与子命令参数冲突
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,36 @@
+use std::process::Command;
+
+#[test]
+fn unreachable_redis_reports_json_connection_error() {
+    // 端口 1 上没有服务，连接会被立即拒绝
+    let output = Command::new(env!("CARGO_BIN_EXE_rc"))
+        .args(["redis", "ping", "-H", "127.0.0.1:1", "--format", "json"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    let body: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(body["success"], false);
+    assert_eq!(body["code"], "connection");
+    assert!(
+        body["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Connection failed")
+    );
+}
+
+#[test]
+fn redis_delp_aborts_without_confirmation() {
+    // stdin 关闭视为未确认，不会尝试连接
+    let output = Command::new(env!("CARGO_BIN_EXE_rc"))
+        .args(["redis", "delp", "-H", "127.0.0.1:1", "-P", "cache:*"])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Aborted"));
+    assert!(!stderr.contains("Connection failed"));
+}
@@ -0,0 +1,44 @@
+use std::process::Command;
+
+/// 对无服务的端口执行 redis ping，连接会被立即拒绝
+fn redis_ping(extra: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_rc"))
+        .args(["redis", "ping", "-H", "127.0.0.1:1", "--timeout", "1"])
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn output_flag_writes_result_to_file() {
+    let path = std::env::temp_dir().join("rc_output_flag.json");
+    let _ = std::fs::remove_file(&path);
+
+    let output = redis_ping(&[
+        "--quiet",
+        "--format",
+        "json",
+        "--output",
+        path.to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    let body: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(body["success"], false);
+    assert_eq!(body["code"], "connection");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn quiet_flag_omits_connection_banner() {
+    let verbose = redis_ping(&[]);
+    assert!(String::from_utf8_lossy(&verbose.stdout).contains("Connecting to Redis"));
+
+    let quiet = redis_ping(&["--quiet"]);
+    assert!(quiet.stdout.is_empty());
+    // 错误信息仍然输出到 stderr
+    assert!(String::from_utf8_lossy(&quiet.stderr).contains("Connection failed"));
+}
@@ -1,11 +1,189 @@
 use crate::event::Event;
 use crate::rule::*;
+use serde::Serialize;
 use std::collections::HashMap;
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tokio::sync::{mpsc, watch};
 use tokio::task::JoinHandle;
 
+/// 单个组件的检查结果
+#[derive(Debug, Clone)]
+pub struct ComponentStatus {
+    /// 组件 ID，与实际运行时一致（如 `pipeline-source-0`）
+    pub key: String,
+    /// 组件类型（如 `file`、`http`）
+    pub component_type: String,
+    /// 检查失败原因，`None` 表示就绪
+    pub error: Option<String>,
+}
+
+impl ComponentStatus {
+    fn new(key: String, component_type: &str, result: Result<()>) -> Self {
+        Self {
+            key,
+            component_type: component_type.to_string(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// 管道配置的预检报告
+#[derive(Debug, Clone)]
+pub struct DryRunReport {
+    pub pipeline_id: String,
+    pub components: Vec<ComponentStatus>,
+}
+
+impl DryRunReport {
+    /// 所有组件是否都已就绪
+    pub fn is_ok(&self) -> bool {
+        self.components.iter().all(ComponentStatus::is_ok)
+    }
+}
+
+/// 管道运行计数
+#[derive(Debug, Default)]
+struct PipelineMetrics {
+    /// Source 产出并进入管道的事件数
+    events_received: AtomicU64,
+    /// 成功写入 Sink 的次数（一个事件写入多个 Sink 时分别计数）
+    events_sent: AtomicU64,
+    /// Source/Transform/Sink 出错次数
+    errors: AtomicU64,
+    /// 主循环是否正常结束，结束但未置位说明任务异常退出
+    completed: AtomicBool,
+}
+
+/// 管道运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PipelineState {
+    Running,
+    Stopped,
+    Failed,
+}
+
+impl PipelineState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PipelineState::Running => "running",
+            PipelineState::Stopped => "stopped",
+            PipelineState::Failed => "failed",
+        }
+    }
+}
+
+/// 管道状态快照
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineStatus {
+    pub id: String,
+    pub name: String,
+    pub state: PipelineState,
+    pub events_received: u64,
+    pub events_sent: u64,
+    pub errors: u64,
+}
+
+impl PipelineMetrics {
+    /// 记录一次 Sink 写入结果，返回是否成功
+    fn record_write(&self, result: Result<()>) -> bool {
+        match result {
+            Ok(()) => {
+                self.events_sent.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(e) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                eprintln!("Sink write error: {e}");
+                false
+            }
+        }
+    }
+}
+
+/// 把事件写入所有 Sink：前 N-1 个写入克隆，最后一个接管原事件。
+/// 单个 Sink 失败不影响其余 Sink，任一失败即返回 false，事件不会被确认。
+async fn fan_out(
+    sinks: &mut [Box<dyn SinkRuntime>],
+    event: Box<dyn Event>,
+    metrics: &PipelineMetrics,
+) -> bool {
+    let Some((last, rest)) = sinks.split_last_mut() else {
+        return true;
+    };
+    let mut succeeded = true;
+    for sink in rest {
+        succeeded &= metrics.record_write(sink.write(event.clone_box()).await);
+    }
+    metrics.record_write(last.write(event).await) && succeeded
+}
+
+/// 刷新仍有缓冲事件的 Sink
+async fn flush_buffered(sinks: &mut [Box<dyn SinkRuntime>], metrics: &PipelineMetrics) {
+    for sink in sinks.iter_mut().filter(|sink| sink.buffered() > 0) {
+        if let Err(e) = sink.flush().await {
+            metrics.errors.fetch_add(1, Ordering::Relaxed);
+            eprintln!("Sink flush error: {e}");
+        }
+    }
+}
+
+/// 所有 Sink 都已写出缓冲事件时，向数据源确认等待中的事件
+fn release_acks(
+    pending: &mut Vec<(usize, String)>,
+    sinks: &[Box<dyn SinkRuntime>],
+    ack_senders: &[Option<mpsc::UnboundedSender<String>>],
+) {
+    if sinks.iter().any(|sink| sink.buffered() > 0) {
+        return;
+    }
+    for (source_index, ack_id) in pending.drain(..) {
+        if let Some(ack_tx) = &ack_senders[source_index] {
+            let _ = ack_tx.send(ack_id);
+        }
+    }
+}
+
+struct Pipeline {
+    name: String,
+    /// 启动时使用的配置，重载失败时用于恢复
+    config: DataTransferConfig,
+    /// 各 Source 的读取任务
+    sources: Vec<JoinHandle<()>>,
+    /// 通知 Source 任务退出
+    stop: watch::Sender<bool>,
+    /// Transform 与 Sink 的主循环
+    handle: JoinHandle<()>,
+    metrics: Arc<PipelineMetrics>,
+}
+
+impl Pipeline {
+    fn status(&self, id: &str) -> PipelineStatus {
+        let state = if !self.handle.is_finished() {
+            PipelineState::Running
+        } else if self.metrics.completed.load(Ordering::Relaxed) {
+            PipelineState::Stopped
+        } else {
+            PipelineState::Failed
+        };
+        PipelineStatus {
+            id: id.to_string(),
+            name: self.name.clone(),
+            state,
+            events_received: self.metrics.events_received.load(Ordering::Relaxed),
+            events_sent: self.metrics.events_sent.load(Ordering::Relaxed),
+            errors: self.metrics.errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
 pub struct Controller {
-    tasks: HashMap<String, JoinHandle<()>>,
+    pipelines: HashMap<String, Pipeline>,
 }
 
 impl Default for Controller {
@@ -17,60 +195,205 @@ impl Default for Controller {
 impl Controller {
     pub fn new() -> Self {
         Self {
-            tasks: HashMap::new(),
+            pipelines: HashMap::new(),
         }
     }
 
-    pub async fn add_config(&mut self, config: DataTransferConfig) -> Result<()> {
-        // 检查 metadata 是否存在
-        let metadata = match &config.metadata {
-            Some(metadata) => metadata,
-            None => {
-                return Err(RsyncError::ConfigError(
-                    "Missing metadata in config".to_string(),
-                ));
-            }
-        };
+    /// 已注册管道的状态，按 ID 排序
+    pub fn pipelines(&self) -> Vec<PipelineStatus> {
+        let mut statuses: Vec<PipelineStatus> = self
+            .pipelines
+            .iter()
+            .map(|(id, pipeline)| pipeline.status(id))
+            .collect();
+        statuses.sort_by(|a, b| a.id.cmp(&b.id));
+        statuses
+    }
 
-        let pipeline_id = metadata.id.clone();
+    /// 只做预检，不搬运数据：构建 Source 与 Transform，检查 Source/Sink 是否就绪
+    ///
+    /// Sink 的 `build` 可能有副作用（如 FileSink 会截断目标文件），因此只调用 `check`。
+    /// 单个组件失败记录在报告中，只有配置本身不完整或组件连接不兼容时才返回错误。
+    pub async fn dry_run(config: DataTransferConfig) -> Result<DryRunReport> {
+        Self::dry_run_replacing(config, &[]).await
+    }
 
-        // 创建通道，用于连接 Source 和 Sink
-        let (tx, mut rx) = mpsc::channel::<Box<dyn Event>>(100);
+    /// 与 [`Controller::dry_run`] 相同，但监听地址在 `replaced_addrs` 中的 Source 不做试绑定
+    ///
+    /// 用于重载：这些端口仍由被替换的管道占用，停止旧管道后才会释放。
+    async fn dry_run_replacing(
+        config: DataTransferConfig,
+        replaced_addrs: &[String],
+    ) -> Result<DryRunReport> {
+        let pipeline_id = pipeline_id(&config)?;
+        config.validate()?;
+        let mut components = Vec::new();
 
-        // 1. 构建并启动 Sources
         for (index, source_config) in config.sources.iter().enumerate() {
             let source_id = format!("{pipeline_id}-source-{index}");
-            let cx = SourceContext {
-                key: ComponentKey::from(source_id.clone()),
-                acknowledgements: source_config.can_acknowledge(),
+            let replaced = source_config
+                .bind_addr()
+                .is_some_and(|addr| replaced_addrs.iter().any(|held| held == addr));
+            if replaced {
+                components.push(ComponentStatus::new(
+                    source_id,
+                    source_config.source_type(),
+                    Ok(()),
+                ));
+                continue;
+            }
+            let result = match source_config.check().await {
+                // 监听型 Source 的 build 会启动后台接受任务，丢弃后端口异步释放，
+                // 紧接着的正式启动可能绑定失败；试绑定已足以确认端口可用
+                Ok(()) if source_config.bind_addr().is_some() => Ok(()),
+                Ok(()) => {
+                    let cx = SourceContext {
+                        key: ComponentKey::from(source_id.clone()),
+                        acknowledgements: source_config.can_acknowledge(),
+                    };
+                    source_config.build(cx).await.map(|_| ())
+                }
+                Err(e) => Err(e),
             };
+            components.push(ComponentStatus::new(
+                source_id,
+                source_config.source_type(),
+                result,
+            ));
+        }
 
-            let mut source_runtime = source_config.build(cx).await?;
-            let tx_clone = tx.clone();
+        for (index, transform_config) in config.transforms.iter().enumerate() {
+            let transform_id = format!("{pipeline_id}-transform-{index}");
+            let cx = TransformContext {
+                key: ComponentKey::from(transform_id.clone()),
+            };
+            let result = transform_config.build(cx).await.map(|_| ());
+            components.push(ComponentStatus::new(
+                transform_id,
+                transform_config.transform_type(),
+                result,
+            ));
+        }
 
-            tokio::spawn(async move {
-                loop {
-                    match source_runtime.next_event().await {
-                        Ok(Some(event)) => {
-                            if tx_clone.send(event).await.is_err() {
-                                break; // Channel closed
-                            }
-                        }
-                        Ok(None) => break, // Source exhausted
-                        Err(e) => {
-                            eprintln!("Source error in {source_id}: {e}");
-                            // 简单的错误处理：暂停一下
-                            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                        }
-                    }
-                }
-            });
+        for (index, sink_config) in config.sinks.iter().enumerate() {
+            let sink_id = format!("{pipeline_id}-sink-{index}");
+            let result = sink_config.check().await;
+            components.push(ComponentStatus::new(
+                sink_id,
+                sink_config.sink_type(),
+                result,
+            ));
         }
 
-        // Drop original tx so rx closes when all sources are done
-        drop(tx);
+        Ok(DryRunReport {
+            pipeline_id,
+            components,
+        })
+    }
 
-        // 2. 构建 Transforms
+    /// 预检配置，任一组件未就绪时返回配置错误
+    pub async fn validate(config: &DataTransferConfig) -> Result<()> {
+        Self::validate_replacing(config, &[]).await
+    }
+
+    /// 预检用于替换现有管道的配置，`replaced_addrs` 为被替换管道占用的监听地址
+    /// （见 [`Controller::bind_addrs`]），这些地址不做试绑定
+    pub async fn validate_replacing(
+        config: &DataTransferConfig,
+        replaced_addrs: &[String],
+    ) -> Result<()> {
+        let report = Self::dry_run_replacing(config.clone(), replaced_addrs).await?;
+        let failures: Vec<String> = report
+            .components
+            .iter()
+            .filter_map(|c| c.error.as_ref().map(|e| format!("{}: {e}", c.key)))
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(RsyncError::ConfigError(failures.join("; ")))
+        }
+    }
+
+    /// 管道中 Source 占用的监听地址
+    pub fn bind_addrs(&self, id: &str) -> Result<Vec<String>> {
+        let pipeline = self
+            .pipelines
+            .get(id)
+            .ok_or_else(|| RsyncError::NotFound(id.to_string()))?;
+        Ok(pipeline
+            .config
+            .sources
+            .iter()
+            .filter_map(|source| source.bind_addr().map(str::to_string))
+            .collect())
+    }
+
+    /// 优雅停止管道：停止读取 Source，等待已读取的事件写完并关闭 Sink
+    pub async fn stop(&mut self, id: &str) -> Result<()> {
+        self.stop_pipeline(id).await.map(|_| ())
+    }
+
+    /// 停止管道并返回它的配置
+    async fn stop_pipeline(&mut self, id: &str) -> Result<DataTransferConfig> {
+        let pipeline = self
+            .pipelines
+            .remove(id)
+            .ok_or_else(|| RsyncError::NotFound(id.to_string()))?;
+        let _ = pipeline.stop.send(true);
+        // 等待 Source 任务关闭运行时退出，监听端口随之释放
+        for source in pipeline.sources {
+            let _ = source.await;
+        }
+        // Source 任务退出后通道关闭，主循环处理完剩余事件即结束
+        let _ = pipeline.handle.await;
+        Ok(pipeline.config)
+    }
+
+    /// 用新配置替换正在运行的管道，新配置的管道 ID 以 `id` 为准
+    ///
+    /// 调用方应先用 [`Controller::validate_replacing`] 预检新配置；新管道启动失败时按旧配置恢复。
+    pub async fn reload(&mut self, id: &str, mut config: DataTransferConfig) -> Result<()> {
+        if !self.pipelines.contains_key(id) {
+            return Err(RsyncError::NotFound(id.to_string()));
+        }
+        match config.metadata.as_mut() {
+            Some(metadata) => metadata.id = id.to_string(),
+            None => {
+                return Err(RsyncError::ConfigError(
+                    "Missing metadata in config".to_string(),
+                ));
+            }
+        }
+        config.validate()?;
+
+        // 旧管道占用的端口在停止后才释放，所以先停止再启动新管道
+        let previous = self.stop_pipeline(id).await?;
+        let Err(e) = self.add_config(config).await else {
+            return Ok(());
+        };
+        if let Err(restore_error) = self.add_config(previous).await {
+            eprintln!("Failed to restore pipeline {id} after reload error: {restore_error}");
+        }
+        Err(e)
+    }
+
+    pub async fn add_config(&mut self, config: DataTransferConfig) -> Result<()> {
+        let pipeline_id = pipeline_id(&config)?;
+        config.validate()?;
+        if self.pipelines.contains_key(&pipeline_id) {
+            return Err(RsyncError::ConfigError(format!(
+                "Pipeline already exists: {pipeline_id}"
+            )));
+        }
+        let pipeline_name = config
+            .metadata
+            .as_ref()
+            .map(|metadata| metadata.name.clone())
+            .unwrap_or_default();
+        let metrics = Arc::new(PipelineMetrics::default());
+
+        // 1. 构建 Transforms
         let mut transform_runtimes = Vec::new();
         for (index, transform_config) in config.transforms.iter().enumerate() {
             let transform_id = format!("{pipeline_id}-transform-{index}");
@@ -81,7 +404,7 @@ impl Controller {
             transform_runtimes.push(runtime);
         }
 
-        // 3. 构建 Sinks
+        // 2. 构建 Sinks
         let mut sink_runtimes = Vec::new();
         for (index, sink_config) in config.sinks.iter().enumerate() {
             let sink_id = format!("{pipeline_id}-sink-{index}");
@@ -93,9 +416,87 @@ impl Controller {
             sink_runtimes.push(runtime);
         }
 
+        // 3. 构建 Sources：全部构建成功后才启动，失败时关闭已构建的 Source 以释放监听端口
+        let mut source_runtimes = Vec::new();
+        for (index, source_config) in config.sources.iter().enumerate() {
+            let cx = SourceContext {
+                key: ComponentKey::from(format!("{pipeline_id}-source-{index}")),
+                acknowledgements: source_config.can_acknowledge(),
+            };
+            match source_config.build(cx).await {
+                Ok(runtime) => source_runtimes.push(runtime),
+                Err(e) => {
+                    for mut runtime in source_runtimes {
+                        let _ = runtime.shutdown().await;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        // 创建通道，用于连接 Source 和 Sink，事件附带来源 Source 的序号
+        let (tx, mut rx) = mpsc::channel::<(usize, Box<dyn Event>)>(100);
+        let (stop, stop_rx) = watch::channel(false);
+
+        // 启动 Sources
+        let mut sources = Vec::new();
+        // 支持确认的 Source 对应的确认通道，事件全部写入成功后发送事件 ID
+        let mut ack_senders = Vec::new();
+        for (index, (source_config, source_runtime)) in
+            config.sources.iter().zip(source_runtimes).enumerate()
+        {
+            let acks = if source_config.can_acknowledge() {
+                let (ack_tx, ack_rx) = mpsc::unbounded_channel();
+                ack_senders.push(Some(ack_tx));
+                Some(ack_rx)
+            } else {
+                ack_senders.push(None);
+                None
+            };
+
+            sources.push(tokio::spawn(run_source(
+                source_runtime,
+                index,
+                format!("{pipeline_id}-source-{index}"),
+                tx.clone(),
+                acks,
+                stop_rx.clone(),
+                metrics.clone(),
+            )));
+        }
+
+        // Drop original tx so rx closes when all sources are done
+        drop(tx);
+
         // 4. 启动主循环处理 (Transform & Sink)
+        let loop_metrics = metrics.clone();
+        let mut flush_timer = sink_runtimes
+            .iter()
+            .filter_map(|sink| sink.flush_interval())
+            .min()
+            .map(tokio::time::interval);
         let handle = tokio::spawn(async move {
-            while let Some(initial_event) = rx.recv().await {
+            let metrics = loop_metrics;
+            let mut pending_acks = Vec::new();
+            loop {
+                // `None` 表示定时刷新缓冲型 Sink
+                let received = match flush_timer.as_mut() {
+                    Some(timer) => tokio::select! {
+                        received = rx.recv() => Some(received),
+                        _ = timer.tick() => None,
+                    },
+                    None => Some(rx.recv().await),
+                };
+                let Some(received) = received else {
+                    flush_buffered(&mut sink_runtimes, &metrics).await;
+                    release_acks(&mut pending_acks, &sink_runtimes, &ack_senders);
+                    continue;
+                };
+                let Some((source_index, initial_event)) = received else {
+                    break;
+                };
+                let ack_id = initial_event.get_metadata().id.clone();
+                let mut succeeded = true;
                 let mut events = vec![initial_event];
 
                 // Apply transforms
@@ -104,7 +505,11 @@ impl Controller {
                     for e in events {
                         match transform.process(e).await {
                             Ok(processed) => next_events.extend(processed),
-                            Err(err) => eprintln!("Transform error: {err}"),
+                            Err(err) => {
+                                succeeded = false;
+                                metrics.errors.fetch_add(1, Ordering::Relaxed);
+                                eprintln!("Transform error: {err}");
+                            }
                         }
                     }
                     events = next_events;
@@ -112,34 +517,367 @@ impl Controller {
 
                 // 分发给所有 Sinks
                 for event in events {
-                    if sink_runtimes.is_empty() {
-                        continue;
-                    }
-
-                    // 处理前 N-1 个 sink
-                    for i in 0..sink_runtimes.len() - 1 {
-                        let event_clone = event.clone();
-                        if let Err(e) = sink_runtimes[i].write(event_clone).await {
-                            eprintln!("Sink write error: {e}");
-                        }
-                    }
+                    succeeded &= fan_out(&mut sink_runtimes, event, &metrics).await;
+                }
 
-                    // 处理最后一个 sink
-                    if let Some(last_sink) = sink_runtimes.last_mut()
-                        && let Err(e) = last_sink.write(event).await
-                    {
-                        eprintln!("Sink write error: {e}");
-                    }
+                // 全部成功才确认，失败的事件由数据源自行决定是否重发
+                if succeeded && ack_senders[source_index].is_some() {
+                    pending_acks.push((source_index, ack_id));
                 }
+                release_acks(&mut pending_acks, &sink_runtimes, &ack_senders);
             }
 
             // 管道结束，清理资源
             for sink in &mut sink_runtimes {
                 let _ = sink.shutdown().await;
             }
+            metrics.completed.store(true, Ordering::Relaxed);
         });
 
-        self.tasks.insert(pipeline_id, handle);
+        self.pipelines.insert(
+            pipeline_id,
+            Pipeline {
+                name: pipeline_name,
+                config,
+                sources,
+                stop,
+                handle,
+                metrics,
+            },
+        );
         Ok(())
     }
 }
+
+/// Source 任务的下一步动作
+enum SourceStep {
+    Event(Result<Option<Box<dyn Event>>>),
+    Ack(String),
+}
+
+/// 持续读取 Source 并送入管道；`acks` 不为空时同时处理下游的确认
+///
+/// 收到 `stop` 通知或 Source 读完后关闭运行时再退出。
+async fn run_source(
+    mut source_runtime: Box<dyn SourceRuntime>,
+    index: usize,
+    source_id: String,
+    tx: mpsc::Sender<(usize, Box<dyn Event>)>,
+    mut acks: Option<mpsc::UnboundedReceiver<String>>,
+    mut stop: watch::Receiver<bool>,
+    metrics: Arc<PipelineMetrics>,
+) {
+    loop {
+        let step = tokio::select! {
+            _ = stop.changed() => break,
+            step = next_step(&mut source_runtime, acks.as_mut()) => step,
+        };
+
+        match step {
+            SourceStep::Ack(id) => {
+                if let Err(e) = source_runtime.acknowledge(&id).await {
+                    eprintln!("Source acknowledge error in {source_id}: {e}");
+                }
+            }
+            SourceStep::Event(Ok(Some(event))) => {
+                if tx.send((index, event)).await.is_err() {
+                    break; // Channel closed
+                }
+                metrics.events_received.fetch_add(1, Ordering::Relaxed);
+            }
+            SourceStep::Event(Ok(None)) => break, // Source exhausted
+            SourceStep::Event(Err(e)) => {
+                metrics.errors.fetch_add(1, Ordering::Relaxed);
+                eprintln!("Source error in {source_id}: {e}");
+                // 简单的错误处理：暂停一下
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+        }
+    }
+    if let Err(e) = source_runtime.shutdown().await {
+        eprintln!("Source shutdown error in {source_id}: {e}");
+    }
+}
+
+async fn next_step(
+    source_runtime: &mut Box<dyn SourceRuntime>,
+    acks: Option<&mut mpsc::UnboundedReceiver<String>>,
+) -> SourceStep {
+    match acks {
+        // 等待新事件时也要及时确认，否则等待确认的数据源（如 HTTP）会阻塞
+        Some(acks) => tokio::select! {
+            Some(id) = acks.recv() => SourceStep::Ack(id),
+            result = source_runtime.next_event() => SourceStep::Event(result),
+        },
+        None => SourceStep::Event(source_runtime.next_event().await),
+    }
+}
+
+/// 获取管道 ID，缺少 metadata 时返回配置错误
+fn pipeline_id(config: &DataTransferConfig) -> Result<String> {
+    match &config.metadata {
+        Some(metadata) => Ok(metadata.id.clone()),
+        None => Err(RsyncError::ConfigError(
+            "Missing metadata in config".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::{FileSinkConfig, FileSourceConfig, JsonTransformConfig, RsyncEnv};
+    use crate::tcp::TcpSourceConfig;
+    use tokio::io::AsyncWriteExt;
+
+    fn file_pipeline(input: &std::path::Path, output: &std::path::Path) -> DataTransferConfig {
+        DataTransferConfig::new(
+            "check".to_string(),
+            "Check".to_string(),
+            None,
+            vec![Box::new(FileSourceConfig::new(
+                input.to_string_lossy().to_string(),
+                false,
+            ))],
+            vec![Box::new(JsonTransformConfig {
+                add_timestamp: false,
+            })],
+            vec![Box::new(FileSinkConfig::new(
+                RsyncEnv::detect(),
+                output.to_string_lossy().to_string(),
+                true,
+                None,
+            ))],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_valid_file_pipeline() {
+        let temp_dir = std::env::temp_dir();
+        let input_path = temp_dir.join("dry_run_input.txt");
+        let output_path = temp_dir.join("dry_run_output.txt");
+        std::fs::write(&input_path, "hello").unwrap();
+        std::fs::write(&output_path, "existing").unwrap();
+
+        let report = Controller::dry_run(file_pipeline(&input_path, &output_path))
+            .await
+            .unwrap();
+
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(report.components.len(), 3);
+        assert_eq!(report.components[0].key, "check-source-0");
+        // 预检不能改动目标文件
+        assert_eq!(std::fs::read_to_string(&output_path).unwrap(), "existing");
+
+        let _ = std::fs::remove_file(&input_path);
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[tokio::test]
+    async fn test_pipelines_track_events() {
+        let temp_dir = std::env::temp_dir();
+        let input_path = temp_dir.join("controller_status_input.txt");
+        let output_path = temp_dir.join("controller_status_output.txt");
+        std::fs::write(&input_path, "hello").unwrap();
+
+        let mut controller = Controller::new();
+        controller
+            .add_config(file_pipeline(&input_path, &output_path))
+            .await
+            .unwrap();
+
+        // 非监听模式的文件源读完即结束，管道随之停止
+        let mut status = controller.pipelines();
+        for _ in 0..50 {
+            if status[0].state == PipelineState::Stopped {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            status = controller.pipelines();
+        }
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].id, "check");
+        assert_eq!(status[0].state, PipelineState::Stopped);
+        assert_eq!(status[0].events_received, 1);
+        assert_eq!(status[0].events_sent, 1);
+        assert_eq!(status[0].errors, 0);
+
+        let _ = std::fs::remove_file(&input_path);
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_reports_missing_source() {
+        let temp_dir = std::env::temp_dir();
+        let input_path = temp_dir.join("dry_run_missing_input.txt");
+        let output_path = temp_dir.join("dry_run_missing_output.txt");
+        let _ = std::fs::remove_file(&input_path);
+
+        let report = Controller::dry_run(file_pipeline(&input_path, &output_path))
+            .await
+            .unwrap();
+
+        assert!(!report.is_ok());
+        assert!(!report.components[0].is_ok());
+        assert!(report.components[1..].iter().all(ComponentStatus::is_ok));
+        assert!(!output_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_writes_every_event_to_all_sinks() {
+        let temp_dir = std::env::temp_dir();
+        let input_path = temp_dir.join("fan_out_input.txt");
+        let outputs = [
+            temp_dir.join("fan_out_output_a.txt"),
+            temp_dir.join("fan_out_output_b.txt"),
+        ];
+        // 超过 64KB，文件源会分两个事件读出
+        let content = "0123456789\n".repeat(8000);
+        std::fs::write(&input_path, &content).unwrap();
+
+        let sinks: Vec<Box<dyn Sink>> = outputs
+            .iter()
+            .map(|path| {
+                Box::new(FileSinkConfig::new(
+                    RsyncEnv::detect(),
+                    path.to_string_lossy().to_string(),
+                    true,
+                    None,
+                )) as Box<dyn Sink>
+            })
+            .collect();
+        let config = DataTransferConfig::new(
+            "fan-out".to_string(),
+            "Fan out".to_string(),
+            None,
+            vec![Box::new(FileSourceConfig::new(
+                input_path.to_string_lossy().to_string(),
+                false,
+            ))],
+            vec![],
+            sinks,
+        );
+
+        let mut controller = Controller::new();
+        controller.add_config(config).await.unwrap();
+        let mut status = controller.pipelines();
+        for _ in 0..50 {
+            if status[0].state == PipelineState::Stopped {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            status = controller.pipelines();
+        }
+        assert_eq!(status[0].state, PipelineState::Stopped);
+        assert_eq!(status[0].events_received, 2);
+        assert_eq!(status[0].events_sent, 4);
+
+        for output in &outputs {
+            assert_eq!(std::fs::read_to_string(output).unwrap(), content);
+            let _ = std::fs::remove_file(output);
+        }
+        let _ = std::fs::remove_file(&input_path);
+    }
+
+    /// 只记录缓冲事件数的 Sink
+    struct BufferingSink(usize);
+
+    #[async_trait::async_trait]
+    impl SinkRuntime for BufferingSink {
+        async fn write(&mut self, _event: Box<dyn Event>) -> Result<()> {
+            self.0 += 1;
+            Ok(())
+        }
+
+        async fn flush(&mut self) -> Result<()> {
+            self.0 = 0;
+            Ok(())
+        }
+
+        fn buffered(&self) -> usize {
+            self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acks_wait_for_buffered_sinks() {
+        let (ack_tx, mut ack_rx) = mpsc::unbounded_channel();
+        let ack_senders = vec![Some(ack_tx)];
+        let mut sinks: Vec<Box<dyn SinkRuntime>> = vec![Box::new(BufferingSink(1))];
+        let mut pending = vec![(0, "event-1".to_string())];
+
+        release_acks(&mut pending, &sinks, &ack_senders);
+        assert!(ack_rx.try_recv().is_err());
+        assert_eq!(pending.len(), 1);
+
+        flush_buffered(&mut sinks, &PipelineMetrics::default()).await;
+        release_acks(&mut pending, &sinks, &ack_senders);
+        assert_eq!(ack_rx.try_recv().unwrap(), "event-1");
+        assert!(pending.is_empty());
+    }
+
+    fn tcp_pipeline(bind_addr: &str, output: &std::path::Path) -> DataTransferConfig {
+        DataTransferConfig::new(
+            "tcp-reload".to_string(),
+            "TCP reload".to_string(),
+            None,
+            vec![Box::new(TcpSourceConfig::new(bind_addr.to_string()))],
+            vec![],
+            vec![Box::new(FileSinkConfig::new(
+                RsyncEnv::detect(),
+                output.to_string_lossy().to_string(),
+                true,
+                None,
+            ))],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_failed_reload_restores_listener_pipeline() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let bind_addr = format!("127.0.0.1:{port}");
+        let output_path = std::env::temp_dir().join("controller_reload_output.txt");
+        let _ = std::fs::remove_file(&output_path);
+
+        let mut controller = Controller::new();
+        controller
+            .add_config(tcp_pipeline(&bind_addr, &output_path))
+            .await
+            .unwrap();
+
+        // Sink 在 build 时失败：新 Source 不能已经占用端口，否则旧管道无法恢复
+        let broken_output = std::env::temp_dir()
+            .join("controller_reload_missing_dir")
+            .join("output.txt");
+        assert!(
+            controller
+                .reload("tcp-reload", tcp_pipeline(&bind_addr, &broken_output))
+                .await
+                .is_err()
+        );
+
+        let status = controller.pipelines();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].state, PipelineState::Running);
+
+        // 恢复的管道仍在原端口接收数据
+        let mut stream = tokio::net::TcpStream::connect(&bind_addr).await.unwrap();
+        stream.write_all(b"hello\n").await.unwrap();
+        let mut written = String::new();
+        for _ in 0..50 {
+            written = std::fs::read_to_string(&output_path).unwrap_or_default();
+            if !written.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(written.contains("hello"), "{written:?}");
+
+        controller.stop("tcp-reload").await.unwrap();
+        let _ = std::fs::remove_file(&output_path);
+    }
+}
@@ -0,0 +1,155 @@
+/// 字段富化转换器：为每个事件附加管道级的静态字段（如环境、来源主机）
+///
+/// 载荷按 JSON 处理：JSON 对象直接合并字段；其他 JSON 值或非 JSON 文本
+/// 包装到 `wrap_key` 下再合并。事件中已有的同名字段保持不变。
+use crate::event::*;
+use crate::rule::*;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+fn default_wrap_key() -> String {
+    "message".to_string()
+}
+
+/// 字段富化转换器配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnrichTransformConfig {
+    /// 要附加的静态字段
+    pub fields: HashMap<String, String>,
+    /// 载荷不是 JSON 对象时，原内容放在该字段下
+    #[serde(default = "default_wrap_key")]
+    pub wrap_key: String,
+}
+
+impl EnrichTransformConfig {
+    pub fn new(fields: HashMap<String, String>) -> Self {
+        Self {
+            fields,
+            wrap_key: default_wrap_key(),
+        }
+    }
+}
+
+#[typetag::serde(name = "enrich")]
+#[async_trait]
+impl Transform for EnrichTransformConfig {
+    fn clone_box(&self) -> Box<dyn Transform> {
+        Box::new(self.clone())
+    }
+
+    async fn build(&self, _cx: TransformContext) -> Result<Box<dyn TransformRuntime>> {
+        Ok(Box::new(EnrichTransformRuntime {
+            fields: self.fields.clone(),
+            wrap_key: self.wrap_key.clone(),
+        }))
+    }
+
+    fn transform_type(&self) -> &str {
+        "enrich"
+    }
+}
+
+/// 字段富化转换器运行时
+pub struct EnrichTransformRuntime {
+    fields: HashMap<String, String>,
+    wrap_key: String,
+}
+
+impl EnrichTransformRuntime {
+    fn enrich(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        let mut object = match serde_json::from_slice::<Value>(payload) {
+            Ok(Value::Object(object)) => object,
+            Ok(value) => Map::from_iter([(self.wrap_key.clone(), value)]),
+            Err(_) => {
+                let text = String::from_utf8_lossy(payload);
+                let text = text.trim_end_matches(['\r', '\n']);
+                Map::from_iter([(self.wrap_key.clone(), Value::String(text.to_string()))])
+            }
+        };
+
+        for (key, value) in &self.fields {
+            object
+                .entry(key.clone())
+                .or_insert_with(|| Value::String(value.clone()));
+        }
+
+        serde_json::to_vec(&Value::Object(object))
+            .map_err(|e| RsyncError::TransformError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl TransformRuntime for EnrichTransformRuntime {
+    async fn process(&mut self, event: Box<dyn Event>) -> Result<Vec<Box<dyn Event>>> {
+        let payload = self.enrich(event.get_payload_slice())?;
+        let mut metadata = event.get_metadata().clone();
+        metadata.payload_size = payload.len();
+        metadata.event_type = EventType::Text(TextType::Json);
+
+        let mut enriched = SimpleEvent::new(metadata, payload);
+        enriched.fields = event.fields().clone();
+        Ok(vec![Box::new(enriched)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runtime() -> EnrichTransformRuntime {
+        EnrichTransformRuntime {
+            fields: HashMap::from([
+                ("env".to_string(), "prod".to_string()),
+                ("host".to_string(), "node-1".to_string()),
+            ]),
+            wrap_key: default_wrap_key(),
+        }
+    }
+
+    fn event(payload: &[u8]) -> Box<dyn Event> {
+        Box::new(SimpleEvent::new(
+            EventMetadata {
+                id: "e1".to_string(),
+                timestamp: 0,
+                name: "test".to_string(),
+                payload_size: payload.len(),
+                event_type: EventType::Text(TextType::PlainText),
+            },
+            payload.to_vec(),
+        ))
+    }
+
+    async fn process(payload: &[u8]) -> (Value, Box<dyn Event>) {
+        let event = runtime().process(event(payload)).await.unwrap().remove(0);
+        let value = serde_json::from_slice(event.get_payload_slice()).unwrap();
+        (value, event)
+    }
+
+    #[tokio::test]
+    async fn test_merges_fields_into_object() {
+        let (value, event) = process(br#"{"level":"info","env":"dev"}"#).await;
+        assert_eq!(
+            value,
+            serde_json::json!({ "level": "info", "env": "dev", "host": "node-1" })
+        );
+        assert_eq!(event.get_metadata().id, "e1");
+        assert_eq!(
+            event.get_metadata().event_type,
+            EventType::Text(TextType::Json)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wraps_plain_text_payload() {
+        let (value, _) = process(b"disk almost full\n").await;
+        assert_eq!(
+            value,
+            serde_json::json!({ "message": "disk almost full", "env": "prod", "host": "node-1" })
+        );
+
+        let (value, _) = process(b"42").await;
+        assert_eq!(value["message"], 42);
+    }
+}
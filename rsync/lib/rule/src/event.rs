@@ -1,3 +1,6 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
 /// 二进制数据的具体平台类型
 #[derive(Debug, Clone, PartialEq)]
 pub enum BinaryType {
@@ -70,11 +73,31 @@ pub struct EventMetadata {
 
 /// 每份数据都是一个事件, 包含元数据和实际数据载荷
 pub trait Event: Send + Sync + std::fmt::Debug {
+    /// 复制一份独立的事件，用于扇出到多个下游；载荷、元数据和附加字段一并复制
     fn clone_box(&self) -> Box<dyn Event>;
 
     /// 获取事件元数据
     fn get_metadata(&self) -> &EventMetadata;
 
+    /// 获取可修改的事件元数据
+    fn metadata_mut(&mut self) -> &mut EventMetadata;
+
+    /// 获取附加字段（转换器写入的结构化标注，不属于载荷）
+    fn fields(&self) -> &HashMap<String, Value>;
+
+    /// 获取可修改的附加字段
+    fn fields_mut(&mut self) -> &mut HashMap<String, Value>;
+
+    /// 读取附加字段
+    fn get_field(&self, key: &str) -> Option<&Value> {
+        self.fields().get(key)
+    }
+
+    /// 写入附加字段，已存在时覆盖
+    fn set_field(&mut self, key: &str, value: Value) {
+        self.fields_mut().insert(key.to_string(), value);
+    }
+
     /// 获取原始二进制载荷数据
     /// 所有数据统一使用二进制格式存储，具体如何解释由 EventType 决定
     fn get_payload(&self) -> &Vec<u8>;
@@ -100,6 +123,17 @@ impl Clone for Box<dyn Event> {
 pub struct SimpleEvent {
     pub metadata: EventMetadata,
     pub payload: Vec<u8>,
+    pub fields: HashMap<String, Value>,
+}
+
+impl SimpleEvent {
+    pub fn new(metadata: EventMetadata, payload: Vec<u8>) -> Self {
+        Self {
+            metadata,
+            payload,
+            fields: HashMap::new(),
+        }
+    }
 }
 
 impl Event for SimpleEvent {
@@ -111,7 +145,83 @@ impl Event for SimpleEvent {
         &self.metadata
     }
 
+    fn metadata_mut(&mut self) -> &mut EventMetadata {
+        &mut self.metadata
+    }
+
+    fn fields(&self) -> &HashMap<String, Value> {
+        &self.fields
+    }
+
+    fn fields_mut(&mut self) -> &mut HashMap<String, Value> {
+        &mut self.fields
+    }
+
     fn get_payload(&self) -> &Vec<u8> {
         &self.payload
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> Box<dyn Event> {
+        Box::new(SimpleEvent::new(
+            EventMetadata {
+                id: "1".to_string(),
+                timestamp: 0,
+                name: "test".to_string(),
+                payload_size: 5,
+                event_type: EventType::Text(TextType::PlainText),
+            },
+            b"hello".to_vec(),
+        ))
+    }
+
+    #[test]
+    fn test_set_and_get_fields_through_trait() {
+        let mut event = event();
+        assert!(event.get_field("env").is_none());
+
+        event.set_field("env", Value::from("prod"));
+        event.set_field("retries", Value::from(2));
+        event.set_field("env", Value::from("staging"));
+
+        assert_eq!(event.get_field("env"), Some(&Value::from("staging")));
+        assert_eq!(event.get_field("retries"), Some(&Value::from(2)));
+        assert_eq!(event.fields().len(), 2);
+        // 字段随事件一起克隆，不影响载荷
+        assert_eq!(event.clone().get_field("retries"), Some(&Value::from(2)));
+        assert_eq!(event.get_payload_slice(), b"hello");
+    }
+
+    #[test]
+    fn test_clone_box_copies_payload_metadata_and_fields() {
+        let mut original: Box<dyn Event> = Box::new(
+            crate::testing::TestEvent::text("hello")
+                .with_id("42")
+                .with_field("env", Value::from("prod")),
+        );
+        let copy = original.clone_box();
+        assert_eq!(copy.get_metadata(), original.get_metadata());
+        assert_eq!(copy.get_payload(), original.get_payload());
+        assert_eq!(copy.get_field("env"), Some(&Value::from("prod")));
+
+        // 扇出后各自修改互不影响
+        original.set_field("env", Value::from("staging"));
+        original.metadata_mut().name = "changed".to_string();
+        assert_eq!(copy.get_field("env"), Some(&Value::from("prod")));
+        assert_eq!(copy.get_metadata().name, "test");
+    }
+
+    #[test]
+    fn test_metadata_mut_through_trait() {
+        let mut event = event();
+        event.metadata_mut().name = "renamed".to_string();
+        event.metadata_mut().event_type = EventType::Text(TextType::Json);
+
+        assert_eq!(event.get_metadata().name, "renamed");
+        assert_eq!(event.get_metadata().event_type.as_str(), "text.json");
+    }
+}
@@ -429,11 +429,42 @@ impl RsyncEnv {
 pub struct FileSourceConfig {
     pub path: String,
     pub watch: bool, // 是否监听文件变化
+    /// 读取进度（偏移量）保存位置，配置后重启从上次进度继续读取
+    #[serde(default)]
+    pub checkpoint_path: Option<String>,
 }
 
 impl FileSourceConfig {
     pub fn new(path: String, watch: bool) -> Self {
-        Self { path, watch }
+        Self {
+            path,
+            watch,
+            checkpoint_path: None,
+        }
+    }
+
+    pub fn with_checkpoint_path(mut self, checkpoint_path: String) -> Self {
+        self.checkpoint_path = Some(checkpoint_path);
+        self
+    }
+
+    /// 读取已保存的偏移量，不存在或内容无效时从头开始
+    fn load_checkpoint(&self, file_len: u64) -> u64 {
+        let Some(checkpoint_path) = &self.checkpoint_path else {
+            return 0;
+        };
+        let offset = match std::fs::read_to_string(checkpoint_path) {
+            Ok(content) => content.trim().parse::<u64>().unwrap_or(0),
+            Err(_) => return 0,
+        };
+        if offset > file_len {
+            eprintln!(
+                "Checkpoint offset {offset} exceeds length {file_len} of {}, file truncated or rotated, reading from start",
+                self.path
+            );
+            return 0;
+        }
+        offset
     }
 }
 
@@ -452,11 +483,16 @@ impl Source for FileSourceConfig {
     }
 
     async fn build(&self, _cx: SourceContext) -> Result<Box<dyn SourceRuntime>> {
+        let fd = std::fs::File::open(&self.path)?;
+        let current_offset = self.load_checkpoint(fd.metadata()?.len());
         Ok(Box::new(FileSourceRuntime {
             path: self.path.clone(),
-            current_offset: 0,
-            fd: std::fs::File::open(&self.path)?,
+            current_offset,
+            fd,
             watch: self.watch,
+            checkpoint_path: self.checkpoint_path.clone(),
+            saved_offset: current_offset,
+            last_checkpoint: std::time::Instant::now(),
         }))
     }
 
@@ -467,6 +503,18 @@ impl Source for FileSourceConfig {
     fn source_type(&self) -> &str {
         "file"
     }
+
+    async fn check(&self) -> Result<()> {
+        let metadata = std::fs::metadata(&self.path)
+            .map_err(|e| RsyncError::ReadError(format!("{}: {e}", self.path)))?;
+        if !metadata.is_file() {
+            return Err(RsyncError::ReadError(format!(
+                "{} is not a file",
+                self.path
+            )));
+        }
+        Ok(())
+    }
 }
 
 /// 文件数据源运行时实例
@@ -475,6 +523,40 @@ pub struct FileSourceRuntime {
     fd: std::fs::File,
     current_offset: u64,
     watch: bool,
+    checkpoint_path: Option<String>,
+    /// 最近一次保存的偏移量
+    saved_offset: u64,
+    last_checkpoint: std::time::Instant,
+}
+
+/// 偏移量保存间隔
+const CHECKPOINT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+impl FileSourceRuntime {
+    /// 保存当前偏移量（先写临时文件再重命名，避免写一半时进程退出）
+    fn save_checkpoint(&mut self) -> Result<()> {
+        let Some(checkpoint_path) = &self.checkpoint_path else {
+            return Ok(());
+        };
+        if self.saved_offset == self.current_offset {
+            return Ok(());
+        }
+        let tmp_path = format!("{checkpoint_path}.tmp");
+        std::fs::write(&tmp_path, self.current_offset.to_string())?;
+        std::fs::rename(&tmp_path, checkpoint_path)?;
+        self.saved_offset = self.current_offset;
+        self.last_checkpoint = std::time::Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for FileSourceRuntime {
+    fn drop(&mut self) {
+        // 管道停止时数据源任务被直接中止，这里兜底保存进度
+        if let Err(e) = self.save_checkpoint() {
+            eprintln!("Failed to save checkpoint for {}: {e}", self.path);
+        }
+    }
 }
 
 #[async_trait]
@@ -486,8 +568,18 @@ impl SourceRuntime for FileSourceRuntime {
             // 获取文件元数据
             let metadata = self.fd.metadata()?;
 
+            // 文件变短说明被截断或轮转，从头读取
+            if self.current_offset > metadata.len() {
+                eprintln!(
+                    "{} shrank below offset {}, file truncated or rotated, reading from start",
+                    self.path, self.current_offset
+                );
+                self.current_offset = 0;
+            }
+
             // 如果已经读到文件末尾
             if self.current_offset >= metadata.len() {
+                self.save_checkpoint()?;
                 if !self.watch {
                     return Ok(None);
                 }
@@ -511,10 +603,13 @@ impl SourceRuntime for FileSourceRuntime {
 
             // 更新偏移量（增加实际读取的字节数）
             self.current_offset += to_read;
+            if self.last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+                self.save_checkpoint()?;
+            }
 
             // 返回事件
-            return Ok(Some(Box::new(SimpleEvent {
-                metadata: EventMetadata {
+            return Ok(Some(Box::new(SimpleEvent::new(
+                EventMetadata {
                     id: format!("file-{}", self.current_offset),
                     timestamp: std::time::SystemTime::now()
                         .duration_since(std::time::UNIX_EPOCH)
@@ -525,9 +620,13 @@ impl SourceRuntime for FileSourceRuntime {
                     event_type: EventType::Binary(BinaryType::Generic),
                 },
                 payload,
-            })));
+            ))));
         }
     }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        self.save_checkpoint()
+    }
 }
 
 /// JSON 格式转换器配置
@@ -552,6 +651,13 @@ impl Transform for JsonTransformConfig {
     fn transform_type(&self) -> &str {
         "json"
     }
+
+    fn accepts(&self) -> Vec<EventType> {
+        vec![
+            EventType::Text(TextType::Json),
+            EventType::Text(TextType::PlainText),
+        ]
+    }
 }
 
 /// JSON 转换器运行时
@@ -573,6 +679,9 @@ impl TransformRuntime for JsonTransformRuntime {
 pub struct HttpSinkConfig {
     pub url: String,
     pub batch_size: usize,
+    /// 事件编码，默认原样发送载荷
+    #[serde(default)]
+    pub encoding: Encoding,
 }
 
 #[typetag::serde(name = "http")]
@@ -586,6 +695,7 @@ impl Sink for HttpSinkConfig {
         Ok(Box::new(HttpSinkRuntime {
             url: self.url.clone(),
             batch_size: self.batch_size,
+            encoding: self.encoding,
             buffer: Vec::new(),
         }))
     }
@@ -593,19 +703,60 @@ impl Sink for HttpSinkConfig {
     fn sink_type(&self) -> &str {
         "http"
     }
+
+    async fn check(&self) -> Result<()> {
+        let addr = http_socket_addr(&self.url)?;
+        match tokio::time::timeout(
+            std::time::Duration::from_secs(3),
+            tokio::net::TcpStream::connect(&addr),
+        )
+        .await
+        {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(RsyncError::WriteError(format!("{addr} unreachable: {e}"))),
+            Err(_) => Err(RsyncError::WriteError(format!("{addr} connect timed out"))),
+        }
+    }
+}
+
+/// 从 HTTP URL 中取出 `host:port`，未指定端口时按协议补全
+fn http_socket_addr(url: &str) -> Result<String> {
+    let (rest, default_port) = if let Some(rest) = url.strip_prefix("http://") {
+        (rest, 80)
+    } else if let Some(rest) = url.strip_prefix("https://") {
+        (rest, 443)
+    } else {
+        return Err(RsyncError::ConfigError(format!("Unsupported url: {url}")));
+    };
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    if host.is_empty() {
+        return Err(RsyncError::ConfigError(format!(
+            "Missing host in url: {url}"
+        )));
+    }
+    // 没有端口（IPv6 地址以 ] 结尾）时补全默认端口
+    match host.rsplit_once(':') {
+        Some((_, port)) if !port.ends_with(']') => Ok(host.to_string()),
+        _ => Ok(format!("{host}:{default_port}")),
+    }
 }
 
 /// HTTP Sink 运行时
 pub struct HttpSinkRuntime {
     url: String,
     batch_size: usize,
-    buffer: Vec<Box<dyn Event>>,
+    encoding: Encoding,
+    /// 已编码、待发送的请求体
+    buffer: Vec<Vec<u8>>,
 }
 
 #[async_trait]
 impl SinkRuntime for HttpSinkRuntime {
     async fn write(&mut self, event: Box<dyn Event>) -> Result<()> {
-        self.buffer.push(event);
+        self.buffer
+            .push(encode_event(event.as_ref(), self.encoding)?);
 
         if self.buffer.len() >= self.batch_size {
             self.flush().await?;
@@ -628,21 +779,28 @@ pub struct FileSinkConfig {
     pub path: String,
     pub force: bool,          // 是否覆盖已存在文件
     pub mask: Option<String>, // 可选的文件名掩码
+    /// 事件编码，默认原样写出载荷；JSON 编码时每个事件一行
+    #[serde(default)]
+    pub encoding: Encoding,
 }
 
 pub struct FileSinkRuntime {
     pub env: RsyncEnv,
     pub fd: std::fs::File,
     pub current_offset: u64,
+    pub encoding: Encoding,
 }
 
 use std::io::Write;
 #[async_trait]
 impl SinkRuntime for FileSinkRuntime {
     async fn write(&mut self, event: Box<dyn Event>) -> Result<()> {
-        let payload = event.get_payload();
+        let mut payload = encode_event(event.as_ref(), self.encoding)?;
+        if self.encoding == Encoding::Json {
+            payload.push(b'\n');
+        }
 
-        if let Err(e) = self.fd.write_all(payload) {
+        if let Err(e) = self.fd.write_all(&payload) {
             return Err(e.into());
         }
         self.current_offset += payload.len() as u64;
@@ -662,12 +820,39 @@ impl Sink for FileSinkConfig {
             env: self.env.clone(),
             fd: std::fs::File::create(&self.path)?,
             current_offset: 0,
+            encoding: self.encoding,
         }))
     }
 
     fn sink_type(&self) -> &str {
         "file"
     }
+
+    async fn check(&self) -> Result<()> {
+        let path = std::path::Path::new(&self.path);
+        if path.exists() {
+            // 以追加方式打开，只验证可写，不截断已有内容
+            std::fs::OpenOptions::new()
+                .append(true)
+                .open(path)
+                .map_err(|e| RsyncError::WriteError(format!("{}: {e}", self.path)))?;
+            return Ok(());
+        }
+
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => std::path::Path::new("."),
+        };
+        let metadata = std::fs::metadata(parent)
+            .map_err(|e| RsyncError::WriteError(format!("{}: {e}", parent.display())))?;
+        if !metadata.is_dir() || metadata.permissions().readonly() {
+            return Err(RsyncError::WriteError(format!(
+                "{} is not a writable directory",
+                parent.display()
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl FileSinkConfig {
@@ -677,8 +862,14 @@ impl FileSinkConfig {
             path,
             force,
             mask,
+            encoding: Encoding::default(),
         }
     }
+
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -700,10 +891,7 @@ mod tests {
         drop(file);
 
         // 1. 创建配置
-        let source_config = FileSourceConfig {
-            path: input_path.to_string_lossy().to_string(),
-            watch: false,
-        };
+        let source_config = FileSourceConfig::new(input_path.to_string_lossy().to_string(), false);
 
         let transform_config = JsonTransformConfig {
             add_timestamp: true,
@@ -714,6 +902,7 @@ mod tests {
             force: true,
             mask: None,
             env: RsyncEnv::detect(),
+            encoding: Encoding::Raw,
         };
 
         // 2. 构建运行时实例
@@ -761,4 +950,79 @@ mod tests {
         let _ = std::fs::remove_file(&input_path);
         let _ = std::fs::remove_file(&output_path);
     }
+
+    #[test]
+    fn test_http_socket_addr() {
+        assert_eq!(
+            http_socket_addr("http://example.com/ingest").unwrap(),
+            "example.com:80"
+        );
+        assert_eq!(
+            http_socket_addr("https://user@example.com:8443?x=1").unwrap(),
+            "example.com:8443"
+        );
+        assert_eq!(http_socket_addr("https://[::1]/").unwrap(), "[::1]:443");
+        assert!(http_socket_addr("ftp://example.com").is_err());
+    }
+
+    async fn read_all(config: &FileSourceConfig) -> String {
+        let mut source = config
+            .build(SourceContext {
+                key: ComponentKey::from("source-1".to_string()),
+                acknowledgements: false,
+            })
+            .await
+            .unwrap();
+        let mut text = String::new();
+        while let Some(event) = source.next_event().await.unwrap() {
+            text.push_str(&event.get_payload_as_text().unwrap());
+        }
+        source.shutdown().await.unwrap();
+        text
+    }
+
+    #[tokio::test]
+    async fn test_file_source_resumes_from_checkpoint() {
+        let temp_dir = std::env::temp_dir();
+        let input_path = temp_dir.join("checkpoint_resume_input.txt");
+        let checkpoint_path = temp_dir.join("checkpoint_resume.offset");
+        let _ = std::fs::remove_file(&checkpoint_path);
+        std::fs::write(&input_path, "first\n").unwrap();
+
+        let config = FileSourceConfig::new(input_path.to_string_lossy().to_string(), false)
+            .with_checkpoint_path(checkpoint_path.to_string_lossy().to_string());
+        assert_eq!(read_all(&config).await, "first\n");
+        assert_eq!(std::fs::read_to_string(&checkpoint_path).unwrap(), "6");
+
+        // 重启后只读取新追加的内容
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&input_path)
+            .unwrap();
+        writeln!(file, "second").unwrap();
+        drop(file);
+        assert_eq!(read_all(&config).await, "second\n");
+        assert_eq!(std::fs::read_to_string(&checkpoint_path).unwrap(), "13");
+
+        let _ = std::fs::remove_file(&input_path);
+        let _ = std::fs::remove_file(&checkpoint_path);
+    }
+
+    #[tokio::test]
+    async fn test_file_source_restarts_after_truncation() {
+        let temp_dir = std::env::temp_dir();
+        let input_path = temp_dir.join("checkpoint_truncated_input.txt");
+        let checkpoint_path = temp_dir.join("checkpoint_truncated.offset");
+        std::fs::write(&input_path, "short").unwrap();
+        // 进度大于文件长度：文件被截断或轮转过
+        std::fs::write(&checkpoint_path, "100").unwrap();
+
+        let config = FileSourceConfig::new(input_path.to_string_lossy().to_string(), false)
+            .with_checkpoint_path(checkpoint_path.to_string_lossy().to_string());
+        assert_eq!(read_all(&config).await, "short");
+        assert_eq!(std::fs::read_to_string(&checkpoint_path).unwrap(), "5");
+
+        let _ = std::fs::remove_file(&input_path);
+        let _ = std::fs::remove_file(&checkpoint_path);
+    }
 }
@@ -0,0 +1,344 @@
+/// HTTP 数据源（Webhook 接收端）：每个 POST 请求体作为一个事件
+///
+/// 启用 `wait_for_ack` 时，HTTP 响应会一直挂起，直到事件被下游全部写入成功并确认，
+/// 发送方据此判断是否需要重试；超时未确认返回 504。
+use crate::event::*;
+use crate::rule::*;
+use async_trait::async_trait;
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    routing::post,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// 待处理请求队列长度，队列满时返回 503
+const QUEUE_SIZE: usize = 100;
+
+fn default_wait_for_ack() -> bool {
+    true
+}
+
+fn default_ack_timeout_secs() -> u64 {
+    30
+}
+
+/// HTTP 数据源配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpSourceConfig {
+    /// 监听地址，如 "0.0.0.0:9000"
+    pub bind_addr: String,
+    /// 接收 POST 的路径，如 "/webhook"
+    pub path: String,
+    /// 是否等事件处理成功后再响应
+    #[serde(default = "default_wait_for_ack")]
+    pub wait_for_ack: bool,
+    /// 等待确认的超时时间（秒）
+    #[serde(default = "default_ack_timeout_secs")]
+    pub ack_timeout_secs: u64,
+}
+
+impl HttpSourceConfig {
+    pub fn new(bind_addr: String, path: String) -> Self {
+        Self {
+            bind_addr,
+            path,
+            wait_for_ack: default_wait_for_ack(),
+            ack_timeout_secs: default_ack_timeout_secs(),
+        }
+    }
+
+    pub fn with_wait_for_ack(mut self, wait_for_ack: bool) -> Self {
+        self.wait_for_ack = wait_for_ack;
+        self
+    }
+
+    /// 绑定端口并启动 HTTP 服务
+    pub async fn bind(&self) -> Result<HttpSourceRuntime> {
+        if !self.path.starts_with('/') {
+            return Err(RsyncError::ConfigError(format!(
+                "http_source path must start with '/': {}",
+                self.path
+            )));
+        }
+        let listener = TcpListener::bind(&self.bind_addr)
+            .await
+            .map_err(|e| RsyncError::BuildError(format!("bind {}: {e}", self.bind_addr)))?;
+        let local_addr = listener.local_addr()?;
+
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        let state = ReceiverState {
+            tx,
+            wait_for_ack: self.wait_for_ack,
+            ack_timeout: Duration::from_secs(self.ack_timeout_secs),
+        };
+        let app = Router::new()
+            .route(&self.path, post(receive))
+            .with_state(state);
+        let server_task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                eprintln!("HTTP source server error: {e}");
+            }
+        });
+
+        Ok(HttpSourceRuntime {
+            local_addr,
+            rx,
+            pending: HashMap::new(),
+            server_task,
+        })
+    }
+}
+
+/// 进入管道的请求：事件与等待确认的响应通道
+type Received = (SimpleEvent, Option<oneshot::Sender<()>>);
+
+#[derive(Clone)]
+struct ReceiverState {
+    tx: mpsc::Sender<Received>,
+    wait_for_ack: bool,
+    ack_timeout: Duration,
+}
+
+async fn receive(
+    State(state): State<ReceiverState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, String) {
+    let id = uuid::Uuid::new_v4().to_string();
+    let event_type = match headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(ct) if ct.starts_with("application/json") => EventType::Text(TextType::Json),
+        Some(ct) if ct.starts_with("text/") => EventType::Text(TextType::PlainText),
+        _ => EventType::Binary(BinaryType::Generic),
+    };
+    let payload = body.to_vec();
+    let event = SimpleEvent::new(
+        EventMetadata {
+            id: id.clone(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            name: "http_source".to_string(),
+            payload_size: payload.len(),
+            event_type,
+        },
+        payload,
+    );
+
+    let (ack_tx, ack_rx) = if state.wait_for_ack {
+        let (tx, rx) = oneshot::channel();
+        (Some(tx), Some(rx))
+    } else {
+        (None, None)
+    };
+    if state.tx.try_send((event, ack_tx)).is_err() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "queue full".to_string());
+    }
+
+    let Some(ack_rx) = ack_rx else {
+        return (StatusCode::ACCEPTED, id);
+    };
+    match tokio::time::timeout(state.ack_timeout, ack_rx).await {
+        Ok(Ok(())) => (StatusCode::OK, id),
+        // 数据源已关闭，事件不会再被确认
+        Ok(Err(_)) => (StatusCode::SERVICE_UNAVAILABLE, id),
+        Err(_) => (StatusCode::GATEWAY_TIMEOUT, id),
+    }
+}
+
+#[typetag::serde(name = "http_source")]
+#[async_trait]
+impl Source for HttpSourceConfig {
+    fn clone_box(&self) -> Box<dyn Source> {
+        Box::new(self.clone())
+    }
+
+    /// 事件类型由请求的 Content-Type 决定，见 `receive`
+    fn outputs(&self) -> Vec<SourceOutput> {
+        [
+            ("http_json", EventType::Text(TextType::Json)),
+            ("http_text", EventType::Text(TextType::PlainText)),
+            ("http_binary", EventType::Binary(BinaryType::Generic)),
+        ]
+        .into_iter()
+        .map(|(output_id, event_type)| SourceOutput {
+            output_id: output_id.to_string(),
+            event_type,
+        })
+        .collect()
+    }
+
+    async fn build(&self, _cx: SourceContext) -> Result<Box<dyn SourceRuntime>> {
+        Ok(Box::new(self.bind().await?))
+    }
+
+    fn can_acknowledge(&self) -> bool {
+        true
+    }
+
+    fn source_type(&self) -> &str {
+        "http_source"
+    }
+
+    fn bind_addr(&self) -> Option<&str> {
+        Some(&self.bind_addr)
+    }
+
+    async fn check(&self) -> Result<()> {
+        TcpListener::bind(&self.bind_addr)
+            .await
+            .map(|_| ())
+            .map_err(|e| RsyncError::BuildError(format!("bind {}: {e}", self.bind_addr)))
+    }
+}
+
+/// HTTP 数据源运行时实例
+pub struct HttpSourceRuntime {
+    local_addr: SocketAddr,
+    rx: mpsc::Receiver<Received>,
+    /// 等待确认的请求，按事件 ID 索引
+    pending: HashMap<String, oneshot::Sender<()>>,
+    server_task: JoinHandle<()>,
+}
+
+impl HttpSourceRuntime {
+    /// 实际监听的地址
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for HttpSourceRuntime {
+    fn drop(&mut self) {
+        self.server_task.abort();
+    }
+}
+
+#[async_trait]
+impl SourceRuntime for HttpSourceRuntime {
+    async fn next_event(&mut self) -> Result<Option<Box<dyn Event>>> {
+        let Some((event, ack)) = self.rx.recv().await else {
+            return Ok(None);
+        };
+        // 清理已超时返回的请求
+        self.pending.retain(|_, ack| !ack.is_closed());
+        if let Some(ack) = ack {
+            self.pending.insert(event.metadata.id.clone(), ack);
+        }
+        Ok(Some(Box::new(event)))
+    }
+
+    async fn acknowledge(&mut self, event_id: &str) -> Result<()> {
+        if let Some(ack) = self.pending.remove(event_id) {
+            let _ = ack.send(());
+        }
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        // 等待任务真正退出，返回时监听端口已释放
+        self.server_task.abort();
+        let _ = (&mut self.server_task).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 发送 POST 请求并返回响应状态码
+    async fn post(addr: SocketAddr, path: &str, body: &str) -> u16 {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "POST {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response[9..12].parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_post_body_becomes_event() {
+        let mut source = HttpSourceConfig::new("127.0.0.1:0".to_string(), "/hook".to_string())
+            .with_wait_for_ack(false)
+            .bind()
+            .await
+            .unwrap();
+
+        assert_eq!(post(source.local_addr(), "/hook", r#"{"a":1}"#).await, 202);
+
+        let event = source.next_event().await.unwrap().unwrap();
+        assert_eq!(event.get_payload_as_text().unwrap(), r#"{"a":1}"#);
+        assert_eq!(
+            event.get_metadata().event_type,
+            EventType::Text(TextType::Json)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_response_waits_for_acknowledge() {
+        let mut source = HttpSourceConfig::new("127.0.0.1:0".to_string(), "/hook".to_string())
+            .bind()
+            .await
+            .unwrap();
+        let response = tokio::spawn(post(source.local_addr(), "/hook", "payload"));
+
+        let event = source.next_event().await.unwrap().unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!response.is_finished());
+
+        source.acknowledge(&event.get_metadata().id).await.unwrap();
+        assert_eq!(response.await.unwrap(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_acknowledges_after_sink_write() {
+        // 先占用一个端口再释放，供管道绑定
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let output_path = std::env::temp_dir().join("http_source_ack_output.txt");
+        let config = DataTransferConfig::new(
+            "webhook".to_string(),
+            "Webhook".to_string(),
+            None,
+            vec![Box::new(HttpSourceConfig::new(
+                addr.to_string(),
+                "/hook".to_string(),
+            ))],
+            vec![],
+            vec![Box::new(crate::file::FileSinkConfig::new(
+                crate::file::RsyncEnv::detect(),
+                output_path.to_string_lossy().to_string(),
+                true,
+                None,
+            ))],
+        );
+        let mut controller = crate::controller::Controller::new();
+        controller.add_config(config).await.unwrap();
+
+        assert_eq!(post(addr, "/hook", "line").await, 200);
+        assert_eq!(std::fs::read_to_string(&output_path).unwrap(), "line");
+
+        controller.stop("webhook").await.unwrap();
+        let _ = std::fs::remove_file(&output_path);
+    }
+}
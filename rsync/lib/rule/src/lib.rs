@@ -1,7 +1,15 @@
 pub mod controller;
+pub mod enrich;
 pub mod event;
 pub mod file;
+pub mod http_source;
 pub mod rule_file_watch;
+#[cfg(feature = "s3")]
+pub mod s3;
+pub mod sample;
+pub mod tcp;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 /// Rule 模块定义了 rsync 的核心抽象：Source, Transform, Sink
 ///
@@ -14,10 +22,18 @@ pub mod rule;
 
 // 重新导出常用类型
 pub use rule::{
-    ComponentKey, DataTransferConfig, DataTransferMetadata, Result, RsyncError, Sink, SinkContext,
-    SinkRuntime, Source, SourceContext, SourceOutput, SourceRuntime, Transform, TransformContext,
-    TransformRuntime,
+    ComponentKey, DataTransferConfig, DataTransferMetadata, Encoding, EventRecord, Result,
+    RsyncError, Sink, SinkContext, SinkRuntime, Source, SourceContext, SourceOutput, SourceRuntime,
+    Transform, TransformContext, TransformRuntime, encode_event,
 };
 
+pub use controller::{ComponentStatus, DryRunReport, PipelineState, PipelineStatus};
+
 // 导出平台相关类型
+pub use enrich::{EnrichTransformConfig, EnrichTransformRuntime};
 pub use file::*;
+pub use http_source::{HttpSourceConfig, HttpSourceRuntime};
+#[cfg(feature = "s3")]
+pub use s3::{S3SinkConfig, S3SinkRuntime};
+pub use sample::{SampleTransformConfig, SampleTransformRuntime};
+pub use tcp::{TcpSourceConfig, TcpSourceRuntime};
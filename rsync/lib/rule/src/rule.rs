@@ -1,6 +1,8 @@
 use crate::event::*;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::fmt;
 use uuid::Uuid;
 
@@ -12,6 +14,7 @@ pub enum RsyncError {
     WriteError(String),
     TransformError(String),
     ConfigError(String),
+    NotFound(String),
 }
 
 impl fmt::Display for RsyncError {
@@ -22,6 +25,7 @@ impl fmt::Display for RsyncError {
             RsyncError::WriteError(msg) => write!(f, "Write error: {msg}"),
             RsyncError::TransformError(msg) => write!(f, "Transform error: {msg}"),
             RsyncError::ConfigError(msg) => write!(f, "Config error: {msg}"),
+            RsyncError::NotFound(id) => write!(f, "Pipeline not found: {id}"),
         }
     }
 }
@@ -147,6 +151,16 @@ pub trait Source: Send + Sync + fmt::Debug {
 
     /// 获取数据源类型的描述性名称
     fn source_type(&self) -> &str;
+
+    /// 检查数据源是否就绪（如文件存在、服务可达），不读取任何事件
+    async fn check(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// 监听的本地地址（如 TCP、HTTP 数据源），不监听端口的数据源返回 `None`
+    fn bind_addr(&self) -> Option<&str> {
+        None
+    }
 }
 
 impl Clone for Box<dyn Source> {
@@ -186,6 +200,11 @@ pub trait Transform: Send + Sync + fmt::Debug {
 
     /// 获取转换器类型名称
     fn transform_type(&self) -> &str;
+
+    /// 可处理的输入事件类型，空列表表示接受任意类型
+    fn accepts(&self) -> Vec<EventType> {
+        Vec::new()
+    }
 }
 
 impl Clone for Box<dyn Transform> {
@@ -215,6 +234,12 @@ pub trait Sink: Send + Sync + fmt::Debug {
 
     /// 获取 Sink 类型名称
     fn sink_type(&self) -> &str;
+
+    /// 检查目的地是否就绪（如目录可写、服务可达），不写入任何事件，
+    /// 也不能有截断文件之类的副作用
+    async fn check(&self) -> Result<()> {
+        Ok(())
+    }
 }
 
 impl Clone for Box<dyn Sink> {
@@ -246,6 +271,76 @@ pub trait SinkRuntime: Send + Sync {
     async fn shutdown(&mut self) -> Result<()> {
         self.flush().await
     }
+
+    /// 已接收但尚未写出的事件数；不为 0 时管道暂不向数据源确认这些事件
+    fn buffered(&self) -> usize {
+        0
+    }
+
+    /// 缓冲事件的最长等待时间，管道至少按此间隔调用一次 `flush`
+    fn flush_interval(&self) -> Option<std::time::Duration> {
+        None
+    }
+}
+
+/// Sink 写出事件时使用的编码
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// 原样写出载荷字节
+    #[default]
+    Raw,
+    /// 结构化事件编码为 JSON
+    Json,
+    /// 结构化事件编码为 MessagePack
+    MsgPack,
+}
+
+/// 结构化编码时的事件内容
+///
+/// 载荷是合法 JSON 时直接嵌入，是 UTF-8 文本时为字符串，否则为字节数组
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct EventRecord {
+    pub id: String,
+    pub timestamp: u64,
+    pub name: String,
+    pub event_type: String,
+    #[serde(default)]
+    pub fields: HashMap<String, Value>,
+    pub payload: Value,
+}
+
+impl EventRecord {
+    pub fn from_event(event: &dyn Event) -> Self {
+        let metadata = event.get_metadata();
+        let payload = event.get_payload_slice();
+        let payload =
+            serde_json::from_slice(payload).unwrap_or_else(|_| {
+                match std::str::from_utf8(payload) {
+                    Ok(text) => Value::from(text),
+                    Err(_) => Value::from(payload),
+                }
+            });
+        Self {
+            id: metadata.id.clone(),
+            timestamp: metadata.timestamp,
+            name: metadata.name.clone(),
+            event_type: metadata.event_type.as_str(),
+            fields: event.fields().clone(),
+            payload,
+        }
+    }
+}
+
+/// 按 `encoding` 编码事件，Sink 写出前统一调用
+pub fn encode_event(event: &dyn Event, encoding: Encoding) -> Result<Vec<u8>> {
+    match encoding {
+        Encoding::Raw => Ok(event.get_payload().clone()),
+        Encoding::Json => serde_json::to_vec(&EventRecord::from_event(event))
+            .map_err(|e| RsyncError::WriteError(format!("encode event as json: {e}"))),
+        Encoding::MsgPack => rmp_serde::to_vec_named(&EventRecord::from_event(event))
+            .map_err(|e| RsyncError::WriteError(format!("encode event as msgpack: {e}"))),
+    }
 }
 
 /// 数据传输管道的元数据
@@ -264,7 +359,7 @@ pub struct GlobalConfig {
 }
 
 /// api API 服务配置
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ApiConfig {
     #[serde(default = "default_listen_address")]
     pub listen_address: String,
@@ -272,10 +367,29 @@ pub struct ApiConfig {
     pub log_level: String,
     #[serde(default)]
     pub metrics_enabled: bool,
+    /// 管道管理接口（提交、停止、重载）要求的 Bearer 令牌，未配置时这些接口不可用
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    /// 通过管理接口提交的管道只能读写此目录下的文件，未配置时不允许使用文件组件
+    #[serde(default)]
+    pub pipeline_data_dir: Option<String>,
 }
 
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            listen_address: default_listen_address(),
+            log_level: default_log_level(),
+            metrics_enabled: false,
+            auth_token: None,
+            pipeline_data_dir: None,
+        }
+    }
+}
+
+/// 默认只监听本机，对外暴露需显式配置
 fn default_listen_address() -> String {
-    "0.0.0.0:8080".to_string()
+    "127.0.0.1:8080".to_string()
 }
 
 fn default_log_level() -> String {
@@ -354,6 +468,44 @@ impl DataTransferConfig {
         Ok(config)
     }
 
+    /// 检查组件连接：每个 Source 至少要有一种声明的输出类型能被第一个 Transform 接受
+    ///
+    /// 按请求内容区分类型的数据源（如 HTTP 按 Content-Type）会声明多种输出，
+    /// 其中不被接受的类型在运行时由 Transform 报错。
+    /// Sink 按原始字节写入，不限制事件类型；没有 Transform 时无需检查。
+    pub fn validate(&self) -> Result<()> {
+        let Some(transform) = self.transforms.first() else {
+            return Ok(());
+        };
+        let accepts = transform.accepts();
+        if accepts.is_empty() {
+            return Ok(());
+        }
+
+        for (index, source) in self.sources.iter().enumerate() {
+            let outputs = source.outputs();
+            if outputs
+                .iter()
+                .any(|output| accepts.contains(&output.event_type))
+            {
+                continue;
+            }
+            let declared: Vec<String> = outputs
+                .iter()
+                .map(|output| format!("{} on {}", output.event_type.as_str(), output.output_id))
+                .collect();
+            let accepted: Vec<String> = accepts.iter().map(EventType::as_str).collect();
+            return Err(RsyncError::ConfigError(format!(
+                "source {index} ({}) outputs [{}], but transform {} accepts only [{}]",
+                source.source_type(),
+                declared.join(", "),
+                transform.transform_type(),
+                accepted.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
     /// 合并全局配置和管道配置
     pub fn with_global_config(mut self, global_config: &GlobalConfigData) -> Self {
         self.metadata = global_config.metadata.clone();
@@ -437,4 +589,197 @@ path = "./test_logs/"
         // 清理临时文件
         std::fs::remove_file("test_global_config.toml").unwrap();
     }
+
+    /// 网络组件的 typetag 名称写在配置文件里，改名会导致已有配置无法加载
+    #[test]
+    fn test_network_pipeline_round_trips_through_toml() {
+        let raw = r#"
+[metadata]
+id = "webhook-to-http"
+name = "Webhook to HTTP"
+
+[[sources]]
+source_type = "http_source"
+bind_addr = "0.0.0.0:9000"
+path = "/webhook"
+wait_for_ack = true
+ack_timeout_secs = 5
+
+[[sources]]
+source_type = "tcp"
+bind_addr = "0.0.0.0:5140"
+
+[[transforms]]
+transform_type = "sample"
+rate = 10
+
+[[transforms]]
+transform_type = "enrich"
+fields = { env = "prod" }
+
+[[sinks]]
+sink_type = "http"
+url = "http://collector:8080/ingest"
+batch_size = 100
+"#;
+
+        let config: DataTransferConfig = toml::from_str(raw).unwrap();
+        let source_types: Vec<&str> = config.sources.iter().map(|s| s.source_type()).collect();
+        assert_eq!(source_types, ["http_source", "tcp"]);
+        let transform_types: Vec<&str> = config
+            .transforms
+            .iter()
+            .map(|t| t.transform_type())
+            .collect();
+        assert_eq!(transform_types, ["sample", "enrich"]);
+        let sink_types: Vec<&str> = config.sinks.iter().map(|s| s.sink_type()).collect();
+        assert_eq!(sink_types, ["http"]);
+
+        let serialized = toml::to_string(&config).unwrap();
+        let reparsed: DataTransferConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(
+            serde_json::to_value(&reparsed).unwrap(),
+            serde_json::to_value(&config).unwrap()
+        );
+
+        // 序列化结果里的类型标签与组件声明的类型一致
+        let value = serde_json::to_value(&reparsed).unwrap();
+        assert_eq!(value["sources"][0]["source_type"], "http_source");
+        assert_eq!(value["sources"][0]["path"], "/webhook");
+        assert_eq!(value["sources"][1]["source_type"], "tcp");
+        assert_eq!(value["transforms"][0]["transform_type"], "sample");
+        assert_eq!(value["transforms"][0]["rate"], 10);
+        assert_eq!(value["transforms"][1]["transform_type"], "enrich");
+        assert_eq!(value["transforms"][1]["fields"]["env"], "prod");
+        assert_eq!(value["sinks"][0]["sink_type"], "http");
+        assert_eq!(value["sinks"][0]["url"], "http://collector:8080/ingest");
+        assert_eq!(value["sinks"][0]["batch_size"], 100);
+    }
+
+    fn structured_event() -> crate::testing::TestEvent {
+        crate::testing::TestEvent::json(&serde_json::json!({"level": "info", "count": 3}))
+            .with_id("evt-1")
+            .with_timestamp(1_700_000_000)
+            .with_field("env", Value::from("prod"))
+    }
+
+    #[test]
+    fn test_encode_event_as_json_and_msgpack_round_trips() {
+        let event = structured_event();
+        let expected = EventRecord::from_event(&event);
+        assert_eq!(expected.event_type, "text.json");
+        assert_eq!(expected.payload["count"], 3);
+
+        let json = encode_event(&event, Encoding::Json).unwrap();
+        let decoded: EventRecord = serde_json::from_slice(&json).unwrap();
+        assert_eq!(decoded, expected);
+
+        let msgpack = encode_event(&event, Encoding::MsgPack).unwrap();
+        assert_ne!(msgpack, json);
+        let decoded: EventRecord = rmp_serde::from_slice(&msgpack).unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn test_encode_event_raw_and_non_json_payloads() {
+        let text = crate::testing::TestEvent::text("plain line");
+        assert_eq!(encode_event(&text, Encoding::Raw).unwrap(), b"plain line");
+        assert_eq!(EventRecord::from_event(&text).payload, "plain line");
+
+        let binary = crate::testing::TestEvent::new(vec![0xff, 0x00]);
+        assert_eq!(
+            EventRecord::from_event(&binary).payload,
+            serde_json::json!([255, 0])
+        );
+    }
+
+    #[test]
+    fn test_encoding_defaults_to_raw_in_sink_config() {
+        let sink: Box<dyn Sink> = toml::from_str(
+            r#"
+sink_type = "http"
+url = "http://collector:8080/ingest"
+batch_size = 10
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&sink).unwrap()["encoding"],
+            serde_json::json!("raw")
+        );
+
+        let encoding: Encoding = serde_json::from_str("\"msgpack\"").unwrap();
+        assert_eq!(encoding, Encoding::MsgPack);
+    }
+
+    fn pipeline(source: Box<dyn Source>) -> DataTransferConfig {
+        DataTransferConfig::new(
+            "wiring".to_string(),
+            "Wiring".to_string(),
+            None,
+            vec![source],
+            vec![Box::new(crate::file::JsonTransformConfig {
+                add_timestamp: false,
+            })],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_validate_accepts_text_source_for_json_transform() {
+        let config = pipeline(Box::new(crate::file::FileSourceConfig::new(
+            "/tmp/input.txt".to_string(),
+            false,
+        )));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_accepts_http_source_for_json_transform() {
+        let config = pipeline(Box::new(crate::http_source::HttpSourceConfig::new(
+            "127.0.0.1:0".to_string(),
+            "/hook".to_string(),
+        )));
+        assert!(config.validate().is_ok());
+    }
+
+    /// 只输出二进制事件的数据源
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct BinarySource;
+
+    #[typetag::serde(name = "test_binary")]
+    #[async_trait]
+    impl Source for BinarySource {
+        fn clone_box(&self) -> Box<dyn Source> {
+            Box::new(self.clone())
+        }
+
+        fn outputs(&self) -> Vec<SourceOutput> {
+            vec![SourceOutput {
+                output_id: "binary_output".to_string(),
+                event_type: EventType::Binary(BinaryType::Generic),
+            }]
+        }
+
+        async fn build(&self, _cx: SourceContext) -> Result<Box<dyn SourceRuntime>> {
+            Err(RsyncError::BuildError("test source".to_string()))
+        }
+
+        fn source_type(&self) -> &str {
+            "test_binary"
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_binary_source_for_json_transform() {
+        let config = pipeline(Box::new(BinarySource));
+        match config.validate() {
+            Err(RsyncError::ConfigError(message)) => {
+                assert!(message.contains("test_binary"), "{message}");
+                assert!(message.contains("binary.generic"), "{message}");
+                assert!(message.contains("json"), "{message}");
+            }
+            other => panic!("expected ConfigError, got {other:?}"),
+        }
+    }
 }
@@ -0,0 +1,345 @@
+/// S3 兼容对象存储 Sink：缓冲事件，`flush` 时把一批事件上传为一个对象
+///
+/// 通过 `object_store` 访问 S3，可通过 `endpoint` 接入 MinIO 等兼容服务（path-style 地址）。
+/// 访问凭证按 AWS 惯例读取环境变量 `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY`
+/// （临时凭证另需 `AWS_SESSION_TOKEN`），未配置时尝试实例元数据等凭证来源。
+///
+/// 缓冲中的事件在上传成功前不会向数据源确认。上传失败时保留缓冲，
+/// 在一个刷新间隔后再重试；缓冲达到 `max_buffered` 后拒绝新事件，由数据源决定是否重发。
+use crate::event::*;
+use crate::rule::*;
+use async_trait::async_trait;
+use object_store::aws::{AmazonS3, AmazonS3Builder};
+use object_store::path::Path;
+use object_store::{ObjectStore, PutPayload, RetryConfig};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+fn default_batch_size() -> usize {
+    1000
+}
+
+fn default_flush_interval_secs() -> u64 {
+    60
+}
+
+fn default_max_buffered() -> usize {
+    10_000
+}
+
+/// S3 Sink 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3SinkConfig {
+    pub bucket: String,
+    /// 对象键前缀，如 "logs/"
+    #[serde(default)]
+    pub prefix: String,
+    pub region: String,
+    /// 自定义服务地址（如 MinIO 的 "http://127.0.0.1:9000"），不配置时使用 AWS
+    pub endpoint: Option<String>,
+    /// 缓冲事件数达到该值时自动上传
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// 缓冲事件最长等待秒数，事件量小时也按此间隔上传
+    #[serde(default = "default_flush_interval_secs")]
+    pub flush_interval_secs: u64,
+    /// 上传持续失败时最多缓冲的事件数，超出后拒绝写入（不小于 `batch_size`）
+    #[serde(default = "default_max_buffered")]
+    pub max_buffered: usize,
+}
+
+impl S3SinkConfig {
+    /// 从环境变量读取凭证，再用配置覆盖 bucket、region 与服务地址
+    fn builder(&self) -> AmazonS3Builder {
+        let mut builder = AmazonS3Builder::from_env()
+            .with_bucket_name(&self.bucket)
+            .with_region(&self.region)
+            // 单次上传内部只做少量重试，持续失败交给 Sink 按刷新间隔重试
+            .with_retry(RetryConfig {
+                max_retries: 2,
+                retry_timeout: Duration::from_secs(30),
+                ..Default::default()
+            });
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder
+                .with_endpoint(endpoint.trim_end_matches('/'))
+                .with_allow_http(endpoint.starts_with("http://"));
+        }
+        builder
+    }
+
+    fn store(&self) -> Result<AmazonS3> {
+        self.builder()
+            .build()
+            .map_err(|e| RsyncError::ConfigError(format!("invalid s3 sink config: {e}")))
+    }
+}
+
+#[typetag::serde(name = "s3")]
+#[async_trait]
+impl Sink for S3SinkConfig {
+    fn clone_box(&self) -> Box<dyn Sink> {
+        Box::new(self.clone())
+    }
+
+    async fn build(&self, _cx: SinkContext) -> Result<Box<dyn SinkRuntime>> {
+        Ok(Box::new(S3SinkRuntime::new(self, self.store()?)))
+    }
+
+    fn sink_type(&self) -> &str {
+        "s3"
+    }
+
+    async fn check(&self) -> Result<()> {
+        // 列出前缀下的一层：确认服务可达、凭证有效且 bucket 存在
+        let prefix = Path::from(self.prefix.as_str());
+        self.store()?
+            .list_with_delimiter(Some(&prefix))
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                RsyncError::WriteError(format!("bucket {} not accessible: {e}", self.bucket))
+            })
+    }
+}
+
+/// S3 Sink 运行时
+pub struct S3SinkRuntime {
+    store: AmazonS3,
+    prefix: String,
+    batch_size: usize,
+    flush_interval: Duration,
+    max_buffered: usize,
+    buffer: Vec<Box<dyn Event>>,
+    /// 缓冲中最早事件的到达时间
+    oldest: Option<Instant>,
+    /// 上次上传失败后，写入在此之前不再触发上传
+    retry_at: Option<Instant>,
+    /// 本实例已上传的批次数，用于区分同一毫秒内的对象
+    batch: u64,
+    /// 最近一次上传的对象键
+    last_key: Option<String>,
+}
+
+impl S3SinkRuntime {
+    fn new(config: &S3SinkConfig, store: AmazonS3) -> Self {
+        let batch_size = config.batch_size.max(1);
+        Self {
+            store,
+            prefix: config.prefix.clone(),
+            batch_size,
+            flush_interval: Duration::from_secs(config.flush_interval_secs.max(1)),
+            max_buffered: config.max_buffered.max(batch_size),
+            buffer: Vec::new(),
+            oldest: None,
+            retry_at: None,
+            batch: 0,
+            last_key: None,
+        }
+    }
+
+    /// 对象键：`{prefix}{yyyy}/{mm}/{dd}/{毫秒时间戳}-{批次}.log`
+    fn object_key(&self) -> String {
+        let now = chrono::Utc::now();
+        format!(
+            "{}{}/{}-{}.log",
+            self.prefix,
+            now.format("%Y/%m/%d"),
+            now.timestamp_millis(),
+            self.batch
+        )
+    }
+}
+
+#[async_trait]
+impl SinkRuntime for S3SinkRuntime {
+    async fn write(&mut self, event: Box<dyn Event>) -> Result<()> {
+        if self.buffer.len() >= self.max_buffered {
+            return Err(RsyncError::WriteError(format!(
+                "s3 sink buffer full ({} events pending upload)",
+                self.buffer.len()
+            )));
+        }
+        self.buffer.push(event);
+        let oldest = *self.oldest.get_or_insert_with(Instant::now);
+        let due = self.buffer.len() >= self.batch_size || oldest.elapsed() >= self.flush_interval;
+        // 上传失败后的退避期内只缓冲，由管道的定时刷新重试
+        let backing_off = self.retry_at.is_some_and(|at| Instant::now() < at);
+        // 事件已进入缓冲，上传失败不算写入失败：确认由 `buffered` 推迟，
+        // 失败由定时调用的 `flush` 上报
+        if due
+            && !backing_off
+            && let Err(e) = self.flush().await
+        {
+            eprintln!("{e}, keeping {} events buffered", self.buffer.len());
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        // 每个事件一行，载荷本身以换行结尾时不再追加
+        let mut body = Vec::new();
+        for event in &self.buffer {
+            body.extend_from_slice(event.get_payload_slice());
+            if !body.ends_with(b"\n") {
+                body.push(b'\n');
+            }
+        }
+
+        let key = self.object_key();
+        if let Err(e) = self
+            .store
+            .put(&Path::from(key.as_str()), PutPayload::from(body))
+            .await
+        {
+            self.retry_at = Some(Instant::now() + self.flush_interval);
+            return Err(RsyncError::WriteError(format!(
+                "s3 upload {key} failed: {e}"
+            )));
+        }
+
+        self.buffer.clear();
+        self.oldest = None;
+        self.retry_at = None;
+        self.batch += 1;
+        self.last_key = Some(key);
+        Ok(())
+    }
+
+    fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn flush_interval(&self) -> Option<Duration> {
+        Some(self.flush_interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestEvent;
+
+    fn unreachable_runtime(batch_size: usize, max_buffered: usize) -> S3SinkRuntime {
+        let config = S3SinkConfig {
+            bucket: "rsync-test".to_string(),
+            prefix: String::new(),
+            region: "us-east-1".to_string(),
+            // 端口 1 上没有服务，上传必然失败
+            endpoint: Some("http://127.0.0.1:1".to_string()),
+            batch_size,
+            flush_interval_secs: 60,
+            max_buffered,
+        };
+        let store = config
+            .builder()
+            .with_access_key_id("AKID")
+            .with_secret_access_key("secret")
+            .with_retry(RetryConfig {
+                max_retries: 0,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        S3SinkRuntime::new(&config, store)
+    }
+
+    #[tokio::test]
+    async fn test_failed_upload_keeps_events_buffered() {
+        let mut runtime = unreachable_runtime(1, 100);
+        assert_eq!(runtime.flush_interval(), Some(Duration::from_secs(60)));
+
+        // 事件已缓冲，上传失败不影响写入结果
+        runtime
+            .write(TestEvent::text("hello").boxed())
+            .await
+            .unwrap();
+        assert_eq!(runtime.buffered(), 1);
+        assert!(runtime.retry_at.is_some());
+        assert!(runtime.flush().await.is_err());
+        assert_eq!(runtime.buffered(), 1);
+        assert!(runtime.last_key.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_write_flushes_when_oldest_event_is_due() {
+        let mut runtime = unreachable_runtime(100, 1000);
+        runtime.write(TestEvent::text("a").boxed()).await.unwrap();
+        assert_eq!(runtime.buffered(), 1);
+
+        // 最早的事件已超过等待时间，下一次写入触发上传
+        runtime.oldest = Some(Instant::now() - Duration::from_secs(61));
+        runtime.write(TestEvent::text("b").boxed()).await.unwrap();
+        assert_eq!(runtime.buffered(), 2);
+        assert!(runtime.retry_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_failed_upload_backs_off_and_caps_buffer() {
+        let mut runtime = unreachable_runtime(1, 3);
+        runtime.write(TestEvent::text("a").boxed()).await.unwrap();
+        let retry_at = runtime.retry_at.unwrap();
+
+        // 退避期内写入只缓冲，不再重复上传
+        runtime.write(TestEvent::text("b").boxed()).await.unwrap();
+        runtime.write(TestEvent::text("c").boxed()).await.unwrap();
+        assert_eq!(runtime.buffered(), 3);
+        assert_eq!(runtime.retry_at, Some(retry_at));
+
+        // 缓冲已满，拒绝新事件而不是继续增长
+        assert!(runtime.write(TestEvent::text("d").boxed()).await.is_err());
+        assert_eq!(runtime.buffered(), 3);
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要 MinIO 运行（http://127.0.0.1:9000，已创建 bucket rsync-test）
+    async fn test_flush_uploads_object_to_minio() {
+        // SAFETY: 测试进程内只有这里读写这两个环境变量
+        unsafe {
+            std::env::set_var("AWS_ACCESS_KEY_ID", "minioadmin");
+            std::env::set_var("AWS_SECRET_ACCESS_KEY", "minioadmin");
+        }
+        let config = S3SinkConfig {
+            bucket: "rsync-test".to_string(),
+            prefix: "test/".to_string(),
+            region: "us-east-1".to_string(),
+            endpoint: Some("http://127.0.0.1:9000".to_string()),
+            batch_size: 10,
+            flush_interval_secs: 60,
+            max_buffered: default_max_buffered(),
+        };
+        config.check().await.unwrap();
+
+        let mut runtime = S3SinkRuntime::new(&config, config.store().unwrap());
+        runtime
+            .write(Box::new(SimpleEvent::new(
+                EventMetadata {
+                    id: "1".to_string(),
+                    timestamp: 0,
+                    name: "test".to_string(),
+                    payload_size: 5,
+                    event_type: EventType::Text(TextType::PlainText),
+                },
+                b"hello".to_vec(),
+            )))
+            .await
+            .unwrap();
+        runtime.flush().await.unwrap();
+
+        let key = runtime.last_key.clone().unwrap();
+        assert!(key.starts_with("test/"));
+        let object = runtime
+            .store
+            .get(&Path::from(key.as_str()))
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        assert_eq!(&object[..], b"hello\n");
+    }
+}
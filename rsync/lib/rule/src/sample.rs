@@ -0,0 +1,105 @@
+/// 采样转换器：每 N 个事件只保留一个，用于降低高流量数据源对下游的压力
+use crate::event::*;
+use crate::rule::*;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// 采样转换器配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleTransformConfig {
+    /// 采样率：每 `rate` 个事件保留第 `rate` 个，1 表示全部保留
+    pub rate: u32,
+}
+
+impl SampleTransformConfig {
+    pub fn new(rate: u32) -> Self {
+        Self { rate }
+    }
+}
+
+#[typetag::serde(name = "sample")]
+#[async_trait]
+impl Transform for SampleTransformConfig {
+    fn clone_box(&self) -> Box<dyn Transform> {
+        Box::new(self.clone())
+    }
+
+    async fn build(&self, _cx: TransformContext) -> Result<Box<dyn TransformRuntime>> {
+        if self.rate == 0 {
+            return Err(RsyncError::ConfigError(
+                "sample rate must be greater than 0".to_string(),
+            ));
+        }
+        Ok(Box::new(SampleTransformRuntime {
+            rate: self.rate as u64,
+            seen: 0,
+        }))
+    }
+
+    fn transform_type(&self) -> &str {
+        "sample"
+    }
+}
+
+/// 采样转换器运行时
+pub struct SampleTransformRuntime {
+    rate: u64,
+    /// 已处理的事件数
+    seen: u64,
+}
+
+#[async_trait]
+impl TransformRuntime for SampleTransformRuntime {
+    async fn process(&mut self, event: Box<dyn Event>) -> Result<Vec<Box<dyn Event>>> {
+        self.seen += 1;
+        if self.seen.is_multiple_of(self.rate) {
+            Ok(vec![event])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(seq: usize) -> Box<dyn Event> {
+        crate::testing::TestEvent::new(Vec::new())
+            .with_id(seq.to_string())
+            .boxed()
+    }
+
+    async fn passed(rate: u32, total: usize) -> Vec<String> {
+        let mut runtime = SampleTransformConfig::new(rate)
+            .build(TransformContext {
+                key: "sample".into(),
+            })
+            .await
+            .unwrap();
+        let mut ids = Vec::new();
+        for seq in 1..=total {
+            for event in runtime.process(event(seq)).await.unwrap() {
+                ids.push(event.get_metadata().id.clone());
+            }
+        }
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_keeps_every_nth_event() {
+        assert_eq!(passed(10, 100).await.len(), 10);
+        assert_eq!(passed(3, 10).await, vec!["3", "6", "9"]);
+        assert_eq!(passed(1, 7).await.len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_zero_rate_is_config_error() {
+        let result = SampleTransformConfig::new(0)
+            .build(TransformContext {
+                key: "sample".into(),
+            })
+            .await;
+        assert!(matches!(result, Err(RsyncError::ConfigError(_))));
+    }
+}
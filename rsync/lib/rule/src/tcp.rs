@@ -0,0 +1,296 @@
+/// TCP 数据源：监听端口，把每个连接收到的每一行作为一个事件（如 TCP 方式的 syslog）
+use crate::event::*;
+use crate::rule::*;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc};
+use tokio::task::JoinHandle;
+
+fn default_max_line_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_max_connections() -> usize {
+    1024
+}
+
+/// TCP 数据源配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TcpSourceConfig {
+    /// 监听地址，如 "0.0.0.0:5140"
+    pub bind_addr: String,
+    /// 单行最大字节数（不含换行符），超出时断开该连接
+    #[serde(default = "default_max_line_bytes")]
+    pub max_line_bytes: usize,
+    /// 同时处理的最大连接数，达到上限后新连接在监听队列中等待
+    #[serde(default = "default_max_connections")]
+    pub max_connections: usize,
+}
+
+impl TcpSourceConfig {
+    pub fn new(bind_addr: String) -> Self {
+        Self {
+            bind_addr,
+            max_line_bytes: default_max_line_bytes(),
+            max_connections: default_max_connections(),
+        }
+    }
+
+    /// 绑定端口并开始接受连接
+    pub async fn bind(&self) -> Result<TcpSourceRuntime> {
+        let listener = TcpListener::bind(&self.bind_addr)
+            .await
+            .map_err(|e| RsyncError::BuildError(format!("bind {}: {e}", self.bind_addr)))?;
+        let local_addr = listener.local_addr()?;
+        let (tx, rx) = mpsc::channel(100);
+        let max_line_bytes = self.max_line_bytes.max(1);
+        let connections = Arc::new(Semaphore::new(self.max_connections.max(1)));
+
+        let accept_task = tokio::spawn(async move {
+            loop {
+                // 先取得许可再接受连接，连接数达到上限时不再接受新连接
+                let Ok(permit) = connections.clone().acquire_owned().await else {
+                    break;
+                };
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        tokio::spawn(read_lines(stream, peer, tx.clone(), max_line_bytes, permit));
+                    }
+                    Err(e) => eprintln!("TCP accept error: {e}"),
+                }
+            }
+        });
+
+        Ok(TcpSourceRuntime {
+            local_addr,
+            rx,
+            accept_task,
+            sequence: 0,
+        })
+    }
+}
+
+/// 逐行读取连接数据，接收端关闭、连接断开或单行超过 `max_line_bytes` 时退出
+///
+/// 连接结束时释放 `_permit`，让出一个连接名额。
+async fn read_lines(
+    stream: TcpStream,
+    peer: SocketAddr,
+    tx: mpsc::Sender<(SocketAddr, String)>,
+    max_line_bytes: usize,
+    _permit: OwnedSemaphorePermit,
+) {
+    let mut reader = BufReader::new(stream);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        // 最多读取上限加 "\r\n"，读满仍没有换行说明该行超长
+        let limit = max_line_bytes as u64 + 2;
+        match (&mut reader).take(limit).read_until(b'\n', &mut buf).await {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("TCP read error from {peer}: {e}");
+                break;
+            }
+        }
+        if buf.ends_with(b"\n") {
+            buf.pop();
+            if buf.ends_with(b"\r") {
+                buf.pop();
+            }
+        }
+        if buf.len() > max_line_bytes {
+            eprintln!("TCP line from {peer} exceeds {max_line_bytes} bytes, closing connection");
+            break;
+        }
+        let line = match String::from_utf8(std::mem::take(&mut buf)) {
+            Ok(line) => line,
+            Err(_) => {
+                eprintln!("TCP read error from {peer}: stream did not contain valid UTF-8");
+                break;
+            }
+        };
+        if tx.send((peer, line)).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[typetag::serde(name = "tcp")]
+#[async_trait]
+impl Source for TcpSourceConfig {
+    fn clone_box(&self) -> Box<dyn Source> {
+        Box::new(self.clone())
+    }
+
+    fn outputs(&self) -> Vec<SourceOutput> {
+        vec![SourceOutput {
+            output_id: "tcp_output".to_string(),
+            event_type: EventType::Text(TextType::PlainText),
+        }]
+    }
+
+    async fn build(&self, _cx: SourceContext) -> Result<Box<dyn SourceRuntime>> {
+        Ok(Box::new(self.bind().await?))
+    }
+
+    fn source_type(&self) -> &str {
+        "tcp"
+    }
+
+    fn bind_addr(&self) -> Option<&str> {
+        Some(&self.bind_addr)
+    }
+
+    async fn check(&self) -> Result<()> {
+        // 试绑定后立即释放，确认地址合法且端口未被占用
+        TcpListener::bind(&self.bind_addr)
+            .await
+            .map(|_| ())
+            .map_err(|e| RsyncError::BuildError(format!("bind {}: {e}", self.bind_addr)))
+    }
+}
+
+/// TCP 数据源运行时实例，多个连接的数据汇入同一个事件流
+pub struct TcpSourceRuntime {
+    local_addr: SocketAddr,
+    rx: mpsc::Receiver<(SocketAddr, String)>,
+    accept_task: JoinHandle<()>,
+    sequence: u64,
+}
+
+impl TcpSourceRuntime {
+    /// 实际监听的地址（绑定端口 0 时可用来获取分配的端口）
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for TcpSourceRuntime {
+    fn drop(&mut self) {
+        // 停止接受新连接；已有连接在发送失败后自行退出
+        self.accept_task.abort();
+    }
+}
+
+#[async_trait]
+impl SourceRuntime for TcpSourceRuntime {
+    async fn next_event(&mut self) -> Result<Option<Box<dyn Event>>> {
+        let Some((peer, line)) = self.rx.recv().await else {
+            return Ok(None);
+        };
+        self.sequence += 1;
+        let payload = line.into_bytes();
+
+        Ok(Some(Box::new(SimpleEvent::new(
+            EventMetadata {
+                id: format!("tcp-{peer}-{}", self.sequence),
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+                name: peer.to_string(),
+                payload_size: payload.len(),
+                event_type: EventType::Text(TextType::PlainText),
+            },
+            payload,
+        ))))
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        // 等待任务真正退出，返回时监听端口已释放
+        self.accept_task.abort();
+        let _ = (&mut self.accept_task).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn test_lines_from_multiple_connections_arrive_as_events() {
+        let mut source = TcpSourceConfig::new("127.0.0.1:0".to_string())
+            .bind()
+            .await
+            .unwrap();
+        let addr = source.local_addr();
+
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        first.write_all(b"<34>first line\r\n").await.unwrap();
+        let first_peer = first.local_addr().unwrap();
+
+        let event = source.next_event().await.unwrap().unwrap();
+        assert_eq!(event.get_payload_as_text().unwrap(), "<34>first line");
+        assert_eq!(event.get_metadata().name, first_peer.to_string());
+
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        second.write_all(b"second\nthird\n").await.unwrap();
+        let second_peer = second.local_addr().unwrap().to_string();
+
+        for expected in ["second", "third"] {
+            let event = source.next_event().await.unwrap().unwrap();
+            assert_eq!(event.get_payload_as_text().unwrap(), expected);
+            assert_eq!(event.get_metadata().name, second_peer);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_line_closes_connection() {
+        let mut config = TcpSourceConfig::new("127.0.0.1:0".to_string());
+        config.max_line_bytes = 8;
+        let mut source = config.bind().await.unwrap();
+        let addr = source.local_addr();
+
+        // 一直不发送换行，服务端读满上限后断开，不会无限缓冲
+        let mut flood = TcpStream::connect(addr).await.unwrap();
+        flood.write_all(&[b'x'; 64]).await.unwrap();
+        let mut rest = Vec::new();
+        let read = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            flood.read_to_end(&mut rest),
+        )
+        .await
+        .expect("connection should be closed");
+        assert!(read.is_err() || rest.is_empty());
+
+        // 不超过上限的行照常接收
+        let mut normal = TcpStream::connect(addr).await.unwrap();
+        normal.write_all(b"12345678\r\n").await.unwrap();
+        let event = source.next_event().await.unwrap().unwrap();
+        assert_eq!(event.get_payload_as_text().unwrap(), "12345678");
+    }
+
+    #[tokio::test]
+    async fn test_connections_beyond_limit_wait_for_a_slot() {
+        let mut config = TcpSourceConfig::new("127.0.0.1:0".to_string());
+        config.max_connections = 1;
+        let mut source = config.bind().await.unwrap();
+        let addr = source.local_addr();
+
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        first.write_all(b"first\n").await.unwrap();
+        let event = source.next_event().await.unwrap().unwrap();
+        assert_eq!(event.get_payload_as_text().unwrap(), "first");
+
+        // 名额被第一个连接占用，第二个连接的数据暂不处理
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        second.write_all(b"second\n").await.unwrap();
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(200), source.next_event())
+                .await
+                .is_err()
+        );
+
+        drop(first);
+        let event = source.next_event().await.unwrap().unwrap();
+        assert_eq!(event.get_payload_as_text().unwrap(), "second");
+    }
+}
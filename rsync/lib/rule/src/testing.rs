@@ -0,0 +1,94 @@
+/// 测试辅助：不依赖真实数据源的内存事件
+use crate::event::*;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 内存事件，默认是 id 为 "test"、时间戳为 0 的纯文本事件，`payload_size` 随载荷自动更新
+#[derive(Debug, Clone)]
+pub struct TestEvent {
+    metadata: EventMetadata,
+    payload: Vec<u8>,
+    fields: HashMap<String, Value>,
+}
+
+impl TestEvent {
+    pub fn new(payload: impl Into<Vec<u8>>) -> Self {
+        let payload = payload.into();
+        Self {
+            metadata: EventMetadata {
+                id: "test".to_string(),
+                timestamp: 0,
+                name: "test".to_string(),
+                payload_size: payload.len(),
+                event_type: EventType::Text(TextType::PlainText),
+            },
+            payload,
+            fields: HashMap::new(),
+        }
+    }
+
+    /// 纯文本事件
+    pub fn text(text: &str) -> Self {
+        Self::new(text.as_bytes())
+    }
+
+    /// JSON 事件，载荷为 `value` 序列化后的文本
+    pub fn json(value: &Value) -> Self {
+        Self::new(value.to_string()).with_event_type(EventType::Text(TextType::Json))
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.metadata.id = id.into();
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.metadata.name = name.into();
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.metadata.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_event_type(mut self, event_type: EventType) -> Self {
+        self.metadata.event_type = event_type;
+        self
+    }
+
+    pub fn with_field(mut self, key: &str, value: Value) -> Self {
+        self.fields.insert(key.to_string(), value);
+        self
+    }
+
+    pub fn boxed(self) -> Box<dyn Event> {
+        Box::new(self)
+    }
+}
+
+impl Event for TestEvent {
+    fn clone_box(&self) -> Box<dyn Event> {
+        Box::new(self.clone())
+    }
+
+    fn get_metadata(&self) -> &EventMetadata {
+        &self.metadata
+    }
+
+    fn metadata_mut(&mut self) -> &mut EventMetadata {
+        &mut self.metadata
+    }
+
+    fn fields(&self) -> &HashMap<String, Value> {
+        &self.fields
+    }
+
+    fn fields_mut(&mut self) -> &mut HashMap<String, Value> {
+        &mut self.fields
+    }
+
+    fn get_payload(&self) -> &Vec<u8> {
+        &self.payload
+    }
+}
@@ -1,21 +1,300 @@
-use axum::{Router, http::StatusCode, response::Json, routing::get};
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{FromRef, Path, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json},
+    routing::{delete, get, post},
+};
 use rule::rule::GlobalConfigData;
+use rule::{DataTransferConfig, PipelineStatus, RsyncError};
 use rule::{controller::Controller, rule_file_watch::RuleFileWatcher};
 use serde_json::{Value, json};
+use std::path::{Component, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::info;
+use tracing::{info, warn};
 use util::log::setup;
+use util::server::{HttpMiddlewareConfig, bearer_token_matches, default_layers};
 
 const DEFAULT_CONFIG_FILE_LIST: [&str; 3] = ["config.toml", "rsync.toml", "example.toml"];
 
-const DEFAULT_LISTEN_ADDR: &str = "0.0.0.0:8080";
+/// 管道管理接口的访问控制
+#[derive(Clone, Debug, Default)]
+struct ApiSettings {
+    /// 提交、停止、重载管道要求的 Bearer 令牌，未配置时这些接口不可用
+    auth_token: Option<String>,
+    /// 文件源与文件目标只能使用此目录下的路径，未配置时不允许使用文件组件
+    data_dir: Option<PathBuf>,
+}
+
+#[derive(Clone)]
+struct AppState {
+    controller: Arc<Mutex<Controller>>,
+    settings: Arc<ApiSettings>,
+}
+
+impl FromRef<AppState> for Arc<Mutex<Controller>> {
+    fn from_ref(state: &AppState) -> Self {
+        state.controller.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<ApiSettings> {
+    fn from_ref(state: &AppState) -> Self {
+        state.settings.clone()
+    }
+}
+
 async fn health_check() -> (StatusCode, Json<Value>) {
     (StatusCode::OK, Json(json!({ "status": "healthy" })))
 }
 
+/// 已注册管道的状态与事件计数
+async fn list_pipelines(
+    State(controller): State<Arc<Mutex<Controller>>>,
+) -> Json<Vec<PipelineStatus>> {
+    Json(controller.lock().await.pipelines())
+}
+
+type ApiResult = Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)>;
+
+fn error_response(err: RsyncError) -> (StatusCode, Json<Value>) {
+    let status = match err {
+        RsyncError::NotFound(_) => StatusCode::NOT_FOUND,
+        RsyncError::ConfigError(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(json!({ "error": err.to_string() })))
+}
+
+/// 校验管理令牌：未配置令牌时返回 403，令牌不匹配时返回 401
+fn authorize(settings: &ApiSettings, headers: &HeaderMap) -> Result<(), (StatusCode, Json<Value>)> {
+    let Some(expected) = settings.auth_token.as_deref() else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "Pipeline management is disabled: no auth token configured" })),
+        ));
+    };
+    if bearer_token_matches(headers, expected) {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "Invalid or missing bearer token" })),
+        ))
+    }
+}
+
+/// 解析请求体中的管道配置（JSON 格式的 `DataTransferConfig`），
+/// 并将文件组件的路径限制在数据目录内
+fn parse_config(
+    body: &[u8],
+    settings: &ApiSettings,
+) -> Result<DataTransferConfig, (StatusCode, Json<Value>)> {
+    let invalid = |message: String| error_response(RsyncError::ConfigError(message));
+    let mut value: Value = serde_json::from_slice(body).map_err(|e| invalid(e.to_string()))?;
+    confine_file_paths(&mut value, settings.data_dir.as_deref()).map_err(invalid)?;
+    serde_json::from_value(value).map_err(|e| invalid(e.to_string()))
+}
+
+/// 将文件源与文件目标的路径解析到数据目录下，越界或未配置数据目录时返回错误
+///
+/// 相对路径按数据目录解析并写回配置；绝对路径必须位于数据目录内。
+fn confine_file_paths(
+    config: &mut Value,
+    data_dir: Option<&std::path::Path>,
+) -> Result<(), String> {
+    for (list, tag) in [("sources", "source_type"), ("sinks", "sink_type")] {
+        let Some(components) = config.get_mut(list).and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for component in components {
+            if component.get(tag).and_then(Value::as_str) != Some("file") {
+                continue;
+            }
+            let Some(data_dir) = data_dir else {
+                return Err(
+                    "File sources and sinks are not allowed: pipeline_data_dir is not configured"
+                        .to_string(),
+                );
+            };
+            for key in ["path", "checkpoint_path"] {
+                let Some(path) = component.get(key).and_then(Value::as_str) else {
+                    continue;
+                };
+                let confined = confine_path(path, data_dir)
+                    .ok_or_else(|| format!("{key} {path} is outside {}", data_dir.display()))?;
+                component[key] = Value::String(confined.to_string_lossy().to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn confine_path(path: &str, data_dir: &std::path::Path) -> Option<PathBuf> {
+    let path = std::path::Path::new(path);
+    if path
+        .components()
+        .any(|component| matches!(component, Component::ParentDir))
+    {
+        return None;
+    }
+    let resolved = data_dir.join(path);
+    resolved.starts_with(data_dir).then_some(resolved)
+}
+
+/// 提交并启动管道
+async fn create_pipeline(
+    State(controller): State<Arc<Mutex<Controller>>>,
+    State(settings): State<Arc<ApiSettings>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult {
+    authorize(&settings, &headers)?;
+    let config = parse_config(&body, &settings)?;
+    // 预检不持有锁，避免检查网络可达性时阻塞其他请求
+    Controller::validate(&config)
+        .await
+        .map_err(error_response)?;
+    let id = config.metadata.as_ref().map(|m| m.id.clone());
+    controller
+        .lock()
+        .await
+        .add_config(config)
+        .await
+        .map_err(error_response)?;
+    info!("Pipeline started via API: {id:?}");
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
+}
+
+/// 停止并移除管道
+async fn delete_pipeline(
+    State(controller): State<Arc<Mutex<Controller>>>,
+    State(settings): State<Arc<ApiSettings>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> ApiResult {
+    authorize(&settings, &headers)?;
+    controller
+        .lock()
+        .await
+        .stop(&id)
+        .await
+        .map_err(error_response)?;
+    info!("Pipeline stopped via API: {id}");
+    Ok((StatusCode::OK, Json(json!({ "id": id }))))
+}
+
+/// 用新配置替换管道
+async fn reload_pipeline(
+    State(controller): State<Arc<Mutex<Controller>>>,
+    State(settings): State<Arc<ApiSettings>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> ApiResult {
+    authorize(&settings, &headers)?;
+    let config = parse_config(&body, &settings)?;
+    let replaced_addrs = controller
+        .lock()
+        .await
+        .bind_addrs(&id)
+        .map_err(error_response)?;
+    // 与创建一样预检时不持有锁；旧管道仍占用的监听端口不做试绑定
+    Controller::validate_replacing(&config, &replaced_addrs)
+        .await
+        .map_err(error_response)?;
+    controller
+        .lock()
+        .await
+        .reload(&id, config)
+        .await
+        .map_err(error_response)?;
+    info!("Pipeline reloaded via API: {id}");
+    Ok((StatusCode::OK, Json(json!({ "id": id }))))
+}
+
+/// Prometheus 格式的管道指标
+async fn metrics(State(controller): State<Arc<Mutex<Controller>>>) -> impl IntoResponse {
+    let pipelines = controller.lock().await.pipelines();
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render_metrics(&pipelines),
+    )
+}
+
+fn render_metrics(pipelines: &[PipelineStatus]) -> String {
+    let mut out = String::new();
+    out.push_str("# HELP rsync_pipeline_up Pipeline state (1 when the state label is current)\n");
+    out.push_str("# TYPE rsync_pipeline_up gauge\n");
+    for p in pipelines {
+        out.push_str(&format!(
+            "rsync_pipeline_up{{pipeline=\"{}\",state=\"{}\"}} 1\n",
+            p.id,
+            p.state.as_str()
+        ));
+    }
+
+    push_counter(
+        &mut out,
+        "rsync_pipeline_events_received_total",
+        "Events read from sources",
+        pipelines,
+        |p| p.events_received,
+    );
+    push_counter(
+        &mut out,
+        "rsync_pipeline_events_sent_total",
+        "Events written to sinks",
+        pipelines,
+        |p| p.events_sent,
+    );
+    push_counter(
+        &mut out,
+        "rsync_pipeline_errors_total",
+        "Source, transform and sink errors",
+        pipelines,
+        |p| p.errors,
+    );
+    out
+}
+
+fn push_counter(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    pipelines: &[PipelineStatus],
+    value: impl Fn(&PipelineStatus) -> u64,
+) {
+    out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} counter\n"));
+    for p in pipelines {
+        out.push_str(&format!("{name}{{pipeline=\"{}\"}} {}\n", p.id, value(p)));
+    }
+}
+
+fn app(controller: Arc<Mutex<Controller>>, settings: ApiSettings) -> Router {
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/pipelines", get(list_pipelines).post(create_pipeline))
+        .route("/pipelines/:id", delete(delete_pipeline))
+        .route("/pipelines/:id/reload", post(reload_pipeline))
+        .route("/metrics", get(metrics))
+        .with_state(AppState {
+            controller,
+            settings: Arc::new(settings),
+        })
+}
+
 #[tokio::main]
 async fn main() {
+    // --check [file ...]: 只预检管道配置，不启动服务
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("--check") {
+        let passed = check_pipelines(&args[1..]).await;
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
     // 首先尝试从默认配置文件加载全局配置
     let global_config = load_global_config_from_file().unwrap_or_else(|_| {
         // 如果无法加载配置文件，则使用默认配置
@@ -40,14 +319,20 @@ async fn main() {
         watcher.run().await;
     });
 
-    // 启动 HTTP 服务
-    let app = Router::new().route("/health", get(health_check));
+    // 启动 HTTP 服务，环境变量优先于配置文件
+    let listen_addr =
+        std::env::var("RSYNC_LISTEN_ADDR").unwrap_or(global_config.api.listen_address.clone());
+    let settings = api_settings(&global_config);
+    if settings.auth_token.is_none() {
+        warn!("No API auth token configured, pipeline management endpoints are disabled");
+    }
+    let app = app(controller, settings).layer(default_layers(&HttpMiddlewareConfig::default()));
 
-    let listener = tokio::net::TcpListener::bind(DEFAULT_LISTEN_ADDR)
+    let listener = tokio::net::TcpListener::bind(&listen_addr)
         .await
         .expect("Failed: listener bind");
 
-    info!("HTTP server running on {}", DEFAULT_LISTEN_ADDR);
+    info!("HTTP server running on {listen_addr}");
 
     // 在单独的任务中运行 HTTP 服务器
     let server_task = tokio::spawn(async move {
@@ -59,6 +344,7 @@ async fn main() {
     // Keep the main thread alive to let the controller run
     info!("Rsync service running... Waiting for config files in current directory.");
     info!("Health check endpoint available at /health");
+    info!("Pipeline status at /pipelines, Prometheus metrics at /metrics");
     info!("Press Ctrl+C to stop.");
 
     // 等待 Ctrl+C 信号
@@ -69,6 +355,29 @@ async fn main() {
     server_task.abort();
 }
 
+/// 管理接口设置：令牌可由 `RSYNC_API_TOKEN` 覆盖，数据目录需存在
+fn api_settings(global_config: &GlobalConfigData) -> ApiSettings {
+    let auth_token = std::env::var("RSYNC_API_TOKEN")
+        .ok()
+        .or_else(|| global_config.api.auth_token.clone())
+        .filter(|token| !token.is_empty());
+    let data_dir = global_config
+        .api
+        .pipeline_data_dir
+        .as_ref()
+        .and_then(|dir| match std::fs::canonicalize(dir) {
+            Ok(dir) => Some(dir),
+            Err(e) => {
+                warn!("Ignoring pipeline_data_dir {dir}: {e}");
+                None
+            }
+        });
+    ApiSettings {
+        auth_token,
+        data_dir,
+    }
+}
+
 fn load_global_config_from_file() -> Result<GlobalConfigData, Box<dyn std::error::Error>> {
     // 尝试加载全局配置文件
     let config_path = DEFAULT_CONFIG_FILE_LIST.iter().find_map(|&file| {
@@ -86,3 +395,364 @@ fn load_global_config_from_file() -> Result<GlobalConfigData, Box<dyn std::error
         Err("No configuration file found".into())
     }
 }
+
+/// 预检管道配置并打印每个组件的状态，全部就绪时返回 true
+///
+/// 未指定文件时检查 `RSYNC_CONFIG_DIR`（默认当前目录）下所有 `*.rule.toml`。
+async fn check_pipelines(files: &[String]) -> bool {
+    let files = if files.is_empty() {
+        let watch_dir = std::env::var("RSYNC_CONFIG_DIR").unwrap_or_else(|_| ".".to_string());
+        match rule_files(&watch_dir) {
+            Ok(files) => files,
+            Err(e) => {
+                eprintln!("Failed to read directory {watch_dir}: {e}");
+                return false;
+            }
+        }
+    } else {
+        files.to_vec()
+    };
+    if files.is_empty() {
+        eprintln!("No pipeline config (*.rule.toml) found");
+        return false;
+    }
+
+    let global_config = load_global_config_from_file().ok();
+    let mut passed = true;
+    for file in files {
+        let mut config = match DataTransferConfig::from_file(&file) {
+            Ok(config) => config,
+            Err(e) => {
+                println!("[FAIL] {file}: {e}");
+                passed = false;
+                continue;
+            }
+        };
+        // 与文件监听器一致：存在全局配置时合并
+        if let Some(global_config) = &global_config {
+            config = config.with_global_config(global_config);
+        }
+
+        match Controller::dry_run(config).await {
+            Ok(report) => {
+                let status = if report.is_ok() { "OK" } else { "FAIL" };
+                println!("[{status}] {file} (pipeline {})", report.pipeline_id);
+                for component in &report.components {
+                    match &component.error {
+                        None => {
+                            println!("  ok    {} ({})", component.key, component.component_type)
+                        }
+                        Some(e) => println!(
+                            "  fail  {} ({}): {e}",
+                            component.key, component.component_type
+                        ),
+                    }
+                }
+                passed &= report.is_ok();
+            }
+            Err(e) => {
+                println!("[FAIL] {file}: {e}");
+                passed = false;
+            }
+        }
+    }
+    passed
+}
+
+/// 列出目录下的管道配置文件
+fn rule_files(dir: &str) -> std::io::Result<Vec<String>> {
+    let mut files: Vec<String> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().ends_with(".rule.toml"))
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use rule::{FileSinkConfig, FileSourceConfig, RsyncEnv};
+    use tower::ServiceExt;
+
+    fn file_pipeline_json(id: &str, input: &std::path::Path, output: &std::path::Path) -> String {
+        json!({
+            "metadata": { "id": id, "name": "API pipeline" },
+            "sources": [
+                { "source_type": "file", "path": input.to_string_lossy(), "watch": true }
+            ],
+            "sinks": [{
+                "sink_type": "file",
+                "path": output.to_string_lossy(),
+                "force": true,
+                "env": { "platform": { "kernel": "Linux", "arch": "X86_64", "distribution": "Unknown" } }
+            }]
+        })
+        .to_string()
+    }
+
+    const TOKEN: &str = "test-token";
+
+    /// 以临时目录为数据目录、带管理令牌的设置
+    fn settings() -> ApiSettings {
+        ApiSettings {
+            auth_token: Some(TOKEN.to_string()),
+            data_dir: Some(std::fs::canonicalize(std::env::temp_dir()).unwrap()),
+        }
+    }
+
+    async fn send_with_token(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: String,
+        token: Option<&str>,
+    ) -> StatusCode {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        app.clone()
+            .oneshot(request.body(Body::from(body)).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    async fn send(app: &Router, method: &str, uri: &str, body: String) -> StatusCode {
+        send_with_token(app, method, uri, body, Some(TOKEN)).await
+    }
+
+    async fn get_body(app: Router, uri: &str) -> String {
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_started_pipeline_is_listed() {
+        let temp_dir = std::env::temp_dir();
+        let input_path = temp_dir.join("rsync_status_input.txt");
+        let output_path = temp_dir.join("rsync_status_output.txt");
+        std::fs::write(&input_path, "hello").unwrap();
+
+        let config = DataTransferConfig::new(
+            "status-pipeline".to_string(),
+            "Status".to_string(),
+            None,
+            vec![Box::new(FileSourceConfig::new(
+                input_path.to_string_lossy().to_string(),
+                true,
+            ))],
+            vec![],
+            vec![Box::new(FileSinkConfig::new(
+                RsyncEnv::detect(),
+                output_path.to_string_lossy().to_string(),
+                true,
+                None,
+            ))],
+        );
+        let controller = Arc::new(Mutex::new(Controller::new()));
+        controller.lock().await.add_config(config).await.unwrap();
+        let app = app(controller, ApiSettings::default());
+
+        let pipelines: Value =
+            serde_json::from_str(&get_body(app.clone(), "/pipelines").await).unwrap();
+        assert_eq!(pipelines[0]["id"], "status-pipeline");
+        assert_eq!(pipelines[0]["state"], "running");
+
+        let metrics = get_body(app, "/metrics").await;
+        assert!(
+            metrics.contains("rsync_pipeline_up{pipeline=\"status-pipeline\",state=\"running\"} 1")
+        );
+
+        let _ = std::fs::remove_file(&input_path);
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[tokio::test]
+    async fn test_submit_list_and_delete_pipeline() {
+        let temp_dir = std::fs::canonicalize(std::env::temp_dir()).unwrap();
+        let input_path = temp_dir.join("rsync_api_input.txt");
+        let output_path = temp_dir.join("rsync_api_output.txt");
+        std::fs::write(&input_path, "hello").unwrap();
+        let app = app(Arc::new(Mutex::new(Controller::new())), settings());
+
+        let body = file_pipeline_json("api-pipeline", &input_path, &output_path);
+        assert_eq!(
+            send(&app, "POST", "/pipelines", body.clone()).await,
+            StatusCode::CREATED
+        );
+        assert_eq!(
+            send(&app, "POST", "/pipelines", body.clone()).await,
+            StatusCode::BAD_REQUEST
+        );
+
+        let pipelines: Value =
+            serde_json::from_str(&get_body(app.clone(), "/pipelines").await).unwrap();
+        assert_eq!(pipelines[0]["id"], "api-pipeline");
+
+        assert_eq!(
+            send(&app, "POST", "/pipelines/api-pipeline/reload", body).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&app, "DELETE", "/pipelines/api-pipeline", String::new()).await,
+            StatusCode::OK
+        );
+        assert_eq!(get_body(app.clone(), "/pipelines").await, "[]");
+        assert_eq!(
+            send(&app, "DELETE", "/pipelines/api-pipeline", String::new()).await,
+            StatusCode::NOT_FOUND
+        );
+
+        let _ = std::fs::remove_file(&input_path);
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[tokio::test]
+    async fn test_reload_keeps_listen_port() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let output_path = std::fs::canonicalize(std::env::temp_dir())
+            .unwrap()
+            .join("rsync_api_tcp_output.txt");
+        let body = json!({
+            "metadata": { "id": "tcp-pipeline", "name": "TCP pipeline" },
+            "sources": [{ "source_type": "tcp", "bind_addr": format!("127.0.0.1:{port}") }],
+            "sinks": [{
+                "sink_type": "file",
+                "path": output_path.to_string_lossy(),
+                "force": true,
+                "env": { "platform": { "kernel": "Linux", "arch": "X86_64", "distribution": "Unknown" } }
+            }]
+        })
+        .to_string();
+        let app = app(Arc::new(Mutex::new(Controller::new())), settings());
+
+        assert_eq!(
+            send(&app, "POST", "/pipelines", body.clone()).await,
+            StatusCode::CREATED
+        );
+        // 旧管道仍占用端口，重载不能因试绑定失败而被拒绝
+        assert_eq!(
+            send(&app, "POST", "/pipelines/tcp-pipeline/reload", body.clone()).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&app, "POST", "/pipelines/tcp-pipeline/reload", body).await,
+            StatusCode::OK
+        );
+        let pipelines: Value =
+            serde_json::from_str(&get_body(app.clone(), "/pipelines").await).unwrap();
+        assert_eq!(pipelines[0]["state"], "running");
+        assert_eq!(
+            send(&app, "DELETE", "/pipelines/tcp-pipeline", String::new()).await,
+            StatusCode::OK
+        );
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_pipeline_is_rejected() {
+        let temp_dir = std::env::temp_dir();
+        let app = app(Arc::new(Mutex::new(Controller::new())), settings());
+
+        assert_eq!(
+            send(&app, "POST", "/pipelines", "{".to_string()).await,
+            StatusCode::BAD_REQUEST
+        );
+        let missing_source = file_pipeline_json(
+            "missing",
+            &temp_dir.join("rsync_api_missing_input.txt"),
+            &temp_dir.join("rsync_api_missing_output.txt"),
+        );
+        assert_eq!(
+            send(&app, "POST", "/pipelines", missing_source.clone()).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            send(&app, "POST", "/pipelines/missing/reload", missing_source).await,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_management_requires_configured_token() {
+        let temp_dir = std::env::temp_dir();
+        let body = file_pipeline_json(
+            "auth",
+            &temp_dir.join("rsync_api_auth_input.txt"),
+            &temp_dir.join("rsync_api_auth_output.txt"),
+        );
+
+        let enabled = app(Arc::new(Mutex::new(Controller::new())), settings());
+        for (method, uri) in [
+            ("POST", "/pipelines"),
+            ("DELETE", "/pipelines/auth"),
+            ("POST", "/pipelines/auth/reload"),
+        ] {
+            assert_eq!(
+                send_with_token(&enabled, method, uri, body.clone(), None).await,
+                StatusCode::UNAUTHORIZED
+            );
+            assert_eq!(
+                send_with_token(&enabled, method, uri, body.clone(), Some("wrong")).await,
+                StatusCode::UNAUTHORIZED
+            );
+        }
+
+        let disabled = app(
+            Arc::new(Mutex::new(Controller::new())),
+            ApiSettings::default(),
+        );
+        assert_eq!(
+            send(&disabled, "POST", "/pipelines", body).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[test]
+    fn test_file_paths_are_confined_to_data_dir() {
+        let data_dir = std::path::Path::new("/srv/rsync");
+        let mut config = json!({
+            "sources": [{ "source_type": "file", "path": "in.txt", "checkpoint_path": "/srv/rsync/in.offset" }],
+            "sinks": [{ "sink_type": "http", "url": "http://example.com" }]
+        });
+        confine_file_paths(&mut config, Some(data_dir)).unwrap();
+        assert_eq!(config["sources"][0]["path"], "/srv/rsync/in.txt");
+        assert_eq!(
+            config["sources"][0]["checkpoint_path"],
+            "/srv/rsync/in.offset"
+        );
+
+        for path in ["/etc/passwd", "../etc/passwd", "/srv/rsync/../etc/passwd"] {
+            let mut config = json!({ "sinks": [{ "sink_type": "file", "path": path }] });
+            assert!(
+                confine_file_paths(&mut config, Some(data_dir)).is_err(),
+                "{path}"
+            );
+        }
+
+        let mut config = json!({ "sinks": [{ "sink_type": "file", "path": "out.txt" }] });
+        assert!(confine_file_paths(&mut config, None).is_err());
+    }
+}
@@ -1,8 +1,8 @@
-use rdkafka::message::Message;
+use rdkafka::message::{Headers, Message};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use util::client::kafka::{
-    KafkaClientConfig, KafkaConsumer, KafkaProducer, extract_json, extract_payload,
+    KafkaClientConfig, KafkaConsumer, KafkaProducer, Offset, extract_json, extract_payload,
 };
 
 const TEST_KAFKA_BROKERS: &str = "test-kafka.bkbase-test.svc.cluster.local:9092";
@@ -250,6 +250,95 @@ async fn test_producer_consumer_e2e() {
     }
 }
 
+/// 测试带 headers 的消息端到端流程
+#[tokio::test]
+#[ignore]
+async fn test_producer_consumer_headers_e2e() {
+    let test_topic = "test_headers_topic";
+
+    let producer_config =
+        KafkaClientConfig::new(vec![TEST_KAFKA_BROKERS.to_string()], "headers-producer")
+            .with_sasl_plaintext(USERNAME, PASSWORD);
+    let producer = KafkaProducer::new(&producer_config).expect("Failed to create producer");
+
+    producer
+        .send_with_headers(
+            test_topic,
+            Some("headers-key"),
+            b"message with headers",
+            &[("content-type", b"text/plain"), ("trace-id", b"abc123")],
+        )
+        .await
+        .expect("Failed to send message");
+    producer
+        .flush(Duration::from_secs(5))
+        .expect("Failed to flush");
+
+    let consumer_config =
+        KafkaClientConfig::new(vec![TEST_KAFKA_BROKERS.to_string()], "headers-consumer")
+            .with_sasl_plaintext(USERNAME, PASSWORD)
+            .with_group_id("headers-consumer-group");
+    let consumer = KafkaConsumer::new(&consumer_config).expect("Failed to create consumer");
+    consumer
+        .subscribe(&[test_topic])
+        .expect("Failed to subscribe");
+
+    let timeout = tokio::time::timeout(Duration::from_secs(30), async {
+        loop {
+            let msg = consumer.recv().await.expect("Failed to receive message");
+            let Some(headers) = msg.headers() else {
+                continue;
+            };
+            let trace_id = headers
+                .iter()
+                .find(|header| header.key == "trace-id")
+                .and_then(|header| header.value);
+            if trace_id == Some(b"abc123".as_slice()) {
+                println!("✓ Received message with {} headers", headers.count());
+                return;
+            }
+        }
+    });
+
+    timeout
+        .await
+        .expect("Timeout waiting for message with headers");
+}
+
+/// 测试手动分配分区并从 offset 0 开始读取
+#[tokio::test]
+#[ignore]
+async fn test_consumer_assign_from_offset_zero() {
+    let config = KafkaClientConfig::new(vec![TEST_KAFKA_BROKERS.to_string()], "seek-consumer")
+        .with_sasl_plaintext(USERNAME, PASSWORD)
+        .with_group_id("seek-consumer-group")
+        .with_auto_commit(false);
+
+    let consumer = KafkaConsumer::new(&config).expect("Failed to create consumer");
+    consumer
+        .assign_partition(TEST_TOPIC, 0, Offset::Offset(0))
+        .expect("Failed to assign partition");
+
+    let msg = tokio::time::timeout(Duration::from_secs(30), consumer.recv())
+        .await
+        .expect("Timeout waiting for first message")
+        .expect("Failed to receive message");
+    assert_eq!(msg.partition(), 0);
+    println!("✓ First message offset: {}", msg.offset());
+    let first_offset = msg.offset();
+    drop(msg);
+
+    // 回到同一位置应再次读到同一条消息
+    consumer
+        .seek(TEST_TOPIC, 0, Offset::Offset(first_offset))
+        .expect("Failed to seek");
+    let replayed = tokio::time::timeout(Duration::from_secs(30), consumer.recv())
+        .await
+        .expect("Timeout waiting for replayed message")
+        .expect("Failed to receive message");
+    assert_eq!(replayed.offset(), first_offset);
+}
+
 /// 测试错误处理 - 无效的 broker 地址
 #[tokio::test]
 async fn test_invalid_broker() {
@@ -312,6 +401,73 @@ async fn test_batch_send_performance() {
     assert!(elapsed.as_secs() < 30, "Batch send took too long");
 }
 
+/// 对比 send_batch 与逐条 await 发送的吞吐
+#[tokio::test]
+#[ignore]
+async fn test_send_batch_vs_serial_throughput() {
+    let config =
+        KafkaClientConfig::new(vec![TEST_KAFKA_BROKERS.to_string()], "test-batch-producer")
+            .with_sasl_plaintext(USERNAME, PASSWORD);
+
+    let producer = KafkaProducer::new(&config).expect("Failed to create producer");
+
+    let message_count = 1000;
+    let messages: Vec<(Option<String>, Vec<u8>)> = (0..message_count)
+        .map(|i| {
+            let msg = TestMessage {
+                id: i,
+                content: format!("Batch message {}", i),
+                timestamp: chrono::Utc::now().timestamp(),
+            };
+            (
+                Some(format!("key-{}", i)),
+                serde_json::to_vec(&msg).unwrap(),
+            )
+        })
+        .collect();
+
+    let start_time = std::time::Instant::now();
+    for (key, payload) in &messages {
+        producer
+            .send(TEST_TOPIC, key.as_deref(), payload)
+            .await
+            .expect("Failed to send message");
+    }
+    let serial = start_time.elapsed();
+
+    let start_time = std::time::Instant::now();
+    let results = producer
+        .send_batch(TEST_TOPIC, messages)
+        .await
+        .expect("Failed to send batch");
+    producer
+        .flush(Duration::from_secs(10))
+        .expect("Failed to flush");
+    let batch = start_time.elapsed();
+
+    assert_eq!(results.len(), message_count as usize);
+    for (i, result) in results.iter().enumerate() {
+        assert!(result.is_ok(), "Message {} failed: {:?}", i, result);
+    }
+
+    println!("✓ Sent {} messages", message_count);
+    println!(
+        "  Serial: {:?} ({:.2} messages/second)",
+        serial,
+        message_count as f64 / serial.as_secs_f64()
+    );
+    println!(
+        "  Batch:  {:?} ({:.2} messages/second)",
+        batch,
+        message_count as f64 / batch.as_secs_f64()
+    );
+
+    assert!(
+        batch < serial,
+        "Batch send should be faster than serial send"
+    );
+}
+
 /// 测试 ping 功能 - 生产者
 #[tokio::test]
 #[ignore]
@@ -274,6 +274,8 @@ export default function AnyboxPage() {
                                 <span className="format-badge">XML</span>
                                 <span className="format-badge">HTML</span>
                                 <span className="format-badge">YAML</span>
+                                <span className="format-badge">CSV</span>
+                                <span className="format-badge">TOML</span>
                             </div>
                         </div>
 
@@ -346,6 +348,8 @@ export default function AnyboxPage() {
                                     <option value="xml">XML</option>
                                     <option value="html">HTML</option>
                                     <option value="yaml">YAML</option>
+                                    <option value="csv">CSV</option>
+                                    <option value="toml">TOML</option>
                                 </select>
                             </div>
 
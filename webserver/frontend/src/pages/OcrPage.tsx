@@ -1,6 +1,15 @@
 import { useState } from 'react'
 import './ToolPage.css'
 
+// OCR 接口返回 { code, message } 结构的错误，图片上传接口返回字符串
+function errorMessage(error: unknown): string {
+    if (typeof error === 'string') return error
+    if (error && typeof error === 'object' && 'message' in error) {
+        return String((error as { message: unknown }).message)
+    }
+    return '未知错误'
+}
+
 export default function OcrPage() {
     const [activeTab, setActiveTab] = useState<'overview' | 'recognize' | 'history'>('overview')
     const [imagePath, setImagePath] = useState('')
@@ -34,7 +43,7 @@ export default function OcrPage() {
                 setImagePath(data.path)
                 setResult(`✅ 图片上传成功: ${data.path}`)
             } else {
-                setResult(`❌ 上传失败: ${data.error || '未知错误'}`)
+                setResult(`❌ 上传失败: ${errorMessage(data.error)}`)
             }
         } catch (error) {
             setResult(`❌ 上传请求失败: ${error}`)
@@ -69,7 +78,7 @@ export default function OcrPage() {
             if (data.success) {
                 setResult(data.text || JSON.stringify(data, null, 2))
             } else {
-                setResult(`错误: ${data.error}`)
+                setResult(`错误: ${errorMessage(data.error)}`)
             }
         } catch (error) {
             setResult(`请求失败: ${error}`)